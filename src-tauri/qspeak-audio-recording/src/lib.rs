@@ -2,6 +2,7 @@ use std::any::{Any, TypeId};
 
 use cpal::traits::{DeviceTrait, HostTrait};
 use std::{
+    collections::{HashMap, VecDeque},
     fs::File,
     io::BufWriter,
     path::Path,
@@ -78,11 +79,97 @@ pub fn get_audio_input_devices() -> Result<Vec<String>, String> {
     Ok::<_, String>(device_names)
 }
 
+/// Input device as shown to the user, with an id that survives restarts and re-plugging.
+///
+/// cpal only exposes display names, so the id combines the host API with the name and
+/// numbers devices sharing the same name in enumeration order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputDeviceInfo {
+    pub id: String,
+    pub name: String,
+}
+
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    let devices = input_devices_with_ids(&cpal::default_host())?
+        .into_iter()
+        .map(|(info, _)| info)
+        .collect();
+
+    Ok(devices)
+}
+
+/// Picks the device with the id from `list_input_devices`, so devices sharing a name
+/// can be told apart. Falls back to the default device when the id is not connected.
+#[allow(unused_variables)]
+pub fn get_device_by_id_or_default(device_id: Option<String>) -> cpal::Device {
+    let host = cpal::default_host();
+    #[cfg(target_os = "macos")]
+    return host
+        .default_input_device()
+        .expect("Failed to get default input device");
+    #[cfg(not(target_os = "macos"))]
+    device_id
+        .and_then(|device_id| {
+            input_devices_with_ids(&host)
+                .ok()?
+                .into_iter()
+                .find(|(info, _)| info.id == device_id)
+                .map(|(_, device)| device)
+        })
+        .unwrap_or_else(|| {
+            host.default_input_device()
+                .expect("Failed to get default input device")
+        })
+}
+
+fn input_devices_with_ids(
+    host: &cpal::Host,
+) -> Result<Vec<(InputDeviceInfo, cpal::Device)>, String> {
+    let host_name = host.id().name();
+    let devices = host.input_devices().map_err(|e| e.to_string())?;
+
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    let devices = devices
+        .filter_map(|device| device.name().ok().map(|name| (name, device)))
+        .map(|(name, device)| {
+            let count = name_counts.entry(name.clone()).or_insert(0);
+            *count += 1;
+            let id = if *count == 1 {
+                format!("{}:{}", host_name, name)
+            } else {
+                format!("{}:{}#{}", host_name, name, count)
+            };
+            (InputDeviceInfo { id, name }, device)
+        })
+        .collect();
+
+    Ok(devices)
+}
+
 pub fn build_stream(
     device: &cpal::Device,
     wav_writer: &Arc<Mutex<WavWriter<BufWriter<File>>>>,
     on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
     err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
+    build_stream_with_recording_flag(
+        device,
+        wav_writer,
+        Arc::new(AtomicBool::new(true)),
+        on_audio_data,
+        err_fn,
+    )
+}
+
+/// Same as `build_stream`, but samples are only written (and passed to `on_audio_data`)
+/// while `recording` is set. Sharing one flag between the input and the system output
+/// streams pauses and resumes both at the same sample, so the files stay aligned.
+pub fn build_stream_with_recording_flag(
+    device: &cpal::Device,
+    wav_writer: &Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    recording: Arc<AtomicBool>,
+    on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
     let config: SupportedStreamConfig = device
         .default_input_config()
@@ -91,7 +178,6 @@ pub fn build_stream(
     let sample_format = config.sample_format();
     let original_sample_rate = config.sample_rate().0;
     let stream_config: cpal::StreamConfig = config.into();
-    let recording = Arc::new(AtomicBool::new(true));
 
     match sample_format {
         cpal::SampleFormat::I16 => build_i16_stream(
@@ -154,6 +240,20 @@ pub fn build_stream(
 pub fn build_output_stream(
     wav_writer: &Arc<Mutex<WavWriter<BufWriter<File>>>>,
     on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
+) -> Result<RUHear, Box<dyn std::error::Error>> {
+    build_output_stream_with_recording_flag(
+        wav_writer,
+        Arc::new(AtomicBool::new(true)),
+        on_audio_data,
+    )
+}
+
+/// Same as `build_output_stream`, but system audio is only written (and passed to
+/// `on_audio_data`) while `recording` is set. See `build_stream_with_recording_flag`.
+pub fn build_output_stream_with_recording_flag(
+    wav_writer: &Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    recording: Arc<AtomicBool>,
+    on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
 ) -> Result<RUHear, Box<dyn std::error::Error>> {
     let writer = wav_writer.clone();
     let mut on_audio_data = on_audio_data;
@@ -170,6 +270,10 @@ pub fn build_output_stream(
 
     // Create the callback for ruhear
     let callback = move |audio_buffers: RUBuffers| {
+        if !recording.load(Ordering::SeqCst) {
+            return;
+        }
+
         if let Some(mut writer_guard) = writer.try_lock().ok() {
             let mut converted_samples = Vec::new();
 
@@ -295,13 +399,172 @@ pub fn build_wav_writer<P: AsRef<Path>>(
     build_wav_writer_with_rate(file_path, None)
 }
 
+/// Destination for the resampled 16kHz mono samples produced by the input streams.
+pub trait SampleSink: Send + 'static {
+    fn write_sample(&mut self, sample: f32) -> Result<(), hound::Error>;
+}
+
+impl SampleSink for WavWriter<BufWriter<File>> {
+    fn write_sample(&mut self, sample: f32) -> Result<(), hound::Error> {
+        WavWriter::write_sample(self, sample)
+    }
+}
+
+/// In-memory ring buffer holding the last moments of microphone audio.
+///
+/// While detached it only keeps the most recent `duration_ms` of audio and never
+/// touches the disk. Attaching a WAV writer flushes the buffered audio into it and
+/// from then on passes every sample straight through, so a recording started from
+/// the pre-roll capture has no gap while a new input stream warms up.
+pub struct PreRollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    writer: Option<Arc<Mutex<WavWriter<BufWriter<File>>>>>,
+    recording: Arc<AtomicBool>,
+}
+
+impl PreRollBuffer {
+    pub fn new(duration_ms: u32) -> Self {
+        // Samples are stored after resampling to 16kHz, so 16 samples per millisecond
+        let capacity = duration_ms as usize * 16;
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            writer: None,
+            recording: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Flushes the buffered audio into `wav_writer` and starts recording into it.
+    /// Returns the number of buffered samples that were written.
+    pub fn attach(
+        &mut self,
+        wav_writer: Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    ) -> Result<usize, hound::Error> {
+        let flushed = self.samples.len();
+        {
+            let mut writer_guard = wav_writer.lock().expect("Failed to lock writer");
+            for sample in self.samples.drain(..) {
+                writer_guard.write_sample(sample)?;
+            }
+        }
+
+        self.writer = Some(wav_writer);
+        self.recording.store(true, Ordering::SeqCst);
+        Ok(flushed)
+    }
+
+    /// Stops writing to the attached writer without closing it.
+    pub fn set_paused(&self, paused: bool) {
+        if self.writer.is_some() {
+            self.recording.store(!paused, Ordering::SeqCst);
+        }
+    }
+
+    /// Drops the attached writer, which finalizes the WAV file once nothing else holds it.
+    /// The ring buffer starts empty again so recorded audio is never reused as pre-roll.
+    pub fn detach(&mut self) {
+        self.recording.store(false, Ordering::SeqCst);
+        self.writer = None;
+        self.samples.clear();
+    }
+}
+
+impl SampleSink for PreRollBuffer {
+    fn write_sample(&mut self, sample: f32) -> Result<(), hound::Error> {
+        match &self.writer {
+            Some(writer) => {
+                if self.recording.load(Ordering::SeqCst) {
+                    writer
+                        .lock()
+                        .expect("Failed to lock writer")
+                        .write_sample(sample)?;
+                }
+            }
+            None => {
+                if self.capacity == 0 {
+                    return Ok(());
+                }
+                if self.samples.len() >= self.capacity {
+                    self.samples.pop_front();
+                }
+                self.samples.push_back(sample);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Build an always-on input stream that feeds a `PreRollBuffer`.
+///
+/// `on_audio_data` is only called while the buffer is attached to a writer and not paused,
+/// so listeners see the same audio as a regular recording stream.
+pub fn build_pre_roll_stream(
+    device: &cpal::Device,
+    buffer: &Arc<Mutex<PreRollBuffer>>,
+    mut on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
+    let config: SupportedStreamConfig = device.default_input_config()?.into();
+    let sample_format = config.sample_format();
+    let original_sample_rate = config.sample_rate().0;
+    let stream_config: cpal::StreamConfig = config.into();
+
+    let recording = buffer
+        .lock()
+        .expect("Failed to lock pre-roll buffer")
+        .recording
+        .clone();
+    let on_audio_data = move |data: Vec<i16>| {
+        if recording.load(Ordering::SeqCst) {
+            on_audio_data(data);
+        }
+    };
+    // The buffer decides where samples go, so the stream itself always captures
+    let capturing = Arc::new(AtomicBool::new(true));
+
+    match sample_format {
+        cpal::SampleFormat::I16 => build_i16_stream(
+            device,
+            &stream_config,
+            on_audio_data,
+            err_fn,
+            buffer.clone(),
+            capturing,
+            original_sample_rate,
+        ),
+        cpal::SampleFormat::U16 => build_u16_stream(
+            device,
+            &stream_config,
+            on_audio_data,
+            err_fn,
+            buffer.clone(),
+            capturing,
+            original_sample_rate,
+        ),
+        cpal::SampleFormat::F32 => build_f32_stream(
+            device,
+            &stream_config,
+            on_audio_data,
+            err_fn,
+            buffer.clone(),
+            capturing,
+            original_sample_rate,
+        ),
+        _ => Err(Box::new(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Unsupported sample format",
+        ))),
+    }
+}
+
 // Build an i16 audio stream - standalone function
-fn build_i16_stream(
+fn build_i16_stream<S: SampleSink>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
     mut on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
     err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    writer: Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    writer: Arc<Mutex<S>>,
     recording: Arc<AtomicBool>,
     original_sample_rate: u32,
 ) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
@@ -367,12 +630,12 @@ fn build_i16_stream(
 }
 
 // Build a u16 audio stream - standalone function
-fn build_u16_stream(
+fn build_u16_stream<S: SampleSink>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
     mut on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
     err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    writer: Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    writer: Arc<Mutex<S>>,
     recording: Arc<AtomicBool>,
     original_sample_rate: u32,
 ) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
@@ -441,12 +704,12 @@ fn build_u16_stream(
 }
 
 // Build an f32 audio stream - standalone function
-fn build_f32_stream(
+fn build_f32_stream<S: SampleSink>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
     mut on_audio_data: impl FnMut(Vec<i16>) + Send + 'static,
     err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    writer: Arc<Mutex<WavWriter<BufWriter<File>>>>,
+    writer: Arc<Mutex<S>>,
     recording: Arc<AtomicBool>,
     original_sample_rate: u32,
 ) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
@@ -1053,3 +1316,62 @@ pub fn apply_simple_echo_cancellation<P: AsRef<Path>>(
         Err("Simple echo cancellation completed but output file was not created".to_string())
     }
 }
+
+/// Compressed formats recordings can be archived in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioEncoding {
+    Flac,
+    Opus,
+}
+
+impl AudioEncoding {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioEncoding::Flac => "flac",
+            AudioEncoding::Opus => "ogg",
+        }
+    }
+}
+
+/// Encode a recording into a compressed mono file for long-term storage.
+/// FLAC is lossless (16-bit), Opus is lossy but tuned for speech and much smaller.
+pub fn encode_audio_file<P: AsRef<Path>>(
+    input_file: P,
+    output_file: P,
+    encoding: AudioEncoding,
+) -> Result<(), String> {
+    let input_path = input_file.as_ref();
+    let output_path = output_file.as_ref();
+
+    log::debug!("Encoding {:?} ({:?})", input_path, encoding);
+    log::debug!("  Output: {:?}", output_path);
+
+    if !input_path.exists() {
+        return Err(format!("Input file does not exist: {:?}", input_path));
+    }
+
+    // Ensure FFmpeg binaries are available
+    ensure_ffmpeg_available()?;
+
+    let codec_args = match encoding {
+        AudioEncoding::Flac => ["-acodec", "flac", "-sample_fmt", "s16"],
+        AudioEncoding::Opus => ["-acodec", "libopus", "-b:a", "24k"],
+    };
+
+    let ffmpeg = FfmpegCommand::new()
+        .input(input_path.to_str().unwrap())
+        .args(["-ac", "1"])
+        .args(codec_args)
+        .args(["-y"])
+        .output(output_path.to_str().unwrap())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn ffmpeg process: {}", e))?;
+
+    process_ffmpeg_output(ffmpeg, "Audio encoding")?;
+
+    if !output_path.exists() {
+        return Err(format!("Encoded file was not created: {:?}", output_path));
+    }
+
+    Ok(())
+}
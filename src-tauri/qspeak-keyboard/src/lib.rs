@@ -24,6 +24,13 @@ pub fn set_text_in_clipboard(text: &str) -> Result<(), Box<dyn std::error::Error
     }
 }
 
+pub fn get_text_from_clipboard() -> Result<String, Box<dyn std::error::Error>> {
+    match CLIPBOARD.lock() {
+        Ok(mut clipboard) => Ok(clipboard.get_text()?),
+        Err(e) => Err(e.to_string().into()),
+    }
+}
+
 pub enum KeyboardError {
     MissingAccessibilityPermissionsError,
     FailedToInstantiateKeyboardError,
@@ -7,7 +7,7 @@ use std::{
     sync::mpsc,
     time::{Duration, SystemTime},
 };
-use std::{net::TcpStream, sync::Mutex};
+use std::sync::Mutex;
 use tauri::Manager;
 use tauri_plugin_fs::FsExt;
 use tauri_plugin_global_shortcut::{Shortcut, ShortcutState};
@@ -21,11 +21,18 @@ mod state_machine;
 use state_machine::{
     Event,
     account::AccountProcessor,
+    audio_retention::AudioRetentionProcessor,
     challenges::ChallengeProcessor,
-    history::HistoryProcessor,
+    connectivity::{self, ConnectivityProcessor},
+    devices::DevicesProcessor,
+    history::{History, HistoryProcessor},
+    history_store::{self, HistorySearchQuery, HistorySearchResult},
+    mcp_server::{MCPServerProcessor, STDIO_BRIDGE_ARG, run_stdio_bridge},
+    mcp_servers_json::export_mcp_servers_json,
+    mcp_supervisor::{MCPServerLogEntry, get_server_logs},
     new_app::{AppProcessor, cleanup_children},
     new_conversation::ConversationProcessor,
-    new_mcp_processor::MCPProcessor,
+    new_mcp_processor::{MCPProcessor, MCPServerTool},
     new_models::ModelsProcessor,
     new_onboarding_window::OnboardingWindowProcessor,
     new_personas::PersonasProcessor,
@@ -37,6 +44,7 @@ use state_machine::{
     processor::Processor,
     releases::ReleasesProcessor,
     state::{AppState, AppStateChannelMessage, AppStateContext, ConversationState},
+    usage::{self, UsageStats, UsageStatsQuery},
     websocket_server::WebsocketServerProcessor,
 };
 
@@ -102,13 +110,55 @@ fn listen_for_audio_data(channel: tauri::ipc::Channel<Vec<i16>>) -> Result<(), S
     Ok(())
 }
 
+#[tauri::command]
+async fn export_mcp_servers() -> Result<String, String> {
+    let context = AppState::get_context();
+    export_mcp_servers_json(&context.mcp_context.server_configs).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_mcp_server_logs(key: String) -> Result<Vec<MCPServerLogEntry>, String> {
+    Ok(get_server_logs(&key))
+}
+
+#[tauri::command]
+async fn get_mcp_server_tools(key: String) -> Result<Vec<MCPServerTool>, String> {
+    MCPProcessor::list_server_tools(&key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_history_conversation(id: String) -> Result<Option<History>, String> {
+    history_store::get(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn search_history(query: HistorySearchQuery) -> Result<Vec<HistorySearchResult>, String> {
+    history_store::search(&query).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_usage_stats(query: UsageStatsQuery) -> Result<UsageStats, String> {
+    usage::stats(&query).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn check_online() -> bool {
-    TcpStream::connect_timeout(&"8.8.8.8:53".parse().unwrap(), Duration::from_secs(3)).is_ok()
+    connectivity::is_online()
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == STDIO_BRIDGE_ARG) {
+        if let Err(e) = run_stdio_bridge(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     #[cfg(debug_assertions)]
     let client = sentry::init({});
 
@@ -298,6 +348,10 @@ pub fn run() {
             WindowStateCache::load_static(&app.handle().clone());
             let (sender, receiver) = mpsc::channel();
             AppState::set_app_handle(app.handle().clone());
+            // The history migration and summaries read from it while the state loads
+            if let Err(e) = history_store::init(app.handle()) {
+                log::error!("Failed to open the history database: {}", e);
+            }
             AppState::load_context_from_store().expect("Failed to load context from store");
 
             let app_context = AppState::get_context();
@@ -317,9 +371,11 @@ pub fn run() {
 
             Processor::start(app.handle().clone(), sender, receiver)
                 .expect("Failed to start processor");
+            DevicesProcessor::start();
             AppProcessor::start(app.handle().clone()).expect("Failed to start app processor");
             RecordingWindowProcessor::start();
             ModelsProcessor::start().expect("Failed to start models processor");
+            ConnectivityProcessor::start();
             SettingsWindowProcessor::start();
             OnboardingWindowProcessor::start();
             PersonasProcessor::start();
@@ -327,13 +383,15 @@ pub fn run() {
             KeybindsProcessor::start(app.handle().clone())
                 .expect("Failed to start keybinds processor");
             HistoryProcessor::start();
+            AudioRetentionProcessor::start(app.handle().clone());
             AccountProcessor::start();
             UpdateProcessor::start().expect("Failed to start update processor");
             PermissionsProcessor::start();
             ChallengeProcessor::start();
-            MCPProcessor::start().expect("Failed to start MCP processor");
+            MCPProcessor::start(app.handle().clone()).expect("Failed to start MCP processor");
             ReleasesProcessor::start();
             WebsocketServerProcessor::start().expect("Failed to start websocket server processor");
+            MCPServerProcessor::start().expect("Failed to start MCP server processor");
             // KoboldCppServerProcessor::start(app.handle().clone())?;
 
             let scope = app.fs_scope();
@@ -364,6 +422,12 @@ pub fn run() {
             get_audio_devices,
             listen_for_audio_data,
             check_online,
+            export_mcp_servers,
+            get_mcp_server_logs,
+            get_mcp_server_tools,
+            get_history_conversation,
+            search_history,
+            get_usage_stats,
             event,
             get_new_app_state,
             subscribe_to_new_app_state,
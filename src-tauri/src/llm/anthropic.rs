@@ -0,0 +1,439 @@
+use async_stream::stream;
+use eventsource_stream::Eventsource;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::error::Error;
+
+use super::{
+    ChatCompletionChunkToolCall, ChatCompletionChunkToolCallFunction, ChatCompletionMessage,
+    ChatCompletionMessageContent, ChunkMessage, ChunkStream, OpenAIClientConfig, TokenUsage,
+    retry::send_with_retry,
+};
+use crate::state_machine::cancellation::CancellationToken;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+// The Messages API requires a limit on the answer
+const MAX_TOKENS: u32 = 8192;
+
+// Server-sent events of the Messages API, the ones not listed are only progress markers
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    MessageStart {
+        message: MessageStart,
+    },
+    ContentBlockStart {
+        index: i32,
+        content_block: ContentBlock,
+    },
+    ContentBlockDelta {
+        index: i32,
+        delta: ContentDelta,
+    },
+    // Carries the output tokens so far
+    MessageDelta {
+        usage: Usage,
+    },
+    MessageStop,
+    Error {
+        error: ApiError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    ToolUse {
+        id: String,
+        name: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MessageStart {
+    pub usage: Usage,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiError {
+    pub message: String,
+}
+
+// Collects tool calls and usage over the stream, they are only complete at the end of the message
+#[derive(Debug, Default)]
+pub struct StreamState {
+    tool_calls: Vec<ChatCompletionChunkToolCall>,
+    usage: TokenUsage,
+}
+
+impl StreamState {
+    pub fn handle_event(&mut self, event: StreamEvent) -> Vec<ChunkMessage> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.usage = message.usage.into();
+                vec![]
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block: ContentBlock::ToolUse { id, name },
+            } => {
+                self.tool_calls.push(ChatCompletionChunkToolCall {
+                    id: Some(id),
+                    index,
+                    r#type: Some("function".to_string()),
+                    function: ChatCompletionChunkToolCallFunction {
+                        name: Some(name),
+                        arguments: String::new(),
+                    },
+                });
+                vec![]
+            }
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::TextDelta { text },
+                ..
+            } => vec![ChunkMessage::Text(text)],
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: ContentDelta::InputJsonDelta { partial_json },
+            } => {
+                if let Some(tool_call) = self.tool_calls.iter_mut().find(|call| call.index == index)
+                {
+                    tool_call.function.arguments += &partial_json;
+                }
+                vec![]
+            }
+            StreamEvent::MessageDelta { usage } => {
+                self.usage.completion_tokens = usage.output_tokens;
+                vec![]
+            }
+            StreamEvent::MessageStop => std::mem::take(&mut self.tool_calls)
+                .into_iter()
+                .map(ChunkMessage::ToolCall)
+                .chain([ChunkMessage::Usage(std::mem::take(&mut self.usage))])
+                .collect(),
+            StreamEvent::Error { error } => vec![ChunkMessage::Error(error.message)],
+            _ => vec![],
+        }
+    }
+}
+
+pub async fn chat_completion(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    tools: Option<Vec<Value>>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    cancellation: CancellationToken,
+) -> Result<ChunkStream, Box<dyn Error>> {
+    let mut payload = build_payload(&messages, &config.model);
+    add_options(&mut payload, &config, config.response_format.as_ref());
+    payload["stream"] = json!(true);
+    if let Some(tools) = tools {
+        payload["tools"] = tools.iter().map(convert_tool).collect();
+    }
+
+    let response = cancellation
+        .run(send(client, &config.url, api_key, &payload))
+        .await??;
+
+    let mut stream = response.bytes_stream().eventsource();
+    let message_stream = stream! {
+        let mut state = StreamState::default();
+
+        while let Some(event) = cancellation.run(stream.next()).await.ok().flatten() {
+            let Ok(event) = event else {
+                continue;
+            };
+            match serde_json::from_str::<StreamEvent>(&event.data) {
+                Ok(event) => {
+                    for message in state.handle_event(event) {
+                        yield message;
+                    }
+                }
+                Err(e) => log::warn!("Failed to parse Anthropic event: {}", e),
+            }
+        }
+    };
+
+    Ok(Box::pin(message_stream))
+}
+
+pub async fn chat_completion_non_streaming(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    response_format: Option<Value>,
+) -> Result<(String, Option<TokenUsage>), Box<dyn Error>> {
+    let mut payload = build_payload(&messages, &config.model);
+    add_options(&mut payload, &config, response_format.as_ref());
+
+    let response = send(client, &config.url, api_key, &payload).await?;
+    let response_json: Value = response.json().await?;
+    let usage = serde_json::from_value::<Usage>(response_json["usage"].clone())
+        .ok()
+        .map(TokenUsage::from);
+    Ok((parse_response(&response_json)?, usage))
+}
+
+fn add_options(payload: &mut Value, config: &OpenAIClientConfig, response_format: Option<&Value>) {
+    let generation = &config.generation;
+    if let Some(temperature) = generation.temperature {
+        payload["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = generation.top_p {
+        payload["top_p"] = json!(top_p);
+    }
+    if let Some(max_tokens) = generation.max_tokens {
+        payload["max_tokens"] = json!(max_tokens);
+    }
+    if !generation.stop.is_empty() {
+        payload["stop_sequences"] = json!(generation.stop);
+    }
+
+    // There is no structured output, the schema is asked for in the system prompt instead
+    if let Some(schema) = response_format.and_then(|format| format.pointer("/json_schema/schema")) {
+        let instruction = format!(
+            "Respond only with a JSON object matching this schema, without any other text: {}",
+            schema
+        );
+        payload["system"] = match payload["system"].as_str() {
+            Some(system) => json!(format!("{}\n\n{}", system, instruction)),
+            None => json!(instruction),
+        };
+    }
+}
+
+// Text of a whole Messages API response
+pub fn parse_response(response_json: &Value) -> Result<String, Box<dyn Error>> {
+    let content = response_json["content"]
+        .as_array()
+        .ok_or("Failed to parse Anthropic response")?;
+    Ok(content
+        .iter()
+        .filter_map(|block| block["text"].as_str())
+        .collect::<Vec<_>>()
+        .join(""))
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: Option<String>,
+    payload: &Value,
+) -> Result<reqwest::Response, Box<dyn Error>> {
+    let url = format!("{}/messages", url);
+    let api_key = api_key.unwrap_or_default();
+    let response = send_with_retry(|| {
+        client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header("x-api-key", &api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(payload)
+    })
+    .await
+    .map_err(|e| format!("Failed to get Anthropic response: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_message = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to get Anthropic response: {} {}",
+            status, error_message
+        )
+        .into());
+    }
+    Ok(response)
+}
+
+pub fn build_payload(messages: &[ChatCompletionMessage], model: &str) -> Value {
+    let mut system = vec![];
+    let mut turns: Vec<(String, Vec<Value>)> = vec![];
+
+    for message in messages {
+        let (role, blocks): (String, Vec<Value>) = match message {
+            ChatCompletionMessage::ChatCompletionTextMessage(message)
+                if message.role == "system" =>
+            {
+                system.extend(message.content.iter().filter_map(|content| match content {
+                    ChatCompletionMessageContent::Text { text } => Some(text.clone()),
+                    ChatCompletionMessageContent::Image { .. } => None,
+                }));
+                continue;
+            }
+            ChatCompletionMessage::ChatCompletionTextMessage(message) => (
+                message.role.clone(),
+                message.content.iter().filter_map(convert_content).collect(),
+            ),
+            ChatCompletionMessage::ChatCompletionToolCallMessage(message) => (
+                "assistant".to_string(),
+                message
+                    .tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        json!({
+                            "type": "tool_use",
+                            "id": tool_call.id,
+                            "name": tool_call.function.name,
+                            "input": parse_arguments(&tool_call.function.arguments),
+                        })
+                    })
+                    .collect(),
+            ),
+            ChatCompletionMessage::ChatCompletionToolCallResultMessage(message) => (
+                "user".to_string(),
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": message.content,
+                })],
+            ),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+
+        // Roles have to alternate, so results of parallel tool calls end up in one message
+        match turns.last_mut() {
+            Some((last_role, last_blocks)) if *last_role == role => last_blocks.extend(blocks),
+            _ => turns.push((role, blocks)),
+        }
+    }
+
+    let mut payload = json!({
+        "model": model,
+        "max_tokens": MAX_TOKENS,
+        "messages": turns
+            .into_iter()
+            .map(|(role, content)| json!({ "role": role, "content": content }))
+            .collect::<Vec<_>>(),
+    });
+    if !system.is_empty() {
+        payload["system"] = json!(system.join("\n\n"));
+    }
+    payload
+}
+
+// Empty text blocks are rejected by the API
+fn convert_content(content: &ChatCompletionMessageContent) -> Option<Value> {
+    match content {
+        ChatCompletionMessageContent::Text { text } if text.is_empty() => None,
+        ChatCompletionMessageContent::Text { text } => {
+            Some(json!({ "type": "text", "text": text }))
+        }
+        ChatCompletionMessageContent::Image { image_url } => Some(match image_url.data() {
+            Some((media_type, data)) => json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": media_type, "data": data },
+            }),
+            None => json!({
+                "type": "image",
+                "source": { "type": "url", "url": image_url.url },
+            }),
+        }),
+    }
+}
+
+// Tools come in the OpenAI format
+fn convert_tool(tool: &Value) -> Value {
+    json!({
+        "name": tool["function"]["name"],
+        "description": tool["function"]["description"].as_str().unwrap_or_default(),
+        "input_schema": tool["function"]["parameters"],
+    })
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Replays a recorded response body the way chat_completion reads it
+    async fn replay(body: &'static str) -> Vec<ChunkMessage> {
+        let mut events =
+            futures_util::stream::iter([Ok::<_, std::convert::Infallible>(body)]).eventsource();
+        let mut state = StreamState::default();
+        let mut messages = vec![];
+        while let Some(event) = events.next().await {
+            let event = event.expect("recorded body is valid SSE");
+            let event =
+                serde_json::from_str::<StreamEvent>(&event.data).expect("recorded event is parsed");
+            messages.extend(state.handle_event(event));
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn streams_text_then_tool_calls_and_usage_at_message_stop() {
+        let messages = replay(include_str!("fixtures/anthropic_stream.sse")).await;
+
+        let [
+            ChunkMessage::Text(first),
+            ChunkMessage::Text(second),
+            ChunkMessage::ToolCall(tool_call),
+            ChunkMessage::Usage(usage),
+        ] = messages.as_slice()
+        else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(format!("{}{}", first, second), "Let me check the weather.");
+        assert_eq!(
+            tool_call.id.as_deref(),
+            Some("toolu_01T1x1fJ34qAmk2tNTrN7Up6")
+        );
+        assert_eq!(tool_call.index, 1);
+        assert_eq!(tool_call.function.name.as_deref(), Some("get_weather"));
+        assert_eq!(tool_call.function.arguments, r#"{"location": "Warsaw"}"#);
+        assert_eq!(usage.prompt_tokens, 472);
+        assert_eq!(usage.completion_tokens, 89);
+    }
+
+    #[tokio::test]
+    async fn passes_on_stream_errors() {
+        let messages = replay(include_str!("fixtures/anthropic_error.sse")).await;
+
+        let [ChunkMessage::Error(error)] = messages.as_slice() else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(error, "Overloaded");
+    }
+}
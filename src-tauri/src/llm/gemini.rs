@@ -0,0 +1,430 @@
+use async_stream::stream;
+use eventsource_stream::Eventsource;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{collections::HashMap, error::Error};
+
+use super::{
+    ChatCompletionChunkToolCall, ChatCompletionChunkToolCallFunction, ChatCompletionMessage,
+    ChatCompletionMessageContent, ChunkMessage, ChunkStream, OpenAIClientConfig, TokenUsage,
+    retry::send_with_retry,
+};
+use crate::state_machine::cancellation::CancellationToken;
+
+// Every streamed event is a partial response of the same shape as the whole one
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentResponse {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub error: Option<ApiError>,
+    // Running total in streamed events
+    pub usage_metadata: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u64,
+    #[serde(default)]
+    pub candidates_token_count: u64,
+    // Thinking models bill their reasoning as output
+    #[serde(default)]
+    pub thoughts_token_count: u64,
+}
+
+impl From<UsageMetadata> for TokenUsage {
+    fn from(usage: UsageMetadata) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count + usage.thoughts_token_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Candidate {
+    pub content: Option<Content>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Content {
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Part {
+    pub text: Option<String>,
+    pub function_call: Option<FunctionCall>,
+    // Reasoning of thinking models, not part of the answer
+    #[serde(default)]
+    pub thought: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FunctionCall {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiError {
+    pub message: String,
+}
+
+impl GenerateContentResponse {
+    fn parts(self) -> impl Iterator<Item = Part> {
+        self.candidates
+            .into_iter()
+            .take(1)
+            .filter_map(|candidate| candidate.content)
+            .flat_map(|content| content.parts)
+            .filter(|part| !part.thought)
+    }
+}
+
+// Function calls arrive whole, they are passed on with the usage once the stream ends
+#[derive(Debug, Default)]
+pub struct StreamState {
+    tool_calls: Vec<ChatCompletionChunkToolCall>,
+    usage: Option<TokenUsage>,
+}
+
+impl StreamState {
+    pub fn handle_event(&mut self, mut response: GenerateContentResponse) -> Vec<ChunkMessage> {
+        if let Some(error) = response.error {
+            return vec![ChunkMessage::Error(error.message)];
+        }
+        if let Some(usage) = response.usage_metadata.take() {
+            self.usage = Some(usage.into());
+        }
+
+        let mut messages = vec![];
+        for part in response.parts() {
+            if let Some(text) = part.text.filter(|text| !text.is_empty()) {
+                messages.push(ChunkMessage::Text(text));
+            }
+            if let Some(function_call) = part.function_call {
+                self.tool_calls.push(ChatCompletionChunkToolCall {
+                    id: Some(
+                        function_call
+                            .id
+                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                    ),
+                    index: self.tool_calls.len() as i32,
+                    r#type: Some("function".to_string()),
+                    function: ChatCompletionChunkToolCallFunction {
+                        name: Some(function_call.name),
+                        arguments: function_call.args.to_string(),
+                    },
+                });
+            }
+        }
+        messages
+    }
+
+    pub fn finish(&mut self) -> Vec<ChunkMessage> {
+        std::mem::take(&mut self.tool_calls)
+            .into_iter()
+            .map(ChunkMessage::ToolCall)
+            .chain(self.usage.take().map(ChunkMessage::Usage))
+            .collect()
+    }
+}
+
+pub async fn chat_completion(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    tools: Option<Vec<Value>>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    cancellation: CancellationToken,
+) -> Result<ChunkStream, Box<dyn Error>> {
+    let mut payload = build_payload(&messages);
+    add_options(&mut payload, &config, config.response_format.as_ref());
+    if let Some(tools) = tools {
+        let declarations: Vec<Value> = tools.iter().map(convert_tool).collect();
+        payload["tools"] = json!([{ "functionDeclarations": declarations }]);
+    }
+
+    let url = format!(
+        "{}/models/{}:streamGenerateContent?alt=sse",
+        config.url, config.model
+    );
+    let response = cancellation
+        .run(send(client, &url, api_key, &payload))
+        .await??;
+
+    let mut stream = response.bytes_stream().eventsource();
+    let message_stream = stream! {
+        let mut state = StreamState::default();
+
+        while let Some(event) = cancellation.run(stream.next()).await.ok().flatten() {
+            let Ok(event) = event else {
+                continue;
+            };
+            match serde_json::from_str::<GenerateContentResponse>(&event.data) {
+                Ok(response) => {
+                    for message in state.handle_event(response) {
+                        yield message;
+                    }
+                }
+                Err(e) => log::warn!("Failed to parse Gemini event: {}", e),
+            }
+        }
+
+        if !cancellation.is_cancelled() {
+            for message in state.finish() {
+                yield message;
+            }
+        }
+    };
+
+    Ok(Box::pin(message_stream))
+}
+
+pub async fn chat_completion_non_streaming(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    response_format: Option<Value>,
+) -> Result<(String, Option<TokenUsage>), Box<dyn Error>> {
+    let mut payload = build_payload(&messages);
+    add_options(&mut payload, &config, response_format.as_ref());
+
+    let url = format!("{}/models/{}:generateContent", config.url, config.model);
+    let response = send(client, &url, api_key, &payload).await?;
+    let mut response: GenerateContentResponse = response.json().await?;
+    if let Some(error) = response.error {
+        return Err(format!("Failed to get Gemini response: {}", error.message).into());
+    }
+    let usage = response.usage_metadata.take().map(TokenUsage::from);
+    let text = response
+        .parts()
+        .filter_map(|part| part.text)
+        .collect::<Vec<_>>()
+        .join("");
+    Ok((text, usage))
+}
+
+fn add_options(payload: &mut Value, config: &OpenAIClientConfig, response_format: Option<&Value>) {
+    let generation = &config.generation;
+    let mut generation_config = json!({});
+    if let Some(temperature) = generation.temperature {
+        generation_config["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = generation.top_p {
+        generation_config["topP"] = json!(top_p);
+    }
+    if let Some(max_tokens) = generation.max_tokens {
+        generation_config["maxOutputTokens"] = json!(max_tokens);
+    }
+    if !generation.stop.is_empty() {
+        generation_config["stopSequences"] = json!(generation.stop);
+    }
+    if let Some(schema) = response_format.and_then(|format| format.pointer("/json_schema/schema")) {
+        generation_config["responseMimeType"] = json!("application/json");
+        generation_config["responseJsonSchema"] = schema.clone();
+    }
+    payload["generationConfig"] = generation_config;
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: Option<String>,
+    payload: &Value,
+) -> Result<reqwest::Response, Box<dyn Error>> {
+    let api_key = api_key.unwrap_or_default();
+    let response = send_with_retry(|| {
+        client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", &api_key)
+            .json(payload)
+    })
+    .await
+    .map_err(|e| format!("Failed to get Gemini response: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_message = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to get Gemini response: {} {}",
+            status, error_message
+        )
+        .into());
+    }
+    Ok(response)
+}
+
+pub fn build_payload(messages: &[ChatCompletionMessage]) -> Value {
+    let mut system = vec![];
+    let mut contents: Vec<(&str, Vec<Value>)> = vec![];
+    // Function responses are matched to their calls by the function name
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+
+    for message in messages {
+        let (role, parts): (&str, Vec<Value>) = match message {
+            ChatCompletionMessage::ChatCompletionTextMessage(message)
+                if message.role == "system" =>
+            {
+                system.extend(message.content.iter().filter_map(|content| match content {
+                    ChatCompletionMessageContent::Text { text } => Some(json!({ "text": text })),
+                    ChatCompletionMessageContent::Image { .. } => None,
+                }));
+                continue;
+            }
+            ChatCompletionMessage::ChatCompletionTextMessage(message) => (
+                if message.role == "assistant" {
+                    "model"
+                } else {
+                    "user"
+                },
+                message.content.iter().filter_map(convert_content).collect(),
+            ),
+            ChatCompletionMessage::ChatCompletionToolCallMessage(message) => (
+                "model",
+                message
+                    .tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        tool_names.insert(&tool_call.id, &tool_call.function.name);
+                        json!({
+                            "functionCall": {
+                                "name": tool_call.function.name,
+                                "args": parse_arguments(&tool_call.function.arguments),
+                            }
+                        })
+                    })
+                    .collect(),
+            ),
+            ChatCompletionMessage::ChatCompletionToolCallResultMessage(message) => (
+                "user",
+                vec![json!({
+                    "functionResponse": {
+                        "name": tool_names.get(message.tool_call_id.as_str()),
+                        "response": { "content": message.content },
+                    }
+                })],
+            ),
+        };
+        if parts.is_empty() {
+            continue;
+        }
+
+        // Responses to parallel calls have to be in one turn
+        match contents.last_mut() {
+            Some((last_role, last_parts)) if *last_role == role => last_parts.extend(parts),
+            _ => contents.push((role, parts)),
+        }
+    }
+
+    let mut payload = json!({
+        "contents": contents
+            .into_iter()
+            .map(|(role, parts)| json!({ "role": role, "parts": parts }))
+            .collect::<Vec<_>>(),
+    });
+    if !system.is_empty() {
+        payload["systemInstruction"] = json!({ "parts": system });
+    }
+    payload
+}
+
+fn convert_content(content: &ChatCompletionMessageContent) -> Option<Value> {
+    match content {
+        ChatCompletionMessageContent::Text { text } if text.is_empty() => None,
+        ChatCompletionMessageContent::Text { text } => Some(json!({ "text": text })),
+        ChatCompletionMessageContent::Image { image_url } => match image_url.data() {
+            Some((media_type, data)) => Some(json!({
+                "inlineData": { "mimeType": media_type, "data": data },
+            })),
+            None => {
+                log::warn!(
+                    "Gemini only accepts embedded images, leaving out {}",
+                    image_url.url
+                );
+                None
+            }
+        },
+    }
+}
+
+// Tools come in the OpenAI format, the schema is passed as JSON Schema rather than Gemini's subset
+fn convert_tool(tool: &Value) -> Value {
+    json!({
+        "name": tool["function"]["name"],
+        "description": tool["function"]["description"].as_str().unwrap_or_default(),
+        "parametersJsonSchema": tool["function"]["parameters"],
+    })
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Replays a recorded response body the way chat_completion reads it
+    async fn replay(body: &'static str) -> Vec<ChunkMessage> {
+        let mut events =
+            futures_util::stream::iter([Ok::<_, std::convert::Infallible>(body)]).eventsource();
+        let mut state = StreamState::default();
+        let mut messages = vec![];
+        while let Some(event) = events.next().await {
+            let event = event.expect("recorded body is valid SSE");
+            let response = serde_json::from_str::<GenerateContentResponse>(&event.data)
+                .expect("recorded event is parsed");
+            messages.extend(state.handle_event(response));
+        }
+        messages.extend(state.finish());
+        messages
+    }
+
+    #[tokio::test]
+    async fn skips_thoughts_and_passes_on_function_calls_and_usage_at_the_end() {
+        let messages = replay(include_str!("fixtures/gemini_stream.sse")).await;
+
+        let [
+            ChunkMessage::Text(first),
+            ChunkMessage::Text(second),
+            ChunkMessage::ToolCall(tool_call),
+            ChunkMessage::Usage(usage),
+        ] = messages.as_slice()
+        else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(format!("{}{}", first, second), "Let me check the weather.");
+        assert!(tool_call.id.is_some());
+        assert_eq!(tool_call.index, 0);
+        assert_eq!(tool_call.function.name.as_deref(), Some("get_weather"));
+        assert_eq!(tool_call.function.arguments, r#"{"location":"Warsaw"}"#);
+        assert_eq!(usage.prompt_tokens, 31);
+        assert_eq!(usage.completion_tokens, 30);
+    }
+
+    #[test]
+    fn passes_on_api_errors() {
+        let response = serde_json::from_str::<GenerateContentResponse>(
+            r#"{"error": {"code": 429, "message": "Resource has been exhausted", "status": "RESOURCE_EXHAUSTED"}}"#,
+        )
+        .unwrap();
+
+        let messages = StreamState::default().handle_event(response);
+
+        let [ChunkMessage::Error(error)] = messages.as_slice() else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(error, "Resource has been exhausted");
+    }
+}
@@ -1,3 +1,7 @@
+pub mod anthropic;
+pub mod gemini;
+pub mod ollama;
 pub mod openai_client;
+pub mod retry;
 
 pub use openai_client::*;
@@ -0,0 +1,341 @@
+use async_stream::stream;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{collections::HashMap, error::Error};
+
+use super::{
+    ChatCompletionChunkToolCall, ChatCompletionChunkToolCallFunction, ChatCompletionMessage,
+    ChatCompletionMessageContent, ChunkMessage, ChunkStream, OpenAIClientConfig, TokenUsage,
+    retry::send_with_retry,
+};
+use crate::state_machine::cancellation::CancellationToken;
+
+// One line of the newline-delimited JSON the native API streams
+#[derive(Debug, Deserialize)]
+pub struct ChatResponse {
+    pub message: Option<ResponseMessage>,
+    #[serde(default)]
+    pub done: bool,
+    pub error: Option<String>,
+    // Token counts, only in the last line
+    #[serde(default)]
+    pub prompt_eval_count: u64,
+    #[serde(default)]
+    pub eval_count: u64,
+}
+
+impl ChatResponse {
+    fn usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_eval_count,
+            completion_tokens: self.eval_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponseMessage {
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub tool_calls: Vec<ResponseToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponseToolCall {
+    pub function: ResponseToolCallFunction,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResponseToolCallFunction {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+// Tool calls arrive whole and without ids, they are passed on once the answer is done
+#[derive(Debug, Default)]
+pub struct StreamState {
+    tool_calls: Vec<ChatCompletionChunkToolCall>,
+}
+
+impl StreamState {
+    pub fn handle_line(&mut self, line: &[u8]) -> Vec<ChunkMessage> {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return vec![];
+        }
+        let response = match serde_json::from_slice::<ChatResponse>(line) {
+            Ok(response) => response,
+            Err(e) => {
+                log::warn!("Failed to parse Ollama response: {}", e);
+                return vec![];
+            }
+        };
+        if let Some(error) = response.error {
+            return vec![ChunkMessage::Error(error)];
+        }
+
+        let mut messages = vec![];
+        let usage = response.usage();
+        if let Some(message) = response.message {
+            if !message.content.is_empty() {
+                messages.push(ChunkMessage::Text(message.content));
+            }
+            for tool_call in message.tool_calls {
+                self.tool_calls.push(ChatCompletionChunkToolCall {
+                    id: Some(uuid::Uuid::new_v4().to_string()),
+                    index: self.tool_calls.len() as i32,
+                    r#type: Some("function".to_string()),
+                    function: ChatCompletionChunkToolCallFunction {
+                        name: Some(tool_call.function.name),
+                        arguments: tool_call.function.arguments.to_string(),
+                    },
+                });
+            }
+        }
+        if response.done {
+            messages.extend(
+                std::mem::take(&mut self.tool_calls)
+                    .into_iter()
+                    .map(ChunkMessage::ToolCall),
+            );
+            messages.push(ChunkMessage::Usage(usage));
+        }
+        messages
+    }
+}
+
+pub async fn chat_completion(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    tools: Option<Vec<Value>>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    cancellation: CancellationToken,
+) -> Result<ChunkStream, Box<dyn Error>> {
+    let mut payload = build_payload(&messages, &config.model);
+    add_options(&mut payload, &config, config.response_format.as_ref());
+    payload["stream"] = json!(true);
+    // Ollama takes tools in the OpenAI format
+    if let Some(tools) = tools {
+        payload["tools"] = json!(tools);
+    }
+
+    let response = cancellation
+        .run(send(client, &config.url, api_key, &payload))
+        .await??;
+
+    let mut bytes = response.bytes_stream();
+    let message_stream = stream! {
+        let mut state = StreamState::default();
+        let mut buffer: Vec<u8> = vec![];
+
+        while let Some(chunk) = cancellation.run(bytes.next()).await.ok().flatten() {
+            let Ok(chunk) = chunk else {
+                continue;
+            };
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                for message in state.handle_line(&line) {
+                    yield message;
+                }
+            }
+        }
+    };
+
+    Ok(Box::pin(message_stream))
+}
+
+pub async fn chat_completion_non_streaming(
+    client: &reqwest::Client,
+    messages: Vec<ChatCompletionMessage>,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    response_format: Option<Value>,
+) -> Result<(String, Option<TokenUsage>), Box<dyn Error>> {
+    let mut payload = build_payload(&messages, &config.model);
+    add_options(&mut payload, &config, response_format.as_ref());
+    payload["stream"] = json!(false);
+
+    let response = send(client, &config.url, api_key, &payload).await?;
+    let response: ChatResponse = response.json().await?;
+    if let Some(error) = response.error {
+        return Err(format!("Failed to get Ollama response: {}", error).into());
+    }
+    let usage = response.usage();
+    response
+        .message
+        .map(|message| (message.content, Some(usage)))
+        .ok_or_else(|| "Failed to parse Ollama response".into())
+}
+
+fn add_options(payload: &mut Value, config: &OpenAIClientConfig, response_format: Option<&Value>) {
+    let generation = &config.generation;
+    let mut options = json!({});
+    if let Some(temperature) = generation.temperature {
+        options["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = generation.top_p {
+        options["top_p"] = json!(top_p);
+    }
+    if let Some(max_tokens) = generation.max_tokens {
+        options["num_predict"] = json!(max_tokens);
+    }
+    if !generation.stop.is_empty() {
+        options["stop"] = json!(generation.stop);
+    }
+    payload["options"] = options;
+
+    if let Some(schema) = response_format.and_then(|format| format.pointer("/json_schema/schema")) {
+        payload["format"] = schema.clone();
+    }
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: Option<String>,
+    payload: &Value,
+) -> Result<reqwest::Response, Box<dyn Error>> {
+    let url = format!("{}/api/chat", url);
+    // Only hosted instances need a key
+    let authorization = api_key
+        .filter(|api_key| !api_key.is_empty())
+        .map(|api_key| format!("Bearer {}", api_key));
+
+    let response = send_with_retry(|| {
+        let request = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(payload);
+        match &authorization {
+            Some(authorization) => request.header("Authorization", authorization),
+            None => request,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to get Ollama response: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_message = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to get Ollama response: {} {}",
+            status, error_message
+        )
+        .into());
+    }
+    Ok(response)
+}
+
+pub fn build_payload(messages: &[ChatCompletionMessage], model: &str) -> Value {
+    // Results are matched to their calls by the tool name
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    let mut converted = vec![];
+
+    for message in messages {
+        converted.push(match message {
+            ChatCompletionMessage::ChatCompletionTextMessage(message) => {
+                convert_text_message(&message.role, &message.content)
+            }
+            ChatCompletionMessage::ChatCompletionToolCallMessage(message) => {
+                let tool_calls: Vec<Value> = message
+                    .tool_calls
+                    .iter()
+                    .map(|tool_call| {
+                        tool_names.insert(&tool_call.id, &tool_call.function.name);
+                        json!({
+                            "function": {
+                                "name": tool_call.function.name,
+                                "arguments": parse_arguments(&tool_call.function.arguments),
+                            }
+                        })
+                    })
+                    .collect();
+                json!({ "role": "assistant", "content": "", "tool_calls": tool_calls })
+            }
+            ChatCompletionMessage::ChatCompletionToolCallResultMessage(message) => json!({
+                "role": "tool",
+                "content": message.content,
+                "tool_name": tool_names.get(message.tool_call_id.as_str()),
+            }),
+        });
+    }
+
+    json!({
+        "model": model,
+        "messages": converted,
+    })
+}
+
+fn convert_text_message(role: &str, content: &[ChatCompletionMessageContent]) -> Value {
+    let mut text = vec![];
+    let mut images = vec![];
+    for content in content {
+        match content {
+            ChatCompletionMessageContent::Text { text: part } => text.push(part.as_str()),
+            ChatCompletionMessageContent::Image { image_url } => match image_url.data() {
+                Some((_, data)) => images.push(data),
+                None => log::warn!(
+                    "Ollama only accepts embedded images, leaving out {}",
+                    image_url.url
+                ),
+            },
+        }
+    }
+
+    let mut message = json!({ "role": role, "content": text.join("\n") });
+    if !images.is_empty() {
+        message["images"] = json!(images);
+    }
+    message
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(body: &str) -> Vec<ChunkMessage> {
+        let mut state = StreamState::default();
+        body.split_inclusive('\n')
+            .flat_map(|line| state.handle_line(line.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn passes_on_tool_calls_and_usage_once_done() {
+        let messages = replay(include_str!("fixtures/ollama_stream.ndjson"));
+
+        let [
+            ChunkMessage::Text(first),
+            ChunkMessage::Text(second),
+            ChunkMessage::ToolCall(tool_call),
+            ChunkMessage::Usage(usage),
+        ] = messages.as_slice()
+        else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(format!("{}{}", first, second), "Let me check the weather.");
+        assert!(tool_call.id.is_some());
+        assert_eq!(tool_call.function.name.as_deref(), Some("get_weather"));
+        assert_eq!(tool_call.function.arguments, r#"{"location":"Warsaw"}"#);
+        assert_eq!(usage.prompt_tokens, 148);
+        assert_eq!(usage.completion_tokens, 27);
+    }
+
+    #[test]
+    fn passes_on_errors_and_skips_unparsable_lines() {
+        let messages = replay("not json\n{\"error\":\"model \\\"llama9\\\" not found\"}\n");
+
+        let [ChunkMessage::Error(error)] = messages.as_slice() else {
+            panic!("unexpected messages: {:?}", messages);
+        };
+        assert_eq!(error, "model \"llama9\" not found");
+    }
+}
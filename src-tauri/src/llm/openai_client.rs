@@ -5,14 +5,33 @@ use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::error::Error;
+use std::pin::Pin;
 
+use super::{anthropic, gemini, ollama, retry::send_with_retry};
 use crate::constants::QSPEAK_API_V1_URL;
-use crate::state_machine::models::ModelConfig;
+use crate::state_machine::cancellation::CancellationToken;
+use crate::state_machine::models::{LlmProvider, ModelConfig};
+use crate::state_machine::personas::GenerationParams;
+use crate::state_machine::usage;
+
+pub type ChunkStream = Pin<Box<dyn Stream<Item = ChunkMessage>>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatCompletionChunk {
     pub id: String,
     pub choices: Vec<ChatCompletionChunkChoice>,
+    // Only in the last chunk, which has no choices
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+// Tokens of one request, counted by the API that answered it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +117,15 @@ pub struct ChatCompletionMessageContentImageUrl {
     pub url: String,
 }
 
+impl ChatCompletionMessageContentImageUrl {
+    // Media type and base64 data of a data URL, None for links
+    pub fn data(&self) -> Option<(&str, &str)> {
+        let (header, data) = self.url.strip_prefix("data:")?.split_once(',')?;
+        let media_type = header.strip_suffix(";base64")?;
+        Some((media_type, data))
+    }
+}
+
 pub struct OpenAIClient {
     client: reqwest::Client,
 }
@@ -110,10 +138,21 @@ pub struct OpenAIClientConfig {
     pub supports_vision: bool,
     pub supports_tools: bool,
     pub api_key: Option<String>,
+    #[serde(default)]
+    pub provider: LlmProvider,
+    // Set from the active persona
+    #[serde(default)]
+    pub generation: GenerationParams,
+    #[serde(default)]
+    pub response_format: Option<serde_json::Value>,
+    // Persona the tokens are counted for in the usage stats
+    #[serde(default)]
+    pub usage_persona: Option<String>,
 }
 
 impl OpenAIClientConfig {
     pub fn from_model_config(model_config: ModelConfig) -> Self {
+        let provider = model_config.provider();
         match model_config {
             ModelConfig::OpenAI {
                 url,
@@ -121,12 +160,37 @@ impl OpenAIClientConfig {
                 api_key,
                 supports_vision,
                 supports_tools,
+            }
+            | ModelConfig::Anthropic {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            }
+            | ModelConfig::Ollama {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            }
+            | ModelConfig::Gemini {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
             } => Self {
                 model: model,
                 url: url,
                 supports_vision: supports_vision,
                 supports_tools: supports_tools,
                 api_key: api_key,
+                provider,
+                generation: GenerationParams::default(),
+                response_format: None,
+                usage_persona: None,
             },
         }
     }
@@ -137,6 +201,10 @@ impl OpenAIClientConfig {
             supports_vision: false,
             supports_tools: false,
             api_key: None,
+            provider: LlmProvider::OpenAI,
+            generation: GenerationParams::default(),
+            response_format: None,
+            usage_persona: None,
         }
     }
 
@@ -148,6 +216,10 @@ impl OpenAIClientConfig {
             supports_vision: true,
             supports_tools: true,
             api_key: None,
+            provider: LlmProvider::OpenAI,
+            generation: GenerationParams::default(),
+            response_format: None,
+            usage_persona: None,
         }
     }
 }
@@ -165,10 +237,8 @@ impl OpenAIClient {
         tools: Option<Vec<serde_json::Value>>,
         config: OpenAIClientConfig,
         api_key: Option<String>,
-    ) -> Result<impl Stream<Item = ChunkMessage>, Box<dyn Error>> {
-        let model = config.model;
-        let url = config.url;
-
+        cancellation: CancellationToken,
+    ) -> Result<ChunkStream, Box<dyn Error>> {
         if let Some(first_message) = messages.get_mut(0) {
             let current_time_iso = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
             let time_xml = format!("<current_time>{}</current_time>\\n", current_time_iso);
@@ -206,6 +276,52 @@ impl OpenAIClient {
             }
         }
 
+        let tools = if config.supports_tools { tools } else { None };
+        let usage_model = config.model.clone();
+        let usage_persona = config.usage_persona.clone();
+        match config.provider {
+            LlmProvider::OpenAI => {}
+            LlmProvider::Anthropic => {
+                return anthropic::chat_completion(
+                    &self.client,
+                    messages,
+                    tools,
+                    config,
+                    api_key,
+                    cancellation,
+                )
+                .await
+                .map(|stream| record_stream_usage(stream, usage_model, usage_persona));
+            }
+            LlmProvider::Ollama => {
+                return ollama::chat_completion(
+                    &self.client,
+                    messages,
+                    tools,
+                    config,
+                    api_key,
+                    cancellation,
+                )
+                .await
+                .map(|stream| record_stream_usage(stream, usage_model, usage_persona));
+            }
+            LlmProvider::Gemini => {
+                return gemini::chat_completion(
+                    &self.client,
+                    messages,
+                    tools,
+                    config,
+                    api_key,
+                    cancellation,
+                )
+                .await
+                .map(|stream| record_stream_usage(stream, usage_model, usage_persona));
+            }
+        }
+
+        let model = config.model;
+        let url = config.url;
+
         let formatted_messages = messages
             .iter_mut()
             .map(|message| match message {
@@ -236,30 +352,51 @@ impl OpenAIClient {
             })
             .collect::<Vec<_>>();
 
-        let payload = json!({
+        let mut payload = json!({
             "model": model,
             "messages": formatted_messages,
             "stream": true,
-            "tools": if config.supports_tools {
-                tools
-            } else {
-                None
-            },
+            "stream_options": { "include_usage": true },
+            "tools": tools,
         });
+        add_generation_params(&mut payload, &config.generation);
+        if let Some(format) = config.response_format {
+            payload["response_format"] = format;
+        }
 
         let url = format!("{}/chat/completions", url);
 
-        let response_result = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .header(
-                "Authorization",
-                format!("Bearer {}", api_key.unwrap_or_default()),
-            )
-            .json(&payload)
-            .send()
-            .await;
+        let authorization = format!("Bearer {}", api_key.unwrap_or_default());
+        let response_result = loop {
+            let response_result = cancellation
+                .run(send_with_retry(|| {
+                    self.client
+                        .post(&url)
+                        .header("Content-Type", "application/json")
+                        .header("Authorization", &authorization)
+                        .json(&payload)
+                }))
+                .await?;
+
+            // Some OpenAI compatible servers reject stream_options, they get the request again without usage
+            let rejected = matches!(
+                &response_result,
+                Ok(response) if response.status() == reqwest::StatusCode::BAD_REQUEST
+            );
+            if rejected
+                && payload
+                    .as_object_mut()
+                    .and_then(|payload| payload.remove("stream_options"))
+                    .is_some()
+            {
+                log::warn!(
+                    "{} rejected the request, retrying without stream usage",
+                    url
+                );
+                continue;
+            }
+            break response_result;
+        };
 
         if let Err(e) = response_result {
             return Err(Box::new(std::io::Error::new(
@@ -286,8 +423,11 @@ impl OpenAIClient {
 
         let message_stream = stream! {
             let mut tool_calls: Vec<ChatCompletionChunkToolCall> = Vec::new();
+            // Some servers repeat the running total in every chunk, only the last one counts
+            let mut usage: Option<TokenUsage> = None;
 
-            while let Some(chunk_result) = stream.next().await {
+            // A cancelled token ends the stream and drops the connection
+            while let Some(chunk_result) = cancellation.run(stream.next()).await.ok().flatten() {
                 match chunk_result {
                     Ok(event) => {
                         let json_str = event.data;
@@ -296,12 +436,21 @@ impl OpenAIClient {
                             for tool_call in tool_calls.iter() {
                                 yield ChunkMessage::ToolCall(tool_call.clone());
                             }
+                            if let Some(usage) = usage.take() {
+                                yield ChunkMessage::Usage(usage);
+                            }
                             continue;
                         }
 
                         match serde_json::from_str::<ChatCompletionChunk>(&json_str) {
                             Ok(chunk) => {
-                                let delta = &chunk.choices[0].delta;
+                                if chunk.usage.is_some() {
+                                    usage = chunk.usage;
+                                }
+                                let Some(choice) = chunk.choices.first() else {
+                                    continue;
+                                };
+                                let delta = &choice.delta;
 
                                 if let Some(new_tool_calls) = &delta.tool_calls {
                                     for tool_call in new_tool_calls {
@@ -332,7 +481,7 @@ impl OpenAIClient {
             }
         };
 
-        Ok(message_stream)
+        Ok(record_stream_usage(Box::pin(message_stream), usage_model, usage_persona))
     }
 
     pub async fn chat_completion_non_streaming(
@@ -343,9 +492,6 @@ impl OpenAIClient {
         api_key: Option<String>,
         response_format: Option<serde_json::Value>,
     ) -> Result<String, Box<dyn Error>> {
-        let model = config.model;
-        let url = config.url;
-
         if let Some(first_message) = messages.get_mut(0) {
             let current_time_iso = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
             let time_xml = format!("<current_time>{}</current_time>\\n", current_time_iso);
@@ -383,6 +529,54 @@ impl OpenAIClient {
             }
         }
 
+        let usage_model = config.model.clone();
+        let usage_persona = config.usage_persona.clone();
+        match config.provider {
+            LlmProvider::OpenAI => {}
+            LlmProvider::Anthropic => {
+                return anthropic::chat_completion_non_streaming(
+                    &self.client,
+                    messages,
+                    config,
+                    api_key,
+                    response_format,
+                )
+                .await
+                .map(|completion| {
+                    record_completion_usage(completion, &usage_model, usage_persona.as_deref())
+                });
+            }
+            LlmProvider::Ollama => {
+                return ollama::chat_completion_non_streaming(
+                    &self.client,
+                    messages,
+                    config,
+                    api_key,
+                    response_format,
+                )
+                .await
+                .map(|completion| {
+                    record_completion_usage(completion, &usage_model, usage_persona.as_deref())
+                });
+            }
+            LlmProvider::Gemini => {
+                return gemini::chat_completion_non_streaming(
+                    &self.client,
+                    messages,
+                    config,
+                    api_key,
+                    response_format,
+                )
+                .await
+                .map(|completion| {
+                    record_completion_usage(completion, &usage_model, usage_persona.as_deref())
+                });
+            }
+        }
+
+        let model = config.model;
+        let url = config.url;
+
         let formatted_messages = messages
             .iter_mut()
             .map(|message| match message {
@@ -424,6 +618,8 @@ impl OpenAIClient {
             },
         });
 
+        add_generation_params(&mut payload, &config.generation);
+
         // Add response_format if provided
         if let Some(format) = response_format {
             payload["response_format"] = format;
@@ -431,17 +627,15 @@ impl OpenAIClient {
 
         let url = format!("{}/chat/completions", url);
 
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .header(
-                "Authorization",
-                format!("Bearer {}", api_key.unwrap_or_default()),
-            )
-            .json(&payload)
-            .send()
-            .await?;
+        let authorization = format!("Bearer {}", api_key.unwrap_or_default());
+        let response = send_with_retry(|| {
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header("Authorization", &authorization)
+                .json(&payload)
+        })
+        .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -456,13 +650,20 @@ impl OpenAIClient {
         }
 
         let response_json: serde_json::Value = response.json().await?;
+        let usage: Option<TokenUsage> = response_json
+            .get("usage")
+            .and_then(|usage| serde_json::from_value(usage.clone()).ok());
         
         if let Some(choices) = response_json.get("choices") {
             if let Some(first_choice) = choices.get(0) {
                 if let Some(message) = first_choice.get("message") {
                     if let Some(content) = message.get("content") {
                         if let Some(content_str) = content.as_str() {
-                            return Ok(content_str.to_string());
+                            return Ok(record_completion_usage(
+                                (content_str.to_string(), usage),
+                                &usage_model,
+                                usage_persona.as_deref(),
+                            ));
                         }
                     }
                 }
@@ -476,9 +677,48 @@ impl OpenAIClient {
     }
 }
 
+// Only the settings the persona sets, so the API defaults apply otherwise
+fn add_generation_params(payload: &mut serde_json::Value, generation: &GenerationParams) {
+    if let Some(temperature) = generation.temperature {
+        payload["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = generation.top_p {
+        payload["top_p"] = json!(top_p);
+    }
+    if let Some(max_tokens) = generation.max_tokens {
+        payload["max_tokens"] = json!(max_tokens);
+    }
+    if !generation.stop.is_empty() {
+        payload["stop"] = json!(generation.stop);
+    }
+}
+
+// Counts the tokens of a streamed answer once the API reports them
+fn record_stream_usage(stream: ChunkStream, model: String, persona: Option<String>) -> ChunkStream {
+    Box::pin(stream.inspect(move |message| {
+        if let ChunkMessage::Usage(usage) = message {
+            usage::record_tokens(&model, persona.as_deref(), usage);
+        }
+    }))
+}
+
+// Counts the tokens of a whole answer and passes its text on
+fn record_completion_usage(
+    (text, usage): (String, Option<TokenUsage>),
+    model: &str,
+    persona: Option<&str>,
+) -> String {
+    if let Some(usage) = usage {
+        usage::record_tokens(model, persona, &usage);
+    }
+    text
+}
+
 #[allow(dead_code)]
+#[derive(Debug)]
 pub enum ChunkMessage {
     Text(String),
     ToolCall(ChatCompletionChunkToolCall),
     Error(String),
+    Usage(TokenUsage),
 }
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+// Attempts of one request, including the first
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+// Longest wait between attempts, also caps what a Retry-After header may ask for
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+// Sends the request again after network errors, rate limits and server errors.
+// Only for requests that change nothing on the server, like model and transcription calls.
+// The request is built anew for every attempt, multipart bodies can't be cloned.
+pub async fn send_with_retry<F>(build_request: F) -> Result<Response, reqwest::Error>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 1;
+    loop {
+        let result = build_request().send().await;
+        if attempt >= MAX_ATTEMPTS {
+            return result;
+        }
+
+        let delay = match &result {
+            Ok(response) if is_retryable_status(response.status()) => {
+                retry_after(response).unwrap_or_else(|| backoff(attempt))
+            }
+            Err(e) if e.is_connect() || e.is_timeout() || e.is_request() => backoff(attempt),
+            _ => return result,
+        };
+        match &result {
+            Ok(response) => log::warn!(
+                "Request to {} failed with {}, retrying in {:?}",
+                response.url(),
+                response.status(),
+                delay
+            ),
+            Err(e) => log::warn!("Request failed: {}, retrying in {:?}", e, delay),
+        }
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds: u64 = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_DELAY))
+}
+
+// Doubles with every attempt, the random half keeps clients that failed together from retrying together
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt - 1))
+        .min(MAX_DELAY);
+    let jitter = (uuid::Uuid::new_v4().as_u128() % 1000) as u32;
+    delay / 2 + delay / 2 * jitter / 1000
+}
@@ -0,0 +1,417 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use qspeak_audio_recording::AudioEncoding;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::{
+    Event, history_store,
+    processor::Processor,
+    state::{AppState, AppStateContext, ConversationMessage, ConversationState, FailedStep},
+};
+
+const RETENTION_STARTUP_DELAY: Duration = Duration::from_secs(60);
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Younger files may still be recorded, transcribed or waiting to be saved to history
+const RETENTION_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+const AUDIO_EXTENSIONS: [&str; 4] = ["wav", "flac", "ogg", "opus"];
+const AUDIO_VARIANT_SUFFIXES: [&str; 2] = ["_output", "_combined"];
+
+static RETENTION_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioRetentionFormat {
+    Wav,
+    Flac,
+    Opus,
+}
+
+impl AudioRetentionFormat {
+    fn encoding(&self) -> Option<AudioEncoding> {
+        match self {
+            AudioRetentionFormat::Wav => None,
+            AudioRetentionFormat::Flac => Some(AudioEncoding::Flac),
+            AudioRetentionFormat::Opus => Some(AudioEncoding::Opus),
+        }
+    }
+}
+
+// Everything is kept as recorded until the user opts into cleaning up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioRetentionContext {
+    #[serde(default = "default_format")]
+    pub format: AudioRetentionFormat,
+    // None keeps recordings until the size quota removes them
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    // None puts no limit on the size of the audio directory
+    #[serde(default)]
+    pub max_size_mb: Option<u64>,
+    // Removes recordings no conversation or history entry points to
+    #[serde(default)]
+    pub remove_orphans: bool,
+}
+
+fn default_format() -> AudioRetentionFormat {
+    AudioRetentionFormat::Wav
+}
+
+impl Default for AudioRetentionContext {
+    fn default() -> Self {
+        Self {
+            format: default_format(),
+            max_age_days: None,
+            max_size_mb: None,
+            remove_orphans: false,
+        }
+    }
+}
+
+pub struct AudioRetentionProcessor;
+
+impl AudioRetentionProcessor {
+    pub fn start(app_handle: AppHandle) {
+        Processor::register_event_listener(
+            "audio_retention",
+            Box::new(|event, app_handle| match event {
+                Event::ActionUpdateAudioRetentionSettings(settings) => {
+                    AppState::update(|context| {
+                        context.audio_retention_context = settings;
+                    })?;
+                    run_audio_retention_in_background(app_handle);
+                    Ok(())
+                }
+                Event::ActionRunAudioRetention => {
+                    run_audio_retention_in_background(app_handle);
+                    Ok(())
+                }
+                _ => Ok(()),
+            }),
+        );
+
+        let audio_dir = get_audio_dir(&app_handle);
+        std::thread::spawn(move || {
+            std::thread::sleep(RETENTION_STARTUP_DELAY);
+            loop {
+                run_audio_retention_once(&audio_dir);
+                std::thread::sleep(RETENTION_INTERVAL);
+            }
+        });
+    }
+}
+
+fn get_audio_dir(app_handle: &AppHandle) -> PathBuf {
+    app_handle
+        .path()
+        .cache_dir()
+        .expect("Failed to get cache dir")
+        .join("audio")
+}
+
+fn run_audio_retention_in_background(app_handle: &AppHandle) {
+    let audio_dir = get_audio_dir(app_handle);
+    std::thread::spawn(move || run_audio_retention_once(&audio_dir));
+}
+
+fn run_audio_retention_once(audio_dir: &Path) {
+    if RETENTION_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if let Err(e) = run_audio_retention(audio_dir) {
+        log::error!("Audio retention failed: {}", e);
+    }
+
+    RETENTION_RUNNING.store(false, Ordering::SeqCst);
+}
+
+struct AudioFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl AudioFile {
+    fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.modified).unwrap_or_default()
+    }
+}
+
+fn run_audio_retention(audio_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let context = AppState::get_context();
+    // An active conversation may still need its files on disk
+    if context.conversation_context.state != ConversationState::Idle {
+        log::info!("Skipping audio retention, conversation is not idle");
+        return Ok(());
+    }
+    if !audio_dir.exists() {
+        return Ok(());
+    }
+
+    let settings = context.audio_retention_context.clone();
+    let referenced_paths = referenced_audio_paths(&context)?;
+    // Recordings are grouped with their _output, _combined and meeting chunk variants by base stem
+    let owners: HashMap<String, String> = referenced_paths
+        .iter()
+        .filter(|path| Path::new(path).parent() == Some(audio_dir))
+        .map(|path| (base_stem(Path::new(path)), path.clone()))
+        .collect();
+
+    let now = SystemTime::now();
+    // New audio path for every history reference that changes, None clears it
+    let mut path_updates: HashMap<String, Option<String>> = HashMap::new();
+
+    for path in referenced_paths.iter() {
+        if !Path::new(path).exists() {
+            let replacement = find_encoded_replacement(Path::new(path));
+            log::info!("Fixing dangling audio path {} -> {:?}", path, replacement);
+            path_updates.insert(path.clone(), replacement);
+        }
+    }
+
+    // Orphans and expired recordings
+    let max_age = settings
+        .max_age_days
+        .map(|days| Duration::from_secs(days as u64 * 24 * 60 * 60));
+    for file in list_audio_files(audio_dir)? {
+        if file.age(now) < RETENTION_GRACE_PERIOD {
+            continue;
+        }
+
+        match owners.get(&base_stem(&file.path)) {
+            None if settings.remove_orphans => {
+                log::info!("Removing orphaned audio file {:?}", file.path);
+                let _ = fs::remove_file(&file.path);
+            }
+            None => {}
+            Some(owner) if max_age.is_some_and(|max_age| file.age(now) > max_age) => {
+                log::info!("Removing expired audio file {:?}", file.path);
+                let _ = fs::remove_file(&file.path);
+                path_updates.insert(owner.clone(), None);
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Compress kept recordings, the combined variant holds both sides when it exists
+    if let Some(encoding) = settings.format.encoding() {
+        for owner in owners.values() {
+            let owner_path = Path::new(owner);
+            let is_wav = owner_path.extension().is_some_and(|ext| ext == "wav");
+            let settled = fs::metadata(owner_path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified).unwrap_or_default() >= RETENTION_GRACE_PERIOD
+                });
+            // The retry reads the recording as a WAV file
+            if !is_wav
+                || !settled
+                || path_updates.contains_key(owner)
+                || failed_recording_path(&context) == Some(owner)
+            {
+                continue;
+            }
+
+            let combined_path = variant_path(owner_path, "_combined");
+            let source_path = if combined_path.exists() {
+                combined_path
+            } else {
+                owner_path.to_path_buf()
+            };
+            let target_path = owner_path.with_extension(encoding.extension());
+
+            match qspeak_audio_recording::encode_audio_file(&source_path, &target_path, encoding) {
+                Ok(_) => {
+                    let _ = fs::remove_file(owner_path);
+                    for suffix in AUDIO_VARIANT_SUFFIXES {
+                        let _ = fs::remove_file(variant_path(owner_path, suffix));
+                    }
+                    path_updates.insert(
+                        owner.clone(),
+                        Some(target_path.to_string_lossy().to_string()),
+                    );
+                }
+                Err(e) => {
+                    log::warn!("Failed to encode {:?}: {}", source_path, e);
+                    let _ = fs::remove_file(&target_path);
+                }
+            }
+        }
+    }
+
+    // Size quota, oldest recordings go first
+    if let Some(max_size_mb) = settings.max_size_mb {
+        let max_size = max_size_mb.saturating_mul(1024 * 1024);
+        let files = list_audio_files(audio_dir)?;
+        let mut total_size: u64 = files.iter().map(|file| file.size).sum();
+
+        let mut groups: HashMap<String, Vec<AudioFile>> = HashMap::new();
+        for file in files {
+            groups.entry(base_stem(&file.path)).or_default().push(file);
+        }
+        let mut groups: Vec<(String, Vec<AudioFile>)> = groups
+            .into_iter()
+            .filter(|(_, files)| {
+                files
+                    .iter()
+                    .all(|file| file.age(now) >= RETENTION_GRACE_PERIOD)
+            })
+            .collect();
+        groups.sort_by_key(|(_, files)| files.iter().map(|file| file.modified).min());
+
+        for (stem, files) in groups {
+            if total_size <= max_size {
+                break;
+            }
+
+            log::info!("Removing recording {} to stay within the size quota", stem);
+            for file in files {
+                if fs::remove_file(&file.path).is_ok() {
+                    total_size = total_size.saturating_sub(file.size);
+                }
+            }
+            if let Some(owner) = owners.get(&stem) {
+                path_updates.insert(owner.clone(), None);
+            }
+        }
+    }
+
+    if path_updates.is_empty() {
+        return Ok(());
+    }
+
+    log::info!("Updating {} audio paths in history", path_updates.len());
+    history_store::visit_conversations(|conversation| {
+        update_audio_paths(conversation, &path_updates)
+    })?;
+    AppState::update(|context| {
+        update_audio_paths(
+            &mut context.conversation_context.conversation,
+            &path_updates,
+        );
+        // Nothing is left to retry once the recording is removed
+        match failed_recording_path(context).and_then(|path| path_updates.get(path).cloned()) {
+            Some(Some(audio_file_path)) => {
+                context.conversation_context.failed_step =
+                    Some(FailedStep::Transcription { audio_file_path });
+            }
+            Some(None) => context.conversation_context.failed_step = None,
+            None => {}
+        }
+    })
+}
+
+fn referenced_audio_paths(context: &AppStateContext) -> Result<HashSet<String>, Box<dyn Error>> {
+    let mut paths: HashSet<String> =
+        audio_paths(&context.conversation_context.conversation).collect();
+    paths.extend(failed_recording_path(context).cloned());
+    history_store::visit_conversations(|conversation| {
+        paths.extend(audio_paths(conversation));
+        false
+    })?;
+    Ok(paths)
+}
+
+// A failed transcription is retried from its recording, which isn't in the conversation yet
+fn failed_recording_path(context: &AppStateContext) -> Option<&String> {
+    match &context.conversation_context.failed_step {
+        Some(FailedStep::Transcription { audio_file_path }) => Some(audio_file_path),
+        _ => None,
+    }
+}
+
+fn audio_paths(conversation: &[ConversationMessage]) -> impl Iterator<Item = String> {
+    conversation.iter().filter_map(|message| match message {
+        ConversationMessage::ConversationTextMessage(text_message) => {
+            text_message.audio_file_path.clone()
+        }
+        _ => None,
+    })
+}
+
+// Returns whether any path changed
+fn update_audio_paths(
+    conversation: &mut [ConversationMessage],
+    path_updates: &HashMap<String, Option<String>>,
+) -> bool {
+    let mut changed = false;
+    for message in conversation.iter_mut() {
+        if let ConversationMessage::ConversationTextMessage(text_message) = message {
+            if let Some(update) = text_message
+                .audio_file_path
+                .as_ref()
+                .and_then(|path| path_updates.get(path))
+            {
+                text_message.audio_file_path = update.clone();
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn list_audio_files(audio_dir: &Path) -> Result<Vec<AudioFile>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(audio_dir)? {
+        let path = entry?.path();
+        let is_audio = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext));
+        if !is_audio {
+            continue;
+        }
+
+        let metadata = fs::metadata(&path)?;
+        if !metadata.is_file() {
+            continue;
+        }
+        files.push(AudioFile {
+            path,
+            size: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+    Ok(files)
+}
+
+fn base_stem(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = AUDIO_VARIANT_SUFFIXES
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .map(|stem| stem.to_string())
+        .unwrap_or(stem);
+
+    // Meeting chunks share the recording, history only references the last one
+    match stem.rsplit_once("_part") {
+        Some((base, index)) if !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()) => {
+            base.to_string()
+        }
+        _ => stem,
+    }
+}
+
+fn variant_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}{}.wav", stem, suffix))
+}
+
+// A path can dangle when the recording was encoded but the history update was lost
+fn find_encoded_replacement(path: &Path) -> Option<String> {
+    [AudioEncoding::Flac, AudioEncoding::Opus]
+        .iter()
+        .map(|encoding| path.with_extension(encoding.extension()))
+        .find(|candidate| candidate.exists())
+        .map(|candidate| candidate.to_string_lossy().to_string())
+}
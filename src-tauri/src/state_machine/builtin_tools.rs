@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+use chrono::Local;
+use get_selected_text::get_selected_text;
+use qspeak_keyboard::get_text_from_clipboard;
+use qspeak_screenshot::make_screenshot;
+use rmcp::model::{CallToolResult, JsonObject, Tool};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tauri::{AppHandle, Manager};
+
+use super::new_mcp_processor::MCPToolPolicy;
+
+// Tools that run inside qSpeak, offered to the model as if they came from a server with this key
+pub const BUILTIN_SERVER_KEY: &str = "qspeak";
+
+const GET_CURRENT_TIME: &str = "get_current_time";
+const READ_CLIPBOARD: &str = "read_clipboard";
+const READ_SELECTED_TEXT: &str = "read_selected_text";
+const TAKE_SCREENSHOT: &str = "take_screenshot";
+const READ_FILE: &str = "read_file";
+const APPEND_TO_NOTES: &str = "append_to_notes";
+
+const MAX_FILE_SIZE: u64 = 256 * 1024;
+const NOTES_FILE_NAME: &str = "qSpeak notes.md";
+
+static DEFAULT_NOTES_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltinToolsConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    // Per tool overrides of the default policies, keyed by tool name
+    #[serde(default)]
+    pub tool_policies: HashMap<String, MCPToolPolicy>,
+    // None appends to a file in the documents folder
+    #[serde(default)]
+    pub notes_file: Option<String>,
+}
+
+impl Default for BuiltinToolsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            tool_policies: HashMap::new(),
+            notes_file: None,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl BuiltinToolsConfig {
+    pub fn policy_for_tool(&self, tool_name: &str) -> MCPToolPolicy {
+        let policy = self
+            .tool_policies
+            .get(tool_name)
+            .copied()
+            .unwrap_or(match tool_name {
+                GET_CURRENT_TIME => MCPToolPolicy::Allow,
+                _ => MCPToolPolicy::Ask,
+            });
+
+        // The user approves every file the model wants to read
+        match (tool_name, policy) {
+            (READ_FILE, MCPToolPolicy::Allow) => MCPToolPolicy::Ask,
+            _ => policy,
+        }
+    }
+
+    fn notes_file(&self) -> Option<PathBuf> {
+        match &self.notes_file {
+            Some(path) if !path.trim().is_empty() => Some(PathBuf::from(path)),
+            _ => DEFAULT_NOTES_FILE.get().cloned(),
+        }
+    }
+}
+
+pub fn init(app_handle: &AppHandle) {
+    let dir = app_handle
+        .path()
+        .document_dir()
+        .or_else(|_| app_handle.path().app_data_dir());
+    match dir {
+        Ok(dir) => {
+            let _ = DEFAULT_NOTES_FILE.set(dir.join(NOTES_FILE_NAME));
+        }
+        Err(e) => log::error!("Failed to resolve the notes directory: {}", e),
+    }
+}
+
+pub fn list_tools() -> Result<Vec<Tool>, serde_json::Error> {
+    let no_arguments = json!({ "type": "object", "properties": {} });
+
+    [
+        (
+            GET_CURRENT_TIME,
+            "Get the current local date, time and time zone of the user.",
+            no_arguments.clone(),
+        ),
+        (
+            READ_CLIPBOARD,
+            "Read the text currently in the user's clipboard.",
+            no_arguments.clone(),
+        ),
+        (
+            READ_SELECTED_TEXT,
+            "Read the text the user has selected in the focused application.",
+            no_arguments.clone(),
+        ),
+        (
+            TAKE_SCREENSHOT,
+            "Take a screenshot of the user's main screen.",
+            no_arguments,
+        ),
+        (
+            READ_FILE,
+            "Read a text file from the user's computer. The user approves every file.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the file" }
+                },
+                "required": ["path"]
+            }),
+        ),
+        (
+            APPEND_TO_NOTES,
+            "Append a note to the user's notes file.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Markdown text of the note" }
+                },
+                "required": ["text"]
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, description, input_schema)| {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": description,
+            "inputSchema": input_schema,
+        }))
+    })
+    .collect()
+}
+
+// Blocking, the clipboard, selection and screen are read from the calling thread
+pub fn call_tool(
+    config: &BuiltinToolsConfig,
+    tool_name: &str,
+    arguments: Option<JsonObject>,
+) -> Result<CallToolResult, serde_json::Error> {
+    let arguments = arguments.unwrap_or_default();
+
+    let content: Result<Value, Box<dyn Error>> = match tool_name {
+        GET_CURRENT_TIME => Ok(text_content(
+            Local::now().format("%A, %Y-%m-%d %H:%M:%S %:z").to_string(),
+        )),
+        READ_CLIPBOARD => get_text_from_clipboard().map(text_content),
+        READ_SELECTED_TEXT => get_selected_text()
+            .map(text_content)
+            .map_err(|e| e.to_string().into()),
+        TAKE_SCREENSHOT => make_screenshot().map(|png_data| {
+            #[allow(deprecated)]
+            let data = base64::encode(&png_data);
+            json!({ "type": "image", "data": data, "mimeType": "image/png" })
+        }),
+        READ_FILE => read_file(string_argument(&arguments, "path")),
+        APPEND_TO_NOTES => append_to_notes(config, string_argument(&arguments, "text")),
+        _ => Err(format!("Unknown tool: {}", tool_name).into()),
+    };
+
+    let (content, is_error) = match content {
+        Ok(content) => (content, false),
+        Err(e) => (text_content(e.to_string()), true),
+    };
+    serde_json::from_value(json!({ "content": [content], "isError": is_error }))
+}
+
+fn read_file(path: Option<&str>) -> Result<Value, Box<dyn Error>> {
+    let path = path.ok_or("Missing path")?;
+    let size = fs::metadata(path)?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(format!(
+            "File is too large ({} KB), the limit is {} KB",
+            size / 1024,
+            MAX_FILE_SIZE / 1024
+        )
+        .into());
+    }
+
+    Ok(text_content(fs::read_to_string(path)?))
+}
+
+fn append_to_notes(
+    config: &BuiltinToolsConfig,
+    text: Option<&str>,
+) -> Result<Value, Box<dyn Error>> {
+    let text = text.ok_or("Missing text")?;
+    let path = config.notes_file().ok_or("Notes file is not configured")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(
+        file,
+        "## {}\n\n{}\n",
+        Local::now().format("%Y-%m-%d %H:%M"),
+        text.trim()
+    )?;
+
+    Ok(text_content(format!("Note added to {}", path.display())))
+}
+
+fn string_argument<'a>(arguments: &'a JsonObject, name: &str) -> Option<&'a str> {
+    arguments.get(name).and_then(|value| value.as_str())
+}
+
+fn text_content(text: String) -> Value {
+    json!({ "type": "text", "text": text })
+}
@@ -0,0 +1,245 @@
+use std::{
+    error::Error,
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use super::{
+    Event, new_kobold_server,
+    personas::Persona,
+    processor::Processor,
+    state::{AppState, AppStateContext},
+};
+
+const CHECK_ADDRESS: &str = "8.8.8.8:53";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// Where requests can go, shown in the recording window, the tray and to websocket clients
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum NetworkMode {
+    #[default]
+    Online,
+    // No connection, cloud models are swapped for downloaded ones
+    Offline,
+    // Picked by the user, nothing leaves the machine even with a connection
+    LocalOnly,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivityContext {
+    // Assumed until the first check finishes, only local_only is kept between runs
+    #[serde(default = "default_online")]
+    pub online: bool,
+    #[serde(default)]
+    pub local_only: bool,
+    #[serde(default)]
+    pub mode: NetworkMode,
+}
+
+fn default_online() -> bool {
+    true
+}
+
+impl Default for ConnectivityContext {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl ConnectivityContext {
+    pub fn new(local_only: bool) -> Self {
+        let mut context = Self {
+            online: default_online(),
+            local_only,
+            mode: NetworkMode::Online,
+        };
+        context.update_mode();
+        context
+    }
+
+    pub fn set_online(&mut self, online: bool) {
+        self.online = online;
+        self.update_mode();
+    }
+
+    pub fn set_local_only(&mut self, local_only: bool) {
+        self.local_only = local_only;
+        self.update_mode();
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.mode != NetworkMode::Online
+    }
+
+    fn update_mode(&mut self) {
+        self.mode = if self.local_only {
+            NetworkMode::LocalOnly
+        } else if self.online {
+            NetworkMode::Online
+        } else {
+            NetworkMode::Offline
+        };
+    }
+}
+
+pub struct ConnectivityProcessor;
+
+impl ConnectivityProcessor {
+    pub fn start() {
+        Processor::register_event_listener(
+            "connectivity",
+            Box::new(|event, app_handle| match event {
+                Event::ConnectivityChange(online) => {
+                    update_mode(|connectivity| connectivity.set_online(online))
+                }
+                Event::ActionChangeLocalOnly(local_only) => {
+                    update_mode(|connectivity| connectivity.set_local_only(local_only))
+                }
+                // KoboldCpp serves downloaded models, once started it reloads on model changes itself
+                Event::NetworkModeChange(mode) if mode != NetworkMode::Online => {
+                    new_kobold_server::serve_local_model(app_handle)
+                }
+                Event::ActionChangeConversationModel(_)
+                    if AppState::get_context().connectivity_context.is_local()
+                        && !new_kobold_server::is_started() =>
+                {
+                    new_kobold_server::serve_local_model(app_handle)
+                }
+                _ => Ok(()),
+            }),
+        );
+
+        std::thread::spawn(|| {
+            let mut last_online = None;
+            loop {
+                let online = is_online();
+                if last_online != Some(online) {
+                    last_online = Some(online);
+                    if let Err(e) = Processor::process_event(Event::ConnectivityChange(online)) {
+                        log::error!("Failed to process connectivity change: {}", e);
+                    }
+                }
+                std::thread::sleep(CHECK_INTERVAL);
+            }
+        });
+    }
+}
+
+pub fn is_online() -> bool {
+    let address: SocketAddr = CHECK_ADDRESS
+        .parse()
+        .expect("Failed to parse check address");
+    TcpStream::connect_timeout(&address, CHECK_TIMEOUT).is_ok()
+}
+
+// Other processors react to the mode, not to every connectivity check
+fn update_mode(change: impl FnOnce(&mut ConnectivityContext)) -> Result<(), Box<dyn Error>> {
+    let previous_mode = AppState::get_context().connectivity_context.mode;
+    AppState::update(|context| change(&mut context.connectivity_context))?;
+
+    let mode = AppState::get_context().connectivity_context.mode;
+    if mode == previous_mode {
+        return Ok(());
+    }
+    log::info!("Network mode changed from {} to {}", previous_mode, mode);
+    Processor::process_event(Event::NetworkModeChange(mode))
+}
+
+// Picked model and its fallback, a downloaded whisper model without a fallback in local modes
+pub fn transcription_models_for_mode(
+    context: &AppStateContext,
+    model_id: &str,
+) -> Result<(String, Option<String>), String> {
+    let fallback = context.fallback_transcription_model.clone();
+    if !context.connectivity_context.is_local() {
+        return Ok((model_id.to_string(), fallback));
+    }
+
+    let local_models = context
+        .models_context
+        .transcription_models
+        .iter()
+        .filter(|model| model.runs_offline())
+        .map(|model| (model.model.as_str(), model.intelligence))
+        .collect();
+    pick_local_model(local_models, model_id, fallback.as_deref()).ok_or_else(|| {
+        format!(
+            "{} and no Whisper model is downloaded. Download one in the models settings.",
+            mode_reason(context)
+        )
+    })
+}
+
+// Same as for transcription, the local model is the one KoboldCpp gets loaded with
+pub fn conversation_models_for_mode(
+    context: &AppStateContext,
+    model_id: &str,
+) -> Result<(String, Option<String>), String> {
+    let fallback = context.fallback_conversation_model.clone();
+    if !context.connectivity_context.is_local() {
+        return Ok((model_id.to_string(), fallback));
+    }
+
+    let local_models = context
+        .models_context
+        .conversation_models
+        .iter()
+        .filter(|model| model.runs_offline())
+        .map(|model| (model.model.as_str(), model.intelligence))
+        .collect();
+    pick_local_model(local_models, model_id, fallback.as_deref()).ok_or_else(|| {
+        format!(
+            "{} and no local conversation model is downloaded. Download one in the models settings.",
+            mode_reason(context)
+        )
+    })
+}
+
+// The picked model first, then the fallback, then the smartest one that runs offline
+fn pick_local_model(
+    local_models: Vec<(&str, f64)>,
+    model_id: &str,
+    fallback: Option<&str>,
+) -> Option<(String, Option<String>)> {
+    let runs_offline = |id: &str| local_models.iter().any(|(model, _)| *model == id);
+    let local_model = if runs_offline(model_id) {
+        model_id
+    } else if let Some(fallback) = fallback.filter(|fallback| runs_offline(fallback)) {
+        fallback
+    } else {
+        local_models
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(model, _)| *model)?
+    };
+    Some((local_model.to_string(), None))
+}
+
+pub fn check_persona(context: &AppStateContext, persona: &Persona) -> Result<(), String> {
+    if !persona.cloud_only || !context.connectivity_context.is_local() {
+        return Ok(());
+    }
+    let next_step = match context.connectivity_context.mode {
+        NetworkMode::LocalOnly => "Turn local only mode off or pick another persona.",
+        _ => "Pick another persona or try again once you are back online.",
+    };
+    Err(format!(
+        "{} and {} only runs on cloud models. {}",
+        mode_reason(context),
+        persona.name,
+        next_step
+    ))
+}
+
+fn mode_reason(context: &AppStateContext) -> &'static str {
+    match context.connectivity_context.mode {
+        NetworkMode::LocalOnly => "Local only mode is on",
+        _ => "You are offline",
+    }
+}
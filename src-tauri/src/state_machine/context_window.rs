@@ -0,0 +1,368 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cancellation::CancellationToken,
+    state::{ConversationMessage, ConversationTextMessage},
+};
+use crate::llm::{
+    ChatCompletionMessage, ChatCompletionMessageContent, ChatCompletionTextMessage, OpenAIClient,
+    OpenAIClientConfig,
+};
+
+pub const DEFAULT_CONTEXT_LENGTH: u32 = 32_000;
+pub const MIN_CONTEXT_LENGTH: u32 = 1_000;
+// Part of the window a request may fill, the rest is left for the answer, tools and counting errors
+const CONTEXT_USAGE_LIMIT: f64 = 0.8;
+// Rough average for English text, other languages take more bytes per token so this overestimates
+pub const BYTES_PER_TOKEN: usize = 4;
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+const IMAGE_TOKENS: u32 = 765;
+const STRIPPED_IMAGE_TEXT: &str = "[Image removed to fit the context window]";
+const SUMMARY_MESSAGE_MAX_CHARS: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextStrategy {
+    #[default]
+    DropOldest,
+    Summarize,
+    StripImages,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextUsage {
+    // Estimated for the whole conversation, before anything was left out
+    pub tokens: u32,
+    pub limit: u32,
+    // Messages left out of the last request or replaced by the summary
+    pub compacted_messages: usize,
+}
+
+// Summary of the messages before `message_count`, reused while that part of the conversation is unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextSummary {
+    pub message_count: usize,
+    pub last_message_at: DateTime<Utc>,
+    pub text: String,
+}
+
+impl ContextSummary {
+    fn covers(&self, conversation: &[ConversationMessage]) -> bool {
+        self.message_count > 0
+            && conversation
+                .get(self.message_count - 1)
+                .is_some_and(|message| message.created_at() == self.last_message_at)
+    }
+}
+
+// The model that writes summaries, the same one the conversation is sent to
+pub struct SummaryModel<'a> {
+    pub openai_client: &'a OpenAIClient,
+    pub config: OpenAIClientConfig,
+    pub api_key: Option<String>,
+}
+
+pub struct FittedConversation {
+    pub conversation: Vec<ConversationMessage>,
+    pub usage: ContextUsage,
+    pub summary: Option<ContextSummary>,
+}
+
+pub fn estimate_text_tokens(text: &str) -> u32 {
+    text.len().div_ceil(BYTES_PER_TOKEN) as u32
+}
+
+pub fn estimate_message_tokens(message: &ConversationMessage) -> u32 {
+    let content_tokens: u32 = match message {
+        ConversationMessage::ConversationTextMessage(message) => message
+            .content
+            .iter()
+            .map(|content| match content {
+                ChatCompletionMessageContent::Text { text } => estimate_text_tokens(text),
+                ChatCompletionMessageContent::Image { .. } => IMAGE_TOKENS,
+            })
+            .sum(),
+        ConversationMessage::ConversationToolCallMessage(message) => message
+            .tool_calls
+            .iter()
+            .map(|tool_call| {
+                MESSAGE_OVERHEAD_TOKENS
+                    + estimate_text_tokens(&tool_call.function.client_name)
+                    + estimate_text_tokens(&tool_call.function.name)
+                    + estimate_text_tokens(&tool_call.function.arguments)
+            })
+            .sum(),
+        ConversationMessage::ConversationToolCallResultMessage(message) => {
+            estimate_text_tokens(&message.content)
+        }
+    };
+    MESSAGE_OVERHEAD_TOKENS + content_tokens
+}
+
+pub fn estimate_tokens(conversation: &[ConversationMessage]) -> u32 {
+    conversation.iter().map(estimate_message_tokens).sum()
+}
+
+/// Makes the conversation fit into the model's context window.
+/// Whole turns are left out from the oldest, so tool calls always keep their results,
+/// and the last turn is always sent.
+pub async fn fit_conversation(
+    conversation: Vec<ConversationMessage>,
+    context_length: u32,
+    // Sent along with the conversation, like the persona examples
+    reserved_tokens: u32,
+    strategy: ContextStrategy,
+    summary: Option<ContextSummary>,
+    summary_model: SummaryModel<'_>,
+    cancellation: &CancellationToken,
+) -> Result<FittedConversation, Box<dyn Error>> {
+    let budget = (context_length as f64 * CONTEXT_USAGE_LIMIT) as u32;
+    let mut usage = ContextUsage {
+        tokens: reserved_tokens + estimate_tokens(&conversation),
+        limit: context_length,
+        compacted_messages: 0,
+    };
+    let summary = summary.filter(|summary| summary.covers(&conversation));
+
+    if usage.tokens <= budget {
+        return Ok(FittedConversation {
+            conversation,
+            usage,
+            summary,
+        });
+    }
+
+    let header_len = conversation
+        .iter()
+        .take_while(|message| message_role(message) == Some("system"))
+        .count();
+    let turn_starts: Vec<usize> = (header_len..conversation.len())
+        .filter(|index| message_role(&conversation[*index]) == Some("user"))
+        .collect();
+    let Some(&last_turn_start) = turn_starts.last() else {
+        return Ok(FittedConversation {
+            conversation,
+            usage,
+            summary,
+        });
+    };
+
+    let mut conversation = conversation;
+    if strategy == ContextStrategy::StripImages {
+        usage.compacted_messages = strip_images(&mut conversation[header_len..last_turn_start]);
+        if reserved_tokens + estimate_tokens(&conversation) <= budget {
+            return Ok(FittedConversation {
+                conversation,
+                usage,
+                summary,
+            });
+        }
+    }
+
+    // The oldest turn start after which everything fits, the last turn is kept regardless
+    let header_tokens = reserved_tokens + estimate_tokens(&conversation[..header_len]);
+    let mut keep_from = turn_starts
+        .iter()
+        .copied()
+        .find(|start| header_tokens + estimate_tokens(&conversation[*start..]) <= budget)
+        .unwrap_or(last_turn_start);
+
+    // A summary reaching past the cut already covers it, a shorter one is extended
+    if let (ContextStrategy::Summarize, Some(summary)) = (strategy, summary.as_ref()) {
+        keep_from = keep_from.max(summary.message_count);
+    }
+    if keep_from <= header_len {
+        return Ok(FittedConversation {
+            conversation,
+            usage,
+            summary,
+        });
+    }
+
+    let mut summary_text = None;
+    let mut next_summary = None;
+    if strategy == ContextStrategy::Summarize {
+        let reusable = summary
+            .as_ref()
+            .filter(|summary| summary.message_count == keep_from);
+
+        let text = match reusable {
+            Some(summary) => Ok(summary.text.clone()),
+            None => {
+                let (previous, from) = match summary.as_ref() {
+                    Some(summary) => (Some(summary.text.as_str()), summary.message_count),
+                    None => (None, header_len),
+                };
+                summarize_messages(
+                    previous,
+                    &conversation[from..keep_from],
+                    summary_model,
+                    cancellation,
+                )
+                .await
+            }
+        };
+        match text {
+            Ok(text) => {
+                summary_text = Some(text.clone());
+                next_summary = Some(ContextSummary {
+                    message_count: keep_from,
+                    last_message_at: conversation[keep_from - 1].created_at(),
+                    text,
+                });
+            }
+            Err(e) if cancellation.is_cancelled() => return Err(e),
+            Err(e) => log::warn!(
+                "Failed to summarise the conversation, leaving it out: {}",
+                e
+            ),
+        }
+    }
+
+    usage.compacted_messages = keep_from - header_len;
+    let mut header = conversation[..header_len].to_vec();
+    if let Some(text) = summary_text {
+        add_summary_to_header(&mut header, &text);
+    }
+    header.extend(conversation.drain(keep_from..));
+    log::info!(
+        "Conversation of about {} tokens left out {} messages to fit {} tokens",
+        usage.tokens,
+        usage.compacted_messages,
+        context_length
+    );
+
+    Ok(FittedConversation {
+        conversation: header,
+        usage,
+        summary: next_summary.or(summary),
+    })
+}
+
+fn message_role(message: &ConversationMessage) -> Option<&str> {
+    match message {
+        ConversationMessage::ConversationTextMessage(message) => Some(message.role.as_str()),
+        _ => None,
+    }
+}
+
+// Returns how many messages had images removed
+fn strip_images(conversation: &mut [ConversationMessage]) -> usize {
+    let mut stripped = 0;
+    for message in conversation {
+        let ConversationMessage::ConversationTextMessage(message) = message else {
+            continue;
+        };
+        let mut has_images = false;
+        for content in message.content.iter_mut() {
+            if let ChatCompletionMessageContent::Image { .. } = content {
+                *content = ChatCompletionMessageContent::Text {
+                    text: STRIPPED_IMAGE_TEXT.to_string(),
+                };
+                has_images = true;
+            }
+        }
+        if has_images {
+            stripped += 1;
+        }
+    }
+    stripped
+}
+
+fn add_summary_to_header(header: &mut Vec<ConversationMessage>, summary: &str) {
+    let text = format!(
+        "Summary of the earlier part of this conversation:\n{}",
+        summary
+    );
+    match header.first_mut() {
+        Some(ConversationMessage::ConversationTextMessage(system_message)) => {
+            system_message
+                .content
+                .push(ChatCompletionMessageContent::Text {
+                    text: format!("\n\n{}", text),
+                });
+        }
+        _ => header.insert(
+            0,
+            ConversationMessage::ConversationTextMessage(ConversationTextMessage {
+                audio_file_path: None,
+                role: "system".to_string(),
+                content: vec![ChatCompletionMessageContent::Text { text }],
+                created_at: Utc::now(),
+            }),
+        ),
+    }
+}
+
+async fn summarize_messages(
+    previous_summary: Option<&str>,
+    messages: &[ConversationMessage],
+    summary_model: SummaryModel<'_>,
+    cancellation: &CancellationToken,
+) -> Result<String, Box<dyn Error>> {
+    let mut transcript = String::new();
+    if let Some(previous_summary) = previous_summary {
+        transcript.push_str(&format!("Earlier summary:\n{}\n\n", previous_summary));
+    }
+    for message in messages {
+        let line = match message {
+            ConversationMessage::ConversationTextMessage(message) => {
+                let text = message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        ChatCompletionMessageContent::Text { text } => Some(text.as_str()),
+                        ChatCompletionMessageContent::Image { .. } => Some("[image]"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{}: {}", message.role, text)
+            }
+            ConversationMessage::ConversationToolCallMessage(message) => message
+                .tool_calls
+                .iter()
+                .map(|tool_call| {
+                    format!(
+                        "assistant called {} with {}",
+                        tool_call.function.name, tool_call.function.arguments
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ConversationMessage::ConversationToolCallResultMessage(message) => {
+                format!("tool result: {}", message.content)
+            }
+        };
+        transcript.extend(line.chars().take(SUMMARY_MESSAGE_MAX_CHARS));
+        transcript.push('\n');
+    }
+
+    let messages = vec![
+        ChatCompletionMessage::ChatCompletionTextMessage(ChatCompletionTextMessage {
+            role: "system".to_string(),
+            content: vec![ChatCompletionMessageContent::Text {
+                text: "You summarise the earlier part of a conversation between a user and an assistant, so that it can continue without it. Keep facts, decisions, names, numbers, open questions and anything the user asked to remember. Use the same language as the conversation. Respond only with the summary.".to_string(),
+            }],
+            created_at: Utc::now(),
+        }),
+        ChatCompletionMessage::ChatCompletionTextMessage(ChatCompletionTextMessage {
+            role: "user".to_string(),
+            content: vec![ChatCompletionMessageContent::Text { text: transcript }],
+            created_at: Utc::now(),
+        }),
+    ];
+
+    let summary = cancellation
+        .run(summary_model.openai_client.chat_completion_non_streaming(
+            messages,
+            None,
+            summary_model.config,
+            summary_model.api_key,
+            None,
+        ))
+        .await??;
+    Ok(summary.trim().to_string())
+}
@@ -0,0 +1,159 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Event,
+    processor::Processor,
+    state::{AppState, AppStateContext},
+};
+
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_PREFERRED_INPUT_DEVICES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputDevice {
+    pub id: String,
+    pub name: String,
+    // The name, numbered when several devices share it
+    pub label: String,
+}
+
+pub struct DevicesProcessor;
+
+impl DevicesProcessor {
+    pub fn start() {
+        Processor::register_event_listener(
+            "devices",
+            Box::new(|event, _app_handle| match event {
+                Event::ActionInputDevicesChanged(devices) => AppState::update(|context| {
+                    update_input_devices(context, devices);
+                }),
+                Event::ActionChangeInputDevice(Some(device_id)) => AppState::update(|context| {
+                    if let Some(device) = find_input_device(&context.input_devices, &device_id) {
+                        let id = device.id.clone();
+                        let preferred = &mut context.preferred_input_devices;
+                        preferred.retain(|preferred_id| preferred_id != &id);
+                        preferred.insert(0, id);
+                        preferred.truncate(MAX_PREFERRED_INPUT_DEVICES);
+                    }
+                }),
+                _ => Ok(()),
+            }),
+        );
+
+        // The first listing goes straight into the state so the tray and the recorder
+        // start with the resolved device, later changes go through events
+        let devices = list_input_devices().unwrap_or_else(|e| {
+            log::warn!("Failed to list input devices: {}", e);
+            Vec::new()
+        });
+        if let Err(e) = AppState::update(|context| {
+            update_input_devices(context, devices.clone());
+        }) {
+            log::error!("Failed to update input devices: {}", e);
+        }
+
+        start_device_watcher(devices);
+    }
+}
+
+fn list_input_devices() -> Result<Vec<InputDevice>, String> {
+    let devices = qspeak_audio_recording::list_input_devices()?;
+
+    let mut name_totals: HashMap<String, usize> = HashMap::new();
+    for device in devices.iter() {
+        *name_totals.entry(device.name.clone()).or_insert(0) += 1;
+    }
+
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    let devices = devices
+        .into_iter()
+        .map(|device| {
+            let count = name_counts.entry(device.name.clone()).or_insert(0);
+            *count += 1;
+            let label = if name_totals[&device.name] > 1 {
+                format!("{} ({})", device.name, count)
+            } else {
+                device.name.clone()
+            };
+            InputDevice {
+                id: device.id,
+                name: device.name,
+                label,
+            }
+        })
+        .collect();
+    Ok(devices)
+}
+
+// Devices are picked by id, older settings and clients still send the name
+pub fn find_input_device<'a>(
+    devices: &'a [InputDevice],
+    device_id: &str,
+) -> Option<&'a InputDevice> {
+    devices
+        .iter()
+        .find(|device| device.id == device_id)
+        .or_else(|| devices.iter().find(|device| device.name == device_id))
+}
+
+fn update_input_devices(context: &mut AppStateContext, devices: Vec<InputDevice>) {
+    // Fall back to the next preferred device when the selected one is gone,
+    // and switch back once a more preferred device is plugged in again
+    if let Some(device) = resolve_input_device(
+        &context.preferred_input_devices,
+        &context.input_device,
+        &devices,
+    ) {
+        if context.input_device.as_ref() != Some(&device.id) {
+            log::info!("Switching input device to {}", device.label);
+            context.input_device = Some(device.id);
+        }
+    }
+    context.input_devices = devices;
+}
+
+fn resolve_input_device(
+    preferred_input_devices: &[String],
+    current_input_device: &Option<String>,
+    devices: &[InputDevice],
+) -> Option<InputDevice> {
+    preferred_input_devices
+        .iter()
+        .find_map(|id| devices.iter().find(|device| &device.id == id))
+        .or_else(|| {
+            current_input_device
+                .as_ref()
+                .and_then(|device_id| find_input_device(devices, device_id))
+        })
+        .cloned()
+}
+
+// cpal has no device change notifications, so the device list is polled
+fn start_device_watcher(initial_devices: Vec<InputDevice>) {
+    std::thread::spawn(move || {
+        let mut known_devices = initial_devices;
+
+        loop {
+            std::thread::sleep(DEVICE_POLL_INTERVAL);
+
+            let devices = match list_input_devices() {
+                Ok(devices) => devices,
+                Err(e) => {
+                    log::warn!("Failed to list input devices: {}", e);
+                    continue;
+                }
+            };
+
+            if devices != known_devices {
+                log::info!("Input devices changed: {} available", devices.len());
+                known_devices = devices.clone();
+                if let Err(e) = Processor::process_event(Event::ActionInputDevicesChanged(devices))
+                {
+                    log::error!("Failed to process input devices changed event: {}", e);
+                }
+            }
+        }
+    });
+}
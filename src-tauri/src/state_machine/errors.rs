@@ -51,3 +51,15 @@ impl fmt::Display for TransformationModelNotFoundError {
         write!(f, "Transformation model not found")
     }
 }
+
+// A model request that failed before any of the answer arrived
+#[derive(Debug, Clone)]
+pub struct ModelRequestError(pub String);
+
+impl error::Error for ModelRequestError {}
+
+impl fmt::Display for ModelRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
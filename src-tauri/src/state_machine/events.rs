@@ -1,14 +1,29 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::state_machine::personas::PersonaExample;
+use crate::state_machine::personas::{
+    GenerationParams, PersonaExample, PersonaTools, StructuredOutput,
+};
 use crate::{
     api::accounts::LoginVerifyResponse, koboldcpp_server::KoboldCppServerState,
     llm::ChatCompletionChunkToolCall,
 };
 
 use super::{
-    InterfaceTheme, Language, account::LoginVerifyPayload, new_conversation::ToolCallResult,
-    new_mcp_processor::MCPServerConfig, personas::Persona, state::ConversationMessage,
+    InterfaceTheme, Language,
+    account::LoginVerifyPayload,
+    audio_retention::AudioRetentionContext,
+    builtin_tools::BuiltinToolsConfig,
+    connectivity::NetworkMode,
+    context_window::ContextStrategy,
+    devices::InputDevice,
+    models::{LlmProvider, default_context_length},
+    new_conversation::ToolCallResult,
+    new_mcp_processor::{MCPServerConfig, MCPToolPolicy},
+    personas::Persona,
+    state::ConversationMessage,
+    usage::ModelPrice,
 };
 
 use crate::api::releases::Release;
@@ -43,6 +58,16 @@ pub struct NewPersona {
     pub record_output_audio: bool,
     #[serde(default)]
     pub examples: Vec<PersonaExample>,
+    #[serde(default)]
+    pub meeting_mode: bool,
+    #[serde(default)]
+    pub tools: PersonaTools,
+    #[serde(default)]
+    pub generation: GenerationParams,
+    #[serde(default)]
+    pub structured_output: Option<StructuredOutput>,
+    #[serde(default)]
+    pub cloud_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +79,10 @@ pub struct NewConversationModel {
     pub supports_tools: bool,
     #[serde(default)]
     pub supports_vision: bool,
+    #[serde(default = "default_context_length")]
+    pub context_length: u32,
+    #[serde(default)]
+    pub provider: LlmProvider,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +93,49 @@ pub struct UpdateConversationModel {
     pub api_key: Option<String>,
     pub supports_tools: bool,
     pub supports_vision: bool,
+    #[serde(default = "default_context_length")]
+    pub context_length: u32,
+    #[serde(default)]
+    pub provider: LlmProvider,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolPolicyPayload {
+    pub server_id: String,
+    // None changes the policy of the whole server
+    pub tool_name: Option<String>,
+    // None removes the tool override and falls back to the server policy
+    pub policy: Option<MCPToolPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolTimeoutPayload {
+    pub server_id: String,
+    // None changes the timeout of the whole server
+    pub tool_name: Option<String>,
+    // None removes the tool override and falls back to the server timeout
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPPromptPayload {
+    pub server_key: String,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPResourcePayload {
+    pub server_key: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricePayload {
+    pub model_name: String,
+    // None removes the price
+    pub price: Option<ModelPrice>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +145,26 @@ pub struct WebsocketServerSettingsPayload {
     pub password: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPServerSettingsPayload {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditMessagePayload {
+    // Index in the current conversation, system message included
+    pub message_index: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForkHistoryPayload {
+    pub history_id: String,
+    // The fork keeps the messages up to and including this one
+    pub message_index: usize,
+}
+
 impl Default for Shortcuts {
     fn default() -> Self {
         Self {
@@ -138,14 +230,23 @@ pub enum Event {
     ActionTransformationToolCallResult(ToolCallResult),
     ActionTransformationError(String),
     ActionTransformationSuccess(),
+    ActionCancelProcessing,
+    PauseTranscription,
+    ResumeTranscription,
     ActionTextMessage(String),
     ActionLoadHistoryConversation(String),
     ActionStartNewConversation,
+    ActionEditMessage(EditMessagePayload),
+    ActionRegenerateResponse,
+    ActionRetryFailedStep,
+    ActionForkHistory(ForkHistoryPayload),
 
     ActionUpdateOrCreateHistory(Option<Persona>, Vec<ConversationMessage>),
     ActionGenerateHistoryTitle(String),
     ActionDeleteHistory(String),
     ActionClearHistory,
+    ActionUpdateAudioRetentionSettings(AudioRetentionContext),
+    ActionRunAudioRetention,
 
     ActionLogin(String),
     ActionLoginSuccess(String),
@@ -157,24 +258,53 @@ pub enum Event {
     ActionAddDictionaryItem(String),
     ActionDeleteDictionaryItem(String),
 
+    ActionChangeMeetingChunkMinutes(u32),
+    ActionMeetingChunkRecorded(String),
+    ActionMeetingChunkTranscriptionSuccess(String, String),
+    ActionMeetingChunkTranscriptionError(String, String),
+    ActionMeetingTranscriptReady(String),
+
+    ActionChangePreRollEnabled(bool),
+    ActionChangePreRollMs(u32),
+    ActionChangeMaxToolIterations(u32),
+    ActionChangeContextStrategy(ContextStrategy),
+
     ActionAddTool(MCPServerConfig),
+    ActionImportTools(String),
     ActionDeleteTool(String),
     ActionEnableTool(String),
     ActionDisableTool(String),
     ActionUpdateTool(MCPServerConfig),
+    ActionChangeToolPolicy(ToolPolicyPayload),
+    ActionChangeToolTimeout(ToolTimeoutPayload),
+    ActionUpdateBuiltinTools(BuiltinToolsConfig),
+    ActionMCPCatalogChanged(String),
+    ActionSelectMCPPrompt(MCPPromptPayload),
+    ActionAddMCPResource(MCPResourcePayload),
+    ActionApproveToolCall(String),
+    ActionDenyToolCall(String),
+    ActionToolApprovalTimeout(String),
 
     // TODO: FIX ALL EVENTS BELOW
 
     // Transcription events
     StartListening,
     StopListening,
-    PauseTranscription,
-    ResumeTranscription,
     TranscriptionError(String),
     ResetTranscription,
     ActionChangeTranscriptionModel(Option<String>),
     ActionChangeConversationModel(Option<String>),
+    ActionChangeFallbackTranscriptionModel(Option<String>),
+    ActionChangeFallbackConversationModel(Option<String>),
+    ActionUpdateModelPrice(ModelPricePayload),
+    ActionClearUsageStats,
+    ActionChangeLocalOnly(bool),
+    ConnectivityChange(bool),
+    NetworkModeChange(NetworkMode),
     ActionUpdateWebsocketServerSettings(WebsocketServerSettingsPayload),
+    ActionUpdateMCPServerSettings(MCPServerSettingsPayload),
+    ActionVoiceInputSuccess(String),
+    ActionVoiceInputError(String),
     // Recording window events
     ActionChangeTheme(Option<InterfaceTheme>),
 
@@ -196,6 +326,9 @@ pub enum Event {
 
     // Input device events
     ActionChangeInputDevice(Option<String>),
+    ActionInputDevicesChanged(Vec<InputDevice>),
+    ActionInputDeviceDisconnected,
+
     // KoboldCPP server events
     KoboldCppServerStateChange(KoboldCppServerState),
 
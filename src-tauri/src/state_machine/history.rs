@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use tauri::async_runtime::block_on;
 
 use super::state::{AppStateContext, ConversationMessage};
-use super::{Event, personas::Persona, processor::Processor};
+use super::{Event, connectivity, errors::AppError, history_store, personas::Persona, processor::Processor};
 use crate::{
     llm::{ChatCompletionMessage, ChatCompletionMessageContent, ChatCompletionTextMessage, OpenAIClient, OpenAIClientConfig},
     state_machine::AppState,
@@ -32,17 +32,34 @@ impl HistoryProcessor {
                     
 
                     if let Some(current_id) = current_history_id {
-                        if let Some(history) = context
+                        if let Some(summary) = context
                             .history_context
                             .history
                             .iter_mut()
                             .find(|h| h.id == current_id)
                         {
-                            history.conversation = non_system_messages.clone();
-                            if let Some(persona) = persona.as_ref() {
-                                history.persona_name = Some(persona.name.clone());
+                            let history = History {
+                                id: summary.id.clone(),
+                                title: summary.title.clone(),
+                                persona_name: persona
+                                    .as_ref()
+                                    .map(|persona| persona.name.clone())
+                                    .or(summary.persona_name.clone()),
+                                model_name: summary.model_name.clone(),
+                                conversation: non_system_messages,
+                                created_at: summary.created_at,
+                                parent: summary.parent.clone(),
+                            };
+                            match history_store::save(&history) {
+                                Ok(updated) => {
+                                    *summary = updated;
+                                    log::info!("Updated history entry {}, conversation has {} messages", current_id, summary.message_count);
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to update history entry {}: {}", current_id, e);
+                                    context.errors.push(AppError::with_message(format!("Failed to save history: {}", e)));
+                                }
                             }
-                            log::info!("Updated history entry {}, conversation has {} messages", current_id, non_system_messages.len());
                         } else {
                             log::warn!("History entry with ID {} not found, creating new one", current_id);
                             create_new_history_entry(context, persona, non_system_messages);
@@ -52,18 +69,24 @@ impl HistoryProcessor {
                     }
                 }),
                 Event::ActionClearHistory => AppState::update(|context| {
+                    if let Err(e) = history_store::clear() {
+                        log::error!("Failed to clear history: {}", e);
+                        context.errors.push(AppError::with_message(format!("Failed to clear history: {}", e)));
+                        return;
+                    }
                     context.history_context.history.clear();
                     context.history_context.current_history_id = None;
                 }),
                 Event::ActionDeleteHistory(id) => AppState::update(|context| {
-                    let history = context
-                        .history_context
-                        .history
-                        .iter()
-                        .find(|history| history.id == id)
-                        .expect("Failed to find history");
-
-                    for message in history.conversation.iter() {
+                    let history = match history_store::get(&id) {
+                        Ok(history) => history,
+                        Err(e) => {
+                            log::error!("Failed to load history entry {}: {}", id, e);
+                            None
+                        }
+                    };
+
+                    for message in history.iter().flat_map(|history| history.conversation.iter()) {
                         match message {
                             ConversationMessage::ConversationTextMessage(text_message) => {
                                 if let Some(audio_file_path) = text_message.audio_file_path.clone()
@@ -75,6 +98,11 @@ impl HistoryProcessor {
                         }
                     }
 
+                    if let Err(e) = history_store::delete(&id) {
+                        log::error!("Failed to delete history entry {}: {}", id, e);
+                        context.errors.push(AppError::with_message(format!("Failed to delete history: {}", e)));
+                        return;
+                    }
                     context.history_context.history.retain(|h| h.id != id);
                     
                     if context.history_context.current_history_id.as_ref() == Some(&id) {
@@ -101,7 +129,7 @@ fn create_new_history_entry(
     let history_id = uuid::Uuid::new_v4().to_string();
     log::info!("Creating new history entry with ID: {}", history_id);
     
-    context.history_context.history.push(History {
+    let history = History {
         id: history_id.clone(),
         title: None,
         persona_name: persona.as_ref().map(|p| p.name.clone()),
@@ -111,9 +139,19 @@ fn create_new_history_entry(
             .expect("Failed to get conversation model"),
         conversation: conversation.clone(),
         created_at: Utc::now(),
-    });
+        parent: context.history_context.branch_parent.clone(),
+    };
+    match history_store::save(&history) {
+        Ok(summary) => context.history_context.history.push(summary),
+        Err(e) => {
+            log::error!("Failed to create history entry {}: {}", history_id, e);
+            context.errors.push(AppError::with_message(format!("Failed to save history: {}", e)));
+            return;
+        }
+    }
     
     context.history_context.current_history_id = Some(history_id.clone());
+    context.history_context.branch_parent = None;
     
     log::info!("History entry created, conversation has {} messages", conversation.len());
     
@@ -128,6 +166,34 @@ fn create_new_history_entry(
         .expect("Failed to process generate history title event");
 }
 
+// Copies a history entry up to and including the message into a new entry branching from it
+pub fn fork_history(
+    history_id: &str,
+    message_index: usize,
+) -> Result<History, Box<dyn std::error::Error>> {
+    let parent = history_store::get(history_id)?.ok_or("History entry not found")?;
+    if message_index >= parent.conversation.len() {
+        return Err(format!("Message {} not found in history entry {}", message_index, history_id).into());
+    }
+
+    let history = History {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: parent.title.clone(),
+        persona_name: parent.persona_name.clone(),
+        model_name: parent.model_name.clone(),
+        conversation: parent.conversation[..=message_index].to_vec(),
+        created_at: Utc::now(),
+        parent: Some(HistoryParent {
+            id: parent.id.clone(),
+            message_count: message_index + 1,
+        }),
+    };
+    history_store::save(&history)?;
+    log::info!("Forked history entry {} into {}", history_id, history.id);
+
+    Ok(history)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct History {
     pub id: String,
@@ -137,12 +203,39 @@ pub struct History {
     pub model_name: String,
     pub conversation: Vec<ConversationMessage>,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub parent: Option<HistoryParent>,
 }
 
 fn default_title() -> Option<String> {
     None
 }
 
+// What the app state keeps of a history entry, the conversation is loaded from the history store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub id: String,
+    pub title: Option<String>,
+    pub persona_name: Option<String>,
+    pub model_name: String,
+    pub created_at: DateTime<Utc>,
+    pub message_count: usize,
+    // First user message, shown until a title is generated
+    pub preview: Option<String>,
+    #[serde(default)]
+    pub has_audio: bool,
+    #[serde(default)]
+    pub parent: Option<HistoryParent>,
+}
+
+// The entry a branch was forked from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryParent {
+    pub id: String,
+    // Messages the branch shares with its parent, the branch differs from the next one on
+    pub message_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersonaHistory {
     pub id: String,
@@ -151,11 +244,17 @@ pub struct PersonaHistory {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryContext {
-    pub history: Vec<History>,
+    pub history: Vec<HistorySummary>,
     #[serde(default = "default_last_persona")]
     pub last_persona: Option<PersonaHistory>,
     #[serde(default = "default_current_history_id")]
     pub current_history_id: Option<String>,
+    // Parent of the entry that will be created for the current conversation
+    #[serde(default)]
+    pub branch_parent: Option<HistoryParent>,
+    // Legacy entries the history store failed to import, saved back to the app state store
+    #[serde(skip)]
+    pub legacy_history: Vec<History>,
 }
 
 fn default_last_persona() -> Option<PersonaHistory> {
@@ -172,6 +271,8 @@ impl Default for HistoryContext {
             history: vec![],
             last_persona: None,
             current_history_id: None,
+            branch_parent: None,
+            legacy_history: vec![],
         }
     }
 }
@@ -193,12 +294,13 @@ impl Default for HistoryMachineState {
 fn generate_title_for_history(history_id: String) {
     let app_context = AppState::get_context();
     
-    let history = app_context
-        .history_context
-        .history
-        .iter()
-        .find(|h| h.id == history_id)
-        .cloned();
+    let history = match history_store::get(&history_id) {
+        Ok(history) => history,
+        Err(e) => {
+            log::error!("Title generation: Failed to load history {}: {}", history_id, e);
+            None
+        }
+    };
     
     if let Some(history) = history {
         let conversation_text = extract_conversation_for_title(&history.conversation);
@@ -212,7 +314,14 @@ fn generate_title_for_history(history_id: String) {
             return;
         }
 
-        let active_model = conversation_model.unwrap();
+        // Titles are skipped rather than sent to the cloud while offline or local only
+        let active_model = match connectivity::conversation_models_for_mode(&app_context, &conversation_model.unwrap()) {
+            Ok((active_model, _)) => active_model,
+            Err(e) => {
+                log::warn!("Title generation: {}", e);
+                return;
+            }
+        };
 
         std::thread::spawn(move || {
             let model = AppState::get_context()
@@ -276,6 +385,10 @@ fn generate_title_for_history(history_id: String) {
                 Ok(title) => {
                     log::info!("Generated title for history {}: {}", history_id, title);
                     AppState::update(|context| {
+                        if let Err(e) = history_store::set_title(&history_id, &title) {
+                            log::error!("Failed to save title for history {}: {}", history_id, e);
+                            return;
+                        }
                         if let Some(history) = context
                             .history_context
                             .history
@@ -0,0 +1,462 @@
+use std::{error::Error, fs, path::PathBuf, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, params, params_from_iter, types::Value};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::{
+    history::{History, HistoryParent, HistorySummary},
+    state::ConversationMessage,
+};
+use crate::llm::ChatCompletionMessageContent;
+
+const DATABASE_FILENAME: &str = "history.sqlite3";
+const PREVIEW_LENGTH: usize = 200;
+const SNIPPET_TOKENS: usize = 24;
+// Wrapped around the matched words in search snippets
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+const SUMMARY_COLUMNS: &str = "history.id, history.title, history.persona_name, history.model_name, \
+    history.created_at, history.message_count, history.preview, history.has_audio, \
+    history.parent_id, history.parent_message_count";
+// Index of the first column selected after the summary
+const SUMMARY_COLUMN_COUNT: usize = 10;
+
+// Applied in order, PRAGMA user_version counts the ones already applied
+const SCHEMA_MIGRATIONS: [&str; 4] = [
+    "CREATE TABLE IF NOT EXISTS history (
+        id TEXT PRIMARY KEY,
+        title TEXT,
+        persona_name TEXT,
+        model_name TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        message_count INTEGER NOT NULL,
+        preview TEXT,
+        content_text TEXT NOT NULL,
+        conversation TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS history_created_at ON history (created_at);",
+    "ALTER TABLE history ADD COLUMN has_audio INTEGER NOT NULL DEFAULT 0;
+    CREATE VIRTUAL TABLE history_fts USING fts5(
+        history_id UNINDEXED,
+        title,
+        content_text,
+        tokenize = 'unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+        INSERT INTO history_fts (history_id, title, content_text)
+        VALUES (new.id, new.title, new.content_text);
+    END;
+    CREATE TRIGGER history_fts_update AFTER UPDATE OF title, content_text ON history BEGIN
+        DELETE FROM history_fts WHERE history_id = old.id;
+        INSERT INTO history_fts (history_id, title, content_text)
+        VALUES (new.id, new.title, new.content_text);
+    END;
+    CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+        DELETE FROM history_fts WHERE history_id = old.id;
+    END;",
+    "ALTER TABLE history ADD COLUMN parent_id TEXT;
+    ALTER TABLE history ADD COLUMN parent_message_count INTEGER;
+    CREATE INDEX history_parent_id ON history (parent_id);",
+    // Usage totals per local day, kept in this file so they share its lifecycle but clearing
+    // history leaves them alone. The empty persona name stands for requests made without one
+    "CREATE TABLE usage (
+        day TEXT NOT NULL,
+        model_name TEXT NOT NULL,
+        persona_name TEXT NOT NULL,
+        requests INTEGER NOT NULL DEFAULT 0,
+        prompt_tokens INTEGER NOT NULL DEFAULT 0,
+        completion_tokens INTEGER NOT NULL DEFAULT 0,
+        audio_seconds REAL NOT NULL DEFAULT 0,
+        PRIMARY KEY (day, model_name, persona_name)
+    );",
+];
+// Tool results and audio are indexed from this version, older rows are rebuilt
+const SEARCH_INDEX_VERSION: usize = 2;
+
+// Full conversations stay on disk, the app state only keeps their summaries
+static HISTORY_DB: Mutex<Option<Connection>> = Mutex::new(None);
+static DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySearchQuery {
+    // Words matched against titles, transcriptions, assistant output and tool results
+    pub text: String,
+    pub persona_name: Option<String>,
+    pub model_name: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub has_audio: Option<bool>,
+    // None returns every match
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySearchResult {
+    #[serde(flatten)]
+    pub summary: HistorySummary,
+    // Best matching part of the conversation, matches wrapped in HIGHLIGHT_START and HIGHLIGHT_END
+    pub snippet: String,
+}
+
+pub fn init(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
+    let dir = app_handle.path().app_data_dir()?;
+    fs::create_dir_all(&dir)?;
+
+    let mut connection = Connection::open(dir.join(DATABASE_FILENAME))?;
+    connection.execute_batch("PRAGMA journal_mode = WAL;")?;
+
+    let version =
+        connection.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))? as usize;
+    for (index, migration) in SCHEMA_MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.execute_batch(&format!("PRAGMA user_version = {};", index + 1))?;
+        transaction.commit()?;
+        log::info!("Migrated history database to version {}", index + 1);
+    }
+
+    *HISTORY_DB
+        .lock()
+        .map_err(|_| "Failed to lock history database")? = Some(connection);
+    *DATA_DIR
+        .lock()
+        .map_err(|_| "Failed to lock history directory")? = Some(dir);
+
+    if version < SEARCH_INDEX_VERSION {
+        visit_conversations(|_| true)?;
+    }
+    log::info!("History database opened");
+    Ok(())
+}
+
+pub(super) fn with_connection<T>(
+    f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+) -> Result<T, Box<dyn Error>> {
+    let mut db = HISTORY_DB
+        .lock()
+        .map_err(|_| "Failed to lock history database")?;
+    let connection = db.as_mut().ok_or("History database is not open")?;
+    Ok(f(connection)?)
+}
+
+// Oldest first, the same order history was kept in before
+pub fn list() -> Result<Vec<HistorySummary>, Box<dyn Error>> {
+    with_connection(|connection| {
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM history ORDER BY history.created_at ASC",
+            SUMMARY_COLUMNS
+        ))?;
+        let summaries = statement
+            .query_map([], summary_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(summaries)
+    })
+}
+
+pub fn get(id: &str) -> Result<Option<History>, Box<dyn Error>> {
+    let row = with_connection(|connection| {
+        connection
+            .query_row(
+                &format!(
+                    "SELECT {}, conversation FROM history WHERE id = ?1",
+                    SUMMARY_COLUMNS
+                ),
+                params![id],
+                |row| {
+                    Ok((
+                        summary_from_row(row)?,
+                        row.get::<_, String>(SUMMARY_COLUMN_COUNT)?,
+                    ))
+                },
+            )
+            .optional()
+    })?;
+
+    let Some((summary, conversation)) = row else {
+        return Ok(None);
+    };
+    Ok(Some(History {
+        id: summary.id,
+        title: summary.title,
+        persona_name: summary.persona_name,
+        model_name: summary.model_name,
+        conversation: serde_json::from_str(&conversation)?,
+        created_at: summary.created_at,
+        parent: summary.parent,
+    }))
+}
+
+// Inserts the entry or replaces it when the id already exists
+pub fn save(history: &History) -> Result<HistorySummary, Box<dyn Error>> {
+    let row = HistoryRow::new(history)?;
+    with_connection(|connection| row.save(connection))?;
+    Ok(row.summary)
+}
+
+pub fn set_title(id: &str, title: &str) -> Result<(), Box<dyn Error>> {
+    with_connection(|connection| {
+        connection.execute(
+            "UPDATE history SET title = ?1 WHERE id = ?2",
+            params![title, id],
+        )
+    })?;
+    Ok(())
+}
+
+pub fn delete(id: &str) -> Result<(), Box<dyn Error>> {
+    with_connection(|connection| {
+        connection.execute("DELETE FROM history WHERE id = ?1", params![id])
+    })?;
+    Ok(())
+}
+
+// Only the conversations, the usage table in the same file is cleared from usage.rs
+pub fn clear() -> Result<(), Box<dyn Error>> {
+    with_connection(|connection| connection.execute("DELETE FROM history", []))?;
+    Ok(())
+}
+
+// All or nothing, used to move entries out of the JSON store
+pub fn import(histories: &[History]) -> Result<(), Box<dyn Error>> {
+    let rows = histories
+        .iter()
+        .map(HistoryRow::new)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    with_connection(|connection| {
+        let transaction = connection.transaction()?;
+        for row in rows.iter() {
+            row.save(&transaction)?;
+        }
+        transaction.commit()
+    })
+}
+
+// Best matches first when there is text to match, newest first otherwise
+pub fn search(query: &HistorySearchQuery) -> Result<Vec<HistorySearchResult>, Box<dyn Error>> {
+    let match_expression = match_expression(&query.text);
+
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(match_expression) = match_expression.as_ref() {
+        conditions.push("history_fts MATCH ?");
+        values.push(Value::Text(match_expression.clone()));
+    }
+    if let Some(persona_name) = query.persona_name.as_ref() {
+        conditions.push("history.persona_name = ?");
+        values.push(Value::Text(persona_name.clone()));
+    }
+    if let Some(model_name) = query.model_name.as_ref() {
+        conditions.push("history.model_name = ?");
+        values.push(Value::Text(model_name.clone()));
+    }
+    if let Some(from) = query.from {
+        conditions.push("history.created_at >= ?");
+        values.push(Value::Integer(from.timestamp_millis()));
+    }
+    if let Some(to) = query.to {
+        conditions.push("history.created_at <= ?");
+        values.push(Value::Integer(to.timestamp_millis()));
+    }
+    if let Some(has_audio) = query.has_audio {
+        conditions.push("history.has_audio = ?");
+        values.push(Value::Integer(has_audio as i64));
+    }
+    // A negative limit is no limit in SQLite
+    values.push(Value::Integer(
+        query.limit.map(|limit| limit as i64).unwrap_or(-1),
+    ));
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    let sql = match match_expression {
+        // Title matches weigh more than matches in the conversation
+        Some(_) => format!(
+            "SELECT {}, snippet(history_fts, 2, '{}', '{}', '…', {}) FROM history_fts
+            JOIN history ON history.id = history_fts.history_id {}
+            ORDER BY bm25(history_fts, 0.0, 5.0, 1.0), history.created_at DESC LIMIT ?",
+            SUMMARY_COLUMNS, HIGHLIGHT_START, HIGHLIGHT_END, SNIPPET_TOKENS, where_clause
+        ),
+        None => format!(
+            "SELECT {}, coalesce(history.preview, '') FROM history {}
+            ORDER BY history.created_at DESC LIMIT ?",
+            SUMMARY_COLUMNS, where_clause
+        ),
+    };
+
+    with_connection(|connection| {
+        let mut statement = connection.prepare(&sql)?;
+        let results = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(HistorySearchResult {
+                    summary: summary_from_row(row)?,
+                    snippet: row.get(SUMMARY_COLUMN_COUNT)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(results)
+    })
+}
+
+// Every word has to match, as a prefix so results show up while typing
+fn match_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+// Visits every stored conversation, entries the callback changes are written back
+pub fn visit_conversations(
+    mut update_fn: impl FnMut(&mut Vec<ConversationMessage>) -> bool,
+) -> Result<(), Box<dyn Error>> {
+    for summary in list()? {
+        let Some(mut history) = get(&summary.id)? else {
+            continue;
+        };
+        if update_fn(&mut history.conversation) {
+            save(&history)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn summarize(history: &History) -> HistorySummary {
+    HistorySummary {
+        id: history.id.clone(),
+        title: history.title.clone(),
+        persona_name: history.persona_name.clone(),
+        model_name: history.model_name.clone(),
+        created_at: history.created_at,
+        message_count: history.conversation.len(),
+        preview: conversation_texts(&history.conversation)
+            .find(|(role, _)| *role == "user")
+            .map(|(_, text)| text.chars().take(PREVIEW_LENGTH).collect()),
+        has_audio: history.conversation.iter().any(|message| match message {
+            ConversationMessage::ConversationTextMessage(message) => {
+                message.audio_file_path.is_some()
+            }
+            _ => false,
+        }),
+        parent: history.parent.clone(),
+    }
+}
+
+// Role and text of every user, assistant and tool message
+fn conversation_texts(conversation: &[ConversationMessage]) -> impl Iterator<Item = (&str, &str)> {
+    conversation.iter().flat_map(|message| {
+        let texts: Vec<(&str, &str)> = match message {
+            ConversationMessage::ConversationTextMessage(message) if message.role != "system" => {
+                message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        ChatCompletionMessageContent::Text { text } => {
+                            Some((message.role.as_str(), text.as_str()))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            }
+            ConversationMessage::ConversationToolCallResultMessage(message) => {
+                vec![(message.role.as_str(), message.content.as_str())]
+            }
+            _ => vec![],
+        };
+        texts
+    })
+}
+
+fn summary_from_row(row: &Row) -> rusqlite::Result<HistorySummary> {
+    let created_at: i64 = row.get(4)?;
+    let message_count: i64 = row.get(5)?;
+    Ok(HistorySummary {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        persona_name: row.get(2)?,
+        model_name: row.get(3)?,
+        created_at: DateTime::<Utc>::from_timestamp_millis(created_at).unwrap_or_default(),
+        message_count: message_count as usize,
+        preview: row.get(6)?,
+        has_audio: row.get(7)?,
+        parent: match row.get::<_, Option<String>>(8)? {
+            Some(id) => Some(HistoryParent {
+                id,
+                message_count: row.get::<_, Option<i64>>(9)?.unwrap_or_default() as usize,
+            }),
+            None => None,
+        },
+    })
+}
+
+struct HistoryRow {
+    summary: HistorySummary,
+    content_text: String,
+    conversation: String,
+}
+
+impl HistoryRow {
+    fn new(history: &History) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            summary: summarize(history),
+            // One message per line, indexed for search
+            content_text: conversation_texts(&history.conversation)
+                .map(|(_, text)| text.replace('\n', " "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            conversation: serde_json::to_string(&history.conversation)?,
+        })
+    }
+
+    fn save(&self, connection: &Connection) -> rusqlite::Result<usize> {
+        connection.execute(
+            // An upsert rather than INSERT OR REPLACE, the replace wouldn't run the delete trigger
+            "INSERT INTO history
+            (id, title, persona_name, model_name, created_at, message_count, preview, has_audio,
+                parent_id, parent_message_count, content_text, conversation)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT (id) DO UPDATE SET
+                title = excluded.title,
+                persona_name = excluded.persona_name,
+                model_name = excluded.model_name,
+                created_at = excluded.created_at,
+                message_count = excluded.message_count,
+                preview = excluded.preview,
+                has_audio = excluded.has_audio,
+                parent_id = excluded.parent_id,
+                parent_message_count = excluded.parent_message_count,
+                content_text = excluded.content_text,
+                conversation = excluded.conversation",
+            params![
+                self.summary.id,
+                self.summary.title,
+                self.summary.persona_name,
+                self.summary.model_name,
+                self.summary.created_at.timestamp_millis(),
+                self.summary.message_count as i64,
+                self.summary.preview,
+                self.summary.has_audio,
+                self.summary.parent.as_ref().map(|parent| parent.id.clone()),
+                self.summary
+                    .parent
+                    .as_ref()
+                    .map(|parent| parent.message_count as i64),
+                self.content_text,
+                self.conversation,
+            ],
+        )
+    }
+}
@@ -0,0 +1,59 @@
+use std::{collections::HashMap, error::Error};
+
+use keyring::Entry;
+
+// Secrets of external MCP servers live in the system keychain, never in the saved state
+const KEYRING_SERVICE: &str = "qspeak-mcp";
+
+fn server_entry(server_id: &str) -> Result<Entry, keyring::Error> {
+    Entry::new(KEYRING_SERVICE, server_id)
+}
+
+// Custom header values are stored together as JSON, next to the auth secret
+fn headers_entry(server_id: &str) -> Result<Entry, keyring::Error> {
+    Entry::new(KEYRING_SERVICE, &format!("{}:headers", server_id))
+}
+
+pub fn store_server_secret(server_id: &str, secret: &str) -> Result<(), keyring::Error> {
+    server_entry(server_id)?.set_password(secret)
+}
+
+pub fn get_server_secret(server_id: &str) -> Result<Option<String>, keyring::Error> {
+    match server_entry(server_id)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn delete_server_secret(server_id: &str) -> Result<(), keyring::Error> {
+    match server_entry(server_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn store_server_headers(
+    server_id: &str,
+    headers: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    headers_entry(server_id)?.set_password(&serde_json::to_string(headers)?)?;
+    Ok(())
+}
+
+pub fn get_server_headers(
+    server_id: &str,
+) -> Result<HashMap<String, String>, Box<dyn Error + Send + Sync>> {
+    match headers_entry(server_id)?.get_password() {
+        Ok(headers) => Ok(serde_json::from_str(&headers)?),
+        Err(keyring::Error::NoEntry) => Ok(HashMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn delete_server_headers(server_id: &str) -> Result<(), keyring::Error> {
+    match headers_entry(server_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,806 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    error::Error,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Query, Request, State},
+    http::{self, StatusCode},
+    middleware::{self, Next},
+    response::{
+        Response,
+        sse::{Event as SseEvent, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use eventsource_stream::Eventsource;
+use futures_util::{Stream, StreamExt, sink, stream};
+use lazy_static::lazy_static;
+use reqwest::{
+    Client, Url,
+    header::{ACCEPT, CONTENT_TYPE},
+};
+use rmcp::{
+    Error as McpError, RoleServer, ServerHandler, ServiceExt,
+    model::{
+        CallToolRequestParam, CallToolResult, ClientJsonRpcMessage, Implementation, JsonObject,
+        ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo,
+        ServerJsonRpcMessage, Tool,
+    },
+    service::RequestContext,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tauri::async_runtime::{self, JoinHandle, block_on};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+use uuid::Uuid;
+
+use crate::{
+    llm::{
+        ChatCompletionMessage, ChatCompletionMessageContent, ChatCompletionTextMessage,
+        OpenAIClient, OpenAIClientConfig,
+    },
+    state_machine::{
+        Event, connectivity,
+        errors::AppError,
+        events::MCPServerSettingsPayload,
+        history_store::{self, HistorySearchQuery},
+        new_conversation::transcribe_file,
+        processor::Processor,
+        state::{AppState, ConversationState, DEFAULT_MCP_SERVER_PORT},
+    },
+};
+
+// `qspeak --mcp-stdio --token <token> [--port <port>]` bridges stdio to the server of the running app
+pub const STDIO_BRIDGE_ARG: &str = "--mcp-stdio";
+
+const SSE_PATH: &str = "/sse";
+const MESSAGE_PATH: &str = "/message";
+
+const RECORD_AND_TRANSCRIBE: &str = "record_and_transcribe";
+const TRANSCRIBE_FILE: &str = "transcribe_file";
+const LIST_PERSONAS: &str = "list_personas";
+const RUN_PERSONA: &str = "run_persona";
+const SEARCH_HISTORY: &str = "search_history";
+
+const VOICE_INPUT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+pub struct MCPServerProcessor;
+
+impl MCPServerProcessor {
+    pub fn start() -> Result<(), Box<dyn Error>> {
+        Processor::register_event_listener(
+            "mcp_server",
+            Box::new(|event, _app_handle| match event {
+                Event::ActionUpdateMCPServerSettings(settings) => {
+                    MCPServerController::apply_settings(settings)?;
+                    Ok(())
+                }
+                Event::ActionVoiceInputSuccess(text) => {
+                    finish_voice_input(Ok(text));
+                    Ok(())
+                }
+                Event::ActionVoiceInputError(e) => {
+                    finish_voice_input(Err(e));
+                    Ok(())
+                }
+                _ => Ok(()),
+            }),
+        );
+
+        // A taken port is reported to the user, it shouldn't stop the app from starting
+        let context = AppState::get_context();
+        MCPServerController::apply_settings(MCPServerSettingsPayload {
+            enabled: context.mcp_server_context.enabled,
+            port: context.mcp_server_context.port,
+        })
+        .ok();
+
+        Ok(())
+    }
+}
+
+lazy_static! {
+    static ref MCP_SERVER: Mutex<MCPServerController> = Mutex::new(MCPServerController::new());
+    // The agent waiting for the user to finish recording, only one at a time
+    static ref VOICE_INPUT: Mutex<Option<oneshot::Sender<Result<String, String>>>> =
+        Mutex::new(None);
+}
+
+struct MCPServerHandle {
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+    port: u16,
+}
+
+struct MCPServerController {
+    handle: Option<MCPServerHandle>,
+}
+
+impl MCPServerController {
+    fn new() -> Self {
+        Self { handle: None }
+    }
+
+    fn apply_settings(settings: MCPServerSettingsPayload) -> Result<(), Box<dyn Error>> {
+        let mut guard = MCP_SERVER.lock().expect("Failed to lock MCP server");
+        guard.apply_settings_internal(settings)
+    }
+
+    fn apply_settings_internal(
+        &mut self,
+        settings: MCPServerSettingsPayload,
+    ) -> Result<(), Box<dyn Error>> {
+        if !settings.enabled {
+            self.stop();
+            return Ok(());
+        }
+
+        let requires_restart = self
+            .handle
+            .as_ref()
+            .map(|handle| handle.port != settings.port)
+            .unwrap_or(true);
+
+        if !requires_restart {
+            return Ok(());
+        }
+
+        self.stop();
+        self.start(settings.port)
+    }
+
+    fn start(&mut self, port: u16) -> Result<(), Box<dyn Error>> {
+        // Only agents on this machine may use the microphone and the models
+        let bind = SocketAddr::from(([127, 0, 0, 1], port));
+        let listener = match async_runtime::block_on(TcpListener::bind(bind)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                report_error(format!(
+                    "Unable to start MCP server on port {}: {}",
+                    port, err
+                ));
+                return Err(Box::new(err));
+            }
+        };
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let app = MCPServerApp {
+            token: AppState::get_context().mcp_server_context.token.into(),
+            sessions: Arc::default(),
+        };
+        let sessions = app.sessions.clone();
+        let router = Router::new()
+            .route(SSE_PATH, get(open_session))
+            .route(MESSAGE_PATH, post(post_message))
+            .layer(middleware::from_fn_with_state(app.clone(), authorize))
+            .with_state(app);
+
+        let task = async_runtime::spawn(async move {
+            // Closing the sessions ends their event streams, so the shutdown doesn't wait on them
+            let shutdown = async move {
+                let _ = shutdown_rx.await;
+                if let Ok(mut sessions) = sessions.lock() {
+                    sessions.clear();
+                }
+            };
+            if let Err(e) = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                log::error!("MCP server stopped with an error: {}", e);
+            }
+        });
+
+        self.handle = Some(MCPServerHandle {
+            shutdown: Some(shutdown_tx),
+            task,
+            port,
+        });
+
+        log::info!(
+            "MCP server listening on http://127.0.0.1:{}{}",
+            port,
+            SSE_PATH
+        );
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut handle) = self.handle.take() {
+            if let Some(shutdown) = handle.shutdown.take() {
+                let _ = shutdown.send(());
+            }
+
+            async_runtime::spawn(async move {
+                let _ = handle.task.await;
+            });
+
+            log::info!("MCP server stopped");
+        }
+    }
+}
+
+#[derive(Clone)]
+struct MCPServerApp {
+    token: Arc<str>,
+    sessions: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<ClientJsonRpcMessage>>>>,
+}
+
+// Browsers send their own Host, so pages rebinding a domain to 127.0.0.1 are turned away here
+async fn authorize(
+    State(app): State<MCPServerApp>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let host = request
+        .headers()
+        .get(http::header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| host.split(':').next())
+        .unwrap_or_default();
+    if host != "127.0.0.1" && host != "localhost" {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    // Messages are posted to a session id only handed out on the stream, so the stream holds the token
+    if request.uri().path() == SSE_PATH && request_token(&request).as_deref() != Some(&*app.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok(next.run(request).await)
+}
+
+// `Authorization: Bearer <token>`, or `?token=<token>` for clients that only take a URL
+fn request_token(request: &Request) -> Option<String> {
+    let bearer = request
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+
+    bearer.or_else(|| {
+        Url::parse(&format!("http://127.0.0.1{}", request.uri()))
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned())
+    })
+}
+
+async fn open_session(
+    State(app): State<MCPServerApp>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let session_id = Uuid::new_v4().simple().to_string();
+    let (from_client_tx, from_client_rx) = mpsc::unbounded_channel::<ClientJsonRpcMessage>();
+    let (to_client_tx, to_client_rx) = mpsc::unbounded_channel::<ServerJsonRpcMessage>();
+    if let Ok(mut sessions) = app.sessions.lock() {
+        sessions.insert(session_id.clone(), from_client_tx);
+    }
+
+    let sink = sink::unfold(to_client_tx, |tx, message| async move {
+        tx.send(message)
+            .map_err(|_| std::io::Error::other("MCP client disconnected"))?;
+        Ok::<_, std::io::Error>(tx)
+    });
+    let transport = (Box::pin(sink), receiver_stream(from_client_rx));
+
+    let sessions = app.sessions.clone();
+    let session = session_id.clone();
+    async_runtime::spawn(async move {
+        match QSpeakMCPServer.serve(transport).await {
+            Ok(service) => {
+                let _ = service.waiting().await;
+            }
+            Err(e) => log::warn!("MCP session {} ended before initializing: {}", session, e),
+        }
+        if let Ok(mut sessions) = sessions.lock() {
+            sessions.remove(&session);
+        }
+    });
+
+    let endpoint = SseEvent::default()
+        .event("endpoint")
+        .data(format!("{}?sessionId={}", MESSAGE_PATH, session_id));
+    let messages = receiver_stream(to_client_rx).filter_map(|message| async move {
+        match serde_json::to_string(&message) {
+            Ok(data) => Some(Ok(SseEvent::default().event("message").data(data))),
+            Err(e) => {
+                log::error!("Unable to serialize MCP message: {}", e);
+                None
+            }
+        }
+    });
+
+    Sse::new(stream::once(async move { Ok(endpoint) }).chain(messages))
+        .keep_alive(KeepAlive::default())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostMessageQuery {
+    session_id: String,
+}
+
+async fn post_message(
+    State(app): State<MCPServerApp>,
+    Query(query): Query<PostMessageQuery>,
+    Json(message): Json<ClientJsonRpcMessage>,
+) -> StatusCode {
+    let sender = app
+        .sessions
+        .lock()
+        .ok()
+        .and_then(|sessions| sessions.get(&query.session_id).cloned());
+
+    match sender {
+        Some(sender) if sender.send(message).is_ok() => StatusCode::ACCEPTED,
+        Some(_) => StatusCode::GONE,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+fn receiver_stream<T: Send + 'static>(
+    rx: mpsc::UnboundedReceiver<T>,
+) -> std::pin::Pin<Box<dyn Stream<Item = T> + Send>> {
+    Box::pin(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|message| (message, rx))
+    }))
+}
+
+#[derive(Clone)]
+struct QSpeakMCPServer;
+
+impl ServerHandler for QSpeakMCPServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "qspeak".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            instructions: Some(
+                "qSpeak can ask the user for voice input, transcribe audio and run the user's personas with their configured models."
+                    .to_string(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let tools = list_tools().map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(ListToolsResult {
+            next_cursor: None,
+            tools,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let arguments = request.arguments.unwrap_or_default();
+
+        let result = match request.name.as_ref() {
+            RECORD_AND_TRANSCRIBE => record_and_transcribe().await,
+            TRANSCRIBE_FILE => {
+                let path = required_argument(&arguments, "path")?;
+                run_blocking(move || transcribe_wav_file(path)).await
+            }
+            LIST_PERSONAS => list_personas().map_err(|e| e.to_string()),
+            RUN_PERSONA => {
+                let text = required_argument(&arguments, "text")?;
+                let persona = string_argument(&arguments, "persona").map(str::to_string);
+                run_blocking(move || run_persona(persona, text)).await
+            }
+            SEARCH_HISTORY => {
+                let query = HistorySearchQuery {
+                    text: required_argument(&arguments, "query")?,
+                    persona_name: string_argument(&arguments, "persona").map(str::to_string),
+                    from: date_argument(&arguments, "from")?,
+                    to: date_argument(&arguments, "to")?,
+                    has_audio: arguments.get("has_audio").and_then(|value| value.as_bool()),
+                    limit: Some(
+                        arguments
+                            .get("limit")
+                            .and_then(|limit| limit.as_u64())
+                            .map(|limit| limit as usize)
+                            .unwrap_or(DEFAULT_SEARCH_LIMIT),
+                    ),
+                    ..HistorySearchQuery::default()
+                };
+                run_blocking(move || search_history(&query)).await
+            }
+            name => {
+                return Err(McpError::invalid_params(
+                    format!("Unknown tool: {}", name),
+                    None,
+                ));
+            }
+        };
+
+        let (text, is_error) = match result {
+            Ok(text) => (text, false),
+            Err(e) => (e, true),
+        };
+        serde_json::from_value(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+        .map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+}
+
+fn list_tools() -> Result<Vec<Tool>, serde_json::Error> {
+    let no_arguments = json!({ "type": "object", "properties": {} });
+
+    [
+        (
+            RECORD_AND_TRANSCRIBE,
+            "Ask the user to speak. Opens the qSpeak recording window and returns the transcription once the user finishes recording.",
+            no_arguments.clone(),
+        ),
+        (
+            TRANSCRIBE_FILE,
+            "Transcribe a 16 kHz mono 32-bit float WAV file with the user's transcription model.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the WAV file" }
+                },
+                "required": ["path"]
+            }),
+        ),
+        (
+            LIST_PERSONAS,
+            "List the user's personas with their descriptions.",
+            no_arguments,
+        ),
+        (
+            RUN_PERSONA,
+            "Run a persona on a text with the user's conversation model and return its answer.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Text sent to the persona" },
+                    "persona": {
+                        "type": "string",
+                        "description": "Name of the persona, the active persona when omitted"
+                    }
+                },
+                "required": ["text"]
+            }),
+        ),
+        (
+            SEARCH_HISTORY,
+            "Search the user's conversation history, best matches first. Matched words are wrapped in <mark> in the snippets.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words to look for" },
+                    "persona": { "type": "string", "description": "Only conversations with this persona" },
+                    "from": {
+                        "type": "string",
+                        "description": "Only conversations started at or after this RFC 3339 time"
+                    },
+                    "to": {
+                        "type": "string",
+                        "description": "Only conversations started at or before this RFC 3339 time"
+                    },
+                    "has_audio": {
+                        "type": "boolean",
+                        "description": "Only conversations with (true) or without (false) recorded audio"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of conversations, 10 by default"
+                    }
+                },
+                "required": ["query"]
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, description, input_schema)| {
+        serde_json::from_value(json!({
+            "name": name,
+            "description": description,
+            "inputSchema": input_schema,
+        }))
+    })
+    .collect()
+}
+
+async fn record_and_transcribe() -> Result<String, String> {
+    let (tx, rx) = oneshot::channel();
+    {
+        let mut voice_input = VOICE_INPUT.lock().map_err(|e| e.to_string())?;
+        if voice_input.is_some() {
+            return Err("Another agent is already waiting for voice input".to_string());
+        }
+
+        let mut is_idle = false;
+        AppState::update(|context| {
+            if context.conversation_context.state == ConversationState::Idle {
+                context.conversation_context.voice_input_requested = true;
+                is_idle = true;
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        if !is_idle {
+            return Err("qSpeak is busy, try again once the user is done".to_string());
+        }
+
+        *voice_input = Some(tx);
+    }
+
+    Processor::process_event(Event::ActionResetRecordingShortcutTimer)
+        .and_then(|_| Processor::process_event(Event::ActionRecording))
+        .map_err(|e| e.to_string())?;
+
+    match tokio::time::timeout(VOICE_INPUT_TIMEOUT, rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err("Voice input was interrupted".to_string()),
+        Err(_) => {
+            // The recording goes on as a normal dictation
+            VOICE_INPUT
+                .lock()
+                .ok()
+                .and_then(|mut voice_input| voice_input.take());
+            AppState::update(|context| {
+                context.conversation_context.voice_input_requested = false;
+            })
+            .ok();
+            Err("Timed out waiting for the user to finish recording".to_string())
+        }
+    }
+}
+
+fn finish_voice_input(result: Result<String, String>) {
+    let sender = VOICE_INPUT
+        .lock()
+        .ok()
+        .and_then(|mut voice_input| voice_input.take());
+    if let Some(sender) = sender {
+        let _ = sender.send(result);
+    }
+}
+
+// Local models read the samples as they are, so only the format qSpeak records in is accepted
+fn transcribe_wav_file(path: String) -> Result<String, Box<dyn Error>> {
+    let spec = hound::WavReader::open(&path)?.spec();
+    if spec.channels != 1
+        || spec.sample_rate != 16000
+        || spec.sample_format != hound::SampleFormat::Float
+    {
+        return Err("Only 16 kHz mono 32-bit float WAV files are supported".into());
+    }
+
+    transcribe_file(path)
+}
+
+fn list_personas() -> Result<String, serde_json::Error> {
+    let context = AppState::get_context();
+    let active_persona_id = context.active_persona.as_ref().map(|persona| &persona.id);
+
+    let personas: Vec<Value> = context
+        .personas_context
+        .personas
+        .iter()
+        .map(|persona| {
+            json!({
+                "name": persona.name,
+                "description": persona.description,
+                "active": Some(&persona.id) == active_persona_id,
+            })
+        })
+        .collect();
+    serde_json::to_string(&personas)
+}
+
+fn run_persona(persona_name: Option<String>, text: String) -> Result<String, Box<dyn Error>> {
+    let context = AppState::get_context();
+    let persona = match persona_name {
+        Some(name) => context
+            .personas_context
+            .personas
+            .iter()
+            .find(|persona| persona.name.eq_ignore_ascii_case(name.trim()))
+            .cloned()
+            .ok_or_else(|| format!("Persona not found: {}", name))?,
+        None => context
+            .active_persona
+            .clone()
+            .ok_or("No persona is active, pass the name of a persona")?,
+    };
+    connectivity::check_persona(&context, &persona)?;
+    let active_model = context
+        .conversation_model
+        .as_ref()
+        .ok_or("Conversation model not picked. Please pick a model in the settings.")?;
+    let (active_model, _) = connectivity::conversation_models_for_mode(&context, active_model)?;
+    let model = context
+        .models_context
+        .conversation_models
+        .iter()
+        .find(|model| model.model == active_model)
+        .cloned()
+        .ok_or("Conversation model not found. Please pick a model in the settings.")?;
+
+    let mut config = OpenAIClientConfig::from_model_config(model.config);
+    config.usage_persona = Some(persona.name.clone());
+    let api_key = config
+        .api_key
+        .clone()
+        .or(context.account_context.account.token.clone());
+
+    let mut messages = vec![text_message("system", persona.system_prompt)];
+    for example in persona
+        .examples
+        .into_iter()
+        .filter(|example| !example.question.trim().is_empty() && !example.answer.trim().is_empty())
+    {
+        messages.push(text_message("user", example.question));
+        messages.push(text_message("assistant", example.answer));
+    }
+    messages.push(text_message("user", text));
+
+    block_on(
+        OpenAIClient::new().chat_completion_non_streaming(messages, None, config, api_key, None),
+    )
+}
+
+fn search_history(query: &HistorySearchQuery) -> Result<String, Box<dyn Error>> {
+    let results: Vec<Value> = history_store::search(query)?
+        .into_iter()
+        .map(|result| {
+            json!({
+                "id": result.summary.id,
+                "title": result.summary.title.unwrap_or_default(),
+                "persona": result.summary.persona_name,
+                "created_at": result.summary.created_at,
+                "snippet": result.snippet,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string(&results)?)
+}
+
+async fn run_blocking<F>(f: F) -> Result<String, String>
+where
+    F: FnOnce() -> Result<String, Box<dyn Error>> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn text_message(role: &str, text: String) -> ChatCompletionMessage {
+    ChatCompletionMessage::ChatCompletionTextMessage(ChatCompletionTextMessage {
+        role: role.to_string(),
+        content: vec![ChatCompletionMessageContent::Text { text }],
+        created_at: Utc::now(),
+    })
+}
+
+fn string_argument<'a>(arguments: &'a JsonObject, name: &str) -> Option<&'a str> {
+    arguments.get(name).and_then(|value| value.as_str())
+}
+
+fn date_argument(arguments: &JsonObject, name: &str) -> Result<Option<DateTime<Utc>>, McpError> {
+    string_argument(arguments, name)
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|date| date.with_timezone(&Utc))
+                .map_err(|e| McpError::invalid_params(format!("Invalid {}: {}", name, e), None))
+        })
+        .transpose()
+}
+
+fn required_argument(arguments: &JsonObject, name: &str) -> Result<String, McpError> {
+    string_argument(arguments, name)
+        .map(str::to_string)
+        .ok_or_else(|| McpError::invalid_params(format!("Missing {}", name), None))
+}
+
+fn report_error(message: String) {
+    log::error!("{}", &message);
+    let _ = AppState::update(|context| {
+        context.errors.push(AppError::with_message(message));
+    });
+}
+
+pub fn run_stdio_bridge(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let argument = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+    let port = argument("--port")
+        .map(|port| port.parse::<u16>())
+        .transpose()?
+        .unwrap_or(DEFAULT_MCP_SERVER_PORT);
+    let token = argument("--token")
+        .cloned()
+        .ok_or("Missing --token, copy the command from the MCP server settings of qSpeak")?;
+
+    async_runtime::block_on(stdio_bridge(port, token))
+}
+
+// Messages are forwarded as they are, the app does all the MCP work
+async fn stdio_bridge(port: u16, token: String) -> Result<(), Box<dyn Error>> {
+    let client = Client::new();
+    let sse_url = Url::parse(&format!("http://127.0.0.1:{}{}", port, SSE_PATH))?;
+    let response = client
+        .get(sse_url.clone())
+        .header(ACCEPT, "text/event-stream")
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            format!(
+                "qSpeak MCP server is not reachable on port {}, enable it in the settings: {}",
+                port, e
+            )
+        })?;
+    let mut events = response.bytes_stream().eventsource();
+
+    let endpoint = loop {
+        match events.next().await {
+            Some(Ok(event)) if event.event == "endpoint" => break sse_url.join(&event.data)?,
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.to_string().into()),
+            None => return Err("qSpeak closed the stream before sending its endpoint".into()),
+        }
+    };
+
+    // One message per line, the stream ends when qSpeak quits or disables the server
+    async_runtime::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(Ok(event)) = events.next().await {
+            if event.event != "message" {
+                continue;
+            }
+            let line = format!("{}\n", event.data);
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+        std::process::exit(0);
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        client
+            .post(endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(line)
+            .send()
+            .await?
+            .error_for_status()?;
+    }
+
+    Ok(())
+}
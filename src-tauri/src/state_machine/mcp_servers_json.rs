@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::new_mcp_processor::{
+    DEFAULT_RECONNECT_ATTEMPTS, DEFAULT_RECONNECT_DELAY_SECS, DEFAULT_REQUEST_TIMEOUT_SECS,
+    DEFAULT_TOOL_TIMEOUT_SECS, MCPExternalAuth, MCPExternalTransport, MCPServerConfig,
+    MCPServerKind,
+};
+
+// The `{"mcpServers": {name: {...}}}` format shared by most MCP clients
+#[derive(Debug, Serialize, Deserialize)]
+struct MCPServersFile<T> {
+    #[serde(rename = "mcpServers")]
+    mcp_servers: BTreeMap<String, T>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MCPServerEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    // "sse", or "http" / "streamable-http"
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    transport: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    headers: HashMap<String, String>,
+}
+
+pub struct ImportedMCPServer {
+    pub name: String,
+    pub config: Result<MCPServerConfig, String>,
+}
+
+// Entries are parsed one by one so a single broken server doesn't reject the whole file
+pub fn parse_mcp_servers_json(json: &str) -> Result<Vec<ImportedMCPServer>, serde_json::Error> {
+    let file: MCPServersFile<Value> = serde_json::from_str(json)?;
+
+    Ok(file
+        .mcp_servers
+        .into_iter()
+        .map(|(name, entry)| ImportedMCPServer {
+            config: serde_json::from_value::<MCPServerEntry>(entry)
+                .map_err(|e| e.to_string())
+                .and_then(|entry| server_config_from_entry(&name, entry)),
+            name,
+        })
+        .collect())
+}
+
+pub fn export_mcp_servers_json(configs: &[MCPServerConfig]) -> Result<String, serde_json::Error> {
+    let mcp_servers = configs
+        .iter()
+        .map(|config| {
+            let entry = match &config.kind {
+                MCPServerKind::Local { command, env_vars } => {
+                    let mut parts = shlex::split(command).unwrap_or_default().into_iter();
+                    MCPServerEntry {
+                        command: parts.next(),
+                        args: parts.collect(),
+                        env: env_vars.clone(),
+                        ..MCPServerEntry::default()
+                    }
+                }
+                // Auth and custom headers hold secrets from the keychain and are left out of the export
+                MCPServerKind::External { url, transport, .. } => MCPServerEntry {
+                    url: Some(url.clone()),
+                    transport: Some(
+                        match transport {
+                            MCPExternalTransport::Sse => "sse",
+                            MCPExternalTransport::StreamableHttp => "http",
+                        }
+                        .to_string(),
+                    ),
+                    ..MCPServerEntry::default()
+                },
+            };
+            (config.key.clone(), entry)
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&MCPServersFile { mcp_servers })
+}
+
+fn server_config_from_entry(name: &str, entry: MCPServerEntry) -> Result<MCPServerConfig, String> {
+    let key = key_from_name(name);
+    if key.is_empty() {
+        return Err("Server name must contain letters or digits".to_string());
+    }
+
+    let kind = match (entry.command, entry.url) {
+        (Some(_), Some(_)) => return Err("Server can't have both a command and a url".to_string()),
+        (Some(command), None) => {
+            // Quoted so the command is split back into the same words when the server starts
+            let command = shlex::try_join(
+                std::iter::once(command.as_str()).chain(entry.args.iter().map(|arg| arg.as_str())),
+            )
+            .map_err(|e| e.to_string())?;
+            MCPServerKind::Local {
+                command,
+                env_vars: entry.env,
+            }
+        }
+        (None, Some(url)) => MCPServerKind::External {
+            url,
+            transport: match entry.transport.as_deref() {
+                Some("sse") => MCPExternalTransport::Sse,
+                // Newer clients default to streamable HTTP when no type is given
+                Some("http" | "streamable-http" | "streamableHttp") | None => {
+                    MCPExternalTransport::StreamableHttp
+                }
+                Some(other) => return Err(format!("Unsupported server type: {}", other)),
+            },
+            headers: entry.headers,
+            auth: MCPExternalAuth::None,
+            auth_secret: None,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            reconnect_attempts: DEFAULT_RECONNECT_ATTEMPTS,
+            reconnect_delay_secs: DEFAULT_RECONNECT_DELAY_SECS,
+        },
+        (None, None) => return Err("Server needs either a command or a url".to_string()),
+    };
+
+    Ok(MCPServerConfig {
+        id: Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        key,
+        description: String::new(),
+        kind,
+        // Imported servers run nothing until the user enables them
+        enabled: false,
+        state: Default::default(),
+        tool_policy: Default::default(),
+        tool_policies: HashMap::new(),
+        tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+        tool_timeouts: HashMap::new(),
+    })
+}
+
+// Same rules as the key generated from the name in the settings form
+fn key_from_name(name: &str) -> String {
+    name.to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_')
+        .collect()
+}
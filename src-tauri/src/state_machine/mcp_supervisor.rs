@@ -0,0 +1,381 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::block_on;
+
+use super::{
+    new_mcp_processor::{MCPProcessor, MCPServerConfig, MCPServerState},
+    state::AppState,
+};
+
+const SUPERVISOR_TICK: Duration = Duration::from_secs(5);
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const SERVER_LOG_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum MCPServerLogSource {
+    Stderr,
+    Protocol,
+    Supervisor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPServerLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub source: MCPServerLogSource,
+    pub message: String,
+}
+
+// Kept outside of AppState, stderr can be noisy and the state is sent to the UI on every update
+static SERVER_LOGS: LazyLock<Mutex<HashMap<String, VecDeque<MCPServerLogEntry>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn push_server_log(key: &str, source: MCPServerLogSource, message: impl Into<String>) {
+    let Ok(mut logs) = SERVER_LOGS.lock() else {
+        return;
+    };
+
+    let server_logs = logs.entry(key.to_string()).or_default();
+    if server_logs.len() >= SERVER_LOG_CAPACITY {
+        server_logs.pop_front();
+    }
+    server_logs.push_back(MCPServerLogEntry {
+        timestamp: Utc::now(),
+        source,
+        message: message.into(),
+    });
+}
+
+pub fn get_server_logs(key: &str) -> Vec<MCPServerLogEntry> {
+    SERVER_LOGS
+        .lock()
+        .ok()
+        .and_then(|logs| {
+            logs.get(key)
+                .map(|server_logs| server_logs.iter().cloned().collect())
+        })
+        .unwrap_or_default()
+}
+
+// What the supervisor needs from the app, so the restart logic can run against a stub server
+trait SupervisedServers {
+    fn configs(&self) -> Vec<MCPServerConfig>;
+    fn check_health(&self, key: &str, ping_timeout: Option<Duration>) -> Result<(), String>;
+    fn restart(&self, config: MCPServerConfig) -> Result<(), String>;
+    // Changes the state only when the user hasn't touched the server in the meantime
+    fn set_state(&self, key: &str, from: MCPServerState, to: MCPServerState) -> bool;
+}
+
+struct AppServers;
+
+impl SupervisedServers for AppServers {
+    fn configs(&self) -> Vec<MCPServerConfig> {
+        AppState::get_context().mcp_context.server_configs
+    }
+
+    fn check_health(&self, key: &str, ping_timeout: Option<Duration>) -> Result<(), String> {
+        block_on(MCPProcessor::check_server_health(key, ping_timeout)).map_err(|e| e.to_string())
+    }
+
+    fn restart(&self, config: MCPServerConfig) -> Result<(), String> {
+        block_on(MCPProcessor::restart_server(config)).map_err(|e| e.to_string())
+    }
+
+    fn set_state(&self, key: &str, from: MCPServerState, to: MCPServerState) -> bool {
+        let mut changed = false;
+        let _ = AppState::update(|context| {
+            if let Some(config) = context
+                .mcp_context
+                .server_configs
+                .iter_mut()
+                .find(|config| config.key == key && config.enabled && config.state == from)
+            {
+                config.state = to;
+                changed = true;
+            }
+        });
+        changed
+    }
+}
+
+struct RestartSchedule {
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+// Watches enabled servers, restarting the ones that crashed or stopped answering pings
+pub struct MCPSupervisor {
+    restarts: HashMap<String, RestartSchedule>,
+    next_ping: Instant,
+}
+
+impl MCPSupervisor {
+    pub fn start() {
+        std::thread::spawn(|| {
+            let mut supervisor = MCPSupervisor {
+                restarts: HashMap::new(),
+                next_ping: Instant::now() + PING_INTERVAL,
+            };
+            loop {
+                std::thread::sleep(SUPERVISOR_TICK);
+                supervisor.tick(&AppServers, Instant::now());
+            }
+        });
+    }
+
+    fn tick(&mut self, servers: &impl SupervisedServers, now: Instant) {
+        let configs = servers.configs();
+        let ping_due = now >= self.next_ping;
+        if ping_due {
+            self.next_ping = now + PING_INTERVAL;
+        }
+
+        // Servers disabled or removed by the user are no longer restarted
+        self.restarts.retain(|key, _| {
+            configs
+                .iter()
+                .any(|config| config.key == *key && config.enabled)
+        });
+
+        for config in configs.into_iter().filter(|config| config.enabled) {
+            match config.state {
+                MCPServerState::Enabled => {
+                    let ping_timeout = ping_due.then_some(PING_TIMEOUT);
+                    match servers.check_health(&config.key, ping_timeout) {
+                        Ok(()) if ping_due => {
+                            self.restarts.remove(&config.key);
+                        }
+                        Ok(()) => {}
+                        Err(e) => {
+                            log::warn!("MCP server {} is unhealthy: {}", config.key, e);
+                            push_server_log(
+                                &config.key,
+                                MCPServerLogSource::Supervisor,
+                                format!("Server is unhealthy: {}", e),
+                            );
+                            servers.set_state(
+                                &config.key,
+                                MCPServerState::Enabled,
+                                MCPServerState::Error,
+                            );
+                            self.schedule_restart(&config.key, now);
+                        }
+                    }
+                }
+                // Also covers servers that failed to start with the app
+                MCPServerState::Error => {
+                    let schedule = self.restarts.entry(config.key.clone()).or_insert_with(|| {
+                        RestartSchedule {
+                            attempts: 0,
+                            next_attempt: now,
+                        }
+                    });
+                    if now >= schedule.next_attempt {
+                        self.restart(servers, config, now);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn schedule_restart(&mut self, key: &str, now: Instant) {
+        let schedule = self
+            .restarts
+            .entry(key.to_string())
+            .or_insert_with(|| RestartSchedule {
+                attempts: 0,
+                next_attempt: now,
+            });
+        schedule.next_attempt = now + restart_backoff(schedule.attempts);
+        schedule.attempts += 1;
+    }
+
+    fn restart(&mut self, servers: &impl SupervisedServers, config: MCPServerConfig, now: Instant) {
+        let key = config.key.clone();
+        if !servers.set_state(&key, MCPServerState::Error, MCPServerState::Starting) {
+            return;
+        }
+
+        log::info!("Restarting MCP server {}", key);
+        push_server_log(&key, MCPServerLogSource::Supervisor, "Restarting server");
+
+        match servers.restart(config) {
+            Ok(()) => {
+                push_server_log(&key, MCPServerLogSource::Supervisor, "Server restarted");
+                servers.set_state(&key, MCPServerState::Starting, MCPServerState::Enabled);
+            }
+            Err(e) => {
+                log::error!("Failed to restart MCP server {}: {}", key, e);
+                push_server_log(
+                    &key,
+                    MCPServerLogSource::Supervisor,
+                    format!("Failed to restart server: {}", e),
+                );
+                servers.set_state(&key, MCPServerState::Starting, MCPServerState::Error);
+            }
+        }
+        self.schedule_restart(&key, now);
+    }
+}
+
+// Doubles with every failed attempt, up to MAX_RESTART_BACKOFF
+fn restart_backoff(attempts: u32) -> Duration {
+    INITIAL_RESTART_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts))
+        .min(MAX_RESTART_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    // A server that is up but never answers a ping
+    struct StubServers {
+        configs: RefCell<Vec<MCPServerConfig>>,
+        restarts: Cell<u32>,
+    }
+
+    impl StubServers {
+        fn new(key: &str) -> Self {
+            let config = serde_json::from_value(serde_json::json!({
+                "name": key,
+                "key": key,
+                "description": "",
+                "kind": { "Local": { "command": "stub", "env_vars": {} } },
+                "enabled": true,
+                "state": "Enabled",
+            }))
+            .expect("Failed to build the stub config");
+            Self {
+                configs: RefCell::new(vec![config]),
+                restarts: Cell::new(0),
+            }
+        }
+
+        fn state(&self) -> MCPServerState {
+            self.configs.borrow()[0].state.clone()
+        }
+    }
+
+    impl SupervisedServers for StubServers {
+        fn configs(&self) -> Vec<MCPServerConfig> {
+            self.configs.borrow().clone()
+        }
+
+        fn check_health(&self, _key: &str, ping_timeout: Option<Duration>) -> Result<(), String> {
+            match ping_timeout {
+                Some(_) => Err("ping timed out".to_string()),
+                None => Ok(()),
+            }
+        }
+
+        fn restart(&self, _config: MCPServerConfig) -> Result<(), String> {
+            self.restarts.set(self.restarts.get() + 1);
+            Ok(())
+        }
+
+        fn set_state(&self, key: &str, from: MCPServerState, to: MCPServerState) -> bool {
+            let mut configs = self.configs.borrow_mut();
+            match configs
+                .iter_mut()
+                .find(|config| config.key == key && config.enabled && config.state == from)
+            {
+                Some(config) => {
+                    config.state = to;
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    fn supervisor() -> MCPSupervisor {
+        MCPSupervisor {
+            restarts: HashMap::new(),
+            next_ping: Instant::now() + PING_INTERVAL,
+        }
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_the_limit() {
+        let backoffs: Vec<u64> = (0..10)
+            .map(|attempts| restart_backoff(attempts).as_secs())
+            .collect();
+
+        assert_eq!(backoffs, [2, 4, 8, 16, 32, 64, 128, 256, 300, 300]);
+        assert_eq!(restart_backoff(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
+    #[test]
+    fn failed_restarts_push_the_next_attempt_back() {
+        let mut supervisor = supervisor();
+        let now = Instant::now();
+
+        supervisor.schedule_restart("notes", now);
+        supervisor.schedule_restart("notes", now);
+        supervisor.schedule_restart("notes", now);
+
+        let schedule = &supervisor.restarts["notes"];
+        assert_eq!(schedule.attempts, 3);
+        assert_eq!(schedule.next_attempt, now + Duration::from_secs(8));
+    }
+
+    #[test]
+    fn servers_are_scheduled_separately() {
+        let mut supervisor = supervisor();
+        let now = Instant::now();
+
+        supervisor.schedule_restart("notes", now);
+        supervisor.schedule_restart("notes", now);
+        supervisor.schedule_restart("search", now);
+
+        assert_eq!(supervisor.restarts["notes"].attempts, 2);
+        assert_eq!(
+            supervisor.restarts["search"].next_attempt,
+            now + INITIAL_RESTART_BACKOFF
+        );
+    }
+
+    #[test]
+    fn servers_failing_pings_are_restarted_with_backoff() {
+        let servers = StubServers::new("notes");
+        let start = Instant::now();
+        let mut supervisor = MCPSupervisor {
+            restarts: HashMap::new(),
+            next_ping: start,
+        };
+
+        supervisor.tick(&servers, start);
+        assert_eq!(servers.state(), MCPServerState::Error);
+
+        supervisor.tick(&servers, start + Duration::from_secs(1));
+        assert_eq!(servers.restarts.get(), 0);
+
+        supervisor.tick(&servers, start + INITIAL_RESTART_BACKOFF);
+        assert_eq!(servers.restarts.get(), 1);
+        assert_eq!(servers.state(), MCPServerState::Enabled);
+
+        // The restart didn't help, the next one waits for the doubled backoff
+        let second_ping = start + PING_INTERVAL;
+        supervisor.tick(&servers, second_ping);
+        assert_eq!(servers.state(), MCPServerState::Error);
+
+        supervisor.tick(&servers, second_ping + restart_backoff(2) - SUPERVISOR_TICK);
+        assert_eq!(servers.restarts.get(), 1);
+
+        supervisor.tick(&servers, second_ping + restart_backoff(2));
+        assert_eq!(servers.restarts.get(), 2);
+        assert_eq!(servers.state(), MCPServerState::Enabled);
+    }
+}
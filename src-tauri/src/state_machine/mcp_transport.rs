@@ -0,0 +1,549 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use eventsource_stream::{Event as SseEvent, EventStreamError, Eventsource};
+use futures_util::{Sink, Stream, StreamExt, sink, stream};
+use reqwest::{
+    Client, StatusCode, Url,
+    header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderName, HeaderValue},
+};
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use tokio::sync::{mpsc, oneshot};
+
+use super::new_mcp_processor::MCPExternalAuth;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+pub type MCPTransportSink = Pin<Box<dyn Sink<ClientJsonRpcMessage, Error = std::io::Error> + Send>>;
+pub type MCPTransportStream = Pin<Box<dyn Stream<Item = ServerJsonRpcMessage> + Send>>;
+
+type TransportError = Box<dyn Error + Send + Sync>;
+
+pub struct MCPHttpOptions {
+    pub headers: HeaderMap,
+    pub request_timeout: Duration,
+    pub reconnect_attempts: u32,
+    pub reconnect_delay: Duration,
+}
+
+impl MCPHttpOptions {
+    pub fn new(
+        headers: &HashMap<String, String>,
+        auth: &MCPExternalAuth,
+        secret: Option<String>,
+        request_timeout: Duration,
+        reconnect_attempts: u32,
+        reconnect_delay: Duration,
+    ) -> Result<Self, TransportError> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        let auth_header = match (auth, secret) {
+            (MCPExternalAuth::None, _) => None,
+            (MCPExternalAuth::Bearer, Some(token)) => {
+                Some((AUTHORIZATION, format!("Bearer {}", token)))
+            }
+            (MCPExternalAuth::ApiKey { header }, Some(key)) => {
+                Some((HeaderName::from_bytes(header.as_bytes())?, key))
+            }
+            (_, None) => return Err("Missing secret for server authentication".into()),
+        };
+        if let Some((name, value)) = auth_header {
+            let mut value = HeaderValue::from_str(&value)?;
+            value.set_sensitive(true);
+            header_map.insert(name, value);
+        }
+
+        Ok(Self {
+            headers: header_map,
+            request_timeout,
+            reconnect_attempts,
+            reconnect_delay,
+        })
+    }
+
+    fn client(&self) -> Result<Client, reqwest::Error> {
+        Client::builder()
+            .default_headers(self.headers.clone())
+            .connect_timeout(self.request_timeout)
+            .build()
+    }
+}
+
+// Streamable HTTP, every message is POSTed and the server answers with JSON or an SSE stream
+pub fn streamable_http_transport(
+    url: &str,
+    options: MCPHttpOptions,
+) -> Result<(MCPTransportSink, MCPTransportStream), TransportError> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let transport = Arc::new(StreamableHttpTransport {
+        client: options.client()?,
+        url: Url::parse(url)?,
+        session_id: Mutex::new(None),
+        tx,
+        options,
+    });
+
+    let sink = sink::unfold(transport, |transport, message| async move {
+        transport
+            .post(message)
+            .await
+            .map_err(std::io::Error::other)?;
+        Ok::<_, std::io::Error>(transport)
+    });
+    Ok((Box::pin(sink), receiver_stream(rx)))
+}
+
+struct StreamableHttpTransport {
+    client: Client,
+    url: Url,
+    session_id: Mutex<Option<String>>,
+    tx: mpsc::UnboundedSender<ServerJsonRpcMessage>,
+    options: MCPHttpOptions,
+}
+
+impl StreamableHttpTransport {
+    fn session_id(&self) -> Option<String> {
+        self.session_id.lock().ok().and_then(|id| id.clone())
+    }
+
+    async fn post(self: &Arc<Self>, message: ClientJsonRpcMessage) -> Result<(), TransportError> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(&message);
+        if let Some(session_id) = self.session_id() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+
+        let response = tokio::time::timeout(self.options.request_timeout, request.send())
+            .await
+            .map_err(|_| "Request timed out")??
+            .error_for_status()?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            let is_new_session = self
+                .session_id
+                .lock()
+                .map(|mut id| id.replace(session_id.to_string()).is_none())
+                .unwrap_or(false);
+            if is_new_session {
+                self.clone().listen_for_server_messages();
+            }
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        if content_type.starts_with("text/event-stream") {
+            // Read in the background, a long running tool call shouldn't block sending
+            let tx = self.tx.clone();
+            tauri::async_runtime::spawn(async move {
+                forward_events(response.bytes_stream().eventsource(), &tx).await;
+            });
+        } else if content_type.starts_with("application/json") {
+            for message in parse_messages(&response.text().await?)? {
+                let _ = self.tx.send(message);
+            }
+        }
+        // 202 Accepted has no body
+
+        Ok(())
+    }
+
+    // Notifications from the server arrive on an optional GET stream
+    fn listen_for_server_messages(self: Arc<Self>) {
+        tauri::async_runtime::spawn(async move {
+            let mut attempts = 0;
+            loop {
+                let mut request = self
+                    .client
+                    .get(self.url.clone())
+                    .header(ACCEPT, "text/event-stream");
+                if let Some(session_id) = self.session_id() {
+                    request = request.header(SESSION_ID_HEADER, session_id);
+                }
+
+                match request.send().await {
+                    Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => return,
+                    Ok(response) if response.status().is_success() => {
+                        attempts = 0;
+                        forward_events(response.bytes_stream().eventsource(), &self.tx).await;
+                    }
+                    Ok(response) => {
+                        log::warn!("MCP event stream failed with status {}", response.status())
+                    }
+                    Err(e) => log::warn!("MCP event stream failed: {}", e),
+                }
+
+                if self.tx.is_closed() || attempts >= self.options.reconnect_attempts {
+                    return;
+                }
+                attempts += 1;
+                tokio::time::sleep(self.options.reconnect_delay).await;
+            }
+        });
+    }
+}
+
+// Legacy HTTP+SSE, the server sends the POST endpoint as the first event of the stream
+pub async fn sse_transport(
+    url: &str,
+    options: MCPHttpOptions,
+) -> Result<(MCPTransportSink, MCPTransportStream), TransportError> {
+    let client = options.client()?;
+    let url = Url::parse(url)?;
+    let (tx, rx) = mpsc::unbounded_channel();
+    let (endpoint_tx, endpoint_rx) = oneshot::channel();
+    let endpoint: Arc<Mutex<Option<Url>>> = Arc::new(Mutex::new(None));
+
+    let stream_client = client.clone();
+    let stream_endpoint = endpoint.clone();
+    let request_timeout = options.request_timeout;
+    tauri::async_runtime::spawn(async move {
+        let mut endpoint_tx = Some(endpoint_tx);
+        let mut attempts = 0;
+        loop {
+            let response = stream_client
+                .get(url.clone())
+                .header(ACCEPT, "text/event-stream")
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            match response {
+                Ok(response) => {
+                    attempts = 0;
+                    let mut events = response.bytes_stream().eventsource();
+                    loop {
+                        let event = tokio::select! {
+                            event = events.next() => event,
+                            _ = tx.closed() => return,
+                        };
+                        match event {
+                            Some(Ok(event)) if event.event == "endpoint" => {
+                                let Some(endpoint_url) = endpoint_url(&url, &event.data) else {
+                                    log::warn!("Invalid MCP endpoint: {}", event.data);
+                                    continue;
+                                };
+                                if let Ok(mut endpoint) = stream_endpoint.lock() {
+                                    *endpoint = Some(endpoint_url);
+                                }
+                                if let Some(endpoint_tx) = endpoint_tx.take() {
+                                    let _ = endpoint_tx.send(());
+                                }
+                            }
+                            Some(Ok(event)) => forward_event(event, &tx),
+                            Some(Err(e)) => {
+                                log::warn!("MCP event stream failed: {}", e);
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+                Err(e) => log::warn!("MCP event stream failed: {}", e),
+            }
+
+            if tx.is_closed() || attempts >= options.reconnect_attempts {
+                return;
+            }
+            attempts += 1;
+            tokio::time::sleep(options.reconnect_delay).await;
+        }
+    });
+
+    tokio::time::timeout(request_timeout, endpoint_rx)
+        .await
+        .map_err(|_| "Timed out waiting for the server endpoint")?
+        .map_err(|_| "Server closed the stream before sending its endpoint")?;
+
+    let sink = sink::unfold(
+        (client, endpoint),
+        move |(client, endpoint), message: ClientJsonRpcMessage| async move {
+            let endpoint_url = endpoint.lock().ok().and_then(|endpoint| endpoint.clone());
+            if let Some(endpoint_url) = endpoint_url {
+                let result = tokio::time::timeout(
+                    request_timeout,
+                    client.post(endpoint_url).json(&message).send(),
+                )
+                .await
+                .map_err(|_| std::io::Error::other("Request timed out"))?
+                .and_then(|response| response.error_for_status());
+                result.map_err(std::io::Error::other)?;
+            }
+            Ok::<_, std::io::Error>((client, endpoint))
+        },
+    );
+    Ok((Box::pin(sink), receiver_stream(rx)))
+}
+
+// The auth headers go to the endpoint as well, so it has to be on the server that was configured
+fn endpoint_url(url: &Url, endpoint: &str) -> Option<Url> {
+    url.join(endpoint)
+        .ok()
+        .filter(|endpoint_url| endpoint_url.origin() == url.origin())
+}
+
+fn receiver_stream(rx: mpsc::UnboundedReceiver<ServerJsonRpcMessage>) -> MCPTransportStream {
+    Box::pin(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|message| (message, rx))
+    }))
+}
+
+async fn forward_events<S>(events: S, tx: &mpsc::UnboundedSender<ServerJsonRpcMessage>)
+where
+    S: Stream<Item = Result<SseEvent, EventStreamError<reqwest::Error>>>,
+{
+    let mut events = Box::pin(events);
+    loop {
+        // Drop the connection as soon as the client is gone
+        let event = tokio::select! {
+            event = events.next() => event,
+            _ = tx.closed() => return,
+        };
+        match event {
+            Some(Ok(event)) => forward_event(event, tx),
+            Some(Err(e)) => {
+                log::warn!("MCP event stream failed: {}", e);
+                return;
+            }
+            None => return,
+        }
+    }
+}
+
+fn forward_event(event: SseEvent, tx: &mpsc::UnboundedSender<ServerJsonRpcMessage>) {
+    if event.event != "message" || event.data.is_empty() {
+        return;
+    }
+
+    match parse_messages(&event.data) {
+        Ok(messages) => {
+            for message in messages {
+                let _ = tx.send(message);
+            }
+        }
+        Err(e) => log::warn!("Invalid MCP message: {}", e),
+    }
+}
+
+// Servers may batch messages into an array
+fn parse_messages(data: &str) -> Result<Vec<ServerJsonRpcMessage>, serde_json::Error> {
+    match serde_json::from_str::<serde_json::Value>(data)? {
+        serde_json::Value::Array(values) => {
+            values.into_iter().map(serde_json::from_value).collect()
+        }
+        value => Ok(vec![serde_json::from_value(value)?]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::SinkExt;
+    use rmcp::model::{JsonRpcMessage, NumberOrString};
+    use serde_json::{Value, json};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    struct StubRequest {
+        method: String,
+        head: String,
+        body: Value,
+    }
+
+    // Answers every request on its own connection, the handler builds the raw HTTP response
+    async fn stub_server(
+        handler: fn(&StubRequest) -> String,
+    ) -> (String, mpsc::UnboundedReceiver<StubRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                socket
+                    .write_all(handler(&request).as_bytes())
+                    .await
+                    .unwrap();
+                let _ = socket.shutdown().await;
+                let _ = tx.send(request);
+            }
+        });
+
+        (url, rx)
+    }
+
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> StubRequest {
+        let mut data = Vec::new();
+        let mut buffer = [0; 4096];
+        let head_end = loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "connection closed before the request was read");
+            data.extend_from_slice(&buffer[..read]);
+            if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+
+        let head = String::from_utf8_lossy(&data[..head_end]).to_lowercase();
+        let content_length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|length| length.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        while data.len() < head_end + content_length {
+            let read = socket.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "connection closed before the body was read");
+            data.extend_from_slice(&buffer[..read]);
+        }
+
+        StubRequest {
+            method: head.split(' ').next().unwrap_or_default().to_uppercase(),
+            body: serde_json::from_slice(&data[head_end..]).unwrap_or_default(),
+            head,
+        }
+    }
+
+    fn http_response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}\r\n", name, value))
+            .collect();
+        format!(
+            "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+        )
+    }
+
+    fn empty_result(request: &StubRequest) -> String {
+        json!({ "jsonrpc": "2.0", "id": request.body["id"], "result": {} }).to_string()
+    }
+
+    fn ping(id: u32) -> ClientJsonRpcMessage {
+        serde_json::from_value(json!({ "jsonrpc": "2.0", "id": id, "method": "ping" })).unwrap()
+    }
+
+    fn options() -> MCPHttpOptions {
+        MCPHttpOptions::new(
+            &HashMap::from([("X-Team".to_string(), "qspeak".to_string())]),
+            &MCPExternalAuth::Bearer,
+            Some("secret-token".to_string()),
+            Duration::from_secs(5),
+            0,
+            Duration::from_millis(10),
+        )
+        .unwrap()
+    }
+
+    fn assert_response_to(message: Option<ServerJsonRpcMessage>, id: u32) {
+        match message {
+            Some(JsonRpcMessage::Response(response)) => {
+                assert_eq!(response.id, NumberOrString::Number(id))
+            }
+            other => panic!("expected a response to {}, got {:?}", id, other),
+        }
+    }
+
+    async fn next_post(requests: &mut mpsc::UnboundedReceiver<StubRequest>) -> StubRequest {
+        loop {
+            let request = requests.recv().await.expect("stub server is running");
+            if request.method == "POST" {
+                return request;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn forwards_json_responses_and_sends_the_session_id_back() {
+        let (url, mut requests) = stub_server(|request| match request.method.as_str() {
+            "POST" => http_response(
+                "200 OK",
+                &[
+                    ("content-type", "application/json"),
+                    (SESSION_ID_HEADER, "session-1"),
+                ],
+                &empty_result(request),
+            ),
+            _ => http_response("405 Method Not Allowed", &[], ""),
+        })
+        .await;
+        let (mut sink, mut stream) = streamable_http_transport(&url, options()).unwrap();
+
+        sink.send(ping(1)).await.unwrap();
+        assert_response_to(stream.next().await, 1);
+        let first = next_post(&mut requests).await;
+        assert!(first.head.contains("authorization: bearer secret-token"));
+        assert!(first.head.contains("x-team: qspeak"));
+        assert!(!first.head.contains(SESSION_ID_HEADER));
+
+        sink.send(ping(2)).await.unwrap();
+        assert_response_to(stream.next().await, 2);
+        let second = next_post(&mut requests).await;
+        assert!(second.head.contains("mcp-session-id: session-1"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn forwards_messages_of_event_stream_responses() {
+        let (url, _requests) = stub_server(|request| {
+            let body = format!(
+                "event: ping\ndata: \n\nevent: message\ndata: {}\n\n",
+                empty_result(request)
+            );
+            http_response("200 OK", &[("content-type", "text/event-stream")], &body)
+        })
+        .await;
+        let (mut sink, mut stream) = streamable_http_transport(&url, options()).unwrap();
+
+        sink.send(ping(7)).await.unwrap();
+        assert_response_to(stream.next().await, 7);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reports_failed_requests_to_the_sender() {
+        let (url, _requests) =
+            stub_server(|_| http_response("500 Internal Server Error", &[], "")).await;
+        let (mut sink, _stream) = streamable_http_transport(&url, options()).unwrap();
+
+        assert!(sink.send(ping(1)).await.is_err());
+    }
+
+    #[test]
+    fn only_accepts_endpoints_on_the_server_origin() {
+        let url = Url::parse("http://127.0.0.1:8000/sse").unwrap();
+
+        assert_eq!(
+            endpoint_url(&url, "/message?sessionId=1").map(String::from),
+            Some("http://127.0.0.1:8000/message?sessionId=1".to_string())
+        );
+        assert_eq!(endpoint_url(&url, "http://evil.example/message"), None);
+        assert_eq!(endpoint_url(&url, "http://127.0.0.1:9000/message"), None);
+        assert_eq!(endpoint_url(&url, "https://127.0.0.1:8000/message"), None);
+    }
+}
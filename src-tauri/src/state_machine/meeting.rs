@@ -0,0 +1,234 @@
+use std::{error::Error, path::Path};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::context_window::{BYTES_PER_TOKEN, MIN_CONTEXT_LENGTH};
+use crate::llm::{
+    ChatCompletionMessage, ChatCompletionMessageContent, ChatCompletionTextMessage, OpenAIClient,
+    OpenAIClientConfig,
+};
+
+pub const DEFAULT_MEETING_CHUNK_MINUTES: u32 = 5;
+pub const MAX_MEETING_CHUNK_MINUTES: u32 = 60;
+
+// Part of the model's context window a transcript part may fill, the rest is for the prompt and the answer
+const MEETING_TRANSCRIPT_CONTEXT_SHARE: f64 = 0.5;
+const MEETING_TRANSCRIPT_MAX_ROUNDS: usize = 3;
+const MEETING_TRANSCRIPT_TRUNCATED_MARKER: &str =
+    "\n\n[The rest of the transcript was cut, it didn't fit into the model's context]";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MeetingChunkState {
+    Recording,
+    Transcribing,
+    Transcribed,
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingChunk {
+    pub index: usize,
+    pub file_path: String,
+    pub state: MeetingChunkState,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingSession {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub base_file_path: String,
+    pub chunks: Vec<MeetingChunk>,
+    pub stopping: bool,
+}
+
+impl MeetingSession {
+    pub fn new(base_file_path: String) -> Self {
+        let mut session = Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+            base_file_path,
+            chunks: vec![],
+            stopping: false,
+        };
+        session.add_chunk();
+        session
+    }
+
+    /// Registers the next chunk and returns the path it should be recorded to.
+    pub fn add_chunk(&mut self) -> String {
+        let index = self.chunks.len();
+        let base_path = Path::new(&self.base_file_path);
+        // Recordings are saved as wav files, the fallbacks only matter for an odd base path
+        let stem = base_path
+            .file_stem()
+            .map_or("meeting".into(), |stem| stem.to_string_lossy());
+        let extension = base_path
+            .extension()
+            .map_or("wav".into(), |extension| extension.to_string_lossy());
+        let file_path = base_path
+            .with_file_name(format!("{}_part{}.{}", stem, index + 1, extension))
+            .to_string_lossy()
+            .to_string();
+
+        self.chunks.push(MeetingChunk {
+            index,
+            file_path: file_path.clone(),
+            state: MeetingChunkState::Recording,
+            text: None,
+        });
+
+        file_path
+    }
+
+    pub fn current_chunk_file_path(&self) -> Option<String> {
+        self.chunks.last().map(|chunk| chunk.file_path.clone())
+    }
+
+    pub fn find_chunk_mut(&mut self, file_path: &str) -> Option<&mut MeetingChunk> {
+        self.chunks
+            .iter_mut()
+            .find(|chunk| chunk.file_path == file_path)
+    }
+
+    pub fn transcript(&self) -> String {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| chunk.text.as_ref())
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.stopping
+            && self.chunks.iter().all(|chunk| {
+                matches!(
+                    chunk.state,
+                    MeetingChunkState::Transcribed | MeetingChunkState::Error(_)
+                )
+            })
+    }
+
+    pub fn first_error(&self) -> Option<String> {
+        self.chunks.iter().find_map(|chunk| match &chunk.state {
+            MeetingChunkState::Error(e) => Some(e.clone()),
+            _ => None,
+        })
+    }
+}
+
+/// Shrinks a long meeting transcript until it fits into a single LLM pass.
+/// Each round splits the text into parts, condenses every part separately (map)
+/// and joins the condensed parts back together (reduce).
+pub async fn condense_meeting_transcript(
+    transcript: String,
+    openai_client: OpenAIClient,
+    config: OpenAIClientConfig,
+    api_key: Option<String>,
+    context_length: u32,
+) -> Result<String, Box<dyn Error>> {
+    let max_bytes = transcript_max_bytes(context_length);
+    let mut text = transcript;
+
+    for round in 0..MEETING_TRANSCRIPT_MAX_ROUNDS {
+        if text.len() <= max_bytes {
+            break;
+        }
+
+        let parts = split_transcript(&text, max_bytes);
+        log::info!(
+            "Condensing meeting transcript, round {} with {} parts",
+            round + 1,
+            parts.len()
+        );
+
+        let mut condensed_parts = Vec::new();
+        for (index, part) in parts.iter().enumerate() {
+            let messages = vec![
+                ChatCompletionMessage::ChatCompletionTextMessage(ChatCompletionTextMessage {
+                    role: "system".to_string(),
+                    content: vec![ChatCompletionMessageContent::Text {
+                        text: "You condense one part of a long meeting transcript so that it can be summarised later. Keep every decision, action item (with owner and due date), open question, name, number and date. Drop fillers and small talk. Use the same language as the transcript. Respond only with the condensed notes.".to_string(),
+                    }],
+                    created_at: Utc::now(),
+                }),
+                ChatCompletionMessage::ChatCompletionTextMessage(ChatCompletionTextMessage {
+                    role: "user".to_string(),
+                    content: vec![ChatCompletionMessageContent::Text {
+                        text: format!("Part {} of {}:\n\n{}", index + 1, parts.len(), part),
+                    }],
+                    created_at: Utc::now(),
+                }),
+            ];
+
+            let response = openai_client
+                .chat_completion_non_streaming(
+                    messages,
+                    None,
+                    config.clone(),
+                    api_key.clone(),
+                    None,
+                )
+                .await?;
+            condensed_parts.push(response.trim().to_string());
+        }
+
+        text = condensed_parts.join("\n\n");
+    }
+
+    // Still too long after the last round, the end is cut so the summary request fits
+    if text.len() > max_bytes {
+        log::warn!(
+            "Meeting transcript is {} bytes after {} rounds, cutting it to {}",
+            text.len(),
+            MEETING_TRANSCRIPT_MAX_ROUNDS,
+            max_bytes
+        );
+        text = truncate_transcript(&text, max_bytes);
+    }
+
+    Ok(text)
+}
+
+fn truncate_transcript(text: &str, max_bytes: usize) -> String {
+    let mut end = max_bytes
+        .saturating_sub(MEETING_TRANSCRIPT_TRUNCATED_MARKER.len())
+        .min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}{}",
+        text[..end].trim_end(),
+        MEETING_TRANSCRIPT_TRUNCATED_MARKER
+    )
+}
+
+fn transcript_max_bytes(context_length: u32) -> usize {
+    let tokens = context_length.max(MIN_CONTEXT_LENGTH) as f64 * MEETING_TRANSCRIPT_CONTEXT_SHARE;
+    tokens as usize * BYTES_PER_TOKEN
+}
+
+fn split_transcript(text: &str, max_bytes: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.len() + word.len() + 1 > max_bytes {
+            parts.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    parts
+}
@@ -1,9 +1,22 @@
 // Re-export the state machine components
 pub mod account;
+pub mod audio_retention;
+pub mod builtin_tools;
+pub mod cancellation;
 pub mod challenges;
+pub mod connectivity;
+pub mod context_window;
+pub mod devices;
 pub mod errors;
 pub mod events;
 pub mod history;
+pub mod history_store;
+pub mod mcp_secrets;
+pub mod mcp_server;
+pub mod mcp_servers_json;
+pub mod mcp_supervisor;
+pub mod mcp_transport;
+pub mod meeting;
 pub mod models;
 pub mod new_app;
 pub mod new_conversation;
@@ -21,7 +34,9 @@ pub mod personas;
 pub mod processor;
 pub mod releases;
 pub mod state;
+pub mod structured_output;
 pub mod types;
+pub mod usage;
 pub mod websocket_server;
 // Re-export the main types
 pub use events::Event;
@@ -1,8 +1,24 @@
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 
+use super::context_window::DEFAULT_CONTEXT_LENGTH;
 use crate::constants::QSPEAK_API_V1_URL;
 
+// API the model is reached through
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LlmProvider {
+    // Anything compatible with OpenAI chat completions, also the qSpeak API
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    #[serde(rename = "ollama")]
+    Ollama,
+    #[serde(rename = "gemini")]
+    Gemini,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ModelConfig {
     #[serde(rename = "openai")]
@@ -15,6 +31,36 @@ pub enum ModelConfig {
         #[serde(default = "default_supports_tools")]
         supports_tools: bool,
     },
+    #[serde(rename = "anthropic")]
+    Anthropic {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        #[serde(default = "default_supports_vision")]
+        supports_vision: bool,
+        #[serde(default = "default_supports_tools")]
+        supports_tools: bool,
+    },
+    #[serde(rename = "ollama")]
+    Ollama {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        #[serde(default = "default_supports_vision")]
+        supports_vision: bool,
+        #[serde(default = "default_supports_tools")]
+        supports_tools: bool,
+    },
+    #[serde(rename = "gemini")]
+    Gemini {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        #[serde(default = "default_supports_vision")]
+        supports_vision: bool,
+        #[serde(default = "default_supports_tools")]
+        supports_tools: bool,
+    },
 }
 
 fn default_supports_vision() -> bool {
@@ -25,6 +71,10 @@ fn default_supports_tools() -> bool {
     true
 }
 
+pub fn default_context_length() -> u32 {
+    DEFAULT_CONTEXT_LENGTH
+}
+
 impl ModelConfig {
     pub fn qspeak(model: String) -> Self {
         Self::OpenAI {
@@ -35,6 +85,64 @@ impl ModelConfig {
             supports_tools: true,
         }
     }
+
+    pub fn new(
+        provider: LlmProvider,
+        url: String,
+        model: String,
+        api_key: Option<String>,
+        supports_vision: bool,
+        supports_tools: bool,
+    ) -> Self {
+        match provider {
+            LlmProvider::OpenAI => Self::OpenAI {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            },
+            LlmProvider::Anthropic => Self::Anthropic {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            },
+            LlmProvider::Ollama => Self::Ollama {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            },
+            LlmProvider::Gemini => Self::Gemini {
+                url,
+                model,
+                api_key,
+                supports_vision,
+                supports_tools,
+            },
+        }
+    }
+
+    pub fn provider(&self) -> LlmProvider {
+        match self {
+            Self::OpenAI { .. } => LlmProvider::OpenAI,
+            Self::Anthropic { .. } => LlmProvider::Anthropic,
+            Self::Ollama { .. } => LlmProvider::Ollama,
+            Self::Gemini { .. } => LlmProvider::Gemini,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            Self::OpenAI { url, .. }
+            | Self::Anthropic { url, .. }
+            | Self::Ollama { url, .. }
+            | Self::Gemini { url, .. } => url,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -69,6 +177,13 @@ pub struct TranscriptionModel {
     pub intelligence: f64,
 }
 
+impl TranscriptionModel {
+    // Local whisper models work without a connection once they are downloaded
+    pub fn runs_offline(&self) -> bool {
+        self.is_local && matches!(self.download_state, DownloadState::Downloaded)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationModel {
     pub name: String,
@@ -80,6 +195,9 @@ pub struct ConversationModel {
     pub supports_tools: bool,
     #[serde(default = "default_supports_vision")]
     pub supports_vision: bool,
+    // Tokens the model accepts in one request
+    #[serde(default = "default_context_length")]
+    pub context_length: u32,
     pub size: f64,
     pub parameters: f64,
     pub vram: f64,
@@ -91,6 +209,17 @@ pub struct ConversationModel {
 
 #[allow(dead_code)]
 impl ConversationModel {
+    // Downloaded KoboldCpp models, and custom endpoints on this machine like Ollama
+    pub fn runs_offline(&self) -> bool {
+        if self.is_local {
+            return matches!(self.download_state, DownloadState::Downloaded);
+        }
+        reqwest::Url::parse(self.config.url())
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+    }
+
     pub fn get_path(&self) -> Option<String> {
         if !self.is_local {
             return None;
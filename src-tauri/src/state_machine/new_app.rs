@@ -10,7 +10,15 @@ use tauri::{
     tray::TrayIconBuilder,
 };
 
-use super::{Event, Language, events::WebsocketServerSettingsPayload, processor::Processor};
+use super::{
+    Event, Language,
+    connectivity::NetworkMode,
+    devices::InputDevice,
+    errors::AppError,
+    events::{MCPServerSettingsPayload, WebsocketServerSettingsPayload},
+    processor::Processor,
+    usage,
+};
 use crate::state_machine::AppState;
 
 lazy_static! {
@@ -21,6 +29,8 @@ pub struct AppProcessor {
     language_submenu: Option<Submenu<tauri::Wry>>,
     input_device_submenu: Option<Submenu<tauri::Wry>>,
     websocket_server_item: Option<CheckMenuItem<tauri::Wry>>,
+    mcp_server_item: Option<CheckMenuItem<tauri::Wry>>,
+    local_only_item: Option<CheckMenuItem<tauri::Wry>>,
 }
 
 impl AppProcessor {
@@ -29,13 +39,15 @@ impl AppProcessor {
             language_submenu: None,
             input_device_submenu: None,
             websocket_server_item: None,
+            mcp_server_item: None,
+            local_only_item: None,
         }
     }
 
     pub fn start(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
         Processor::register_event_listener(
             "app",
-            Box::new(|event, _app_handle| match event {
+            Box::new(|event, app_handle| match event {
                 Event::ActionChangeTranscriptionLanguage(language) => {
                     AppState::update_language(language.clone())?;
 
@@ -109,10 +121,11 @@ impl AppProcessor {
                     Ok(())
                 }
                 Event::ActionChangeInputDevice(device) => {
-                    AppState::update_input_device(device.clone())?;
+                    AppState::update_input_device(device)?;
 
-                    let device = device.unwrap_or_default();
-                    let event_id = format!("device_{}", device.clone());
+                    // Names are resolved to ids, check the item for the stored one
+                    let device = AppState::get_context().input_device.unwrap_or_default();
+                    let event_id = format!("device_{}", device);
                     let app_processor = APP_PROCESSOR.lock().expect("Failed to lock app processor");
                     app_processor
                         .input_device_submenu
@@ -142,6 +155,24 @@ impl AppProcessor {
                         });
                     Ok(())
                 }
+                Event::ActionInputDevicesChanged(devices) => {
+                    let current_input_device = AppState::get_context().input_device;
+                    let app_processor = APP_PROCESSOR.lock().expect("Failed to lock app processor");
+                    if let Some(submenu) = app_processor.input_device_submenu.as_ref() {
+                        let menu_items = create_input_device_menu_items(
+                            app_handle,
+                            devices,
+                            &current_input_device,
+                        );
+                        for item in submenu.items()? {
+                            submenu.remove(&item)?;
+                        }
+                        for item in menu_items.iter() {
+                            submenu.append(item)?;
+                        }
+                    }
+                    Ok(())
+                }
                 Event::ActionChangeInterfaceLanguage(language) => {
                     AppState::update_interface_language(language.clone())
                 }
@@ -151,6 +182,21 @@ impl AppProcessor {
                 Event::ActionChangeConversationModel(model) => {
                     AppState::update_conversation_model(model.clone())
                 }
+                Event::ActionChangeFallbackTranscriptionModel(model) => {
+                    AppState::update_fallback_transcription_model(model.clone())
+                }
+                Event::ActionChangeFallbackConversationModel(model) => {
+                    AppState::update_fallback_conversation_model(model.clone())
+                }
+                Event::ActionUpdateModelPrice(payload) => {
+                    AppState::update_model_price(payload.model_name.clone(), payload.price.clone())
+                }
+                Event::ActionClearUsageStats => {
+                    if let Err(e) = usage::clear() {
+                        AppState::add_error(AppError::with_message(e.to_string()))?;
+                    }
+                    Ok(())
+                }
                 Event::ActionUpdateWebsocketServerSettings(settings) => {
                     AppState::update_websocket_server_settings(settings.clone())?;
 
@@ -163,6 +209,32 @@ impl AppProcessor {
 
                     Ok(())
                 }
+                Event::ActionUpdateMCPServerSettings(settings) => {
+                    AppState::update_mcp_server_settings(settings.clone())?;
+
+                    let app_processor = APP_PROCESSOR.lock().expect("Failed to lock app processor");
+                    if let Some(toggle) = app_processor.mcp_server_item.as_ref() {
+                        toggle
+                            .set_checked(settings.enabled)
+                            .expect("Failed to set MCP server toggle state");
+                    }
+
+                    Ok(())
+                }
+                Event::NetworkModeChange(mode) => {
+                    let local_only = AppState::get_context().connectivity_context.local_only;
+                    let app_processor = APP_PROCESSOR.lock().expect("Failed to lock app processor");
+                    if let Some(toggle) = app_processor.local_only_item.as_ref() {
+                        toggle
+                            .set_checked(local_only)
+                            .expect("Failed to set local only toggle state");
+                    }
+                    if let Some(tray) = app_handle.tray_by_id("tray") {
+                        tray.set_tooltip(Some(tray_tooltip(mode)))?;
+                    }
+
+                    Ok(())
+                }
                 Event::ActionChangePersona(persona) => {
                     AppState::update_active_persona(persona.clone())
                 }
@@ -224,11 +296,12 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
     )
     .expect("Failed to create image");
 
+    let tooltip = tray_tooltip(AppState::get_context().connectivity_context.mode);
     let tray = {
         #[cfg(target_os = "macos")]
         {
             TrayIconBuilder::with_id("tray")
-                .tooltip("qSpeak")
+                .tooltip(tooltip)
                 .icon(image)
                 .icon_as_template(true)
                 .build(&app_handle)
@@ -238,7 +311,7 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
         #[cfg(not(target_os = "macos"))]
         {
             TrayIconBuilder::with_id("tray")
-                .tooltip("qSpeak")
+                .tooltip(tooltip)
                 .icon(image)
                 .build(&app_handle)
                 .expect("tray failed")
@@ -254,7 +327,16 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
 
     let submenus = vec![language_submenu.clone(), input_device_submenu.clone()];
     let websocket_enabled = context.websocket_server_context.enabled;
-    let (menu, websocket_toggle_item) = build_tray_menu(&app_handle, submenus, websocket_enabled);
+    let mcp_server_enabled = context.mcp_server_context.enabled;
+    let local_only = context.connectivity_context.local_only;
+    let (menu, websocket_toggle_item, mcp_server_toggle_item, local_only_toggle_item) =
+        build_tray_menu(
+            &app_handle,
+            submenus,
+            websocket_enabled,
+            mcp_server_enabled,
+            local_only,
+        );
     let _ = tray.set_menu(Some(menu));
 
     let language_submenu_clone = language_submenu.clone();
@@ -263,6 +345,8 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
     app_processor.language_submenu = Some(language_submenu_clone);
     app_processor.input_device_submenu = Some(input_device_submenu_clone);
     app_processor.websocket_server_item = Some(websocket_toggle_item.clone());
+    app_processor.mcp_server_item = Some(mcp_server_toggle_item.clone());
+    app_processor.local_only_item = Some(local_only_toggle_item.clone());
 
     tray.on_menu_event(move |app, event| {
         let event_id = event.id.as_ref();
@@ -278,11 +362,7 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
                 .expect("Failed to process change transcription language event");
         }
 
-        if event_id.starts_with("device_") {
-            let device = event_id
-                .split("_")
-                .nth(1)
-                .expect("Failed to get device from event id");
+        if let Some(device) = event_id.strip_prefix("device_") {
             let device = Some(device.to_string());
             Processor::process_event(Event::ActionChangeInputDevice(device))
                 .expect("Failed to process change input device event");
@@ -339,6 +419,21 @@ fn setup_tray(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
                 ))
                 .expect("Failed to toggle websocket server");
             }
+            "mcp_server_toggle" => {
+                let context = AppState::get_context();
+                Processor::process_event(Event::ActionUpdateMCPServerSettings(
+                    MCPServerSettingsPayload {
+                        enabled: !context.mcp_server_context.enabled,
+                        port: context.mcp_server_context.port,
+                    },
+                ))
+                .expect("Failed to toggle MCP server");
+            }
+            "local_only_toggle" => {
+                let local_only = AppState::get_context().connectivity_context.local_only;
+                Processor::process_event(Event::ActionChangeLocalOnly(!local_only))
+                    .expect("Failed to toggle local only mode");
+            }
             _ => {}
         }
     });
@@ -349,7 +444,14 @@ fn build_tray_menu(
     app_handle: &AppHandle,
     submenus: Vec<Submenu<tauri::Wry>>,
     websocket_enabled: bool,
-) -> (Menu<tauri::Wry>, CheckMenuItem<tauri::Wry>) {
+    mcp_server_enabled: bool,
+    local_only: bool,
+) -> (
+    Menu<tauri::Wry>,
+    CheckMenuItem<tauri::Wry>,
+    CheckMenuItem<tauri::Wry>,
+    CheckMenuItem<tauri::Wry>,
+) {
     let recording_i = MenuItem::with_id(app_handle, "recording", "Recording", true, None::<&str>)
         .expect("Failed to create recording menu item");
     let center_window_i = MenuItem::with_id(
@@ -378,6 +480,24 @@ fn build_tray_menu(
         None::<&str>,
     )
     .expect("Failed to create WebSocket server toggle");
+    let mcp_server_toggle_i = CheckMenuItem::with_id(
+        app_handle,
+        "mcp_server_toggle",
+        "MCP Server",
+        true,
+        mcp_server_enabled,
+        None::<&str>,
+    )
+    .expect("Failed to create MCP server toggle");
+    let local_only_toggle_i = CheckMenuItem::with_id(
+        app_handle,
+        "local_only_toggle",
+        "Local Only",
+        true,
+        local_only,
+        None::<&str>,
+    )
+    .expect("Failed to create local only toggle");
 
     let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<_>> = vec![
         &recording_i,
@@ -392,10 +512,25 @@ fn build_tray_menu(
     }
 
     menu_items.push(&websocket_toggle_i);
+    menu_items.push(&mcp_server_toggle_i);
+    menu_items.push(&local_only_toggle_i);
     menu_items.push(&quit_i);
 
     let menu = Menu::with_items(app_handle, &menu_items).expect("Failed to create tray menu");
-    (menu, websocket_toggle_i)
+    (
+        menu,
+        websocket_toggle_i,
+        mcp_server_toggle_i,
+        local_only_toggle_i,
+    )
+}
+
+fn tray_tooltip(mode: NetworkMode) -> &'static str {
+    match mode {
+        NetworkMode::Online => "qSpeak",
+        NetworkMode::Offline => "qSpeak (offline)",
+        NetworkMode::LocalOnly => "qSpeak (local only)",
+    }
 }
 
 fn create_language_submenu(
@@ -434,28 +569,11 @@ fn create_input_device_submenu(
     app_handle: &AppHandle,
     current_input_device: &Option<String>,
 ) -> Submenu<tauri::Wry> {
-    let mut input_device_menu_items = Vec::new();
-
-    if let Ok(devices) = qspeak_audio_recording::get_audio_input_devices() {
-        for device_name in devices {
-            let menu_id = format!("device_{}", device_name.clone());
-            let is_checked = device_name.to_string()
-                == current_input_device.clone().unwrap_or_default().to_string();
-            let check_item = CheckMenuItem::with_id(
-                app_handle,
-                menu_id.as_str(),
-                &device_name,
-                true,
-                is_checked,
-                None::<&str>,
-            )
-            .expect("Failed to create input device check item");
-
-            input_device_menu_items.push(check_item);
-        }
-    } else {
-        log::info!("No devices found");
-    }
+    let input_device_menu_items = create_input_device_menu_items(
+        app_handle,
+        AppState::get_context().input_devices,
+        current_input_device,
+    );
 
     let input_device_refs: Vec<&dyn tauri::menu::IsMenuItem<_>> = input_device_menu_items
         .iter()
@@ -465,3 +583,30 @@ fn create_input_device_submenu(
     Submenu::with_items(app_handle, "Device", true, &input_device_refs)
         .expect("Failed to create input device submenu")
 }
+
+fn create_input_device_menu_items(
+    app_handle: &AppHandle,
+    devices: Vec<InputDevice>,
+    current_input_device: &Option<String>,
+) -> Vec<CheckMenuItem<tauri::Wry>> {
+    if devices.is_empty() {
+        log::info!("No devices found");
+    }
+
+    devices
+        .into_iter()
+        .map(|device| {
+            let menu_id = format!("device_{}", device.id);
+            let is_checked = current_input_device.as_ref() == Some(&device.id);
+            CheckMenuItem::with_id(
+                app_handle,
+                menu_id.as_str(),
+                &device.label,
+                true,
+                is_checked,
+                None::<&str>,
+            )
+            .expect("Failed to create input device check item")
+        })
+        .collect()
+}
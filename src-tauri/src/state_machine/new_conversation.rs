@@ -6,14 +6,20 @@ use hound::{WavSpec, WavWriter};
 use lazy_static::lazy_static;
 use qruhear;
 use qspeak_audio_player::{play_cancel_sound, play_paste_sound, play_start_sound, play_stop_sound};
+use qspeak_audio_recording::PreRollBuffer;
 use qspeak_keyboard::set_text_in_clipboard;
 use qspeak_screenshot::make_screenshot;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{
     fs::{self, File},
     io::BufWriter,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex, mpsc::Sender},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
     time::{Duration, Instant},
 };
 
@@ -23,13 +29,22 @@ use whisper_rs::WhisperContextParameters;
 use crate::constants::QSPEAK_API_V1_URL;
 use super::{
     Event, Language,
-    errors::{AppError, ConversationError},
-    models::TranscriptionProvider,
+    cancellation::{CancellationToken, Cancelled},
+    connectivity,
+    context_window::{SummaryModel, estimate_text_tokens, fit_conversation},
+    errors::{AppError, ConversationError, ModelRequestError},
+    history::{HistoryParent, fork_history},
+    history_store,
+    meeting::{
+        MAX_MEETING_CHUNK_MINUTES, MeetingChunkState, MeetingSession, condense_meeting_transcript,
+    },
+    models::{ConversationModel, DownloadState, TranscriptionProvider},
     new_models::get_transcription_model_path,
     processor::Processor,
     state::{
         ConversationMessage, ConversationState, ConversationTextMessage,
-        ConversationToolCallResultMessage, CopyTextState, ScreenshotState,
+        ConversationToolCallResultMessage, CopyTextState, FailedStep, MAX_PRE_ROLL_MS,
+        MAX_TOOL_ITERATIONS, ScreenshotState,
     },
 };
 use crate::{
@@ -37,13 +52,18 @@ use crate::{
         ChatCompletionMessage, ChatCompletionMessageContent, ChatCompletionMessageContentImageUrl,
         ChatCompletionTextMessage, ChatCompletionToolCall, ChatCompletionToolCallFunction,
         ChatCompletionToolCallMessage, ChatCompletionToolCallResultMessage, ChunkMessage,
-        OpenAIClient, OpenAIClientConfig,
+        OpenAIClient, OpenAIClientConfig, retry::send_with_retry,
     },
     state_machine::{
         AppState,
-        new_mcp_processor::MCPProcessor,
-        personas::Persona,
-        state::{ConversationToolCallMessage, ToolCall, ToolCallFunction},
+        new_mcp_processor::{MCPError, MCPProcessor, MCPToolPolicy, tool_result_content},
+        personas::{Persona, PersonaTools},
+        state::{
+            AppStateContext, ConversationToolCallMessage, ToolApprovalRequest, ToolCall,
+            ToolCallFunction, ToolResultContent,
+        },
+        structured_output::{self, MAX_STRUCTURED_OUTPUT_RETRIES},
+        usage,
     },
 };
 
@@ -60,13 +80,22 @@ pub struct ConversationProcessor {
 pub struct ToolCallResult {
     pub tool_call_id: String,
     pub content: String,
+    #[serde(default)]
+    pub parts: Vec<ToolResultContent>,
+    #[serde(default)]
+    pub is_error: bool,
     pub created_at: DateTime<Utc>,
 }
 
 enum RecordingCommand {
     Start(PathBuf, Option<String>, bool),
+    RotateChunk(PathBuf),
+    Pause,
+    Resume,
     Stop,
     Cancel,
+    ConfigurePreRoll(Option<PreRollSettings>),
+    InputDevicesChanged(Vec<String>, Option<PreRollSettings>),
 }
 
 #[derive(Debug, Clone)]
@@ -101,7 +130,7 @@ fn convert_conversation_to_chat_completion_messages(
                                         "{}--{}",
                                         tool_call.function.client_name, tool_call.function.name
                                     ),
-                                    arguments: "".to_string(),
+                                    arguments: tool_call.function.arguments.clone(),
                                 },
                                 r#type: "function".to_string(),
                             })
@@ -169,6 +198,48 @@ fn convert_conversation_to_chat_completion_messages(
     chat_messages
 }
 
+// Leaves out what doesn't fit into the model's context window and keeps the usage for the UI
+fn fit_conversation_to_model(
+    conversation: Vec<ConversationMessage>,
+    context_length: u32,
+    summary_model: SummaryModel<'_>,
+    cancellation: &CancellationToken,
+) -> Result<Vec<ChatCompletionMessage>, Box<dyn std::error::Error>> {
+    let app_context = AppState::get_context();
+    let reserved_tokens = app_context
+        .active_persona
+        .as_ref()
+        .map(|persona| {
+            persona
+                .examples
+                .iter()
+                .map(|example| {
+                    estimate_text_tokens(&example.question) + estimate_text_tokens(&example.answer)
+                })
+                .sum()
+        })
+        .unwrap_or(0);
+
+    let fitted = block_on(fit_conversation(
+        conversation,
+        context_length,
+        reserved_tokens,
+        app_context.conversation_context.context_strategy,
+        app_context.conversation_context.context_summary.clone(),
+        summary_model,
+        cancellation,
+    ))?;
+
+    AppState::update(|context| {
+        context.conversation_context.context_usage = Some(fitted.usage.clone());
+        context.conversation_context.context_summary = fitted.summary.clone();
+    })?;
+
+    Ok(convert_conversation_to_chat_completion_messages(
+        &fitted.conversation,
+    ))
+}
+
 impl ConversationProcessor {
     pub fn new() -> Self {
         Self {
@@ -185,26 +256,40 @@ impl ConversationProcessor {
                     (Event::ActionRecording, ConversationState::Idle) => {
                         start_recording(&app_handle)
                     }
-                    (Event::ActionCloseRecordingWindow, ConversationState::Listening) => {
-                        cancel_recording(&app_handle)
+                    (
+                        Event::ActionCloseRecordingWindow,
+                        ConversationState::Listening | ConversationState::Paused,
+                    ) => cancel_recording(&app_handle),
+                    (
+                        Event::ActionRecording,
+                        ConversationState::Listening | ConversationState::Paused,
+                    ) => finish_recording(&app_handle),
+                    (
+                        Event::ActionInputDeviceDisconnected,
+                        ConversationState::Listening | ConversationState::Paused,
+                    ) => {
+                        log::warn!("Input device disconnected, finishing the recording");
+                        AppState::update(|context| {
+                            context.errors.push(AppError::with_message(
+                                "The input device was disconnected. The recording was stopped and what was captured so far is being transcribed.".to_string(),
+                            ));
+                        })?;
+                        finish_recording(&app_handle)
                     }
-                    (Event::ActionRecording, ConversationState::Listening) => {
-                        stop_recording(&app_handle)?;
-                        
-                        match start_transcription(&app_handle) {
-                            Ok(_) => Ok(()),
-                            Err(_e) => AppState::update(|context| {
-                                context
-                                    .reset_state_with_error(AppError::with_message(
-                                        "Transcription model not picked. Please pick a model in the settings.".to_string(),
-                                    ))
-                                    .unwrap();
-                            }),
-                        }
+                    (Event::ActionInputDevicesChanged(devices), _) => {
+                        audio_recorder_sender().send(RecordingCommand::InputDevicesChanged(
+                            devices.into_iter().map(|device| device.id).collect(),
+                            pre_roll_settings(app_context.input_device),
+                        ))?;
+                        Ok(())
                     }
+                    (Event::PauseTranscription, ConversationState::Listening) => pause_recording(),
+                    (Event::ResumeTranscription, ConversationState::Paused) => resume_recording(),
                     (
                         Event::ActionTextMessage(text),
-                        ConversationState::Listening | ConversationState::Idle,
+                        ConversationState::Listening
+                        | ConversationState::Paused
+                        | ConversationState::Idle,
                     ) => {
                         start_transformation(
                             &app_handle,
@@ -220,22 +305,113 @@ impl ConversationProcessor {
                         Event::ActionLoadHistoryConversation(conversation_id),
                         ConversationState::Idle,
                     ) => {
-                        let conversation = app_context
-                            .history_context
-                            .history
-                            .iter()
-                            .find(|conversation| conversation.id == conversation_id)
-                            .cloned();
+                        let conversation = history_store::get(&conversation_id)?;
                         if let Some(conversation) = conversation {
                             AppState::update(|context| {
                                 context.conversation_context.conversation =
                                     conversation.conversation.clone();
+                                context.conversation_context.context_usage = None;
+                                context.conversation_context.failed_step = None;
                                 context.history_context.current_history_id = None;
+                                // Continuing it creates a branch of the loaded entry
+                                context.history_context.branch_parent = Some(HistoryParent {
+                                    id: conversation.id.clone(),
+                                    message_count: conversation.conversation.len(),
+                                });
                             })
                             .unwrap();
                         }
                         Ok(())
                     }
+                    (Event::ActionEditMessage(payload), ConversationState::Idle) => {
+                        let is_user_message = matches!(
+                            app_context
+                                .conversation_context
+                                .conversation
+                                .get(payload.message_index),
+                            Some(ConversationMessage::ConversationTextMessage(message))
+                                if message.role == "user"
+                        );
+                        if !is_user_message {
+                            return Err(format!(
+                                "Message {} is not a user message",
+                                payload.message_index
+                            )
+                            .into());
+                        }
+
+                        AppState::update(|context| {
+                            branch_conversation(context, payload.message_index);
+                        })?;
+                        start_transformation(
+                            &app_handle,
+                            payload.text,
+                            TransformationContext {
+                                is_text_message: true,
+                            },
+                        )
+                    }
+                    (Event::ActionRegenerateResponse, ConversationState::Idle) => {
+                        let last_user_message = app_context
+                            .conversation_context
+                            .conversation
+                            .iter()
+                            .rposition(|message| match message {
+                                ConversationMessage::ConversationTextMessage(message) => {
+                                    message.role == "user"
+                                }
+                                _ => false,
+                            });
+                        let Some(last_user_message) = last_user_message else {
+                            return Ok(());
+                        };
+                        if app_context.active_persona.is_none()
+                            || app_context.conversation_model.is_none()
+                        {
+                            return AppState::update(|context| {
+                                context.errors.push(AppError::with_message(
+                                    "Pick a persona and a conversation model to regenerate the response."
+                                        .to_string(),
+                                ));
+                            });
+                        }
+
+                        AppState::update(|context| {
+                            branch_conversation(context, last_user_message + 1);
+                            insert_system_prompt(context);
+                            context.conversation_context.pending_tool_call_ids.clear();
+                            context.conversation_context.tool_iterations = 0;
+                            context.conversation_context.structured_output_retries = 0;
+                            context.conversation_context.failed_step = None;
+                            context.conversation_context.cancellation = CancellationToken::new();
+                            context.conversation_context.state = ConversationState::Transforming;
+                        })?;
+                        run_transformation()
+                    }
+                    (Event::ActionForkHistory(payload), ConversationState::Idle) => {
+                        let history = fork_history(&payload.history_id, payload.message_index)?;
+                        AppState::update(|context| {
+                            context.conversation_context.conversation =
+                                history.conversation.clone();
+                            context.history_context.current_history_id = Some(history.id.clone());
+                            context.history_context.branch_parent = None;
+                            context
+                                .history_context
+                                .history
+                                .push(history_store::summarize(&history));
+                        })?;
+                        Processor::process_event(Event::ActionOpenRecordingWindow)
+                    }
+                    (Event::ActionTranscriptionSuccess(text), ConversationState::Transcribing)
+                        if app_context.conversation_context.voice_input_requested =>
+                    {
+                        // The agent that asked for the recording gets the text, no persona runs
+                        AppState::update(|context| {
+                            context.conversation_context.set_idle_state().unwrap();
+                        })?;
+                        Processor::process_event(Event::ActionVoiceInputSuccess(text))?;
+                        Processor::process_event(Event::ActionCloseRecordingWindow)
+                    }
                     (Event::ActionTranscriptionSuccess(text), ConversationState::Transcribing) => {
                         log::info!("Transcription success!");
                         let personas = app_context.personas_context.personas.clone();
@@ -259,20 +435,35 @@ impl ConversationProcessor {
                         )
                     }
                     (Event::ActionTranscriptionError(e), ConversationState::Transcribing) => {
+                        if app_context.conversation_context.voice_input_requested {
+                            Processor::process_event(Event::ActionVoiceInputError(e.clone()))?;
+                        }
+                        // Agents get the error instead, a meeting can't go on from a single chunk
+                        let failed_step = app_context
+                            .conversation_context
+                            .current_audio_file_path
+                            .clone()
+                            .filter(|_| {
+                                !app_context.conversation_context.voice_input_requested
+                                    && app_context.conversation_context.meeting.is_none()
+                            })
+                            .map(|audio_file_path| FailedStep::Transcription { audio_file_path });
                         AppState::update(|context| {
                             context
                                 .reset_state_with_error(AppError::with_message(e))
                                 .unwrap();
+                            context.conversation_context.failed_step = failed_step;
                         })
                     }
-                    (Event::ActionTransformationChunk(chunk), ConversationState::Transforming) => {
-                        AppState::update(|context| {
-                            context.conversation_context.add_chunk(chunk).unwrap();
-                        })
-                    }
+                    (
+                        Event::ActionTransformationChunk(chunk),
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        context.conversation_context.add_chunk(chunk).unwrap();
+                    }),
                     (
                         Event::ActionTransformationToolCall(tool_call),
-                        ConversationState::Transforming,
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
                     ) => AppState::update(|context| {
                         let tool_call_name = tool_call.function.name.unwrap().clone();
                         let tool_call_id = tool_call.id.unwrap().clone();
@@ -337,44 +528,50 @@ impl ConversationProcessor {
                             }
                         }
 
-                        let arguments =
-                            serde_json::from_str(&tool_call.function.arguments).unwrap();
-                        std::thread::spawn(move || {
-                            let result = block_on(MCPProcessor::call_tool(
-                                mcp_client_name,
-                                mcp_tool_name,
-                                arguments,
-                            ));
-                            match result {
-                                Ok(result) => {
-                                    let content = serde_json::to_string(&result).unwrap();
-                                    Processor::process_event(
-                                        Event::ActionTransformationToolCallResult(ToolCallResult {
-                                            tool_call_id: tool_call_id.clone(),
-                                            content: content,
-                                            created_at: Utc::now(),
-                                        }),
-                                    )
-                                    .expect(
-                                        "Failed to process transformation tool call result event",
-                                    );
-                                }
-                                Err(e) => {
-                                    Processor::process_event(
-                                        Event::ActionTransformationToolCallResult(ToolCallResult {
-                                            tool_call_id: tool_call_id.clone(),
-                                            content: format!("Error calling tool: {:?}", e),
-                                            created_at: Utc::now(),
-                                        }),
-                                    )
-                                    .expect("Failed to process transformation error event");
-                                }
+                        let pending_tool_call = ToolCall {
+                            function: ToolCallFunction {
+                                client_name: mcp_client_name,
+                                name: mcp_tool_name,
+                                arguments: tool_call.function.arguments.clone(),
+                            },
+                            id: tool_call_id,
+                        };
+                        // The model may still call a tool it remembers from earlier in the conversation
+                        if !persona_tools(context).allows(
+                            &pending_tool_call.function.client_name,
+                            &pending_tool_call.function.name,
+                        ) {
+                            send_tool_call_error(
+                                pending_tool_call.id,
+                                "Tool is not enabled for the active persona".to_string(),
+                            );
+                            return;
+                        }
+                        match context.mcp_context.tool_policy(
+                            &pending_tool_call.function.client_name,
+                            &pending_tool_call.function.name,
+                        ) {
+                            MCPToolPolicy::Allow => {
+                                call_tool_in_background(context, pending_tool_call)
                             }
-                        });
+                            MCPToolPolicy::Deny => {
+                                log::info!(
+                                    "Tool call {} denied by policy",
+                                    pending_tool_call.function.name
+                                );
+                                send_tool_call_error(
+                                    pending_tool_call.id,
+                                    "Tool call denied by the user's tool policy".to_string(),
+                                );
+                            }
+                            MCPToolPolicy::Ask => {
+                                request_tool_approval(context, pending_tool_call);
+                            }
+                        }
                     }),
                     (
                         Event::ActionTransformationToolCallResult(tool_call_result),
-                        ConversationState::Transforming,
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
                     ) => {
                         AppState::update(|context| {
                             // Add the tool call result to conversation
@@ -384,6 +581,8 @@ impl ConversationProcessor {
                                         role: "tool".to_string(),
                                         tool_call_id: tool_call_result.tool_call_id.clone(),
                                         content: tool_call_result.content,
+                                        parts: tool_call_result.parts,
+                                        is_error: tool_call_result.is_error,
                                         created_at: tool_call_result.created_at,
                                     },
                                 ),
@@ -418,118 +617,245 @@ impl ConversationProcessor {
                             return Ok(());
                         }
 
-                        let active_model = app_context
-                            .conversation_model
-                            .clone()
-                            .expect("Failed to get conversation model");
-                        let conversation = app_context.conversation_context.conversation.clone();
-                        std::thread::spawn(move || {
-                            let model = AppState::get_context()
-                                .models_context
-                                .conversation_models
-                                .iter()
-                                .find(|model| model.model == active_model)
-                                .cloned();
-
-                            if model.is_none() {
-                                AppState::update(|context| {
-                                context.errors.push(AppError::with_message(
-                                    "Transformation model not found. Please pick a model in the settings."
-                                        .to_string(),
-                                ));
-                            })
-                            .expect("Failed to update app state");
-                                return;
-                            }
-
-                            let model = model.unwrap();
-
-                            let openai_client = OpenAIClient::new();
-                            let config = if model.is_local {
-                                OpenAIClientConfig::local()
-                            } else {
-                                OpenAIClientConfig::openai(model.model.clone())
-                            };
-
-                            let api_key = AppState::get_context()
-                                .account_context
-                                .account
-                                .token
-                                .clone();
-
-                            let messages =
-                                convert_conversation_to_chat_completion_messages(&conversation);
-
-                            match block_on(transform_with_openai(
-                                messages,
-                                openai_client,
-                                config,
-                                api_key,
-                            )) {
-                                Ok(_) => {
-                                    Processor::process_event(Event::ActionTransformationSuccess())
-                                        .expect("Failed to process transformation success event");
-                                }
-                                Err(e) => {
-                                    Processor::process_event(Event::ActionTransformationError(
-                                        e.to_string(),
-                                    ))
-                                    .expect("Failed to process transformation error event");
-                                }
-                            }
-                        });
+                        // Stop models that keep calling tools without ever answering
+                        let max_tool_iterations =
+                            app_context.conversation_context.max_tool_iterations;
+                        if app_context.conversation_context.tool_iterations >= max_tool_iterations {
+                            log::warn!(
+                                "Tool loop stopped after {} iterations",
+                                max_tool_iterations
+                            );
+                            return AppState::update(|context| {
+                                context
+                                    .reset_state_with_error(AppError::with_message(format!(
+                                        "Stopped after {} rounds of tool calls without an answer. You can raise the limit in the settings.",
+                                        max_tool_iterations
+                                    )))
+                                    .unwrap();
+                            });
+                        }
+                        AppState::update(|context| {
+                            context.conversation_context.tool_iterations += 1;
+                        })?;
 
-                        Ok(())
+                        // Same model and persona settings as the turn that asked for the tools
+                        run_transformation()
                     }
-                    (Event::ActionTransformationSuccess(), ConversationState::Transforming) => {
-                        AppState::update(|context| {
-                            let active_persona = context.active_persona.clone();
+                    (
+                        Event::ActionTransformationSuccess(),
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
+                    ) if app_context
+                        .active_persona
+                        .as_ref()
+                        .is_some_and(|persona| persona.structured_output.is_some()) =>
+                    {
+                        finish_structured_output(app_handle)
+                    }
+                    (
+                        Event::ActionTransformationSuccess(),
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        let active_persona = context.active_persona.clone();
 
-                            if let Some(last_message) =
-                                context.conversation_context.conversation.last()
-                            {
-                                match last_message {
-                                    ConversationMessage::ConversationTextMessage(last_message) => {
-                                        if last_message.role == "assistant" {
-                                            if let Some(ChatCompletionMessageContent::Text {
-                                                text,
-                                            }) = last_message.content.last()
+                        if let Some(last_message) = context.conversation_context.conversation.last()
+                        {
+                            match last_message {
+                                ConversationMessage::ConversationTextMessage(last_message) => {
+                                    if last_message.role == "assistant" {
+                                        if let Some(ChatCompletionMessageContent::Text { text }) =
+                                            last_message.content.last()
+                                        {
+                                            context.conversation_context.state =
+                                                ConversationState::Idle;
+                                            if active_persona.is_some()
+                                                && active_persona.unwrap().paste_on_finish
                                             {
-                                                context.conversation_context.state =
-                                                    ConversationState::Idle;
-                                                if active_persona.is_some()
-                                                    && active_persona.unwrap().paste_on_finish
-                                                {
-                                                    set_text_in_clipboard_and_paste(
-                                                        text, app_handle,
-                                                    );
-                                                }
-                                                if let Err(e) = set_text_in_clipboard(text) {
-                                                    context.errors.push(AppError::with_message(
-                                                        e.to_string(),
-                                                    ));
-                                                }
+                                                set_text_in_clipboard_and_paste(text, app_handle);
+                                            }
+                                            if let Err(e) = set_text_in_clipboard(text) {
+                                                context
+                                                    .errors
+                                                    .push(AppError::with_message(e.to_string()));
                                             }
                                         }
                                     }
-                                    _ => {}
                                 }
+                                _ => {}
                             }
+                        }
 
-                            Processor::process_event(Event::ActionUpdateOrCreateHistory(
-                                context.active_persona.clone(),
-                                context.conversation_context.conversation.clone(),
-                            ))
-                            .ok();
+                        Processor::process_event(Event::ActionUpdateOrCreateHistory(
+                            context.active_persona.clone(),
+                            context.conversation_context.conversation.clone(),
+                        ))
+                        .ok();
+                    }),
+                    (
+                        Event::ActionTransformationError(e),
+                        ConversationState::Transforming | ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        context
+                            .reset_state_with_error(AppError::with_message(e))
+                            .unwrap();
+                        context.conversation_context.failed_step = Some(FailedStep::Transformation);
+                    }),
+                    (Event::ActionRetryFailedStep, ConversationState::Idle) => {
+                        retry_failed_step(&app_handle)
+                    }
+                    (
+                        Event::ActionCancelProcessing,
+                        ConversationState::Transcribing
+                        | ConversationState::Transforming
+                        | ConversationState::AwaitingToolApproval,
+                    ) => {
+                        log::info!(
+                            "Cancelling processing in state {:?}",
+                            app_context.conversation_context.state
+                        );
+                        if app_context.conversation_context.voice_input_requested {
+                            Processor::process_event(Event::ActionVoiceInputError(
+                                Cancelled.to_string(),
+                            ))?;
+                        }
+                        AppState::update(|context| cancel_processing(context))
+                    }
+                    (
+                        Event::ActionApproveToolCall(tool_call_id),
+                        ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        if let Some(request) = context
+                            .conversation_context
+                            .take_tool_approval(&tool_call_id)
+                        {
+                            log::info!("Tool call {} approved", request.tool_call.function.name);
+                            call_tool_in_background(context, request.tool_call);
+                        }
+                    }),
+                    (
+                        Event::ActionDenyToolCall(tool_call_id),
+                        ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        if let Some(request) = context
+                            .conversation_context
+                            .take_tool_approval(&tool_call_id)
+                        {
+                            log::info!("Tool call {} denied", request.tool_call.function.name);
+                            send_tool_call_error(
+                                request.tool_call.id,
+                                "Tool call denied by the user".to_string(),
+                            );
+                        }
+                    }),
+                    (
+                        Event::ActionToolApprovalTimeout(tool_call_id),
+                        ConversationState::AwaitingToolApproval,
+                    ) => AppState::update(|context| {
+                        if let Some(request) = context
+                            .conversation_context
+                            .take_tool_approval(&tool_call_id)
+                        {
+                            log::info!(
+                                "Tool call {} was not approved in time",
+                                request.tool_call.function.name
+                            );
+                            send_tool_call_error(
+                                request.tool_call.id,
+                                "Tool call denied, the user did not approve it in time".to_string(),
+                            );
+                        }
+                    }),
+                    (Event::ActionMeetingChunkRecorded(file_path), _) => {
+                        let mut is_meeting_chunk = false;
+                        AppState::update(|context| {
+                            if let Some(chunk) = context
+                                .conversation_context
+                                .meeting
+                                .as_mut()
+                                .and_then(|meeting| meeting.find_chunk_mut(&file_path))
+                            {
+                                chunk.state = MeetingChunkState::Transcribing;
+                                is_meeting_chunk = true;
+                            }
+                        })?;
+
+                        if is_meeting_chunk {
+                            transcribe_meeting_chunk(file_path);
+                        }
+                        Ok(())
+                    }
+                    (Event::ActionMeetingChunkTranscriptionSuccess(file_path, text), _) => {
+                        AppState::update(|context| {
+                            if let Some(meeting) = context.conversation_context.meeting.as_mut() {
+                                if let Some(chunk) = meeting.find_chunk_mut(&file_path) {
+                                    chunk.state = MeetingChunkState::Transcribed;
+                                    chunk.text = Some(text);
+                                }
+                                context.conversation_context.transcription_text =
+                                    meeting.transcript();
+                            }
+                        })?;
+                        finish_meeting_if_ready()
+                    }
+                    (Event::ActionMeetingChunkTranscriptionError(file_path, e), _) => {
+                        log::error!("Failed to transcribe meeting chunk {}: {}", file_path, e);
+                        AppState::update(|context| {
+                            if let Some(chunk) = context
+                                .conversation_context
+                                .meeting
+                                .as_mut()
+                                .and_then(|meeting| meeting.find_chunk_mut(&file_path))
+                            {
+                                chunk.state = MeetingChunkState::Error(e);
+                            }
+                        })?;
+                        finish_meeting_if_ready()
+                    }
+                    (
+                        Event::ActionMeetingTranscriptReady(text),
+                        ConversationState::Transcribing,
+                    ) => start_transformation(
+                        &app_handle,
+                        text,
+                        TransformationContext {
+                            is_text_message: false,
+                        },
+                    ),
+                    (Event::ActionChangeMeetingChunkMinutes(minutes), _) => {
+                        AppState::update(|context| {
+                            context.conversation_context.meeting_chunk_minutes =
+                                minutes.clamp(1, MAX_MEETING_CHUNK_MINUTES);
                         })
                     }
-                    (Event::ActionTransformationError(e), ConversationState::Transforming) => {
+                    (Event::ActionChangeMaxToolIterations(max_tool_iterations), _) => {
                         AppState::update(|context| {
-                            context
-                                .reset_state_with_error(AppError::with_message(e))
-                                .unwrap();
+                            context.conversation_context.max_tool_iterations =
+                                max_tool_iterations.clamp(1, MAX_TOOL_ITERATIONS);
+                        })
+                    }
+                    (Event::ActionChangeContextStrategy(strategy), _) => {
+                        AppState::update(|context| {
+                            context.conversation_context.context_strategy = strategy;
                         })
                     }
+                    (Event::ActionChangePreRollEnabled(enabled), _) => {
+                        AppState::update(|context| {
+                            context.conversation_context.pre_roll_enabled = enabled;
+                        })?;
+                        sync_pre_roll(app_context.input_device)
+                    }
+                    (Event::ActionChangePreRollMs(duration_ms), _) => {
+                        AppState::update(|context| {
+                            context.conversation_context.pre_roll_ms =
+                                duration_ms.min(MAX_PRE_ROLL_MS);
+                        })?;
+                        sync_pre_roll(app_context.input_device)
+                    }
+                    // Follow the newly selected device
+                    (Event::ActionChangeInputDevice(device), _)
+                        if app_context.conversation_context.pre_roll_enabled =>
+                    {
+                        sync_pre_roll(device)
+                    }
                     (Event::ActionChangePersona(_persona), _) => AppState::update(|context| {
                         Self::reset_conversation_state(context);
                     }),
@@ -538,7 +864,9 @@ impl ConversationProcessor {
                     }),
                     (
                         Event::ActionAddImage(data_url),
-                        ConversationState::Idle | ConversationState::Listening,
+                        ConversationState::Idle
+                        | ConversationState::Listening
+                        | ConversationState::Paused,
                     ) => AppState::update(|context| {
                         context.conversation_context.conversation.push(
                             ConversationMessage::ConversationTextMessage(ConversationTextMessage {
@@ -561,7 +889,9 @@ impl ConversationProcessor {
                     }),
                     (
                         Event::ActionAddFile(binary_data),
-                        ConversationState::Idle | ConversationState::Listening,
+                        ConversationState::Idle
+                        | ConversationState::Listening
+                        | ConversationState::Paused,
                     ) => AppState::update(|context| {
                         let decoded_file = match decode_binary_file(&binary_data) {
                             Ok(file) => file,
@@ -599,7 +929,9 @@ impl ConversationProcessor {
                     }),
                     (
                         Event::ActionScreenshot,
-                        ConversationState::Idle | ConversationState::Listening,
+                        ConversationState::Idle
+                        | ConversationState::Listening
+                        | ConversationState::Paused,
                     ) => {
                         let app_handle_clone = app_handle.clone();
                         if app_context.conversation_context.screenshot_state
@@ -633,7 +965,9 @@ impl ConversationProcessor {
                     }
                     (
                         Event::ActionCopyText,
-                        ConversationState::Listening | ConversationState::Idle,
+                        ConversationState::Listening
+                        | ConversationState::Paused
+                        | ConversationState::Idle,
                     ) => {
                         if app_context.conversation_context.copy_text_state
                             == CopyTextState::Copying
@@ -647,7 +981,9 @@ impl ConversationProcessor {
                     }
                     (
                         Event::ActionAddText(text),
-                        ConversationState::Listening | ConversationState::Idle,
+                        ConversationState::Listening
+                        | ConversationState::Paused
+                        | ConversationState::Idle,
                     ) => AppState::update(|context| {
                         context.conversation_context.conversation.push(
                             ConversationMessage::ConversationTextMessage(ConversationTextMessage {
@@ -665,13 +1001,17 @@ impl ConversationProcessor {
                     }),
                     (
                         Event::ActionAddDictionaryItem(term),
-                        ConversationState::Idle | ConversationState::Listening,
+                        ConversationState::Idle
+                        | ConversationState::Listening
+                        | ConversationState::Paused,
                     ) => AppState::update(|context| {
                         context.conversation_context.dictionary.push(term);
                     }),
                     (
                         Event::ActionDeleteDictionaryItem(term),
-                        ConversationState::Idle | ConversationState::Listening,
+                        ConversationState::Idle
+                        | ConversationState::Listening
+                        | ConversationState::Paused,
                     ) => AppState::update(|context| {
                         let index = context
                             .conversation_context
@@ -688,13 +1028,173 @@ impl ConversationProcessor {
                 }
             }),
         );
+
+        let app_context = AppState::get_context();
+        if app_context.conversation_context.pre_roll_enabled {
+            if let Err(e) = sync_pre_roll(app_context.input_device) {
+                log::error!("Failed to start pre-roll capture: {}", e);
+            }
+        }
     }
 
     fn reset_conversation_state(context: &mut super::state::AppStateContext) {
         context.history_context.current_history_id = None;
+        context.history_context.branch_parent = None;
         context.conversation_context.state = ConversationState::Idle;
         context.conversation_context.conversation = vec![];
         context.conversation_context.pending_tool_call_ids.clear();
+        context.conversation_context.pending_tool_approvals.clear();
+        context.conversation_context.meeting = None;
+        context.conversation_context.context_usage = None;
+        context.conversation_context.context_summary = None;
+        context.conversation_context.failed_step = None;
+    }
+}
+
+// Stops the running turn, what the model answered so far stays in the conversation
+fn cancel_processing(context: &mut AppStateContext) {
+    context.conversation_context.cancellation.cancel();
+    let was_transcribing = context.conversation_context.state == ConversationState::Transcribing;
+
+    // The model expects a result for every call it made, or the conversation can't be continued
+    let pending_tool_call_ids =
+        std::mem::take(&mut context.conversation_context.pending_tool_call_ids);
+    for tool_call_id in pending_tool_call_ids {
+        let parts = vec![ToolResultContent::Text {
+            text: MCPError::ToolCallCancelled.to_string(),
+        }];
+        context.conversation_context.conversation.push(
+            ConversationMessage::ConversationToolCallResultMessage(
+                ConversationToolCallResultMessage {
+                    role: "tool".to_string(),
+                    tool_call_id,
+                    content: format_tool_result_for_model(&parts, true),
+                    parts,
+                    is_error: true,
+                    created_at: Utc::now(),
+                },
+            ),
+        );
+    }
+    context.conversation_context.set_idle_state().unwrap();
+
+    if !was_transcribing {
+        Processor::process_event(Event::ActionUpdateOrCreateHistory(
+            context.active_persona.clone(),
+            context.conversation_context.conversation.clone(),
+        ))
+        .ok();
+    }
+}
+
+const TOOL_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn request_tool_approval(context: &mut AppStateContext, tool_call: ToolCall) {
+    log::info!(
+        "Waiting for approval of tool call {}",
+        tool_call.function.name
+    );
+    let tool_call_id = tool_call.id.clone();
+    let requested_at = Utc::now();
+    context
+        .conversation_context
+        .pending_tool_approvals
+        .push(ToolApprovalRequest {
+            tool_call,
+            requested_at,
+            expires_at: requested_at
+                + chrono::Duration::from_std(TOOL_APPROVAL_TIMEOUT).unwrap_or_default(),
+        });
+    context.conversation_context.state = ConversationState::AwaitingToolApproval;
+
+    // Unanswered requests are denied so the conversation can't hang forever
+    std::thread::spawn(move || {
+        std::thread::sleep(TOOL_APPROVAL_TIMEOUT);
+        if let Err(e) = Processor::process_event(Event::ActionToolApprovalTimeout(tool_call_id)) {
+            log::error!("Failed to process tool approval timeout event: {}", e);
+        }
+    });
+}
+
+fn call_tool_in_background(context: &AppStateContext, tool_call: ToolCall) {
+    let timeout = context
+        .mcp_context
+        .tool_timeout(&tool_call.function.client_name, &tool_call.function.name);
+    let cancellation = context.conversation_context.cancellation.clone();
+
+    // Every call gets its own thread, so calls from one assistant turn run concurrently
+    std::thread::spawn(move || {
+        // Tools without parameters may stream no arguments at all
+        let arguments = if tool_call.function.arguments.trim().is_empty() {
+            None
+        } else {
+            match serde_json::from_str(&tool_call.function.arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => {
+                    send_tool_call_error(tool_call.id, format!("Invalid tool arguments: {}", e));
+                    return;
+                }
+            }
+        };
+        let result = block_on(MCPProcessor::call_tool(
+            tool_call.function.client_name,
+            tool_call.function.name,
+            arguments,
+            timeout,
+            &cancellation,
+        ));
+        // The cancelled turn already closed its pending calls
+        if cancellation.is_cancelled() {
+            return;
+        }
+        match result {
+            Ok(result) => {
+                let (parts, is_error) = tool_result_content(&result);
+                send_tool_call_result(tool_call.id, parts, is_error);
+            }
+            Err(e) => send_tool_call_error(tool_call.id, format!("Error calling tool: {}", e)),
+        }
+    });
+}
+
+fn send_tool_call_error(tool_call_id: String, message: String) {
+    send_tool_call_result(
+        tool_call_id,
+        vec![ToolResultContent::Text { text: message }],
+        true,
+    );
+}
+
+fn send_tool_call_result(tool_call_id: String, parts: Vec<ToolResultContent>, is_error: bool) {
+    Processor::process_event(Event::ActionTransformationToolCallResult(ToolCallResult {
+        tool_call_id,
+        content: format_tool_result_for_model(&parts, is_error),
+        parts,
+        is_error,
+        created_at: Utc::now(),
+    }))
+    .expect("Failed to process transformation tool call result event");
+}
+
+// Tool messages only carry text, so other parts are described instead
+fn format_tool_result_for_model(parts: &[ToolResultContent], is_error: bool) -> String {
+    let text = parts
+        .iter()
+        .map(|part| match part {
+            ToolResultContent::Text { text } => text.clone(),
+            ToolResultContent::Image { mime_type, .. } => format!("[image: {}]", mime_type),
+            ToolResultContent::Resource {
+                text: Some(text), ..
+            } => text.clone(),
+            ToolResultContent::Resource { uri, .. } => format!("[resource: {}]", uri),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if is_error {
+        format!("Error: {}", text)
+    } else {
+        text
     }
 }
 
@@ -777,194 +1277,406 @@ fn get_persona_and_text_from_transcription(
     (persona.cloned(), text)
 }
 
-fn start_recording(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let sender = CONVERSATION_PROCESSOR
-        .lock()
-        .expect("Failed to lock conversation processor")
-        .audio_recorder_sender
-        .clone();
-    if sender.is_none() {
-        let (sender, receiver) = std::sync::mpsc::channel();
-        let mut conversation_processor = CONVERSATION_PROCESSOR
-            .lock()
-            .expect("Failed to lock conversation processor");
-        conversation_processor.audio_recorder_sender = Some(sender);
-        std::thread::spawn(move || {
-            let mut stream_option: Option<cpal::Stream> = None;
-            let mut output_ruhear_option: Option<qruhear::RUHear> = None;
-            let mut current_input_file: Option<PathBuf> = None;
-            let mut current_output_file: Option<PathBuf> = None;
-
-            while let Ok(command) = receiver.recv() {
-                match command {
-                    RecordingCommand::Start(file_path, input_device_name, record_output_audio) => {
-                        play_start_sound();
+#[derive(Clone, PartialEq)]
+struct PreRollSettings {
+    input_device_id: Option<String>,
+    duration_ms: u32,
+}
 
-                        stream_option.take().map(|stream| {
-                            drop(stream);
-                        });
-                        #[allow(unused_mut)]
-                        if let Some(mut ruhear) = output_ruhear_option.take() {
-                            let _ = ruhear.stop();
-                        }
+// Always-on input capture keeping the last moments of audio in memory
+struct PreRollCapture {
+    _stream: cpal::Stream,
+    buffer: Arc<Mutex<PreRollBuffer>>,
+    settings: PreRollSettings,
+}
 
-                        // Store the input file path
-                        current_input_file = Some(file_path.clone());
+impl PreRollCapture {
+    fn start(settings: &PreRollSettings) -> Result<Self, Box<dyn std::error::Error>> {
+        let input_device =
+            qspeak_audio_recording::get_device_by_id_or_default(settings.input_device_id.clone());
+        let buffer = Arc::new(Mutex::new(PreRollBuffer::new(settings.duration_ms)));
+        let stream = qspeak_audio_recording::build_pre_roll_stream(
+            &input_device,
+            &buffer,
+            |data| {
+                Processor::process_audio_data(data).expect("Failed to process audio data");
+            },
+            |err| {
+                log::error!("an error occurred on the pre-roll audio stream: {}", err);
+            },
+        )?;
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            buffer,
+            settings: settings.clone(),
+        })
+    }
 
-                        let input_device = qspeak_audio_recording::get_device_by_name_or_default(
-                            input_device_name,
-                        );
+    // Returns the number of buffered samples written ahead of the live audio
+    fn attach(&self, file_path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let wav_writer = build_wav_writer(file_path)?;
+        let flushed = self
+            .buffer
+            .lock()
+            .expect("Failed to lock pre-roll buffer")
+            .attach(wav_writer)?;
+        Ok(flushed)
+    }
 
-                        // Start input recording
-                        build_wav_writer(&file_path)
-                            .and_then(|wav_writer| {
-                                qspeak_audio_recording::build_stream(
-                                    &input_device,
-                                    &wav_writer,
-                                    |data| {
-                                        Processor::process_audio_data(data)
-                                            .expect("Failed to process audio data");
-                                    },
-                                    |err| {
-                                        log::error!(
-                                            "an error occurred on the input audio stream: {}",
-                                            err
-                                        );
-                                    },
-                                )
-                            })
-                            .and_then(|stream| {
-                                stream
-                                    .play()
-                                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
-                                    .map(|_| stream)
-                            })
-                            .map(|stream| {
-                                stream_option = Some(stream);
-                            })
-                            .expect("Failed to record input audio");
-
-                        // Start output recording if output device is specified
-                        if record_output_audio {
-                            // Create output file path with "_output" suffix
-                            let output_file_path = {
-                                let input_path = file_path.clone();
-                                let stem = input_path.file_stem().unwrap().to_string_lossy();
-                                let extension = input_path.extension().unwrap().to_string_lossy();
-                                input_path.with_file_name(format!("{}_output.{}", stem, extension))
-                            };
-
-                            // Store the output file path
-                            current_output_file = Some(output_file_path.clone());
-
-                            println!("Recording output file path: {:?}", output_file_path);
-
-                            // Use ruhear to capture system audio
-                            match qspeak_audio_recording::build_wav_writer(&output_file_path) {
-                                Ok(output_wav_writer) => {
-                                    match qspeak_audio_recording::build_system_output_stream(
-                                        &output_wav_writer,
-                                        |data| {
-                                            Processor::process_audio_data(data)
-                                                .expect("Failed to process audio data");
-                                        },
-                                    ) {
-                                        Ok(mut ruhear) => {
-                                            let _ = ruhear.start();
-                                            output_ruhear_option = Some(ruhear);
-                                            log::info!(
-                                                "Successfully started system audio recording using ruhear to: {:?}",
-                                                output_file_path
-                                            );
-                                        }
-                                        Err(e) => {
-                                            log::warn!(
-                                                "Failed to create system audio stream with ruhear: {}",
-                                                e
-                                            );
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    log::warn!(
-                                        "Failed to create WAV writer for system audio: {}",
-                                        e
-                                    );
-                                }
+    fn set_paused(&self, paused: bool) {
+        self.buffer
+            .lock()
+            .expect("Failed to lock pre-roll buffer")
+            .set_paused(paused);
+    }
+
+    fn detach(&self) {
+        self.buffer
+            .lock()
+            .expect("Failed to lock pre-roll buffer")
+            .detach();
+    }
+}
+
+fn start_pre_roll_capture(settings: &Option<PreRollSettings>) -> Option<PreRollCapture> {
+    let settings = settings.as_ref()?;
+    match PreRollCapture::start(settings) {
+        Ok(capture) => {
+            log::info!("Started pre-roll capture ({} ms)", settings.duration_ms);
+            Some(capture)
+        }
+        Err(e) => {
+            log::error!("Failed to start pre-roll capture: {}", e);
+            None
+        }
+    }
+}
+
+fn audio_recorder_sender() -> Sender<RecordingCommand> {
+    let mut conversation_processor = CONVERSATION_PROCESSOR
+        .lock()
+        .expect("Failed to lock conversation processor");
+    if let Some(sender) = &conversation_processor.audio_recorder_sender {
+        return sender.clone();
+    }
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    conversation_processor.audio_recorder_sender = Some(sender.clone());
+    std::thread::spawn(move || {
+        let mut stream_option: Option<cpal::Stream> = None;
+        let mut output_ruhear_option: Option<qruhear::RUHear> = None;
+        let mut current_input_file: Option<PathBuf> = None;
+        let mut current_output_file: Option<PathBuf> = None;
+        let mut current_input_device_id: Option<String> = None;
+        let mut current_record_output_audio = false;
+        // Shared by the input and output streams so pausing keeps both files aligned
+        let mut recording_flag = Arc::new(AtomicBool::new(true));
+        let mut pre_roll_settings: Option<PreRollSettings> = None;
+        let mut pre_roll_option: Option<PreRollCapture> = None;
+        // Set while the pre-roll capture is feeding the current recording
+        let mut recording_with_pre_roll = false;
+        let mut pre_roll_needs_restart = false;
+
+        while let Ok(command) = receiver.recv() {
+            match command {
+                RecordingCommand::Start(file_path, input_device_id, record_output_audio) => {
+                    play_start_sound();
+
+                    stream_option.take().map(|stream| {
+                        drop(stream);
+                    });
+                    #[allow(unused_mut)]
+                    if let Some(mut ruhear) = output_ruhear_option.take() {
+                        let _ = ruhear.stop();
+                    }
+
+                    // Store the input file path
+                    current_input_file = Some(file_path.clone());
+                    current_output_file = None;
+                    current_input_device_id = input_device_id.clone();
+                    current_record_output_audio = record_output_audio;
+                    recording_flag = Arc::new(AtomicBool::new(true));
+
+                    // Record from the pre-roll capture when it listens to the same device,
+                    // its buffered audio becomes the start of the recording
+                    let pre_roll_samples = pre_roll_option
+                        .as_ref()
+                        .filter(|pre_roll| pre_roll.settings.input_device_id == input_device_id)
+                        .and_then(|pre_roll| match pre_roll.attach(&file_path) {
+                            Ok(samples) => Some(samples),
+                            Err(e) => {
+                                log::error!("Failed to record from pre-roll capture: {}", e);
+                                None
                             }
-                        }
+                        });
+                    recording_with_pre_roll = pre_roll_samples.is_some();
+
+                    // Start input recording
+                    if !recording_with_pre_roll {
+                        stream_option = Some(
+                            start_input_stream(&file_path, input_device_id, recording_flag.clone())
+                                .expect("Failed to record input audio"),
+                        );
                     }
-                    RecordingCommand::Stop => {
-                        if let Some(stream) = stream_option {
-                            play_stop_sound();
-                            drop(stream);
-                        }
-                        #[allow(unused_mut)]
-                        if let Some(mut ruhear) = output_ruhear_option.take() {
-                            let _ = ruhear.stop();
+
+                    // Start output recording if output device is specified
+                    if record_output_audio {
+                        let (ruhear, output_file_path) = start_output_stream(
+                            &file_path,
+                            recording_flag.clone(),
+                            pre_roll_samples.unwrap_or(0),
+                        );
+                        output_ruhear_option = ruhear;
+                        current_output_file = Some(output_file_path);
+                    }
+                }
+                RecordingCommand::RotateChunk(file_path) => {
+                    if current_input_file.is_none() {
+                        continue;
+                    }
+
+                    // Start the next chunk before closing the previous one so nothing is lost in between
+                    if recording_with_pre_roll {
+                        let pre_roll = pre_roll_option
+                            .as_ref()
+                            .expect("Pre-roll capture is not running");
+                        if let Err(e) = pre_roll.attach(&file_path) {
+                            log::error!("Failed to start next meeting chunk: {}", e);
+                            continue;
                         }
+                    } else {
+                        let next_stream = match start_input_stream(
+                            &file_path,
+                            current_input_device_id.clone(),
+                            recording_flag.clone(),
+                        ) {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                log::error!("Failed to start next meeting chunk: {}", e);
+                                continue;
+                            }
+                        };
+                        stream_option.replace(next_stream).map(|stream| {
+                            drop(stream);
+                        });
+                    }
+                    #[allow(unused_mut)]
+                    if let Some(mut ruhear) = output_ruhear_option.take() {
+                        let _ = ruhear.stop();
+                    }
+
+                    let previous_input_file = current_input_file.replace(file_path.clone());
+                    let previous_output_file = current_output_file.take();
 
-                        // Combine audio files if both exist
-                        if let (Some(input_file), Some(output_file)) =
-                            (&current_input_file, &current_output_file)
+                    if current_record_output_audio {
+                        let (ruhear, output_file_path) =
+                            start_output_stream(&file_path, recording_flag.clone(), 0);
+                        output_ruhear_option = ruhear;
+                        current_output_file = Some(output_file_path);
+                    }
+
+                    if let (Some(input_file), Some(output_file)) =
+                        (&previous_input_file, &previous_output_file)
+                    {
+                        combine_recording_files(input_file, output_file);
+                    }
+
+                    if let Some(input_file) = previous_input_file {
+                        Processor::process_event(Event::ActionMeetingChunkRecorded(
+                            input_file.to_string_lossy().to_string(),
+                        ))
+                        .expect("Failed to process meeting chunk recorded event");
+                    }
+                }
+                RecordingCommand::Pause => {
+                    if current_input_file.is_some() {
+                        // The WAV writers stay open, the streams just stop writing samples
+                        recording_flag.store(false, Ordering::SeqCst);
+                        if let Some(stream) = &stream_option {
+                            let _ = stream.pause();
+                        }
+                        if let Some(pre_roll) =
+                            pre_roll_option.as_ref().filter(|_| recording_with_pre_roll)
                         {
-                            // Create combined file path with "_combined" suffix
-                            let combined_file_path = {
-                                let stem = input_file.file_stem().unwrap().to_string_lossy();
-                                let extension = input_file.extension().unwrap().to_string_lossy();
-                                input_file
-                                    .with_file_name(format!("{}_combined.{}", stem, extension))
-                            };
-
-                            match qspeak_audio_recording::combine_audio_files_with_echo_cancellation(
-                                input_file,
-                                output_file,
-                                &combined_file_path,
-                                qspeak_audio_recording::EchoCancellationMode::Advanced,
-                            ) {
-                                Ok(_) => {
-                                    log::info!(
-                                        "Successfully combined audio files into: {:?}",
-                                        combined_file_path
-                                    );
-                                }
-                                Err(e) => {
-                                    log::warn!("Failed to combine audio files: {}", e);
-                                }
-                            }
+                            pre_roll.set_paused(true);
                         }
-
-                        stream_option = None;
-                        output_ruhear_option = None;
-                        current_input_file = None;
-                        current_output_file = None;
+                        play_stop_sound();
                     }
-                    RecordingCommand::Cancel => {
-                        if let Some(stream) = stream_option {
-                            drop(stream);
+                }
+                RecordingCommand::Resume => {
+                    if current_input_file.is_some() {
+                        if let Some(stream) = &stream_option {
+                            if let Err(e) = stream.play() {
+                                log::error!("Failed to resume input audio stream: {}", e);
+                            }
                         }
-                        #[allow(unused_mut)]
-                        if let Some(mut ruhear) = output_ruhear_option.take() {
-                            let _ = ruhear.stop();
+                        if let Some(pre_roll) =
+                            pre_roll_option.as_ref().filter(|_| recording_with_pre_roll)
+                        {
+                            pre_roll.set_paused(false);
                         }
-                        stream_option = None;
-                        output_ruhear_option = None;
-                        current_input_file = None;
-                        current_output_file = None;
+                        recording_flag.store(true, Ordering::SeqCst);
+                        play_start_sound();
+                    }
+                }
+                RecordingCommand::Stop => {
+                    if let Some(stream) = stream_option {
+                        play_stop_sound();
+                        drop(stream);
+                    }
+                    if let Some(pre_roll) =
+                        pre_roll_option.as_ref().filter(|_| recording_with_pre_roll)
+                    {
+                        play_stop_sound();
+                        pre_roll.detach();
+                    }
+                    #[allow(unused_mut)]
+                    if let Some(mut ruhear) = output_ruhear_option.take() {
+                        let _ = ruhear.stop();
+                    }
+
+                    // Combine audio files if both exist
+                    if let (Some(input_file), Some(output_file)) =
+                        (&current_input_file, &current_output_file)
+                    {
+                        combine_recording_files(input_file, output_file);
+                    }
+
+                    stream_option = None;
+                    output_ruhear_option = None;
+                    current_input_file = None;
+                    current_output_file = None;
+                    recording_with_pre_roll = false;
+                    restart_pre_roll_if_outdated(
+                        &mut pre_roll_option,
+                        &pre_roll_settings,
+                        std::mem::take(&mut pre_roll_needs_restart),
+                    );
+                }
+                RecordingCommand::Cancel => {
+                    if let Some(stream) = stream_option {
+                        drop(stream);
+                    }
+                    if let Some(pre_roll) =
+                        pre_roll_option.as_ref().filter(|_| recording_with_pre_roll)
+                    {
+                        pre_roll.detach();
+                    }
+                    #[allow(unused_mut)]
+                    if let Some(mut ruhear) = output_ruhear_option.take() {
+                        let _ = ruhear.stop();
+                    }
+                    stream_option = None;
+                    output_ruhear_option = None;
+                    current_input_file = None;
+                    current_output_file = None;
+                    recording_with_pre_roll = false;
+                    restart_pre_roll_if_outdated(
+                        &mut pre_roll_option,
+                        &pre_roll_settings,
+                        std::mem::take(&mut pre_roll_needs_restart),
+                    );
+                }
+                RecordingCommand::ConfigurePreRoll(settings) => {
+                    pre_roll_settings = settings;
+                    // A capture feeding the recording is restarted once the recording ends
+                    if !recording_with_pre_roll {
+                        restart_pre_roll_if_outdated(
+                            &mut pre_roll_option,
+                            &pre_roll_settings,
+                            false,
+                        );
+                    }
+                }
+                RecordingCommand::InputDevicesChanged(device_ids, settings) => {
+                    let recording_device_missing = current_input_file.is_some()
+                        && current_input_device_id
+                            .as_ref()
+                            .is_some_and(|device_id| !device_ids.contains(device_id));
+                    if recording_device_missing {
+                        Processor::process_event(Event::ActionInputDeviceDisconnected)
+                            .expect("Failed to process input device disconnected event");
+                    }
+
+                    // Reopen the capture, its device may be the one that changed
+                    pre_roll_settings = settings;
+                    if recording_with_pre_roll {
+                        pre_roll_needs_restart = true;
+                    } else {
+                        restart_pre_roll_if_outdated(
+                            &mut pre_roll_option,
+                            &pre_roll_settings,
+                            true,
+                        );
                     }
                 }
             }
-        });
+        }
+    });
+
+    sender
+}
+
+fn restart_pre_roll_if_outdated(
+    pre_roll_option: &mut Option<PreRollCapture>,
+    settings: &Option<PreRollSettings>,
+    force: bool,
+) {
+    let outdated = match (pre_roll_option.as_ref(), settings) {
+        (Some(pre_roll), Some(settings)) => pre_roll.settings != *settings,
+        (None, None) => false,
+        _ => true,
+    };
+
+    if outdated || force {
+        *pre_roll_option = None;
+        *pre_roll_option = start_pre_roll_capture(settings);
     }
+}
+
+// Starts, restarts or stops the pre-roll capture to match the settings
+fn sync_pre_roll(input_device_id: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    audio_recorder_sender().send(RecordingCommand::ConfigurePreRoll(pre_roll_settings(
+        input_device_id,
+    )))?;
+    Ok(())
+}
 
+fn pre_roll_settings(input_device_id: Option<String>) -> Option<PreRollSettings> {
+    let conversation_context = AppState::get_context().conversation_context;
+    conversation_context
+        .pre_roll_enabled
+        .then(|| PreRollSettings {
+            input_device_id,
+            duration_ms: conversation_context.pre_roll_ms,
+        })
+}
+
+fn start_recording(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let app_context = AppState::get_context();
+    // Refused before anything is said, an agent asking for voice input runs no persona
+    let persona_check = app_context
+        .active_persona
+        .as_ref()
+        .filter(|_| !app_context.conversation_context.voice_input_requested)
+        .map_or(Ok(()), |persona| {
+            connectivity::check_persona(&app_context, persona)
+        });
+    if let Err(e) = persona_check {
+        return AppState::update(|context| {
+            context.errors.push(AppError::with_message(e));
+        });
+    }
+
     let record_output_audio = app_context
         .active_persona
         .clone()
         .map(|persona| persona.record_output_audio)
         .unwrap_or(false);
 
-    let input_device_name = app_context.input_device;
+    let input_device_id = app_context.input_device;
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
     let file_path = app_handle
         .path()
@@ -973,30 +1685,416 @@ fn start_recording(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Err
         .join("audio")
         .join(format!("recording_{}.wav", timestamp));
 
+    // Meetings are recorded in chunks, each transcribed as soon as it is closed
+    let meeting = app_context
+        .active_persona
+        .as_ref()
+        .filter(|persona| persona.meeting_mode)
+        .map(|_| MeetingSession::new(file_path.to_string_lossy().to_string()));
+    let file_path = meeting
+        .as_ref()
+        .and_then(|meeting| meeting.current_chunk_file_path())
+        .map(PathBuf::from)
+        .unwrap_or(file_path);
+
     println!("file_path: {:?}", file_path);
 
     AppState::update(|context| {
         context.conversation_context.current_audio_file_path =
             Some(file_path.to_string_lossy().to_string());
+        context.conversation_context.meeting = meeting.clone();
         context.conversation_context.state = ConversationState::Listening;
+        context.conversation_context.failed_step = None;
+        context.conversation_context.cancellation = CancellationToken::new();
     })?;
 
-    let sender = CONVERSATION_PROCESSOR
-        .lock()
-        .expect("Failed to lock conversation processor")
-        .audio_recorder_sender
-        .clone();
-    sender
-        .expect("Failed to send start command")
-        .send(RecordingCommand::Start(
-            file_path,
-            input_device_name,
-            record_output_audio,
-        ))?;
+    audio_recorder_sender().send(RecordingCommand::Start(
+        file_path,
+        input_device_id,
+        record_output_audio,
+    ))?;
+
+    if let Some(meeting) = meeting {
+        start_meeting_chunk_timer(
+            meeting.id,
+            app_context.conversation_context.meeting_chunk_minutes,
+        );
+    }
+
+    Ok(())
+}
+
+fn start_input_stream(
+    file_path: &Path,
+    input_device_id: Option<String>,
+    recording: Arc<AtomicBool>,
+) -> Result<cpal::Stream, Box<dyn std::error::Error>> {
+    let input_device = qspeak_audio_recording::get_device_by_id_or_default(input_device_id);
+
+    build_wav_writer(file_path)
+        .and_then(|wav_writer| {
+            qspeak_audio_recording::build_stream_with_recording_flag(
+                &input_device,
+                &wav_writer,
+                recording,
+                |data| {
+                    Processor::process_audio_data(data).expect("Failed to process audio data");
+                },
+                |err| {
+                    log::error!("an error occurred on the input audio stream: {}", err);
+                    if let cpal::StreamError::DeviceNotAvailable = err {
+                        Processor::process_event(Event::ActionInputDeviceDisconnected)
+                            .expect("Failed to process input device disconnected event");
+                    }
+                },
+            )
+        })
+        .and_then(|stream| {
+            stream
+                .play()
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
+                .map(|_| stream)
+        })
+}
+
+// Captures system audio next to the input file, with an "_output" suffix
+fn start_output_stream(
+    input_file_path: &Path,
+    recording: Arc<AtomicBool>,
+    leading_silence_samples: usize,
+) -> (Option<qruhear::RUHear>, PathBuf) {
+    let output_file_path = {
+        let stem = input_file_path.file_stem().unwrap().to_string_lossy();
+        let extension = input_file_path.extension().unwrap().to_string_lossy();
+        input_file_path.with_file_name(format!("{}_output.{}", stem, extension))
+    };
+
+    // Use ruhear to capture system audio
+    let ruhear = match qspeak_audio_recording::build_wav_writer(&output_file_path) {
+        Ok(output_wav_writer) => {
+            // Keep the system audio aligned with pre-roll audio at the start of the input file
+            if leading_silence_samples > 0 {
+                let mut writer_guard = output_wav_writer.lock().expect("Failed to lock writer");
+                for _ in 0..leading_silence_samples {
+                    if let Err(e) = writer_guard.write_sample(0.0f32) {
+                        log::warn!("Failed to pad system audio: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            match qspeak_audio_recording::build_output_stream_with_recording_flag(
+                &output_wav_writer,
+                recording,
+                |data| {
+                    Processor::process_audio_data(data).expect("Failed to process audio data");
+                },
+            ) {
+                Ok(mut ruhear) => {
+                    let _ = ruhear.start();
+                    log::info!(
+                        "Successfully started system audio recording using ruhear to: {:?}",
+                        output_file_path
+                    );
+                    Some(ruhear)
+                }
+                Err(e) => {
+                    log::warn!("Failed to create system audio stream with ruhear: {}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to create WAV writer for system audio: {}", e);
+            None
+        }
+    };
+
+    (ruhear, output_file_path)
+}
+
+fn combine_recording_files(input_file: &Path, output_file: &Path) {
+    // Create combined file path with "_combined" suffix
+    let combined_file_path = {
+        let stem = input_file.file_stem().unwrap().to_string_lossy();
+        let extension = input_file.extension().unwrap().to_string_lossy();
+        input_file.with_file_name(format!("{}_combined.{}", stem, extension))
+    };
+
+    match qspeak_audio_recording::combine_audio_files_with_echo_cancellation(
+        input_file,
+        output_file,
+        &combined_file_path,
+        qspeak_audio_recording::EchoCancellationMode::Advanced,
+    ) {
+        Ok(_) => {
+            log::info!(
+                "Successfully combined audio files into: {:?}",
+                combined_file_path
+            );
+        }
+        Err(e) => {
+            log::warn!("Failed to combine audio files: {}", e);
+        }
+    }
+}
+
+fn start_meeting_chunk_timer(meeting_id: String, chunk_minutes: u32) {
+    let chunk_duration = Duration::from_secs(chunk_minutes.max(1) as u64 * 60);
+
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(chunk_duration);
+
+            // Keep the processor locked so a stop can't slip in between registering the chunk and rotating
+            let conversation_processor = CONVERSATION_PROCESSOR
+                .lock()
+                .expect("Failed to lock conversation processor");
+
+            let mut is_active = false;
+            let mut next_file_path: Option<String> = None;
+            AppState::update(|context| {
+                let state = context.conversation_context.state.clone();
+                if let Some(meeting) = context.conversation_context.meeting.as_mut() {
+                    if meeting.id != meeting_id || meeting.stopping {
+                        return;
+                    }
+                    match state {
+                        ConversationState::Listening => {
+                            let file_path = meeting.add_chunk();
+                            context.conversation_context.current_audio_file_path =
+                                Some(file_path.clone());
+                            next_file_path = Some(file_path);
+                            is_active = true;
+                        }
+                        // Nothing new was recorded while paused, keep the current chunk
+                        ConversationState::Paused => is_active = true,
+                        _ => {}
+                    }
+                }
+            })
+            .ok();
+
+            if !is_active {
+                return;
+            }
+            let Some(next_file_path) = next_file_path else {
+                continue;
+            };
+
+            if let Some(sender) = conversation_processor.audio_recorder_sender.as_ref() {
+                sender
+                    .send(RecordingCommand::RotateChunk(PathBuf::from(next_file_path)))
+                    .ok();
+            }
+        }
+    });
+}
+
+fn stop_meeting_recording(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let mut last_chunk_file_path = None;
+    AppState::update(|context| {
+        context.conversation_context.state = ConversationState::Transcribing;
+        if let Some(meeting) = context.conversation_context.meeting.as_mut() {
+            meeting.stopping = true;
+            last_chunk_file_path = meeting.current_chunk_file_path();
+        }
+    })?;
+
+    stop_recording(app_handle)?;
+
+    if let Some(file_path) = last_chunk_file_path {
+        Processor::process_event(Event::ActionMeetingChunkRecorded(file_path))?;
+    }
 
     Ok(())
 }
 
+fn transcribe_meeting_chunk(file_path: String) {
+    let app_context = AppState::get_context();
+    let language = app_context.language.clone();
+    let model_id = app_context.transcription_model.clone();
+    let api_key = app_context.account_context.account.token.clone();
+    let cancellation = app_context.conversation_context.cancellation.clone();
+    let persona_name = app_context
+        .active_persona
+        .as_ref()
+        .map(|persona| persona.name.clone());
+    let record_output_audio = app_context
+        .active_persona
+        .map(|persona| persona.record_output_audio)
+        .unwrap_or(false);
+
+    std::thread::spawn(move || {
+        let Some(model_id) = model_id else {
+            Processor::process_event(Event::ActionMeetingChunkTranscriptionError(
+                file_path,
+                "Transcription model not picked. Please pick a model in the settings.".to_string(),
+            ))
+            .expect("Failed to process meeting chunk transcription error event");
+            return;
+        };
+        let model_id = match connectivity::transcription_models_for_mode(
+            &AppState::get_context(),
+            &model_id,
+        ) {
+            Ok((model_id, _)) => model_id,
+            Err(e) => {
+                Processor::process_event(Event::ActionMeetingChunkTranscriptionError(file_path, e))
+                    .expect("Failed to process meeting chunk transcription error event");
+                return;
+            }
+        };
+
+        let transcription_file_path = get_transcription_file_path(&file_path, record_output_audio);
+
+        let result = transcribe_audio_file(
+            transcription_file_path,
+            language,
+            model_id,
+            api_key,
+            persona_name,
+            &cancellation,
+        );
+        if cancellation.is_cancelled() {
+            return;
+        }
+        match result {
+            Ok(text) => {
+                Processor::process_event(Event::ActionMeetingChunkTranscriptionSuccess(
+                    file_path, text,
+                ))
+                .expect("Failed to process meeting chunk transcription success event");
+            }
+            Err(e) => {
+                Processor::process_event(Event::ActionMeetingChunkTranscriptionError(
+                    file_path,
+                    e.to_string(),
+                ))
+                .expect("Failed to process meeting chunk transcription error event");
+            }
+        }
+    });
+}
+
+fn finish_meeting_if_ready() -> Result<(), Box<dyn std::error::Error>> {
+    let app_context = AppState::get_context();
+    let meeting = match app_context.conversation_context.meeting.clone() {
+        Some(meeting) => meeting,
+        None => return Ok(()),
+    };
+
+    if !meeting.is_finished()
+        || app_context.conversation_context.state != ConversationState::Transcribing
+    {
+        return Ok(());
+    }
+
+    let transcript = meeting.transcript();
+    if transcript.is_empty() {
+        return AppState::update(|context| {
+            context
+                .reset_state_with_error(AppError::with_message(
+                    meeting.first_error().unwrap_or(
+                        "No speech was recognized in the meeting recording.".to_string(),
+                    ),
+                ))
+                .unwrap();
+        });
+    }
+
+    AppState::update(|context| {
+        context.conversation_context.meeting = None;
+        context.conversation_context.transcription_text = transcript.clone();
+    })?;
+
+    std::thread::spawn(move || {
+        let context = AppState::get_context();
+        let models = context
+            .conversation_model
+            .as_ref()
+            .filter(|_| context.active_persona.is_some())
+            .map(|active_model| connectivity::conversation_models_for_mode(&context, active_model))
+            .transpose();
+        let active_model = match models {
+            Ok(models) => models.map(|(active_model, _)| active_model),
+            Err(e) => {
+                Processor::process_event(Event::ActionTranscriptionError(e))
+                    .expect("Failed to process transcription error event");
+                return;
+            }
+        };
+        let model = active_model.and_then(|active_model| {
+            context
+                .models_context
+                .conversation_models
+                .iter()
+                .find(|model| model.model == active_model)
+                .cloned()
+        });
+
+        // Long meetings don't fit into a single request, so condense them first
+        let text = match model {
+            Some(model) if context.active_persona.is_some() => {
+                let context_length = model.context_length;
+                let mut config = OpenAIClientConfig::from_model_config(model.config);
+                config.usage_persona = context
+                    .active_persona
+                    .as_ref()
+                    .map(|persona| persona.name.clone());
+                let api_key = config.api_key.clone().or(context
+                    .account_context
+                    .account
+                    .token
+                    .clone());
+                block_on(condense_meeting_transcript(
+                    transcript,
+                    OpenAIClient::new(),
+                    config,
+                    api_key,
+                    context_length,
+                ))
+            }
+            _ => Ok(transcript),
+        };
+
+        match text {
+            Ok(text) => {
+                Processor::process_event(Event::ActionMeetingTranscriptReady(text))
+                    .expect("Failed to process meeting transcript ready event");
+            }
+            Err(e) => {
+                Processor::process_event(Event::ActionTranscriptionError(e.to_string()))
+                    .expect("Failed to process transcription error event");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// Stops the recording and transcribes what was captured
+fn finish_recording(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let app_context = AppState::get_context();
+    if app_context.conversation_context.meeting.is_some() {
+        return stop_meeting_recording(app_handle);
+    }
+
+    stop_recording(app_handle)?;
+
+    match start_transcription(app_handle) {
+        Ok(_) => Ok(()),
+        Err(_e) => AppState::update(|context| {
+            context
+                .reset_state_with_error(AppError::with_message(
+                    "Transcription model not picked. Please pick a model in the settings."
+                        .to_string(),
+                ))
+                .unwrap();
+        }),
+    }
+}
+
 fn stop_recording(_app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let sender = CONVERSATION_PROCESSOR
         .lock()
@@ -1020,8 +2118,48 @@ fn cancel_recording(_app_handle: &AppHandle) -> Result<(), Box<dyn std::error::E
         .expect("Failed to send cancel command")
         .send(RecordingCommand::Cancel)?;
     play_cancel_sound();
+    if AppState::get_context()
+        .conversation_context
+        .voice_input_requested
+    {
+        Processor::process_event(Event::ActionVoiceInputError(
+            "The user cancelled the recording".to_string(),
+        ))?;
+    }
     AppState::update(|context| {
         context.conversation_context.state = ConversationState::Idle;
+        context.conversation_context.meeting = None;
+        context.conversation_context.voice_input_requested = false;
+    })?;
+    Ok(())
+}
+
+fn pause_recording() -> Result<(), Box<dyn std::error::Error>> {
+    let sender = CONVERSATION_PROCESSOR
+        .lock()
+        .expect("Failed to lock conversation processor")
+        .audio_recorder_sender
+        .clone();
+    sender
+        .expect("Failed to send pause command")
+        .send(RecordingCommand::Pause)?;
+    AppState::update(|context| {
+        context.conversation_context.state = ConversationState::Paused;
+    })?;
+    Ok(())
+}
+
+fn resume_recording() -> Result<(), Box<dyn std::error::Error>> {
+    let sender = CONVERSATION_PROCESSOR
+        .lock()
+        .expect("Failed to lock conversation processor")
+        .audio_recorder_sender
+        .clone();
+    sender
+        .expect("Failed to send resume command")
+        .send(RecordingCommand::Resume)?;
+    AppState::update(|context| {
+        context.conversation_context.state = ConversationState::Listening;
     })?;
     Ok(())
 }
@@ -1054,63 +2192,47 @@ fn start_transcription(_app_handle: &AppHandle) -> Result<(), ConversationError>
     })
     .unwrap();
 
+    let models = connectivity::transcription_models_for_mode(&app_context, &model_id);
+    let persona_name = app_context
+        .active_persona
+        .as_ref()
+        .map(|persona| persona.name.clone());
+    let cancellation = app_context.conversation_context.cancellation.clone();
     std::thread::spawn(move || {
-        let context = AppState::get_context();
-        let api_key = context.account_context.account.token.clone();
-        
-        // Find the transcription model to determine its provider
-        let model = context
-            .models_context
-            .transcription_models
-            .iter()
-            .find(|m| m.model == model_id)
-            .expect("Transcription model not found");
-        
-        // Dispatch based on provider type
-        match model.provider {
-            TranscriptionProvider::OpenAI => {
-                match block_on(transcribe_audio_with_openai_api(
-                    transcription_file_path,
-                    language,
-                    api_key,
-                )) {
-                    Ok(text) => {
-                        Processor::process_event(Event::ActionTranscriptionSuccess(text))
-                            .expect("Failed to process transcription success event");
-                    }
-                    Err(e) => {
-                        Processor::process_event(Event::ActionTranscriptionError(e.to_string()))
-                            .expect("Failed to process transcription error event");
-                    }
-                }
+        let (model_id, fallback_model_id) = match models {
+            Ok(models) => models,
+            Err(e) => {
+                Processor::process_event(Event::ActionTranscriptionError(e))
+                    .expect("Failed to process transcription error event");
+                return;
             }
-            TranscriptionProvider::Mistral => {
-                match block_on(transcribe_audio_with_mistral_api(
-                    transcription_file_path,
-                    language,
-                    api_key,
-                )) {
-                    Ok(text) => {
-                        Processor::process_event(Event::ActionTranscriptionSuccess(text))
-                            .expect("Failed to process transcription success event");
-                    }
-                    Err(e) => {
-                        Processor::process_event(Event::ActionTranscriptionError(e.to_string()))
-                            .expect("Failed to process transcription error event");
-                    }
-                }
+        };
+        let api_key = AppState::get_context()
+            .account_context
+            .account
+            .token
+            .clone();
+
+        let result = transcribe_with_fallback(
+            transcription_file_path,
+            language,
+            model_id,
+            fallback_model_id,
+            api_key,
+            persona_name,
+            &cancellation,
+        );
+        if cancellation.is_cancelled() {
+            return;
+        }
+        match result {
+            Ok(text) => {
+                Processor::process_event(Event::ActionTranscriptionSuccess(text))
+                    .expect("Failed to process transcription success event");
             }
-            TranscriptionProvider::WhisperLocal => {
-                match transcribe_audio_with_local_model(transcription_file_path, language, model_id) {
-                    Ok(text) => {
-                        Processor::process_event(Event::ActionTranscriptionSuccess(text))
-                            .expect("Failed to process transcription success event");
-                    }
-                    Err(e) => {
-                        Processor::process_event(Event::ActionTranscriptionError(e.to_string()))
-                            .expect("Failed to process transcription error event");
-                    }
-                }
+            Err(e) => {
+                Processor::process_event(Event::ActionTranscriptionError(e.to_string()))
+                    .expect("Failed to process transcription error event");
             }
         }
     });
@@ -1118,6 +2240,126 @@ fn start_transcription(_app_handle: &AppHandle) -> Result<(), ConversationError>
     Ok(())
 }
 
+// Transcribes a file with the picked model and language, outside of the conversation
+pub fn transcribe_file(file_path: String) -> Result<String, Box<dyn std::error::Error>> {
+    let app_context = AppState::get_context();
+    let model_id = app_context
+        .transcription_model
+        .clone()
+        .ok_or("Transcription model not picked. Please pick a model in the settings.")?;
+    let (model_id, fallback_model_id) =
+        connectivity::transcription_models_for_mode(&app_context, &model_id)?;
+    let api_key = app_context.account_context.account.token.clone();
+
+    transcribe_with_fallback(
+        file_path,
+        app_context.language.clone(),
+        model_id,
+        fallback_model_id,
+        api_key,
+        None,
+        &CancellationToken::new(),
+    )
+}
+
+// Gives the recording to the fallback model when the picked one fails, e.g. cloud to local whisper
+fn transcribe_with_fallback(
+    file_path: String,
+    language: Language,
+    model_id: String,
+    fallback_model_id: Option<String>,
+    api_key: Option<String>,
+    persona_name: Option<String>,
+    cancellation: &CancellationToken,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let result = transcribe_audio_file(
+        file_path.clone(),
+        language.clone(),
+        model_id.clone(),
+        api_key.clone(),
+        persona_name.clone(),
+        cancellation,
+    );
+    let Err(e) = result else {
+        return result;
+    };
+    if cancellation.is_cancelled() {
+        return Err(e);
+    }
+
+    // Only models that can run right away, a local one that isn't downloaded would fail as well
+    let fallback_model = fallback_model_id
+        .filter(|fallback_model_id| *fallback_model_id != model_id)
+        .and_then(|fallback_model_id| {
+            AppState::get_context()
+                .models_context
+                .transcription_models
+                .into_iter()
+                .find(|model| model.model == fallback_model_id)
+        })
+        .filter(|model| matches!(model.download_state, DownloadState::Downloaded));
+    let Some(fallback_model) = fallback_model else {
+        return Err(e);
+    };
+
+    log::warn!(
+        "Transcription with {} failed: {}, falling back to {}",
+        model_id,
+        e,
+        fallback_model.model
+    );
+    transcribe_audio_file(
+        file_path,
+        language,
+        fallback_model.model,
+        api_key,
+        persona_name,
+        cancellation,
+    )
+}
+
+fn transcribe_audio_file(
+    file_path: String,
+    language: Language,
+    model_id: String,
+    api_key: Option<String>,
+    persona_name: Option<String>,
+    cancellation: &CancellationToken,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // Find the transcription model to determine its provider
+    let model = AppState::get_context()
+        .models_context
+        .transcription_models
+        .iter()
+        .find(|m| m.model == model_id)
+        .cloned()
+        .expect("Transcription model not found");
+
+    let audio_seconds = audio_duration_seconds(&file_path);
+
+    // Dispatch based on provider type
+    let text = match model.provider {
+        TranscriptionProvider::OpenAI => block_on(cancellation.run(
+            transcribe_audio_with_openai_api(file_path, language, api_key),
+        ))?,
+        TranscriptionProvider::Mistral => block_on(cancellation.run(
+            transcribe_audio_with_mistral_api(file_path, language, api_key),
+        ))?,
+        TranscriptionProvider::WhisperLocal => {
+            transcribe_audio_with_local_model(file_path, language, model_id.clone(), cancellation)
+        }
+    }?;
+    usage::record_audio(&model_id, persona_name.as_deref(), audio_seconds);
+    Ok(text)
+}
+
+// Length of a recording, 0 when it can't be read
+fn audio_duration_seconds(file_path: &str) -> f64 {
+    hound::WavReader::open(file_path)
+        .map(|reader| reader.duration() as f64 / reader.spec().sample_rate as f64)
+        .unwrap_or_default()
+}
+
 // Helper function to determine which file to use for transcription
 fn get_transcription_file_path(input_file_path: &str, should_wait_for_combined: bool) -> String {
     use std::path::Path;
@@ -1173,6 +2415,7 @@ fn transcribe_audio_with_local_model(
     file_path: String,
     language: Language,
     model_id: String,
+    cancellation: &CancellationToken,
 ) -> Result<String, Box<dyn std::error::Error>> {
     println!("Local transcription using file: {}", file_path);
 
@@ -1223,10 +2466,16 @@ fn transcribe_audio_with_local_model(
     params.set_print_progress(false);
     params.set_print_realtime(false);
 
+    // Whisper checks this between its decoding steps and stops when it returns true
+    let abort_cancellation = cancellation.clone();
+    params.set_abort_callback_safe(move || abort_cancellation.is_cancelled());
+
     let mut state = ctx.create_state().expect("Failed to create whisper state");
-    state
-        .full(params, &audio_data)
-        .expect("Failed to transcribe audio");
+    let result = state.full(params, &audio_data);
+    if cancellation.is_cancelled() {
+        return Err(Box::new(Cancelled));
+    }
+    result.map_err(|e| format!("Failed to transcribe audio: {}", e))?;
 
     let num_segments = state
         .full_n_segments()
@@ -1255,24 +2504,6 @@ async fn transcribe_audio_with_openai_api(
     // Wait for file to be ready and validate it
     let file_bytes = std::fs::read(&file_path)?;
 
-    // Create a form part for the file
-    let file_part = reqwest::multipart::Part::bytes(file_bytes)
-        .file_name(file_path)
-        .mime_str("audio/wav")?;
-
-    // Create a multipart form with the file and model parameters
-    let form = reqwest::multipart::Form::new()
-        .part("file", file_part)
-        .text("model", "whisper-1")
-        .text("response_format", "json");
-
-    // Add language if specified
-    let form = if !language.to_str().is_empty() && language.to_str() != "auto" {
-        form.text("language", language.to_str().to_string())
-    } else {
-        form
-    };
-
     let app_context = AppState::get_context();
     let initial_prompt = "Glossary: ".to_string()
         + &app_context
@@ -1281,18 +2512,39 @@ async fn transcribe_audio_with_openai_api(
             .clone()
             .join(", ");
 
-    let form = form.text("prompt", initial_prompt);
+    // The form is built for every attempt, it can't be reused once sent
+    let build_form = || {
+        // Create a form part for the file
+        let file_part = reqwest::multipart::Part::bytes(file_bytes.clone())
+            .file_name(file_path.clone())
+            .mime_str("audio/wav")
+            .expect("Failed to set audio mime type");
+
+        // Create a multipart form with the file and model parameters
+        let form = reqwest::multipart::Form::new()
+            .part("file", file_part)
+            .text("model", "whisper-1")
+            .text("response_format", "json");
+
+        // Add language if specified
+        let form = if !language.to_str().is_empty() && language.to_str() != "auto" {
+            form.text("language", language.to_str().to_string())
+        } else {
+            form
+        };
+
+        form.text("prompt", initial_prompt.clone())
+    };
 
     // Send the request
-    let response = client
-        .post(format!("{}/audio/transcriptions", QSPEAK_API_V1_URL))
-        .header(
-            "Authorization",
-            format!("Bearer {}", api_key.unwrap_or_default()),
-        )
-        .multipart(form)
-        .send()
-        .await?;
+    let authorization = format!("Bearer {}", api_key.unwrap_or_default());
+    let response = send_with_retry(|| {
+        client
+            .post(format!("{}/audio/transcriptions", QSPEAK_API_V1_URL))
+            .header("Authorization", &authorization)
+            .multipart(build_form())
+    })
+    .await?;
 
     if !response.status().is_success() {
         let error_text = response.text().await?;
@@ -1326,16 +2578,20 @@ async fn transcribe_audio_with_mistral_api(
     let file_bytes = wait_for_file_ready(&file_path)?;
     #[cfg(not(target_os = "macos"))]
     let file_bytes = std::fs::read(&file_path)?;
-    
-    // Create a form part for the file
-    let file_part = reqwest::multipart::Part::bytes(file_bytes)
-        .file_name(file_path.clone())
-        .mime_str("audio/wav")?;
-
-    // Create a multipart form with the file and model parameters
-    let form = reqwest::multipart::Form::new()
-        .part("file", file_part)
-        .text("model", "voxtral-mini-2507");
+
+    // The form is built for every attempt, it can't be reused once sent
+    let build_form = || {
+        // Create a form part for the file
+        let file_part = reqwest::multipart::Part::bytes(file_bytes.clone())
+            .file_name(file_path.clone())
+            .mime_str("audio/wav")
+            .expect("Failed to set audio mime type");
+
+        // Create a multipart form with the file and model parameters
+        reqwest::multipart::Form::new()
+            .part("file", file_part)
+            .text("model", "voxtral-mini-2507")
+    };
     // .text("response_format", "json");
 
     // Add language if specified
@@ -1356,17 +2612,15 @@ async fn transcribe_audio_with_mistral_api(
     // let form = form.text("prompt", initial_prompt);
 
     // Send the request
-    let request = client
-        .post(format!("{}/audio/transcriptions", QSPEAK_API_V1_URL))
-        .header(
-            "Authorization",
-            format!("Bearer {}", api_key.unwrap_or_default()),
-        )
-        .header("x-provider", "use_mistral")
-        .multipart(form);
-
-    
-    let response = request.send().await?;
+    let authorization = format!("Bearer {}", api_key.unwrap_or_default());
+    let response = send_with_retry(|| {
+        client
+            .post(format!("{}/audio/transcriptions", QSPEAK_API_V1_URL))
+            .header("Authorization", &authorization)
+            .header("x-provider", "use_mistral")
+            .multipart(build_form())
+    })
+    .await?;
 
     if !response.status().is_success() {
         let error_text = response.text().await?;
@@ -1395,6 +2649,10 @@ fn start_transformation(
 ) -> Result<(), Box<dyn std::error::Error>> {
     AppState::update(|context| {
         context.conversation_context.pending_tool_call_ids.clear();
+        context.conversation_context.tool_iterations = 0;
+        context.conversation_context.structured_output_retries = 0;
+        context.conversation_context.failed_step = None;
+        context.conversation_context.cancellation = CancellationToken::new();
 
         let active_persona = context.active_persona.clone();
         let active_model = context.conversation_model.clone();
@@ -1408,33 +2666,7 @@ fn start_transformation(
 
         context.conversation_context.transcription_text = text.clone();
 
-        if active_persona.is_some()
-            && !context
-                .conversation_context
-                .conversation
-                .iter()
-                .any(|message| match message {
-                    ConversationMessage::ConversationTextMessage(text_message) => {
-                        text_message.role == "system"
-                    }
-                    _ => false,
-                })
-        {
-            context.conversation_context.conversation.insert(
-                0,
-                ConversationMessage::ConversationTextMessage(ConversationTextMessage {
-                    audio_file_path: None,
-                    role: "system".to_string(),
-                    content: vec![ChatCompletionMessageContent::Text {
-                        text: active_persona
-                            .expect("Failed to get active persona")
-                            .system_prompt
-                            .clone(),
-                    }],
-                    created_at: Utc::now(),
-                }),
-            );
-        }
+        insert_system_prompt(context);
 
         context.conversation_context.conversation.push(
             ConversationMessage::ConversationTextMessage(ConversationTextMessage {
@@ -1450,6 +2682,11 @@ fn start_transformation(
         );
     })?;
 
+    run_transformation()
+}
+
+// Sends the current conversation to the model, then to the fallback model if the request fails
+fn run_transformation() -> Result<(), Box<dyn std::error::Error>> {
     let app_context = AppState::get_context();
     if app_context.conversation_context.state == ConversationState::Idle {
         return Ok(());
@@ -1459,17 +2696,26 @@ fn start_transformation(
         .conversation_model
         .clone()
         .expect("Failed to get conversation model");
+    let persona_check = app_context
+        .active_persona
+        .as_ref()
+        .map_or(Ok(()), |persona| {
+            connectivity::check_persona(&app_context, persona)
+        });
+    let models = persona_check
+        .and_then(|_| connectivity::conversation_models_for_mode(&app_context, &active_model));
+    let (active_model, fallback_model) = match models {
+        Ok(models) => models,
+        Err(e) => return Processor::process_event(Event::ActionTransformationError(e)),
+    };
     let conversation = app_context.conversation_context.conversation.clone();
+    let persona_tools = persona_tools(&app_context);
+    let persona = app_context.active_persona.clone();
+    let cancellation = app_context.conversation_context.cancellation.clone();
 
     std::thread::spawn(move || {
-        let model = AppState::get_context()
-            .models_context
-            .conversation_models
-            .iter()
-            .find(|model| model.model == active_model)
-            .cloned();
-
-        if model.is_none() {
+        let models = conversation_models_with_fallback(&active_model, fallback_model);
+        if models.is_empty() {
             AppState::update(|context| {
                 context.errors.push(AppError::with_message(
                     "Transformation model not found. Please pick a model in the settings."
@@ -1480,30 +2726,30 @@ fn start_transformation(
             return;
         }
 
-        let model = model.unwrap();
-
-        let openai_client = OpenAIClient::new();
-        let config = OpenAIClientConfig::from_model_config(model.config);
-        let config_clone = config.clone();
-
-        let api_key = match config.api_key {
-            Some(api_key) => api_key,
-            None => AppState::get_context()
-                .account_context
-                .account
-                .token
-                .clone()
-                .unwrap_or_default(),
-        };
-
-        let messages = convert_conversation_to_chat_completion_messages(&conversation);
+        let mut result = Ok(());
+        for (index, model) in models.into_iter().enumerate() {
+            if index > 0 {
+                log::warn!("Falling back to conversation model {}", model.model);
+            }
+            result = transform_with_model(
+                model,
+                conversation.clone(),
+                persona.clone(),
+                persona_tools.clone(),
+                &cancellation,
+            );
+            // Only a request that got no answer at all can go to another model
+            let request_failed = result.as_ref().is_err_and(|e| e.is::<ModelRequestError>());
+            if !request_failed || cancellation.is_cancelled() {
+                break;
+            }
+        }
 
-        match block_on(transform_with_openai(
-            messages,
-            openai_client,
-            config_clone,
-            Some(api_key),
-        )) {
+        // The cancelled turn is already idle, a new one may have started since
+        if cancellation.is_cancelled() {
+            return;
+        }
+        match result {
             Ok(_) => {
                 Processor::process_event(Event::ActionTransformationSuccess())
                     .expect("Failed to process transformation success event");
@@ -1517,15 +2763,342 @@ fn start_transformation(
     Ok(())
 }
 
+// Local fallbacks are left out, only the model the local server was started with can answer
+fn conversation_models_with_fallback(
+    active_model: &str,
+    fallback_model: Option<String>,
+) -> Vec<ConversationModel> {
+    let conversation_models = AppState::get_context().models_context.conversation_models;
+    let find_model = |id: &str| {
+        conversation_models
+            .iter()
+            .find(|model| model.model == id)
+            .cloned()
+    };
+
+    let mut models: Vec<ConversationModel> = find_model(active_model).into_iter().collect();
+    if let Some(fallback) = fallback_model
+        .filter(|fallback| fallback != active_model)
+        .and_then(|fallback| find_model(&fallback))
+        .filter(|fallback| !fallback.is_local)
+    {
+        models.push(fallback);
+    }
+    models
+}
+
+fn transform_with_model(
+    model: ConversationModel,
+    conversation: Vec<ConversationMessage>,
+    persona: Option<Persona>,
+    persona_tools: PersonaTools,
+    cancellation: &CancellationToken,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let openai_client = OpenAIClient::new();
+    let mut config = OpenAIClientConfig::from_model_config(model.config);
+    // Summaries of older messages are plain text, the persona's output settings don't apply to them
+    let mut summary_config = config.clone();
+    if let Some(persona) = persona {
+        summary_config.usage_persona = Some(persona.name.clone());
+        config.usage_persona = Some(persona.name);
+        config.generation = persona.generation;
+        config.response_format = persona.structured_output.map(|structured_output| {
+            json!({
+                "type": "json_schema",
+                "json_schema": { "name": "persona_output", "schema": structured_output.schema },
+            })
+        });
+    }
+
+    let api_key = match config.api_key.clone() {
+        Some(api_key) => api_key,
+        None => AppState::get_context()
+            .account_context
+            .account
+            .token
+            .clone()
+            .unwrap_or_default(),
+    };
+
+    let messages = fit_conversation_to_model(
+        conversation,
+        model.context_length,
+        SummaryModel {
+            openai_client: &openai_client,
+            config: summary_config,
+            api_key: Some(api_key.clone()),
+        },
+        cancellation,
+    )?;
+
+    block_on(transform_with_openai(
+        messages,
+        openai_client,
+        config,
+        Some(api_key),
+        persona_tools,
+        cancellation.clone(),
+    ))
+}
+
+// Structured output personas answer with JSON, it is checked against the schema before being used
+fn finish_structured_output(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let app_context = AppState::get_context();
+    let Some(persona) = app_context.active_persona.clone() else {
+        return Ok(());
+    };
+    let Some(structured_output) = persona.structured_output.clone() else {
+        return Ok(());
+    };
+
+    let answer = match app_context.conversation_context.conversation.last() {
+        Some(ConversationMessage::ConversationTextMessage(message))
+            if message.role == "assistant" =>
+        {
+            match message.content.last() {
+                Some(ChatCompletionMessageContent::Text { text }) => text.clone(),
+                _ => String::new(),
+            }
+        }
+        _ => String::new(),
+    };
+    let result = structured_output::parse_output(&answer).and_then(|value| {
+        structured_output::validate(&structured_output.schema, &value)?;
+        Ok(value)
+    });
+
+    match result {
+        Ok(value) => {
+            let output = structured_output::format_output(&value, &structured_output.template);
+            AppState::update(|context| {
+                context.conversation_context.state = ConversationState::Idle;
+                if persona.paste_on_finish {
+                    set_text_in_clipboard_and_paste(&output, app_handle);
+                }
+                if let Err(e) = set_text_in_clipboard(&output) {
+                    context.errors.push(AppError::with_message(e.to_string()));
+                }
+
+                Processor::process_event(Event::ActionUpdateOrCreateHistory(
+                    context.active_persona.clone(),
+                    context.conversation_context.conversation.clone(),
+                ))
+                .ok();
+            })?;
+
+            if let Some(webhook_url) = structured_output
+                .webhook_url
+                .filter(|webhook_url| !webhook_url.trim().is_empty())
+            {
+                std::thread::spawn(move || {
+                    if let Err(e) = block_on(send_to_webhook(&webhook_url, &value)) {
+                        log::error!("Failed to send structured output to webhook: {}", e);
+                        AppState::add_error(AppError::with_message(e.to_string()))
+                            .expect("Failed to update app state");
+                    }
+                });
+            }
+            Ok(())
+        }
+        Err(e)
+            if app_context.conversation_context.structured_output_retries
+                < MAX_STRUCTURED_OUTPUT_RETRIES =>
+        {
+            log::warn!(
+                "Structured output did not match the schema, asking again: {}",
+                e
+            );
+            AppState::update(|context| {
+                context.conversation_context.structured_output_retries += 1;
+                context.conversation_context.conversation.push(
+                    ConversationMessage::ConversationTextMessage(ConversationTextMessage {
+                        audio_file_path: None,
+                        role: "user".to_string(),
+                        content: vec![ChatCompletionMessageContent::Text {
+                            text: format!(
+                                "Your answer does not match the required JSON schema: {}. Respond again with only the corrected JSON.",
+                                e
+                            ),
+                        }],
+                        created_at: Utc::now(),
+                    }),
+                );
+            })?;
+            run_transformation()
+        }
+        Err(e) => AppState::update(|context| {
+            context
+                .reset_state_with_error(AppError::with_message(format!(
+                    "The answer does not match the persona's JSON schema: {}",
+                    e
+                )))
+                .unwrap();
+        }),
+    }
+}
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn send_to_webhook(
+    webhook_url: &str,
+    value: &serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let response = reqwest::Client::new()
+        .post(webhook_url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(value)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send to webhook: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to send to webhook: {}", response.status()).into());
+    }
+    Ok(())
+}
+
+fn insert_system_prompt(context: &mut AppStateContext) {
+    let Some(active_persona) = context.active_persona.as_ref() else {
+        return;
+    };
+    let has_system_prompt = context
+        .conversation_context
+        .conversation
+        .iter()
+        .any(|message| match message {
+            ConversationMessage::ConversationTextMessage(text_message) => {
+                text_message.role == "system"
+            }
+            _ => false,
+        });
+    if has_system_prompt {
+        return;
+    }
+
+    context.conversation_context.conversation.insert(
+        0,
+        ConversationMessage::ConversationTextMessage(ConversationTextMessage {
+            audio_file_path: None,
+            role: "system".to_string(),
+            content: vec![ChatCompletionMessageContent::Text {
+                text: active_persona.system_prompt.clone(),
+            }],
+            created_at: Utc::now(),
+        }),
+    );
+}
+
+// Runs the step that failed last again, with the same recording or conversation
+fn retry_failed_step(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(failed_step) = AppState::get_context().conversation_context.failed_step else {
+        return Ok(());
+    };
+
+    match failed_step {
+        FailedStep::Transcription { audio_file_path } => {
+            if !Path::new(&audio_file_path).exists() {
+                return AppState::update(|context| {
+                    context.conversation_context.failed_step = None;
+                    context.errors.push(AppError::with_message(
+                        "The recording is no longer available.".to_string(),
+                    ));
+                });
+            }
+
+            AppState::update(|context| {
+                context.conversation_context.failed_step = None;
+                context.conversation_context.current_audio_file_path = Some(audio_file_path);
+                context.conversation_context.cancellation = CancellationToken::new();
+            })?;
+            match start_transcription(app_handle) {
+                Ok(_) => Ok(()),
+                Err(_e) => AppState::update(|context| {
+                    context
+                        .reset_state_with_error(AppError::with_message(
+                            "Transcription model not picked. Please pick a model in the settings."
+                                .to_string(),
+                        ))
+                        .unwrap();
+                }),
+            }
+        }
+        FailedStep::Transformation => {
+            let app_context = AppState::get_context();
+            if app_context.active_persona.is_none() || app_context.conversation_model.is_none() {
+                return AppState::update(|context| {
+                    context.errors.push(AppError::with_message(
+                        "Pick a persona and a conversation model to retry.".to_string(),
+                    ));
+                });
+            }
+
+            AppState::update(|context| {
+                let conversation = &mut context.conversation_context.conversation;
+                // What the model answered before failing is asked for again
+                while let Some(ConversationMessage::ConversationTextMessage(message)) =
+                    conversation.last()
+                {
+                    if message.role != "assistant" {
+                        break;
+                    }
+                    conversation.pop();
+                }
+                context.conversation_context.failed_step = None;
+                context.conversation_context.pending_tool_call_ids.clear();
+                context.conversation_context.tool_iterations = 0;
+                context.conversation_context.structured_output_retries = 0;
+                context.conversation_context.cancellation = CancellationToken::new();
+                context.conversation_context.state = ConversationState::Transforming;
+            })?;
+            run_transformation()
+        }
+    }
+}
+
+// Keeps the first messages, what comes next continues as a branch of the current history entry
+fn branch_conversation(context: &mut AppStateContext, keep: usize) {
+    context.conversation_context.conversation.truncate(keep);
+    // History entries are saved without the system prompt, count the messages the same way
+    let message_count = context
+        .conversation_context
+        .conversation
+        .iter()
+        .filter(|message| {
+            !matches!(message, ConversationMessage::ConversationTextMessage(text_message) if text_message.role == "system")
+        })
+        .count();
+
+    let history_context = &mut context.history_context;
+    if let Some(id) = history_context.current_history_id.take() {
+        history_context.branch_parent = Some(HistoryParent { id, message_count });
+    } else if let Some(parent) = history_context.branch_parent.as_mut() {
+        parent.message_count = parent.message_count.min(message_count);
+    }
+}
+
+fn persona_tools(context: &AppStateContext) -> PersonaTools {
+    context
+        .active_persona
+        .as_ref()
+        .map(|persona| persona.tools.clone())
+        .unwrap_or_default()
+}
+
 async fn transform_with_openai(
     conversation: Vec<ChatCompletionMessage>,
     openai_client: OpenAIClient,
     config: OpenAIClientConfig,
     api_key: Option<String>,
+    persona_tools: PersonaTools,
+    cancellation: CancellationToken,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let tools = MCPProcessor::list_all_tools().await?;
+    let tools = match persona_tools {
+        PersonaTools::Disabled => vec![],
+        _ => MCPProcessor::list_all_tools().await?,
+    };
     let tools: Vec<serde_json::Value> = tools
         .into_iter()
+        .filter(|tool| persona_tools.allows(&tool.name, &tool.tool.name))
         .map(|tool| {
             serde_json::json!({
                 "type": "function",
@@ -1537,17 +3110,14 @@ async fn transform_with_openai(
             })
         })
         .collect();
+    // Some APIs reject an empty tools array
+    let tools = (!tools.is_empty()).then_some(tools);
 
     let message_stream = openai_client
-        .chat_completion(conversation, Some(tools), config, api_key)
+        .chat_completion(conversation, tools, config, api_key, cancellation)
         .await
-        .map_err(|e| {
-            log::error!("Error: {}", e);
-            println!("Error: {}", e);
-            Processor::process_event(Event::ActionTransformationError(e.to_string()))
-                .expect("Failed to process transformation error event");
-        })
-        .unwrap();
+        .inspect_err(|e| log::error!("Error: {}", e))
+        .map_err(|e| ModelRequestError(e.to_string()))?;
     pin_mut!(message_stream);
 
     while let Some(message) = message_stream.next().await {
@@ -1566,6 +3136,8 @@ async fn transform_with_openai(
                 Processor::process_event(Event::ActionTransformationToolCall(tool_call))
                     .expect("Failed to process transformation tool call event");
             }
+            // Already counted by the client
+            ChunkMessage::Usage(_) => {}
         }
     }
     Ok(())
@@ -5,9 +5,15 @@ use crate::{
     state_machine::AppState,
 };
 
-use super::{Event, processor::Processor, state::AppStateContext};
+use super::{Event, connectivity, processor::Processor, state::AppStateContext};
 use lazy_static::lazy_static;
-use std::{error::Error, sync::Mutex};
+use std::{
+    error::Error,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 use tauri::{AppHandle, async_runtime::block_on};
 
 lazy_static! {
@@ -15,6 +21,9 @@ lazy_static! {
         Mutex::new(KoboldCppServerProcessor::new());
 }
 
+// Set before the server exists so a second mode change doesn't start it again
+static STARTED: AtomicBool = AtomicBool::new(false);
+
 #[allow(dead_code)]
 pub struct KoboldCppServerProcessor {
     kobold_cpp_server: Option<KoboldCppServer>,
@@ -86,16 +95,59 @@ impl KoboldCppServerProcessor {
     }
 }
 
+pub fn is_started() -> bool {
+    STARTED.load(Ordering::SeqCst)
+}
+
+// Offline routing can pick a downloaded model, KoboldCpp is started the first time one is needed
+pub fn serve_local_model(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
+    let context = AppState::get_context();
+    let Some(Ok((model, _))) = context
+        .conversation_model
+        .as_ref()
+        .map(|model| connectivity::conversation_models_for_mode(&context, model))
+    else {
+        return Ok(());
+    };
+    let is_downloaded = context
+        .models_context
+        .conversation_models
+        .iter()
+        .any(|m| m.model == model && m.is_local);
+    if !is_downloaded {
+        return Ok(());
+    }
+
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return match context.koboldcpp_server_context.state {
+            KoboldCppServerState::Running(_, _) => change_kobold_model(&context),
+            _ => Ok(()),
+        };
+    }
+    // Listeners can't be registered while an event is processed, start waits for it to finish
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = KoboldCppServerProcessor::start(app_handle) {
+            log::error!("Failed to start KoboldCPP server: {}", e);
+        }
+    });
+    Ok(())
+}
+
 #[allow(dead_code)]
 fn change_kobold_model(app_context: &AppStateContext) -> Result<(), Box<dyn Error>> {
-    let new_model_name = app_context.conversation_model.clone();
+    let new_model_name = app_context.conversation_model.as_ref().and_then(|model| {
+        connectivity::conversation_models_for_mode(app_context, model)
+            .ok()
+            .map(|(model, _)| model)
+    });
     println!("new_model: {:?}", new_model_name);
-    let new_model = if let Some(model) = new_model_name {
+    let new_model = if let Some(model) = new_model_name.as_ref() {
         let active_model = app_context
             .models_context
             .conversation_models
             .iter()
-            .find(|m| m.model == model)
+            .find(|m| &m.model == model)
             .unwrap();
         println!("active_model: {:?}", active_model);
         match active_model.get_path() {
@@ -108,7 +160,7 @@ fn change_kobold_model(app_context: &AppStateContext) -> Result<(), Box<dyn Erro
     } else {
         None
     };
-    let new_model_name_clone = app_context.conversation_model.clone();
+    let new_model_name_clone = new_model_name.clone();
     std::thread::spawn(move || {
         let current_model = block_on(
             KoboldServerProcessor
@@ -1,25 +1,111 @@
-use std::{borrow::Cow, collections::HashMap, error::Error, process::Stdio, sync::Arc};
+use std::{
+    borrow::Cow, collections::HashMap, error::Error, process::Stdio, sync::Arc, time::Duration,
+};
 
 use rmcp::{
-    RoleClient, ServiceExt,
-    model::{CallToolRequestParam, CallToolResult, JsonObject, Tool},
-    service::RunningService,
-    transport::SseTransport,
-    transport::TokioChildProcess,
+    ClientHandler, RoleClient, ServiceExt,
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, ClientRequest,
+        GetPromptRequestParam, JsonObject, LoggingMessageNotificationParam,
+        ReadResourceRequestParam, ResourceUpdatedNotificationParam, ServerResult, Tool,
+    },
+    service::{Peer, PeerRequestOptions, RunningService, ServiceError},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tauri::{AppHandle, async_runtime::block_on};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::{Child, Command},
 };
-use serde::{Deserialize, Serialize};
-use tauri::async_runtime::block_on;
-use tokio::process::Command;
 use uuid::Uuid;
 
 use crate::state_machine::AppState;
 use crate::state_machine::errors::AppError;
-
-use super::{Event, processor::Processor};
+use crate::state_machine::state::ToolResultContent;
+
+use super::{
+    Event,
+    builtin_tools::{self, BUILTIN_SERVER_KEY, BuiltinToolsConfig},
+    cancellation::CancellationToken,
+    events::{MCPPromptPayload, MCPResourcePayload},
+    mcp_secrets::{
+        delete_server_headers, delete_server_secret, get_server_headers, get_server_secret,
+        store_server_headers, store_server_secret,
+    },
+    mcp_servers_json::parse_mcp_servers_json,
+    mcp_supervisor::{MCPServerLogSource, MCPSupervisor, push_server_log},
+    mcp_transport::{MCPHttpOptions, sse_transport, streamable_http_transport},
+    personas::{GenerationParams, Persona, PersonaTools},
+    processor::Processor,
+};
 
 pub struct MCPClient {
-    client: RunningService<RoleClient, ()>,
+    client: RunningService<RoleClient, MCPClientHandler>,
     name: String,
+    // Local servers only, the process is killed when the client is dropped
+    child: Option<Child>,
+}
+
+// Forwards server notifications about changed prompts and resources to the state machine
+pub struct MCPClientHandler {
+    key: String,
+    peer: Option<Peer<RoleClient>>,
+}
+
+impl MCPClientHandler {
+    fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            peer: None,
+        }
+    }
+
+    fn notify_catalog_changed(&self) {
+        if let Err(e) = Processor::process_event(Event::ActionMCPCatalogChanged(self.key.clone())) {
+            log::error!("Failed to process MCP catalog changed event: {}", e);
+        }
+    }
+}
+
+impl ClientHandler for MCPClientHandler {
+    fn on_resource_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
+        self.notify_catalog_changed();
+        std::future::ready(())
+    }
+
+    fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        log::info!("MCP resource {} updated on {}", params.uri, self.key);
+        self.notify_catalog_changed();
+        std::future::ready(())
+    }
+
+    fn on_prompt_list_changed(&self) -> impl Future<Output = ()> + Send + '_ {
+        self.notify_catalog_changed();
+        std::future::ready(())
+    }
+
+    fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        push_server_log(
+            &self.key,
+            MCPServerLogSource::Protocol,
+            format!("{:?}: {}", params.level, params.data),
+        );
+        std::future::ready(())
+    }
+
+    fn get_peer(&self) -> Option<Peer<RoleClient>> {
+        self.peer.clone()
+    }
+
+    fn set_peer(&mut self, peer: Peer<RoleClient>) {
+        self.peer = Some(peer);
+    }
 }
 
 pub struct MCPClientTool {
@@ -27,6 +113,13 @@ pub struct MCPClientTool {
     pub tool: Tool,
 }
 
+// A tool of one server as shown in the settings, where its policy and timeout are picked
+#[derive(Debug, Clone, Serialize)]
+pub struct MCPServerTool {
+    pub name: String,
+    pub description: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum MCPError {
     ClientNotFound(String),
@@ -39,6 +132,10 @@ pub enum MCPError {
         source: Box<dyn Error + Send + Sync>,
     },
     ToolCallFailed(Box<dyn Error + Send + Sync>),
+    ToolCallTimedOut(Duration),
+    ToolCallCancelled,
+    RequestFailed(Box<dyn Error + Send + Sync>),
+    ServerUnhealthy(String),
 }
 
 impl std::fmt::Display for MCPError {
@@ -52,6 +149,12 @@ impl std::fmt::Display for MCPError {
                 write!(f, "Failed to disable server {}: {}", name, source)
             }
             MCPError::ToolCallFailed(source) => write!(f, "Tool call failed: {}", source),
+            MCPError::ToolCallTimedOut(timeout) => {
+                write!(f, "Tool call timed out after {} seconds", timeout.as_secs())
+            }
+            MCPError::ToolCallCancelled => write!(f, "Tool call cancelled by the user"),
+            MCPError::RequestFailed(source) => write!(f, "Request failed: {}", source),
+            MCPError::ServerUnhealthy(reason) => write!(f, "Server is unhealthy: {}", reason),
         }
     }
 }
@@ -62,6 +165,7 @@ impl std::error::Error for MCPError {
             MCPError::ServerStartFailed { source, .. } => Some(source.as_ref()),
             MCPError::ServerDisableFailed { source, .. } => Some(source.as_ref()),
             MCPError::ToolCallFailed(source) => Some(source.as_ref()),
+            MCPError::RequestFailed(source) => Some(source.as_ref()),
             _ => None,
         }
     }
@@ -70,8 +174,10 @@ impl std::error::Error for MCPError {
 pub struct MCPProcessor {}
 
 impl MCPProcessor {
-    pub fn start() -> Result<(), Box<dyn Error>> {
+    pub fn start(app_handle: AppHandle) -> Result<(), Box<dyn Error>> {
+        builtin_tools::init(&app_handle);
         Self::register_event_handlers();
+        MCPSupervisor::start();
         std::thread::spawn(|| {
             Self::start_enabled_servers().unwrap();
         });
@@ -85,7 +191,8 @@ impl MCPProcessor {
             .mcp_context
             .server_configs
             .iter()
-            .any(|existing| existing.key == tool.key);
+            .any(|existing| existing.key == tool.key)
+            || tool.key == BUILTIN_SERVER_KEY;
         
         if key_exists {
             return Self::throw_error_if_key_exists(tool);
@@ -101,7 +208,8 @@ impl MCPProcessor {
             .mcp_context
             .server_configs
             .iter()
-            .any(|existing| existing.key == tool.key && existing.id != tool.id);
+            .any(|existing| existing.key == tool.key && existing.id != tool.id)
+            || tool.key == BUILTIN_SERVER_KEY;
 
             if key_exists {
            return Self::throw_error_if_key_exists(tool);
@@ -120,11 +228,80 @@ impl MCPProcessor {
         return Err("Duplicate tool key".into());
     }
 
+    // Moves the secret from the form into the keychain, so it never ends up in logs or saved state
+    fn save_tool_secret(tool: &mut MCPServerConfig) -> bool {
+        let (had_auth, had_headers) = AppState::get_context()
+            .mcp_context
+            .server_configs
+            .iter()
+            .find(|existing| existing.id == tool.id)
+            .map(keychain_usage)
+            .unwrap_or_default();
+        let (has_auth, has_headers) = keychain_usage(tool);
+
+        // Leftovers of the previous settings don't stop the server from being saved
+        if had_auth && !has_auth {
+            if let Err(e) = delete_server_secret(&tool.id) {
+                log::warn!("Failed to delete secret of tool {}: {}", tool.key, e);
+            }
+        }
+        if had_headers && !has_headers {
+            if let Err(e) = delete_server_headers(&tool.id) {
+                log::warn!("Failed to delete headers of tool {}: {}", tool.key, e);
+            }
+        }
+
+        let result = match &mut tool.kind {
+            MCPServerKind::External { auth_secret, .. } if has_auth => match auth_secret.take() {
+                Some(secret) if !secret.is_empty() => store_server_secret(&tool.id, &secret),
+                // Keeps the stored secret when the user didn't type a new one
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
+        .map_err(Into::into)
+        .and_then(|()| {
+            if has_headers {
+                Self::save_tool_headers(tool)
+            } else {
+                Ok(())
+            }
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to save secret of tool {}: {}", tool.key, e);
+            let _ = AppState::update(|context| {
+                context.errors.push(AppError::with_message(format!(
+                    "Failed to save the secret of '{}' in the system keychain: {}",
+                    tool.name, e
+                )));
+            });
+            return false;
+        }
+        true
+    }
+
+    // Header values can hold tokens as well, only their names stay in the config
+    fn save_tool_headers(tool: &mut MCPServerConfig) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let MCPServerKind::External { headers, .. } = &mut tool.kind else {
+            return Ok(());
+        };
+
+        // Empty values keep the stored ones, the form never gets them back
+        let values = with_stored_header_values(headers, &get_server_headers(&tool.id)?);
+        store_server_headers(&tool.id, &values)?;
+        headers.values_mut().for_each(String::clear);
+        Ok(())
+    }
+
     fn register_event_handlers() {
         Processor::register_event_listener(
             "mcp_processor",
             Box::new(|event, _app_handle| match event {
-                Event::ActionAddTool(tool) => {
+                Event::ActionAddTool(mut tool) => {
+                    if !Self::save_tool_secret(&mut tool) {
+                        return Ok(());
+                    }
                     log::info!("Adding tool: {:?}", tool);
 
                     if let Err(_) = Self::validate_tool_key_uniqueness_for_new_tool(&tool) {
@@ -151,7 +328,12 @@ impl MCPProcessor {
                     Ok(())
                 }
 
-                Event::ActionUpdateTool(tool) => {
+                Event::ActionImportTools(json) => Self::handle_import_tools(&json),
+
+                Event::ActionUpdateTool(mut tool) => {
+                    if !Self::save_tool_secret(&mut tool) {
+                        return Ok(());
+                    }
                     log::info!("Updating tool: {:?}", tool);
                   
                     if let Err(_) = Self::validate_tool_key_uniqueness_for_update(&tool) {
@@ -337,6 +519,24 @@ impl MCPProcessor {
                 Event::ActionDeleteTool(id) => {
                     log::info!("Deleting tool: {:?}", id);
 
+                    let (has_auth, has_headers) = AppState::get_context()
+                        .mcp_context
+                        .server_configs
+                        .iter()
+                        .find(|tool| tool.id == id)
+                        .map(keychain_usage)
+                        .unwrap_or_default();
+                    if has_auth {
+                        if let Err(e) = delete_server_secret(&id) {
+                            log::warn!("Failed to delete secret of tool {}: {}", id, e);
+                        }
+                    }
+                    if has_headers {
+                        if let Err(e) = delete_server_headers(&id) {
+                            log::warn!("Failed to delete headers of tool {}: {}", id, e);
+                        }
+                    }
+
                     // Check if tool is currently enabled/running
                     let context = AppState::get_context();
                     let tool_config = context
@@ -438,6 +638,121 @@ impl MCPProcessor {
                     }
                 }
 
+                Event::ActionChangeToolPolicy(payload)
+                    if payload.server_id == BUILTIN_SERVER_KEY =>
+                {
+                    AppState::update(|context| {
+                        let builtin_tools = &mut context.mcp_context.builtin_tools;
+                        match (payload.tool_name, payload.policy) {
+                            (Some(tool_name), Some(policy)) => {
+                                builtin_tools.tool_policies.insert(tool_name, policy);
+                            }
+                            (Some(tool_name), None) => {
+                                builtin_tools.tool_policies.remove(&tool_name);
+                            }
+                            // Built-in tools only have per tool policies
+                            (None, _) => {}
+                        }
+                    })
+                }
+
+                Event::ActionUpdateBuiltinTools(config) => AppState::update(|context| {
+                    context.mcp_context.builtin_tools = config;
+                }),
+
+                Event::ActionChangeToolPolicy(payload) => AppState::update(|context| {
+                    if let Some(config) = context
+                        .mcp_context
+                        .server_configs
+                        .iter_mut()
+                        .find(|config| config.id == payload.server_id)
+                    {
+                        match payload.tool_name {
+                            Some(tool_name) => match payload.policy {
+                                Some(policy) => {
+                                    config.tool_policies.insert(tool_name, policy);
+                                }
+                                None => {
+                                    config.tool_policies.remove(&tool_name);
+                                }
+                            },
+                            None => {
+                                if let Some(policy) = payload.policy {
+                                    config.tool_policy = policy;
+                                }
+                            }
+                        }
+                    }
+                }),
+
+                Event::ActionMCPCatalogChanged(key) => {
+                    std::thread::spawn(move || {
+                        if let Err(e) = block_on(Self::refresh_server_catalog(&key)) {
+                            log::error!("Failed to refresh catalog of server {}: {}", key, e);
+                        }
+                    });
+                    Ok(())
+                }
+
+                Event::ActionSelectMCPPrompt(payload) => {
+                    std::thread::spawn(move || match block_on(Self::get_prompt_persona(payload)) {
+                        Ok(persona) => {
+                            Processor::process_event(Event::ActionChangePersona(Some(persona)))
+                                .ok();
+                        }
+                        Err(e) => {
+                            log::error!("Failed to get MCP prompt: {}", e);
+                            let _ = AppState::update(|context| {
+                                context.errors.push(AppError::with_message(format!(
+                                    "Failed to load prompt: {}",
+                                    e
+                                )));
+                            });
+                        }
+                    });
+                    Ok(())
+                }
+
+                Event::ActionAddMCPResource(payload) => {
+                    std::thread::spawn(move || {
+                        if let Err(e) = block_on(Self::attach_resource(payload)) {
+                            log::error!("Failed to attach MCP resource: {}", e);
+                            let _ = AppState::update(|context| {
+                                context.errors.push(AppError::with_message(format!(
+                                    "Failed to attach resource: {}",
+                                    e
+                                )));
+                            });
+                        }
+                    });
+                    Ok(())
+                }
+
+                Event::ActionChangeToolTimeout(payload) => AppState::update(|context| {
+                    if let Some(config) = context
+                        .mcp_context
+                        .server_configs
+                        .iter_mut()
+                        .find(|config| config.id == payload.server_id)
+                    {
+                        match payload.tool_name {
+                            Some(tool_name) => match payload.timeout_secs {
+                                Some(timeout_secs) => {
+                                    config.tool_timeouts.insert(tool_name, timeout_secs);
+                                }
+                                None => {
+                                    config.tool_timeouts.remove(&tool_name);
+                                }
+                            },
+                            None => {
+                                if let Some(timeout_secs) = payload.timeout_secs {
+                                    config.tool_timeout_secs = timeout_secs;
+                                }
+                            }
+                        }
+                    }
+                }),
+
                 Event::ActionEnableTool(id) => {
                     let context = AppState::get_context();
                     let tool = context
@@ -461,6 +776,50 @@ impl MCPProcessor {
         );
     }
 
+    fn handle_import_tools(json: &str) -> Result<(), Box<dyn Error>> {
+        let servers = match parse_mcp_servers_json(json) {
+            Ok(servers) => servers,
+            Err(e) => {
+                return AppState::update(|context| {
+                    context.errors.push(AppError::with_message(format!(
+                        "Invalid MCP servers config: {}",
+                        e
+                    )));
+                });
+            }
+        };
+
+        for server in servers {
+            let mut config = match server.config {
+                Ok(config) => config,
+                Err(e) => {
+                    AppState::update(|context| {
+                        context.errors.push(AppError::with_message(format!(
+                            "Failed to import MCP server '{}': {}",
+                            server.name, e
+                        )));
+                    })?;
+                    continue;
+                }
+            };
+
+            // Checked one by one, so duplicates within the file are reported too
+            if let Err(_) = Self::validate_tool_key_uniqueness_for_new_tool(&config) {
+                continue;
+            }
+            if !Self::save_tool_secret(&mut config) {
+                continue;
+            }
+
+            log::info!("Importing tool: {:?}", config);
+            AppState::update(|context| {
+                context.mcp_context.server_configs.push(config);
+            })?;
+        }
+
+        Ok(())
+    }
+
     fn handle_disable_tool(key: String) -> Result<(), Box<dyn Error>> {
         let context = AppState::get_context();
         let tool_config = context
@@ -685,76 +1044,133 @@ impl MCPProcessor {
 
         match &client_config.kind {
             MCPServerKind::Local { command, env_vars } => {
-                let client_service =
-                    Self::create_local_client(command, env_vars)
+                let (client_service, child) =
+                    Self::create_local_client(&client_config.key, command, env_vars)
                         .await
-                        .map_err(|e| MCPError::ServerStartFailed {
-                            name: client_config.key.clone(),
-                            source: e,
-                        })?;
+                        .map_err(|e| Self::server_start_failed(&client_config.key, e))?;
 
                 GLOBAL_MCP_REGISTRY.lock().await.add_client(MCPClient {
                     client: client_service,
                     name: client_config.key.clone(),
+                    child: Some(child),
                 });
                 log::info!("✅ Local server {} started successfully", client_config.key);
             }
-            MCPServerKind::External { url } => {
-                let client_service = Self::create_external_client(url).await.map_err(|e| {
-                    MCPError::ServerStartFailed {
-                        name: client_config.key.clone(),
-                        source: e,
-                    }
-                })?;
+            MCPServerKind::External { url, .. } => {
+                let client_service = Self::create_external_client(&client_config)
+                    .await
+                    .map_err(|e| Self::server_start_failed(&client_config.key, e))?;
 
                 GLOBAL_MCP_REGISTRY.lock().await.add_client(MCPClient {
                     client: client_service,
                     name: client_config.key.clone(),
+                    child: None,
                 });
                 log::info!(
-                    "ℹ️ External server {} configured with URL: {}. Connection logic pending.",
+                    "✅ External server {} connected to {}",
                     client_config.key,
                     url
                 );
-                // For now, we consider it "started" at the AppState level by returning Ok.
-                // No client is added to GLOBAL_MCP_REGISTRY for external servers yet.
             }
         }
+
+        Processor::process_event(Event::ActionMCPCatalogChanged(client_config.key.clone())).ok();
         Ok(())
     }
 
+    fn server_start_failed(key: &str, source: Box<dyn Error + Send + Sync>) -> MCPError {
+        push_server_log(
+            key,
+            MCPServerLogSource::Protocol,
+            format!("Failed to start: {}", source),
+        );
+        MCPError::ServerStartFailed {
+            name: key.to_string(),
+            source,
+        }
+    }
+
     async fn create_local_client(
+        key: &str,
         command_str: &str,
         env_vars_map: &HashMap<String, String>,
-    ) -> Result<RunningService<RoleClient, ()>, Box<dyn Error + Send + Sync>> {
-        let args: Vec<&str> = command_str.split(' ').collect();
+    ) -> Result<(RunningService<RoleClient, MCPClientHandler>, Child), Box<dyn Error + Send + Sync>>
+    {
+        let args = shlex::split(command_str).ok_or("Invalid command, check the quotes")?;
         if args.is_empty() {
             return Err("Empty command".into());
         }
 
-        let mut cmd = Command::new(args[0]);
-        for arg in &args[1..] {
-            cmd.arg(arg);
-        }
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]);
 
-        cmd.stderr(Stdio::inherit())
+        cmd.stderr(Stdio::piped())
             .stdin(Stdio::piped())
-            .stdout(Stdio::piped());
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
 
         for (key, value) in env_vars_map {
             cmd.env(key, value);
         }
 
-        let transport = TokioChildProcess::new(&mut cmd)?;
-        let client = ().serve(transport).await?;
-        Ok(client)
+        let mut child = cmd.spawn()?;
+        let stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+        if let Some(stderr) = child.stderr.take() {
+            let key = key.to_string();
+            tauri::async_runtime::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    push_server_log(&key, MCPServerLogSource::Stderr, line);
+                }
+            });
+        }
+
+        let client = MCPClientHandler::new(key).serve((stdout, stdin)).await?;
+        Ok((client, child))
     }
 
     async fn create_external_client(
-        url: &str,
-    ) -> Result<RunningService<RoleClient, ()>, Box<dyn Error + Send + Sync>> {
-        let transport = SseTransport::start(url).await?;
-        let client = ().serve(transport).await?;
+        config: &MCPServerConfig,
+    ) -> Result<RunningService<RoleClient, MCPClientHandler>, Box<dyn Error + Send + Sync>> {
+        let MCPServerKind::External {
+            url,
+            transport,
+            headers,
+            auth,
+            request_timeout_secs,
+            reconnect_attempts,
+            reconnect_delay_secs,
+            ..
+        } = &config.kind
+        else {
+            return Err("Not an external server".into());
+        };
+
+        let secret = match auth {
+            MCPExternalAuth::None => None,
+            _ => get_server_secret(&config.id)?,
+        };
+        // Servers without custom headers work without a keychain
+        let headers = if headers.is_empty() {
+            headers.clone()
+        } else {
+            with_stored_header_values(headers, &get_server_headers(&config.id)?)
+        };
+        let options = MCPHttpOptions::new(
+            &headers,
+            auth,
+            secret,
+            Duration::from_secs((*request_timeout_secs).max(1)),
+            *reconnect_attempts,
+            Duration::from_secs(*reconnect_delay_secs),
+        )?;
+
+        let transport = match transport {
+            MCPExternalTransport::StreamableHttp => streamable_http_transport(url, options)?,
+            MCPExternalTransport::Sse => sse_transport(url, options).await?,
+        };
+        let client = MCPClientHandler::new(&config.key).serve(transport).await?;
         Ok(client)
     }
 
@@ -796,6 +1212,17 @@ impl MCPProcessor {
             }
         }
 
+        let _ = AppState::update(|context| {
+            context
+                .mcp_context
+                .prompts
+                .retain(|prompt| prompt.server_key != key);
+            context
+                .mcp_context
+                .resources
+                .retain(|resource| resource.server_key != key);
+        });
+
         log::info!("✅ Server {} disabled successfully", key);
         Ok(())
     }
@@ -813,6 +1240,204 @@ impl MCPProcessor {
         Self::async_start_server(client_config).await
     }
 
+    // A ping timeout of None only checks that the local process is still running
+    pub async fn check_server_health(
+        key: &str,
+        ping_timeout: Option<Duration>,
+    ) -> Result<(), MCPError> {
+        let client = {
+            let mut registry = GLOBAL_MCP_REGISTRY.lock().await;
+            let client = registry
+                .clients
+                .iter_mut()
+                .find(|client| client.name == key)
+                .ok_or_else(|| MCPError::ClientNotFound(key.to_string()))?;
+
+            if let Some(Ok(Some(status))) = client.child.as_mut().map(|child| child.try_wait()) {
+                return Err(MCPError::ServerUnhealthy(format!(
+                    "process exited with {}",
+                    status
+                )));
+            }
+            client.client.clone()
+        };
+
+        if let Some(ping_timeout) = ping_timeout {
+            tokio::time::timeout(
+                ping_timeout,
+                client.send_request(ClientRequest::PingRequest(Default::default())),
+            )
+            .await
+            .map_err(|_| MCPError::ServerUnhealthy("ping timed out".to_string()))?
+            .map_err(|e| MCPError::ServerUnhealthy(format!("ping failed: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn restart_server(config: MCPServerConfig) -> Result<(), MCPError> {
+        Self::async_disable_server(&config.key).await?;
+        Self::async_start_server(config).await
+    }
+
+    async fn get_client(key: &str) -> Result<Peer<RoleClient>, MCPError> {
+        let registry = GLOBAL_MCP_REGISTRY.lock().await;
+        registry
+            .clients
+            .iter()
+            .find(|client| client.name == key)
+            .map(|client| client.client.clone())
+            .ok_or_else(|| MCPError::ClientNotFound(key.to_string()))
+    }
+
+    async fn refresh_server_catalog(key: &str) -> Result<(), MCPError> {
+        let client = Self::get_client(key).await?;
+        // Servers without the capability would answer with "method not found"
+        let capabilities = serde_json::to_value(client.peer_info())
+            .map(|info| info["capabilities"].clone())
+            .unwrap_or_default();
+
+        let mut prompts = Vec::new();
+        if capabilities["prompts"].is_object() {
+            for prompt in client
+                .list_all_prompts()
+                .await
+                .map_err(|e| MCPError::RequestFailed(e.into()))?
+            {
+                let prompt: WirePrompt = from_wire(&prompt)?;
+                prompts.push(MCPPrompt {
+                    server_key: key.to_string(),
+                    name: prompt.name,
+                    description: prompt.description,
+                    arguments: prompt.arguments,
+                });
+            }
+        }
+
+        let mut resources = Vec::new();
+        if capabilities["resources"].is_object() {
+            for resource in client
+                .list_all_resources()
+                .await
+                .map_err(|e| MCPError::RequestFailed(e.into()))?
+            {
+                let resource: WireResource = from_wire(&resource)?;
+                resources.push(MCPResource {
+                    server_key: key.to_string(),
+                    uri: resource.uri,
+                    name: resource.name,
+                    description: resource.description,
+                    mime_type: resource.mime_type,
+                });
+            }
+        }
+
+        log::info!(
+            "Server {} has {} prompts and {} resources",
+            key,
+            prompts.len(),
+            resources.len()
+        );
+        AppState::update(|context| {
+            let mcp_context = &mut context.mcp_context;
+            mcp_context
+                .prompts
+                .retain(|prompt| prompt.server_key != key);
+            mcp_context.prompts.extend(prompts);
+            mcp_context
+                .resources
+                .retain(|resource| resource.server_key != key);
+            mcp_context.resources.extend(resources);
+        })
+        .map_err(|e| MCPError::RequestFailed(e.to_string().into()))
+    }
+
+    // Prompts are used as personas, their messages become the system prompt
+    async fn get_prompt_persona(payload: MCPPromptPayload) -> Result<Persona, MCPError> {
+        let client = Self::get_client(&payload.server_key).await?;
+        let arguments = if payload.arguments.is_empty() {
+            None
+        } else {
+            Some(
+                payload
+                    .arguments
+                    .into_iter()
+                    .map(|(name, value)| (name, serde_json::Value::String(value)))
+                    .collect::<JsonObject>(),
+            )
+        };
+
+        let result = client
+            .get_prompt(GetPromptRequestParam {
+                name: payload.name.clone(),
+                arguments,
+            })
+            .await
+            .map_err(|e| MCPError::RequestFailed(e.into()))?;
+        let result: WirePromptResult = from_wire(&result)?;
+
+        let system_prompt = result
+            .messages
+            .iter()
+            .filter_map(|message| match &message.content {
+                WireContent::Text { text } => Some(text.clone()),
+                WireContent::Resource { resource } => resource.text.clone(),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        Ok(Persona {
+            id: format!("mcp-prompt:{}:{}", payload.server_key, payload.name),
+            name: payload.name,
+            system_prompt,
+            description: result.description.unwrap_or_default(),
+            voice_command: String::new(),
+            paste_on_finish: false,
+            icon: None,
+            record_output_audio: false,
+            examples: vec![],
+            meeting_mode: false,
+            tools: PersonaTools::All,
+            generation: GenerationParams::default(),
+            structured_output: None,
+            cloud_only: false,
+        })
+    }
+
+    async fn attach_resource(payload: MCPResourcePayload) -> Result<(), MCPError> {
+        let client = Self::get_client(&payload.server_key).await?;
+        let result = client
+            .read_resource(ReadResourceRequestParam {
+                uri: payload.uri.clone(),
+            })
+            .await
+            .map_err(|e| MCPError::RequestFailed(e.into()))?;
+        let result: WireReadResourceResult = from_wire(&result)?;
+
+        for contents in result.contents {
+            let mime_type = contents.mime_type.unwrap_or_default();
+            let event = match (contents.text, contents.blob) {
+                (Some(text), _) => Event::ActionAddText(format!(
+                    "<resource uri=\"{}\">\n{}\n</resource>",
+                    contents.uri, text
+                )),
+                (None, Some(blob)) if mime_type.starts_with("image/") => {
+                    Event::ActionAddImage(format!("data:{};base64,{}", mime_type, blob))
+                }
+                _ => {
+                    return Err(MCPError::RequestFailed(
+                        format!("Unsupported resource type: {}", mime_type).into(),
+                    ));
+                }
+            };
+            Processor::process_event(event)
+                .map_err(|e| MCPError::RequestFailed(e.to_string().into()))?;
+        }
+
+        Ok(())
+    }
+
     pub async fn list_all_tools() -> Result<Vec<MCPClientTool>, MCPError> {
         // Extract client references while holding the lock, then drop it
         let client_refs = {
@@ -826,6 +1451,15 @@ impl MCPProcessor {
 
         let mut all_tools = Vec::new();
 
+        if AppState::get_context().mcp_context.builtin_tools.enabled {
+            let tools =
+                builtin_tools::list_tools().map_err(|e| MCPError::RequestFailed(e.into()))?;
+            all_tools.extend(tools.into_iter().map(|tool| MCPClientTool {
+                name: BUILTIN_SERVER_KEY.to_string(),
+                tool,
+            }));
+        }
+
         for (client_name, client) in client_refs {
             match client.list_all_tools().await {
                 Ok(tools) => {
@@ -845,11 +1479,50 @@ impl MCPProcessor {
         Ok(all_tools)
     }
 
+    // Only running servers can be asked for their tools
+    pub async fn list_server_tools(server_key: &str) -> Result<Vec<MCPServerTool>, MCPError> {
+        let tools = if server_key == BUILTIN_SERVER_KEY {
+            builtin_tools::list_tools().map_err(|e| MCPError::RequestFailed(e.into()))?
+        } else {
+            let client = {
+                let registry = GLOBAL_MCP_REGISTRY.lock().await;
+                registry
+                    .clients
+                    .iter()
+                    .find(|c| c.name == server_key)
+                    .map(|c| c.client.clone())
+            }
+            .ok_or_else(|| MCPError::ClientNotFound(server_key.to_string()))?;
+
+            client
+                .list_all_tools()
+                .await
+                .map_err(|e| MCPError::RequestFailed(e.into()))?
+        };
+
+        Ok(tools
+            .into_iter()
+            .map(|tool| MCPServerTool {
+                name: tool.name.to_string(),
+                description: tool.description.map(|description| description.to_string()),
+            })
+            .collect())
+    }
+
     pub async fn call_tool(
         client_name: String,
         tool_name: String,
         arguments: Option<JsonObject>,
+        timeout: Duration,
+        cancellation: &CancellationToken,
     ) -> Result<CallToolResult, MCPError> {
+        if client_name == BUILTIN_SERVER_KEY {
+            return cancellation
+                .run(Self::call_builtin_tool(tool_name, arguments, timeout))
+                .await
+                .map_err(|_| MCPError::ToolCallCancelled)?;
+        }
+
         // Clone the client while holding the lock, then drop it
         let client = {
             let registry = GLOBAL_MCP_REGISTRY.lock().await;
@@ -861,18 +1534,206 @@ impl MCPProcessor {
         }
         .ok_or_else(|| MCPError::ClientNotFound(client_name.clone()))?; // Lock is dropped here
 
-        let result = client
-            .call_tool(CallToolRequestParam {
-                name: Cow::Owned(tool_name),
-                arguments,
-            })
+        let mut request = client
+            .send_cancellable_request(
+                ClientRequest::CallToolRequest(CallToolRequest {
+                    method: Default::default(),
+                    params: CallToolRequestParam {
+                        name: Cow::Owned(tool_name),
+                        arguments,
+                    },
+                }),
+                PeerRequestOptions::no_options(),
+            )
             .await
             .map_err(|e| MCPError::ToolCallFailed(e.into()))?;
 
-        Ok(result)
+        let response = tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => Err(MCPError::ToolCallCancelled),
+            response = tokio::time::timeout(timeout, &mut request.rx) => {
+                response.map_err(|_| MCPError::ToolCallTimedOut(timeout))
+            }
+        };
+
+        let result = match response {
+            Ok(response) => response
+                .map_err(|e| MCPError::ToolCallFailed(e.into()))
+                .and_then(|result| result.map_err(|e| MCPError::ToolCallFailed(e.into())))
+                .and_then(|result| match result {
+                    ServerResult::CallToolResult(result) => Ok(result),
+                    _ => Err(MCPError::ToolCallFailed(
+                        ServiceError::UnexpectedResponse.into(),
+                    )),
+                }),
+            Err(e) => {
+                // Lets the server stop working on a call nobody waits for anymore
+                if let Err(cancel_error) = request.cancel(Some(e.to_string())).await {
+                    log::warn!(
+                        "Failed to send the cancellation to server {}: {}",
+                        client_name,
+                        cancel_error
+                    );
+                }
+                Err(e)
+            }
+        };
+
+        result.inspect_err(|e| {
+            push_server_log(&client_name, MCPServerLogSource::Protocol, e.to_string())
+        })
+    }
+
+    async fn call_builtin_tool(
+        tool_name: String,
+        arguments: Option<JsonObject>,
+        timeout: Duration,
+    ) -> Result<CallToolResult, MCPError> {
+        let config = AppState::get_context().mcp_context.builtin_tools;
+        if !config.enabled {
+            return Err(MCPError::ClientNotFound(BUILTIN_SERVER_KEY.to_string()));
+        }
+
+        let task = tokio::task::spawn_blocking(move || {
+            builtin_tools::call_tool(&config, &tool_name, arguments)
+        });
+        tokio::time::timeout(timeout, task)
+            .await
+            .map_err(|_| MCPError::ToolCallTimedOut(timeout))?
+            .map_err(|e| MCPError::ToolCallFailed(e.into()))?
+            .map_err(|e| MCPError::ToolCallFailed(e.into()))
+    }
+}
+
+// Whether the server keeps an auth secret and header values in the keychain
+fn keychain_usage(config: &MCPServerConfig) -> (bool, bool) {
+    match &config.kind {
+        MCPServerKind::External { auth, headers, .. } => {
+            (*auth != MCPExternalAuth::None, !headers.is_empty())
+        }
+        _ => (false, false),
     }
 }
 
+fn with_stored_header_values(
+    headers: &HashMap<String, String>,
+    stored: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| match stored.get(name) {
+            Some(stored) if value.is_empty() => (name.clone(), stored.clone()),
+            _ => (name.clone(), value.clone()),
+        })
+        .collect()
+}
+
+// Reads the result through its wire format, which follows the MCP spec
+pub fn tool_result_content(result: &CallToolResult) -> (Vec<ToolResultContent>, bool) {
+    let value = serde_json::to_value(result).unwrap_or_default();
+    let is_error = value["isError"].as_bool().unwrap_or(false);
+    let parts = value["content"]
+        .as_array()
+        .map(|content| {
+            content
+                .iter()
+                .filter_map(|part| match part["type"].as_str() {
+                    Some("text") => Some(ToolResultContent::Text {
+                        text: part["text"].as_str().unwrap_or_default().to_string(),
+                    }),
+                    Some("image") => Some(ToolResultContent::Image {
+                        data: part["data"].as_str().unwrap_or_default().to_string(),
+                        mime_type: part["mimeType"].as_str().unwrap_or_default().to_string(),
+                    }),
+                    Some("resource") => Some(ToolResultContent::Resource {
+                        uri: part["resource"]["uri"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        mime_type: part["resource"]["mimeType"].as_str().map(String::from),
+                        text: part["resource"]["text"].as_str().map(String::from),
+                    }),
+                    other => {
+                        log::warn!("Skipping unsupported tool result content: {:?}", other);
+                        None
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    (parts, is_error)
+}
+
+// MCP results are read through their JSON form, which follows the spec
+fn from_wire<T: DeserializeOwned>(value: &impl Serialize) -> Result<T, MCPError> {
+    serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .map_err(|e| MCPError::RequestFailed(e.into()))
+}
+
+#[derive(Deserialize)]
+struct WirePrompt {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    arguments: Vec<MCPPromptArgument>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireResource {
+    uri: String,
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    mime_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WirePromptResult {
+    #[serde(default)]
+    description: Option<String>,
+    messages: Vec<WirePromptMessage>,
+}
+
+#[derive(Deserialize)]
+struct WirePromptMessage {
+    content: WireContent,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WireContent {
+    Text {
+        text: String,
+    },
+    Resource {
+        resource: WireResourceContents,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct WireReadResourceResult {
+    contents: Vec<WireResourceContents>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireResourceContents {
+    uri: String,
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    blob: Option<String>,
+}
+
 // Global registry to manage MCP clients since we can't store them in AppState
 struct MCPClientRegistry {
     clients: Vec<MCPClient>,
@@ -901,9 +1762,59 @@ pub enum MCPServerKind {
     },
     External {
         url: String,
+        #[serde(default)]
+        transport: MCPExternalTransport,
+        // Values are kept in the system keychain, the saved state only has the names
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        auth: MCPExternalAuth,
+        // Only sent by the settings form, the secret itself is kept in the system keychain
+        #[serde(default, skip_serializing)]
+        auth_secret: Option<String>,
+        #[serde(default = "default_request_timeout_secs")]
+        request_timeout_secs: u64,
+        #[serde(default = "default_reconnect_attempts")]
+        reconnect_attempts: u32,
+        #[serde(default = "default_reconnect_delay_secs")]
+        reconnect_delay_secs: u64,
+    },
+}
+
+// Servers saved before the transport could be chosen all used SSE
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum MCPExternalTransport {
+    #[default]
+    Sse,
+    StreamableHttp,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum MCPExternalAuth {
+    #[default]
+    None,
+    Bearer,
+    ApiKey {
+        header: String,
     },
 }
 
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_RECONNECT_ATTEMPTS: u32 = 3;
+pub const DEFAULT_RECONNECT_DELAY_SECS: u64 = 2;
+
+fn default_request_timeout_secs() -> u64 {
+    DEFAULT_REQUEST_TIMEOUT_SECS
+}
+
+fn default_reconnect_attempts() -> u32 {
+    DEFAULT_RECONNECT_ATTEMPTS
+}
+
+fn default_reconnect_delay_secs() -> u64 {
+    DEFAULT_RECONNECT_DELAY_SECS
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MCPServerState {
     Disabled,
@@ -930,21 +1841,127 @@ pub struct MCPServerConfig {
     pub enabled: bool,
     #[serde(default)]
     pub state: MCPServerState,
+    #[serde(default)]
+    pub tool_policy: MCPToolPolicy,
+    // Per tool overrides of the server policy, keyed by tool name
+    #[serde(default)]
+    pub tool_policies: HashMap<String, MCPToolPolicy>,
+    #[serde(default = "default_tool_timeout_secs")]
+    pub tool_timeout_secs: u64,
+    // Per tool overrides of the server timeout, keyed by tool name
+    #[serde(default)]
+    pub tool_timeouts: HashMap<String, u64>,
+}
+
+impl MCPServerConfig {
+    pub fn policy_for_tool(&self, tool_name: &str) -> MCPToolPolicy {
+        self.tool_policies
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.tool_policy)
+    }
+
+    pub fn timeout_for_tool(&self, tool_name: &str) -> Duration {
+        let timeout_secs = self
+            .tool_timeouts
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.tool_timeout_secs);
+        Duration::from_secs(timeout_secs.max(1))
+    }
+}
+
+pub const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 60;
+
+fn default_tool_timeout_secs() -> u64 {
+    DEFAULT_TOOL_TIMEOUT_SECS
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum MCPToolPolicy {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl Default for MCPToolPolicy {
+    fn default() -> Self {
+        MCPToolPolicy::Ask
+    }
 }
 
 fn default_id() -> String {
     Uuid::new_v4().to_string()
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPPrompt {
+    pub server_key: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<MCPPromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPResource {
+    pub server_key: String,
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPContext {
     pub server_configs: Vec<MCPServerConfig>,
+    // Prompts and resources of the running servers
+    #[serde(default)]
+    pub prompts: Vec<MCPPrompt>,
+    #[serde(default)]
+    pub resources: Vec<MCPResource>,
+    #[serde(default)]
+    pub builtin_tools: BuiltinToolsConfig,
 }
 
 impl Default for MCPContext {
     fn default() -> Self {
         Self {
             server_configs: vec![],
+            prompts: vec![],
+            resources: vec![],
+            builtin_tools: BuiltinToolsConfig::default(),
         }
     }
 }
+
+impl MCPContext {
+    // Tools of servers that are no longer configured are never allowed to run
+    pub fn tool_policy(&self, client_name: &str, tool_name: &str) -> MCPToolPolicy {
+        if client_name == BUILTIN_SERVER_KEY {
+            return self.builtin_tools.policy_for_tool(tool_name);
+        }
+
+        self.server_configs
+            .iter()
+            .find(|config| config.key == client_name)
+            .map(|config| config.policy_for_tool(tool_name))
+            .unwrap_or(MCPToolPolicy::Deny)
+    }
+
+    pub fn tool_timeout(&self, client_name: &str, tool_name: &str) -> Duration {
+        self.server_configs
+            .iter()
+            .find(|config| config.key == client_name)
+            .map(|config| config.timeout_for_tool(tool_name))
+            .unwrap_or(Duration::from_secs(DEFAULT_TOOL_TIMEOUT_SECS))
+    }
+}
@@ -5,8 +5,10 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     Event,
+    connectivity::NetworkMode,
     events::{NewConversationModel, UpdateConversationModel},
-    models::{ConversationModel, ModelConfig, TranscriptionModel},
+    context_window::MIN_CONTEXT_LENGTH,
+    models::{ConversationModel, ModelConfig, TranscriptionModel, default_context_length},
     processor::Processor,
 };
 use crate::constants::{QSPEAK_API_V1_URL, QSPEAK_API_MODELS_URL, QSPEAK_API_TRANSCRIPTION_MODELS_URL};
@@ -21,6 +23,8 @@ pub struct ApiModel {
     pub model: String,
     pub supports_tools: bool,
     pub supports_vision: bool,
+    #[serde(default)]
+    pub context_length: Option<u32>,
     pub speed: f64,
     pub intelligence: f64,
 }
@@ -37,14 +41,9 @@ pub struct ApiTranscriptionModel {
 pub struct ModelsProcessor {}
 
 /// Check if a conversation model is a custom user-added model
-/// Custom models have ModelConfig::OpenAI with a URL different from the qSpeak API
+/// Custom models have a URL different from the qSpeak API
 fn is_custom_conversation_model(model: &ConversationModel) -> bool {
-    match &model.config {
-        ModelConfig::OpenAI { url, .. } => {
-            // Custom models use non-qspeak URLs
-            url != QSPEAK_API_V1_URL
-        }
-    }
+    model.config.url() != QSPEAK_API_V1_URL
 }
 
 /// Check if a conversation model can be deleted
@@ -90,6 +89,7 @@ pub fn fetch_conversation_models_from_api() -> Result<Vec<ConversationModel>, Bo
                 },
                 supports_tools: api_model.supports_tools,
                 supports_vision: api_model.supports_vision,
+                context_length: api_model.context_length.unwrap_or_else(default_context_length),
                 size: 0.0,
                 parameters: 0.0,
                 vram: 0.0,
@@ -133,6 +133,7 @@ fn get_fallback_conversation_models() -> Vec<ConversationModel> {
             }),
             supports_tools: true,
             supports_vision: true,
+            context_length: 1_047_576,
             size: 0.0,
             parameters: 0.0,
             vram: 0.0,
@@ -152,6 +153,7 @@ fn get_fallback_conversation_models() -> Vec<ConversationModel> {
                 is_local: false,
             }),
             supports_vision: true,
+            context_length: 128_000,
             size: 0.0,
             parameters: 0.0,
             vram: 0.0,
@@ -210,6 +212,8 @@ impl ModelsProcessor {
                 Event::ActionRefetchConversationModels => {
                     refetch_conversation_models()
                 }
+                // Only the hardcoded fallback models could be fetched while offline
+                Event::NetworkModeChange(NetworkMode::Online) => refetch_conversation_models(),
                 _ => Ok(()),
             }),
         );
@@ -230,15 +234,9 @@ impl ModelsProcessor {
 }
 
 /// Initialize conversation models from API and update the state
+/// Custom and local models from the last run are kept, offline they are the only ones that answer
 pub fn initialize_conversation_models() -> Result<(), Box<dyn Error + Send + Sync>> {
-    let models = fetch_conversation_models_with_fallback();
-    
-    match AppState::update(|context| {
-        context.models_context.conversation_models = models;
-    }) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to update models state: {}", e).into()),
-    }
+    do_refetch_conversation_models()
 }
 
 /// Fetch transcription models from the qSpeak API (blocking)
@@ -774,14 +772,15 @@ fn delete_conversation_model(model_id: &str) -> Result<(), Box<dyn Error>> {
 fn add_conversation_model(new_model: &NewConversationModel) -> Result<(), Box<dyn Error>> {
     AppState::update(|context| {
         // Create a custom model configuration
-        // Custom models use ModelConfig::OpenAI with user-provided URLs
-        let config = ModelConfig::OpenAI {
-            url: new_model.url.clone(),
-            model: new_model.model.clone(),
-            api_key: new_model.api_key.clone(),
-            supports_vision: new_model.supports_vision,
-            supports_tools: new_model.supports_tools,
-        };
+        // Custom models use user-provided URLs of the chosen provider
+        let config = ModelConfig::new(
+            new_model.provider,
+            new_model.url.clone(),
+            new_model.model.clone(),
+            new_model.api_key.clone(),
+            new_model.supports_vision,
+            new_model.supports_tools,
+        );
 
         let conversation_model = ConversationModel {
             name: new_model.model.clone(),
@@ -795,6 +794,7 @@ fn add_conversation_model(new_model: &NewConversationModel) -> Result<(), Box<dy
             }),
             supports_tools: new_model.supports_tools,
             supports_vision: new_model.supports_vision,
+            context_length: new_model.context_length.max(MIN_CONTEXT_LENGTH),
             size: 0.0,
             parameters: 0.0,
             vram: 0.0,
@@ -836,13 +836,14 @@ fn update_conversation_model(update_model: &UpdateConversationModel) -> Result<(
         }
 
         // Update the model configuration
-        let config = ModelConfig::OpenAI {
-            url: update_model.url.clone(),
-            model: update_model.model.clone(),
-            api_key: update_model.api_key.clone(),
-            supports_vision: update_model.supports_vision,
-            supports_tools: update_model.supports_tools,
-        };
+        let config = ModelConfig::new(
+            update_model.provider,
+            update_model.url.clone(),
+            update_model.model.clone(),
+            update_model.api_key.clone(),
+            update_model.supports_vision,
+            update_model.supports_tools,
+        );
 
         // Update all the changeable fields
         existing_model.model = update_model.model.clone();
@@ -850,6 +851,7 @@ fn update_conversation_model(update_model: &UpdateConversationModel) -> Result<(
         existing_model.config = config;
         existing_model.supports_tools = update_model.supports_tools;
         existing_model.supports_vision = update_model.supports_vision;
+        existing_model.context_length = update_model.context_length.max(MIN_CONTEXT_LENGTH);
 
         // Update vision model if vision support changed
         existing_model.vision = update_model.supports_vision.then(|| VisionModel {
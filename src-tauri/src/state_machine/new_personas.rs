@@ -19,6 +19,11 @@ impl PersonasProcessor {
                         icon: Some(persona.icon.clone()),
                         record_output_audio: persona.record_output_audio,
                         examples: persona.examples.clone(),
+                        meeting_mode: persona.meeting_mode,
+                        tools: persona.tools.clone(),
+                        generation: persona.generation.clone(),
+                        structured_output: persona.structured_output.clone(),
+                        cloud_only: persona.cloud_only,
                     });
                 }),
                 Event::ActionUpdatePersona(persona) => AppState::update(|context| {
@@ -50,6 +55,11 @@ impl PersonasProcessor {
                         icon: persona.icon.clone(),
                         record_output_audio: persona.record_output_audio,
                         examples: persona.examples.clone(),
+                        meeting_mode: persona.meeting_mode,
+                        tools: persona.tools.clone(),
+                        generation: persona.generation.clone(),
+                        structured_output: persona.structured_output.clone(),
+                        cloud_only: persona.cloud_only,
                     };
                     context.personas_context.personas.push(new_persona);
                 }),
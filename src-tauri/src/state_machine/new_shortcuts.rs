@@ -220,7 +220,7 @@ impl KeybindsProcessor {
         if self.registered_shortcuts.screenshot.is_none()
             && (matches!(
                 app_context.conversation_context.state,
-                ConversationState::Listening | ConversationState::Idle
+                ConversationState::Listening | ConversationState::Paused | ConversationState::Idle
             ))
         {
             let hotkey = Shortcut::from_str(&screenshot_shortcut)?;
@@ -229,7 +229,7 @@ impl KeybindsProcessor {
         } else if self.registered_shortcuts.screenshot.is_some()
             && !(matches!(
                 app_context.conversation_context.state,
-                ConversationState::Listening | ConversationState::Idle
+                ConversationState::Listening | ConversationState::Paused | ConversationState::Idle
             ))
         {
             let hotkey = Shortcut::from_str(
@@ -253,7 +253,7 @@ impl KeybindsProcessor {
         if self.registered_shortcuts.copy_text.is_none()
             && (matches!(
                 app_context.conversation_context.state,
-                ConversationState::Listening | ConversationState::Idle
+                ConversationState::Listening | ConversationState::Paused | ConversationState::Idle
             ))
         {
             let hotkey = Shortcut::from_str(&copy_text_shortcut)?;
@@ -262,7 +262,7 @@ impl KeybindsProcessor {
         } else if self.registered_shortcuts.copy_text.is_some()
             && !(matches!(
                 app_context.conversation_context.state,
-                ConversationState::Listening | ConversationState::Idle
+                ConversationState::Listening | ConversationState::Paused | ConversationState::Idle
             ))
         {
             let hotkey = Shortcut::from_str(
@@ -20,6 +20,90 @@ pub struct Persona {
     pub record_output_audio: bool,
     #[serde(default = "default_examples")]
     pub examples: Vec<PersonaExample>,
+    #[serde(default = "default_meeting_mode")]
+    pub meeting_mode: bool,
+    #[serde(default)]
+    pub tools: PersonaTools,
+    #[serde(default)]
+    pub generation: GenerationParams,
+    #[serde(default)]
+    pub structured_output: Option<StructuredOutput>,
+    // Needs a cloud model, so it is refused while offline or in local only mode
+    #[serde(default)]
+    pub cloud_only: bool,
+}
+
+// Sampling settings sent with every request of the persona, unset ones are left to the model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenerationParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+// Makes the persona answer with JSON matching the schema instead of free text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StructuredOutput {
+    pub schema: serde_json::Value,
+    // What gets pasted, `{{field}}` or `{{field.nested}}` are replaced with values, the JSON itself when empty
+    #[serde(default)]
+    pub template: String,
+    // Receives the validated JSON in a POST request
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+// MCP tools offered to the model, patterns are `server` or `server--tool` and may contain `*`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum PersonaTools {
+    #[default]
+    All,
+    Only(Vec<String>),
+    Disabled,
+}
+
+impl PersonaTools {
+    pub fn allows(&self, server_key: &str, tool_name: &str) -> bool {
+        match self {
+            PersonaTools::All => true,
+            PersonaTools::Only(patterns) => {
+                let full_name = format!("{}--{}", server_key, tool_name);
+                patterns.iter().any(|pattern| {
+                    if pattern.contains("--") {
+                        matches_pattern(pattern, &full_name)
+                    } else {
+                        matches_pattern(pattern, server_key)
+                    }
+                })
+            }
+            PersonaTools::Disabled => false,
+        }
+    }
+}
+
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return false;
+    };
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard, the whole value has to match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
 }
 
 fn default_icon() -> Option<String> {
@@ -34,6 +118,10 @@ fn default_examples() -> Vec<PersonaExample> {
     vec![]
 }
 
+fn default_meeting_mode() -> bool {
+    false
+}
+
 // Context data that's associated with the state machine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersonasContext {
@@ -63,6 +151,11 @@ impl Default for PersonasContext {
                             answer: "J'ai besoin de réserver un vol pour Paris.".to_string(),
                         },
                     ],
+                    meeting_mode: false,
+                    tools: PersonaTools::Disabled,
+                    generation: GenerationParams::default(),
+                    structured_output: None,
+                    cloud_only: false,
                 },
                 Persona {
                     id: "b23c29af-efdb-4b1f-b153-a473de80a447".to_string(),
@@ -83,6 +176,11 @@ impl Default for PersonasContext {
                             answer: "Here are some tips for writing a professional email:\n1. Use a clear, specific subject line\n2. Start with a proper greeting\n3. Keep your message concise and organized\n4. Use a professional tone\n5. End with a courteous closing\n6. Proofread before sending".to_string(),
                         },
                     ],
+                    meeting_mode: false,
+                    tools: PersonaTools::All,
+                    generation: GenerationParams::default(),
+                    structured_output: None,
+                    cloud_only: false,
                 },
                 Persona {
                     id: "b23c29af-efdb-4b1f-b153-a473de80a448".to_string(),
@@ -127,6 +225,11 @@ Only reply with the email body text, do not include any extra information or exp
                             answer: "Hi [Recipient],\n\nI wanted to follow up on the project proposal we discussed last week. Could you please let me know if you need any additional information from my end?\n\nLooking forward to hearing from you.\n\nBest regards,\n[User's name]".to_string(),
                         },
                     ],
+                    meeting_mode: false,
+                    tools: PersonaTools::Disabled,
+                    generation: GenerationParams::default(),
+                    structured_output: None,
+                    cloud_only: false,
                 },
                 Persona {
                     id: "5f81e151-9d3e-478b-9de6-958042ffac59".to_string(),
@@ -201,6 +304,11 @@ Next Meeting
                             answer: "Project Ideas\n\n• Mobile app for habit tracking\n• Web tool for team collaboration\n• AI assistant integration features".to_string(),
                         },
                     ],
+                    meeting_mode: false,
+                    tools: PersonaTools::Disabled,
+                    generation: GenerationParams::default(),
+                    structured_output: None,
+                    cloud_only: false,
                 },
                 Persona {
                     id: "5f81e151-9d3e-478b-9de6-958042ffac60".to_string(),
@@ -252,6 +360,11 @@ Respond only with the finished notes—no explanations, no markdown fences, and
                             answer: "Daily Standup\n\nYesterday\n• Worked on user interface design\n\nToday\n• Focus on backend API development\n\nBlockers\n• Need database credentials from IT".to_string(),
                         },
                     ],
+                    meeting_mode: true,
+                    tools: PersonaTools::Disabled,
+                    generation: GenerationParams::default(),
+                    structured_output: None,
+                    cloud_only: false,
                 },
             ],
         }
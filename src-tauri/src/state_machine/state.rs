@@ -3,16 +3,25 @@ use crate::{koboldcpp_server::KoboldCppServerState, llm::ChatCompletionMessageCo
 use super::{
     InterfaceTheme, Language,
     account::{Account, AccountContext, LoginState},
+    audio_retention::AudioRetentionContext,
     challenges::{Challenge, ChallengeContext, get_default_challenges},
+    connectivity::ConnectivityContext,
+    devices::{InputDevice, find_input_device},
     errors::AppError,
-    events::{Shortcuts, WebsocketServerSettingsPayload},
+    events::{MCPServerSettingsPayload, Shortcuts, WebsocketServerSettingsPayload},
     history::{History, HistoryContext},
+    history_store,
+    meeting::{MeetingSession, DEFAULT_MEETING_CHUNK_MINUTES},
     models::{ModelsContext, TranscriptionModel, ConversationModel, TranscriptionProvider},
+    builtin_tools::BuiltinToolsConfig,
+    cancellation::CancellationToken,
+    context_window::{ContextStrategy, ContextSummary, ContextUsage},
     new_mcp_processor::{MCPServerConfig, MCPContext},
     permissions::PermissionsContext,
     personas::{Persona, PersonasContext},
     challenges::{create_customize_shortcuts_challenge, ChallengeName},
     releases::ReleasesContext,
+    usage::ModelPrice,
 };
 use chrono::{DateTime, Utc};
 use json_patch::{Patch, diff};
@@ -21,6 +30,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
 use std::{
+    collections::HashMap,
     error::Error,
     sync::{
         Mutex,
@@ -35,6 +45,11 @@ use tauri_plugin_store::StoreExt;
 const STORE_FILENAME: &str = "app_state.json";
 const STORE_KEY: &str = "app_state";
 const DEFAULT_WEBSOCKET_PORT: u16 = 4456;
+pub const DEFAULT_MCP_SERVER_PORT: u16 = 4457;
+pub const DEFAULT_PRE_ROLL_MS: u32 = 500;
+pub const MAX_PRE_ROLL_MS: u32 = 3000;
+pub const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 10;
+pub const MAX_TOOL_ITERATIONS: u32 = 50;
 
 lazy_static! {
     static ref APP_STATE: Mutex<AppState> = Mutex::new(AppState::new());
@@ -83,8 +98,15 @@ pub struct AppStateContext {
     pub interface_language: Language,
     pub preferred_languages: Vec<Language>,
     pub input_device: Option<String>,
+    pub input_devices: Vec<InputDevice>,
+    pub preferred_input_devices: Vec<String>,
     pub transcription_model: Option<String>,
     pub conversation_model: Option<String>,
+    // Tried when a request to the picked model fails
+    pub fallback_transcription_model: Option<String>,
+    pub fallback_conversation_model: Option<String>,
+    // Keyed by the model name the usage stats use
+    pub model_prices: HashMap<String, ModelPrice>,
     pub active_persona: Option<Persona>,
     pub recording_window_context: RecordingWindowContext,
     pub models_context: ModelsContext,
@@ -100,7 +122,10 @@ pub struct AppStateContext {
     pub challenge_context: ChallengeContext,
     pub mcp_context: MCPContext,
     pub websocket_server_context: WebsocketServerContext,
+    pub mcp_server_context: MCPServerContext,
     pub releases_context: ReleasesContext,
+    pub audio_retention_context: AudioRetentionContext,
+    pub connectivity_context: ConnectivityContext,
 }
 
 impl AppStateContext {
@@ -119,8 +144,13 @@ impl AppStateContext {
             interface_language: dump.interface_language,
             preferred_languages: dump.preferred_languages,
             input_device: dump.input_device,
+            input_devices: Vec::new(),
+            preferred_input_devices: dump.preferred_input_devices,
             transcription_model: dump.transcription_model,
             conversation_model: dump.conversation_model,
+            fallback_transcription_model: dump.fallback_transcription_model,
+            fallback_conversation_model: dump.fallback_conversation_model,
+            model_prices: dump.model_prices,
             active_persona: dump.active_persona.clone(),
             challenge_context: ChallengeContext {
                 challenges: dump.challenge_context.challenges,
@@ -148,18 +178,28 @@ impl AppStateContext {
             conversation_context: ConversationContext {
                 dictionary: dump.conversation_context.dictionary,
                 replacements: dump.conversation_context.replacements,
+                meeting_chunk_minutes: dump.conversation_context.meeting_chunk_minutes,
+                pre_roll_enabled: dump.conversation_context.pre_roll_enabled,
+                pre_roll_ms: dump.conversation_context.pre_roll_ms,
+                max_tool_iterations: dump.conversation_context.max_tool_iterations,
+                context_strategy: dump.conversation_context.context_strategy,
                 ..ConversationContext::default()
             },
             koboldcpp_server_context: KoboldCppServerContext {
                 state: dump.koboldcpp_server_context.state,
             },
             history_context: HistoryContext {
-                history: dump.history_context.history,
+                history: history_store::list().unwrap_or_else(|e| {
+                    log::error!("Failed to load history: {}", e);
+                    vec![]
+                }),
                 last_persona: dump.active_persona.as_ref().map(|persona| PersonaHistory {
                     id: persona.id.clone(),
                     name: persona.name.clone(),
                 }),
                 current_history_id: dump.history_context.current_history_id,
+                branch_parent: None,
+                legacy_history: dump.history_context.history.clone(),
             },
             account_context: AccountContext {
                 account: Account {
@@ -177,13 +217,22 @@ impl AppStateContext {
             },
             mcp_context: MCPContext {
                 server_configs: dump.mcp_context.server_configs,
+                builtin_tools: dump.mcp_context.builtin_tools,
+                ..MCPContext::default()
             },
             websocket_server_context: WebsocketServerContext {
                 enabled: dump.websocket_server_context.enabled,
                 port: dump.websocket_server_context.port,
                 password: dump.websocket_server_context.password.clone(),
             },
+            mcp_server_context: MCPServerContext {
+                enabled: dump.mcp_server_context.enabled,
+                port: dump.mcp_server_context.port,
+                token: dump.mcp_server_context.token.clone(),
+            },
             releases_context: ReleasesContext::default(),
+            audio_retention_context: dump.audio_retention_context,
+            connectivity_context: ConnectivityContext::new(dump.connectivity_context.local_only),
         }
     }
 
@@ -198,6 +247,8 @@ impl AppStateContext {
     pub fn update_active_persona(&mut self, persona: Option<Persona>) -> Result<(), Box<dyn Error>> {
         self.active_persona = persona;
         self.conversation_context.conversation = vec![];
+        self.conversation_context.context_usage = None;
+        self.conversation_context.failed_step = None;
         Ok(())
     }
 }
@@ -211,6 +262,10 @@ fn default_preferred_languages() -> Vec<Language> {
     vec![Language::English, Language::Auto]
 }
 
+fn default_preferred_input_devices() -> Vec<String> {
+    Vec::new()
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AppStateContextDump {
     pub shortcuts: Shortcuts,
@@ -219,8 +274,16 @@ pub struct AppStateContextDump {
     #[serde(default = "default_preferred_languages")]
     pub preferred_languages: Vec<Language>,
     pub input_device: Option<String>,
+    #[serde(default = "default_preferred_input_devices")]
+    pub preferred_input_devices: Vec<String>,
     pub transcription_model: Option<String>,
     pub conversation_model: Option<String>,
+    #[serde(default)]
+    pub fallback_transcription_model: Option<String>,
+    #[serde(default)]
+    pub fallback_conversation_model: Option<String>,
+    #[serde(default)]
+    pub model_prices: HashMap<String, ModelPrice>,
     #[serde(default = "default_active_persona")]
     pub active_persona: Option<Persona>,
     #[serde(default)]
@@ -246,7 +309,13 @@ pub struct AppStateContextDump {
     #[serde(default)]
     pub websocket_server_context: WebsocketServerContextDump,
     #[serde(default)]
+    pub mcp_server_context: MCPServerContextDump,
+    #[serde(default)]
     pub mcp_context: MCPContextDump,
+    #[serde(default)]
+    pub audio_retention_context: AudioRetentionContext,
+    #[serde(default)]
+    pub connectivity_context: ConnectivityContext,
 }
 
 impl AppStateContextDump {
@@ -257,8 +326,12 @@ impl AppStateContextDump {
             interface_language: context.interface_language.clone(),
             preferred_languages: context.preferred_languages.clone(),
             input_device: context.input_device.clone(),
+            preferred_input_devices: context.preferred_input_devices.clone(),
             transcription_model: context.transcription_model.clone(),
             conversation_model: context.conversation_model.clone(),
+            fallback_transcription_model: context.fallback_transcription_model.clone(),
+            fallback_conversation_model: context.fallback_conversation_model.clone(),
+            model_prices: context.model_prices.clone(),
             active_persona: context.active_persona.clone(),
             recording_window_context: RecordingWindowContextDump {
                 minimized: context.recording_window_context.minimized,
@@ -280,12 +353,17 @@ impl AppStateContextDump {
             conversation_context: ConversationContextDump {
                 dictionary: context.conversation_context.dictionary.clone(),
                 replacements: context.conversation_context.replacements.clone(),
+                meeting_chunk_minutes: context.conversation_context.meeting_chunk_minutes,
+                pre_roll_enabled: context.conversation_context.pre_roll_enabled,
+                pre_roll_ms: context.conversation_context.pre_roll_ms,
+                max_tool_iterations: context.conversation_context.max_tool_iterations,
+                context_strategy: context.conversation_context.context_strategy,
             },
             koboldcpp_server_context: KoboldCppServerContextDump {
                 state: context.koboldcpp_server_context.state.clone(),
             },
             history_context: HistoryContextDump {
-                history: context.history_context.history.clone(),
+                history: context.history_context.legacy_history.clone(),
                 last_persona: context.history_context.last_persona.clone(),
                 current_history_id: None
             },
@@ -307,9 +385,17 @@ impl AppStateContextDump {
                 port: context.websocket_server_context.port,
                 password: context.websocket_server_context.password.clone(),
             },
+            mcp_server_context: MCPServerContextDump {
+                enabled: context.mcp_server_context.enabled,
+                port: context.mcp_server_context.port,
+                token: context.mcp_server_context.token.clone(),
+            },
             mcp_context: MCPContextDump {
                 server_configs: context.mcp_context.server_configs.clone(),
+                builtin_tools: context.mcp_context.builtin_tools.clone(),
             },
+            audio_retention_context: context.audio_retention_context.clone(),
+            connectivity_context: context.connectivity_context.clone(),
         }
     }
 
@@ -366,6 +452,8 @@ impl Default for ChallengeContextDump {
 pub struct MCPContextDump {
     #[serde(default = "default_server_configs")]
     pub server_configs: Vec<MCPServerConfig>,
+    #[serde(default)]
+    pub builtin_tools: BuiltinToolsConfig,
 }
 
 fn default_server_configs() -> Vec<MCPServerConfig> {
@@ -376,6 +464,7 @@ impl Default for MCPContextDump {
     fn default() -> Self {
         Self {
             server_configs: default_server_configs(),
+            builtin_tools: BuiltinToolsConfig::default(),
         }
     }
 }
@@ -456,6 +545,8 @@ impl Default for PermissionsContextDump {
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct HistoryContextDump {
+    // Entries from before history moved to the history store, kept until they are imported
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub history: Vec<History>,
     #[serde(default = "default_last_persona")]
     pub last_persona: Option<PersonaHistory>,
@@ -534,6 +625,35 @@ impl Default for WebsocketServerContextDump {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MCPServerContextDump {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_mcp_server_port")]
+    pub port: u16,
+    #[serde(default = "new_mcp_server_token")]
+    pub token: String,
+}
+
+fn default_mcp_server_port() -> u16 {
+    DEFAULT_MCP_SERVER_PORT
+}
+
+// Agents pass it to the server, any other process on the machine is turned away
+fn new_mcp_server_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+impl Default for MCPServerContextDump {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_MCP_SERVER_PORT,
+            token: new_mcp_server_token(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct RecordingWindowPosition {
     pub x: f64,
@@ -619,11 +739,21 @@ impl Default for OnboardingWindowContext {
 pub enum ConversationState {
     Idle,
     Listening,
+    Paused,
     Transcribing,
     Transforming,
+    AwaitingToolApproval,
     Error,
 } // Context data that's associated with the state machine
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum FailedStep {
+    // Audio retention keeps the recording while the step can be retried
+    Transcription { audio_file_path: String },
+    Transformation,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ConversationMessage {
@@ -632,6 +762,16 @@ pub enum ConversationMessage {
     ConversationTextMessage(ConversationTextMessage),
 }
 
+impl ConversationMessage {
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            ConversationMessage::ConversationToolCallMessage(message) => message.created_at,
+            ConversationMessage::ConversationToolCallResultMessage(message) => message.created_at,
+            ConversationMessage::ConversationTextMessage(message) => message.created_at,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationTextMessage {
     pub audio_file_path: Option<String>,
@@ -661,6 +801,13 @@ pub struct ToolCallFunction {
     pub arguments: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolApprovalRequest {
+    pub tool_call: ToolCall,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -672,10 +819,33 @@ pub struct ToolResult {
 pub struct ConversationToolCallResultMessage {
     pub role: String,
     pub tool_call_id: String,
+    // Text sent back to the model
     pub content: String,
+    // Original parts returned by the tool
+    #[serde(default)]
+    pub parts: Vec<ToolResultContent>,
+    #[serde(default)]
+    pub is_error: bool,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolResultContent {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        mime_type: String,
+    },
+    Resource {
+        uri: String,
+        mime_type: Option<String>,
+        text: Option<String>,
+    },
+}
+
 fn default_content() -> Vec<ChatCompletionMessageContent> {
     Vec::new()
 }
@@ -705,6 +875,31 @@ pub struct ConversationContext {
     pub copy_text_state: CopyTextState,
     pub screenshot_state: ScreenshotState,
     pub pending_tool_call_ids: Vec<String>,
+    // Tool calls waiting for the user, the model is paused until all are resolved
+    pub pending_tool_approvals: Vec<ToolApprovalRequest>,
+    pub meeting: Option<MeetingSession>,
+    pub meeting_chunk_minutes: u32,
+    // Keeps the microphone open to buffer audio from just before a recording starts
+    pub pre_roll_enabled: bool,
+    pub pre_roll_ms: u32,
+    // Model calls made for tool results since the last user message
+    pub tool_iterations: u32,
+    pub max_tool_iterations: u32,
+    // What the user can run again after it failed, None once anything else happens
+    pub failed_step: Option<FailedStep>,
+    // Answers of a structured output persona sent back for not matching the schema
+    #[serde(skip)]
+    pub structured_output_retries: u32,
+    // What is left out of a request when the conversation outgrows the model's context window
+    pub context_strategy: ContextStrategy,
+    pub context_usage: Option<ContextUsage>,
+    #[serde(skip)]
+    pub context_summary: Option<ContextSummary>,
+    // An agent connected to the MCP server waits for the next transcription instead of the persona
+    pub voice_input_requested: bool,
+    // Replaced when a turn starts, cancelling it stops the work still running for that turn
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
 }
 
 impl Default for ConversationContext {
@@ -719,6 +914,20 @@ impl Default for ConversationContext {
             copy_text_state: CopyTextState::Idle,
             screenshot_state: ScreenshotState::Idle,
             pending_tool_call_ids: Vec::new(),
+            pending_tool_approvals: Vec::new(),
+            meeting: None,
+            meeting_chunk_minutes: DEFAULT_MEETING_CHUNK_MINUTES,
+            pre_roll_enabled: false,
+            pre_roll_ms: DEFAULT_PRE_ROLL_MS,
+            tool_iterations: 0,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            failed_step: None,
+            structured_output_retries: 0,
+            context_strategy: ContextStrategy::default(),
+            context_usage: None,
+            context_summary: None,
+            voice_input_requested: false,
+            cancellation: CancellationToken::new(),
         }
     }
 }
@@ -729,9 +938,28 @@ impl ConversationContext {
         self.copy_text_state = CopyTextState::Idle;
         self.screenshot_state = ScreenshotState::Idle;
         self.pending_tool_call_ids = Vec::new();
+        self.pending_tool_approvals = Vec::new();
+        self.meeting = None;
+        self.voice_input_requested = false;
         Ok(())
     }
 
+    pub fn take_tool_approval(&mut self, tool_call_id: &str) -> Option<ToolApprovalRequest> {
+        let index = self
+            .pending_tool_approvals
+            .iter()
+            .position(|request| request.tool_call.id == tool_call_id)?;
+        let request = self.pending_tool_approvals.remove(index);
+
+        if self.pending_tool_approvals.is_empty()
+            && self.state == ConversationState::AwaitingToolApproval
+        {
+            self.state = ConversationState::Transforming;
+        }
+
+        Some(request)
+    }
+
 
 
     pub fn add_chunk(&mut self, chunk: String) -> Result<(), Box<dyn Error>> {
@@ -803,6 +1031,16 @@ pub struct ConversationContextDump {
     pub dictionary: Vec<String>,
     #[serde(default = "default_replacements")]
     pub replacements: Vec<(String, String)>,
+    #[serde(default = "default_meeting_chunk_minutes")]
+    pub meeting_chunk_minutes: u32,
+    #[serde(default = "default_pre_roll_enabled")]
+    pub pre_roll_enabled: bool,
+    #[serde(default = "default_pre_roll_ms")]
+    pub pre_roll_ms: u32,
+    #[serde(default = "default_max_tool_iterations")]
+    pub max_tool_iterations: u32,
+    #[serde(default)]
+    pub context_strategy: ContextStrategy,
 }
 
 fn default_dictionary() -> Vec<String> {
@@ -813,11 +1051,32 @@ fn default_replacements() -> Vec<(String, String)> {
     Vec::new()
 }
 
+fn default_meeting_chunk_minutes() -> u32 {
+    DEFAULT_MEETING_CHUNK_MINUTES
+}
+
+fn default_pre_roll_enabled() -> bool {
+    false
+}
+
+fn default_pre_roll_ms() -> u32 {
+    DEFAULT_PRE_ROLL_MS
+}
+
+fn default_max_tool_iterations() -> u32 {
+    DEFAULT_MAX_TOOL_ITERATIONS
+}
+
 impl Default for ConversationContextDump {
     fn default() -> Self {
         Self {
             dictionary: Vec::new(),
             replacements: Vec::new(),
+            meeting_chunk_minutes: DEFAULT_MEETING_CHUNK_MINUTES,
+            pre_roll_enabled: false,
+            pre_roll_ms: DEFAULT_PRE_ROLL_MS,
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            context_strategy: ContextStrategy::default(),
         }
     }
 }
@@ -851,6 +1110,23 @@ impl Default for WebsocketServerContext {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct MCPServerContext {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for MCPServerContext {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_MCP_SERVER_PORT,
+            token: new_mcp_server_token(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
 pub enum UpdateState {
     Idle,
@@ -887,8 +1163,13 @@ impl Default for AppStateContext {
             interface_language: Language::English,
             preferred_languages: default_preferred_languages(),
             input_device: None,
+            input_devices: Vec::new(),
+            preferred_input_devices: Vec::new(),
             transcription_model: Some("whisper-1".to_string()),
             conversation_model: Some("gpt-4.1-mini".to_string()),
+            fallback_transcription_model: None,
+            fallback_conversation_model: None,
+            model_prices: HashMap::new(),
             active_persona: None,
             recording_window_context: RecordingWindowContext::default(),
             models_context: ModelsContext::default(),
@@ -904,7 +1185,10 @@ impl Default for AppStateContext {
             challenge_context: ChallengeContext::default(),
             mcp_context: MCPContext::default(),
             websocket_server_context: WebsocketServerContext::default(),
+            mcp_server_context: MCPServerContext::default(),
             releases_context: ReleasesContext::default(),
+            audio_retention_context: AudioRetentionContext::default(),
+            connectivity_context: ConnectivityContext::default(),
         }
     }
 }
@@ -1073,7 +1357,11 @@ impl AppState {
     }
 
     pub fn update_input_device_fn(context: &mut AppStateContext, device: Option<String>) -> Result<(), Box<dyn Error>> {
-        context.input_device = device;
+        context.input_device = device.map(|device| {
+            find_input_device(&context.input_devices, &device)
+                .map(|input_device| input_device.id.clone())
+                .unwrap_or(device)
+        });
         Ok(())
     }
 
@@ -1099,6 +1387,30 @@ impl AppState {
         Ok(())
     }
 
+    pub fn update_fallback_transcription_model(model: Option<String>) -> Result<(), Box<dyn Error>> {
+        AppState::update(|context| {
+            context.fallback_transcription_model = model;
+        })
+    }
+
+    pub fn update_fallback_conversation_model(model: Option<String>) -> Result<(), Box<dyn Error>> {
+        AppState::update(|context| {
+            context.fallback_conversation_model = model;
+        })
+    }
+
+    // No price removes the model from the cost stats
+    pub fn update_model_price(model: String, price: Option<ModelPrice>) -> Result<(), Box<dyn Error>> {
+        AppState::update(|context| match price {
+            Some(price) => {
+                context.model_prices.insert(model, price);
+            }
+            None => {
+                context.model_prices.remove(&model);
+            }
+        })
+    }
+
     pub fn update_websocket_server_settings(settings: WebsocketServerSettingsPayload) -> Result<(), Box<dyn Error>> {
         AppState::update(|context| {
             AppState::update_websocket_server_settings_fn(context, settings.clone()).unwrap();
@@ -1121,6 +1433,25 @@ impl AppState {
         Ok(())
     }
 
+    pub fn update_mcp_server_settings(settings: MCPServerSettingsPayload) -> Result<(), Box<dyn Error>> {
+        AppState::update(|context| {
+            AppState::update_mcp_server_settings_fn(context, settings.clone()).unwrap();
+        })
+    }
+
+    pub fn update_mcp_server_settings_fn(
+        context: &mut AppStateContext,
+        settings: MCPServerSettingsPayload,
+    ) -> Result<(), Box<dyn Error>> {
+        context.mcp_server_context.enabled = settings.enabled;
+        context.mcp_server_context.port = if settings.port == 0 {
+            DEFAULT_MCP_SERVER_PORT
+        } else {
+            settings.port
+        };
+        Ok(())
+    }
+
     pub fn update_active_persona(persona: Option<Persona>) -> Result<(), Box<dyn Error>> {
         AppState::update(|context| {
             AppState::update_active_persona_fn(context, persona).unwrap();
@@ -1447,6 +1778,30 @@ impl Migration for MigrateTranscriptionModelIds {
     }
 }
 
+struct MigrateHistoryToDatabase {
+    name: String,
+}
+
+impl Migration for MigrateHistoryToDatabase {
+    fn apply(&self, state: &mut AppStateContextDump) -> Result<bool, Box<dyn Error>> {
+        if state.history_context.history.is_empty() {
+            return Ok(false);
+        }
+
+        // The entries stay in the store and the import is tried again on the next start
+        if let Err(e) = history_store::import(&state.history_context.history) {
+            log::error!("Failed to move history to the database: {}", e);
+            return Ok(false);
+        }
+
+        state.history_context.history.clear();
+        Ok(true)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
 
 pub fn run_migrations(state: &mut AppStateContextDump) -> Result<(), Box<dyn Error>> {
     let migrations: Vec<Box<dyn Migration>> = vec![
@@ -1462,6 +1817,9 @@ pub fn run_migrations(state: &mut AppStateContextDump) -> Result<(), Box<dyn Err
         Box::new(MigrateTranscriptionModelIds {
             name: "Migrate old transcription model IDs (use_openai, use_mistral) to new API model IDs".to_string()
         }),
+        Box::new(MigrateHistoryToDatabase {
+            name: "Move history entries from the app state store to the history database".to_string()
+        }),
         //   more here
     ];
     
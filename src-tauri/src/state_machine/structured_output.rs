@@ -0,0 +1,152 @@
+use std::error::Error;
+
+use serde_json::{Map, Value};
+
+// Times the model is asked to correct an answer that does not match the schema
+pub const MAX_STRUCTURED_OUTPUT_RETRIES: u32 = 2;
+
+// Models tend to wrap JSON in a markdown code block even when asked not to
+pub fn parse_output(text: &str) -> Result<Value, Box<dyn Error>> {
+    let text = text.trim();
+    let text = match text.strip_prefix("```") {
+        Some(fenced) => fenced
+            .trim_start_matches("json")
+            .trim_end()
+            .trim_end_matches("```"),
+        None => text,
+    };
+    serde_json::from_str(text.trim())
+        .map_err(|e| format!("The answer is not valid JSON: {}", e).into())
+}
+
+// Checks the subset of JSON Schema personas use: types, enums, properties, required and items
+pub fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    let mut errors = vec![];
+    validate_at(schema, value, "$", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(expected) => vec![expected.as_str()],
+            Value::Array(expected) => expected.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|expected| has_type(value, expected)) {
+            errors.push(format!("{} should be {}", path, types.join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .filter(|allowed| !allowed.contains(value))
+    {
+        errors.push(format!(
+            "{} should be one of {}",
+            path,
+            Value::Array(allowed.clone())
+        ));
+    }
+
+    if let Value::Object(object) = value {
+        validate_object(schema, object, path, errors);
+    }
+
+    if let (Value::Array(items), Some(items_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(items_schema, item, &format!("{}[{}]", path, index), errors);
+        }
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    for required in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(required) {
+            errors.push(format!("{}.{} is required", path, required));
+        }
+    }
+
+    for (key, property) in object {
+        let property_path = format!("{}.{}", path, key);
+        match properties.and_then(|properties| properties.get(key)) {
+            Some(property_schema) => validate_at(property_schema, property, &property_path, errors),
+            None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                errors.push(format!("{} is not allowed", property_path))
+            }
+            None => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+// Fills {{field}} placeholders with values from the answer, nested fields are separated by dots
+pub fn format_output(value: &Value, template: &str) -> String {
+    if template.trim().is_empty() {
+        return serde_json::to_string_pretty(value).unwrap_or_default();
+    }
+
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let field = rest[start + 2..start + end].trim();
+        output.push_str(&field_text(value, field));
+        rest = &rest[start + end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn field_text(value: &Value, field: &str) -> String {
+    let field = field
+        .split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |value, key| match value {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+            _ => value.get(key),
+        });
+    match field {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    }
+}
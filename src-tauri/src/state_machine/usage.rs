@@ -0,0 +1,185 @@
+use std::error::Error;
+
+use chrono::{Local, NaiveDate};
+use rusqlite::{Row, params, params_from_iter, types::Value};
+use serde::{Deserialize, Serialize};
+
+use super::{history_store, state::AppState};
+use crate::llm::TokenUsage;
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+// What a model costs, in the currency the endpoint bills in
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ModelPrice {
+    pub input_per_million_tokens: f64,
+    pub output_per_million_tokens: f64,
+    pub audio_per_minute: f64,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64, audio_seconds: f64) -> f64 {
+        (prompt_tokens as f64 * self.input_per_million_tokens
+            + completion_tokens as f64 * self.output_per_million_tokens)
+            / 1_000_000.0
+            + audio_seconds / 60.0 * self.audio_per_minute
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStatsQuery {
+    // Local days, both included
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub model_name: Option<String>,
+    pub persona_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStatsRow {
+    pub day: NaiveDate,
+    pub model_name: String,
+    // None for requests made without a persona, like history titles
+    pub persona_name: Option<String>,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub audio_seconds: f64,
+    // With the prices set now, models without one cost nothing
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub rows: Vec<UsageStatsRow>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub audio_seconds: f64,
+    pub cost: f64,
+}
+
+pub fn record_tokens(model_name: &str, persona_name: Option<&str>, usage: &TokenUsage) {
+    record(
+        model_name,
+        persona_name,
+        usage.prompt_tokens,
+        usage.completion_tokens,
+        0.0,
+    );
+}
+
+pub fn record_audio(model_name: &str, persona_name: Option<&str>, audio_seconds: f64) {
+    record(model_name, persona_name, 0, 0, audio_seconds);
+}
+
+// Counting never fails the request it counts, errors are only logged
+fn record(
+    model_name: &str,
+    persona_name: Option<&str>,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    audio_seconds: f64,
+) {
+    let day = Local::now().date_naive().format(DAY_FORMAT).to_string();
+    let result = history_store::with_connection(|connection| {
+        connection.execute(
+            "INSERT INTO usage (day, model_name, persona_name, requests, prompt_tokens, completion_tokens, audio_seconds)
+            VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6)
+            ON CONFLICT (day, model_name, persona_name) DO UPDATE SET
+                requests = requests + 1,
+                prompt_tokens = prompt_tokens + excluded.prompt_tokens,
+                completion_tokens = completion_tokens + excluded.completion_tokens,
+                audio_seconds = audio_seconds + excluded.audio_seconds",
+            params![
+                day,
+                model_name,
+                persona_name.unwrap_or_default(),
+                prompt_tokens as i64,
+                completion_tokens as i64,
+                audio_seconds
+            ],
+        )
+    });
+    if let Err(e) = result {
+        log::error!("Failed to record usage of {}: {}", model_name, e);
+    }
+}
+
+// Newest days first, costs use the prices set in the settings
+pub fn stats(query: &UsageStatsQuery) -> Result<UsageStats, Box<dyn Error>> {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(from) = query.from {
+        conditions.push("day >= ?");
+        values.push(Value::Text(from.format(DAY_FORMAT).to_string()));
+    }
+    if let Some(to) = query.to {
+        conditions.push("day <= ?");
+        values.push(Value::Text(to.format(DAY_FORMAT).to_string()));
+    }
+    if let Some(model_name) = query.model_name.as_ref() {
+        conditions.push("model_name = ?");
+        values.push(Value::Text(model_name.clone()));
+    }
+    if let Some(persona_name) = query.persona_name.as_ref() {
+        conditions.push("persona_name = ?");
+        values.push(Value::Text(persona_name.clone()));
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let prices = AppState::get_context().model_prices;
+    let rows = history_store::with_connection(|connection| {
+        let mut statement = connection.prepare(&format!(
+            "SELECT day, model_name, persona_name, requests, prompt_tokens, completion_tokens, audio_seconds
+            FROM usage {} ORDER BY day DESC, model_name ASC, persona_name ASC",
+            where_clause
+        ))?;
+        let rows = statement
+            .query_map(params_from_iter(values), row_from_sql)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    })?;
+
+    let mut stats = UsageStats::default();
+    for mut row in rows {
+        if let Some(price) = prices.get(&row.model_name) {
+            row.cost = price.cost(row.prompt_tokens, row.completion_tokens, row.audio_seconds);
+        }
+        stats.prompt_tokens += row.prompt_tokens;
+        stats.completion_tokens += row.completion_tokens;
+        stats.audio_seconds += row.audio_seconds;
+        stats.cost += row.cost;
+        stats.rows.push(row);
+    }
+    Ok(stats)
+}
+
+// The usage table lives in the history database, clearing history keeps it but removing
+// that file resets the stats too
+pub fn clear() -> Result<(), Box<dyn Error>> {
+    history_store::with_connection(|connection| connection.execute("DELETE FROM usage", []))?;
+    Ok(())
+}
+
+fn row_from_sql(row: &Row) -> rusqlite::Result<UsageStatsRow> {
+    let day: String = row.get(0)?;
+    let persona_name: String = row.get(2)?;
+    Ok(UsageStatsRow {
+        day: NaiveDate::parse_from_str(&day, DAY_FORMAT).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        model_name: row.get(1)?,
+        persona_name: (!persona_name.is_empty()).then_some(persona_name),
+        requests: row.get::<_, i64>(3)? as u64,
+        prompt_tokens: row.get::<_, i64>(4)? as u64,
+        completion_tokens: row.get::<_, i64>(5)? as u64,
+        audio_seconds: row.get(6)?,
+        cost: 0.0,
+    })
+}
@@ -17,8 +17,10 @@ use crate::state_machine::{
     Event,
     errors::AppError,
     events::WebsocketServerSettingsPayload,
+    history_store::{self, HistorySearchQuery},
     processor::Processor,
-    state::AppState,
+    state::{AppState, ConversationState},
+    usage::{self, UsageStatsQuery},
 };
 
 pub struct WebsocketServerProcessor;
@@ -246,8 +248,9 @@ fn process_message(payload: &str, config: &WebsocketServerConfig) -> (bool, Stri
     }
 
     let action_label = command.action.as_str();
-    match execute_action(&command.action) {
-        Ok(_) => (true, format!("Action '{}' executed", action_label)),
+    match execute_action(&command) {
+        Ok(Some(message)) => (true, message),
+        Ok(None) => (true, format!("Action '{}' executed", action_label)),
         Err(err) => (
             false,
             format!("Failed to execute '{}': {}", action_label, err),
@@ -267,8 +270,8 @@ fn verify_password(
     }
 }
 
-fn execute_action(action: &RemoteAction) -> Result<(), Box<dyn Error>> {
-    match action {
+fn execute_action(command: &WebsocketCommand) -> Result<Option<String>, Box<dyn Error>> {
+    match &command.action {
         RemoteAction::ToggleRecording => {
             Processor::process_event(Event::ActionResetRecordingShortcutTimer)?;
             Processor::process_event(Event::ActionRecording)?;
@@ -297,9 +300,79 @@ fn execute_action(action: &RemoteAction) -> Result<(), Box<dyn Error>> {
         RemoteAction::SwitchLanguage => {
             Processor::process_event(Event::ActionSwitchToNextPreferredLanguage)?;
         }
+        RemoteAction::PauseRecording => {
+            Processor::process_event(Event::PauseTranscription)?;
+        }
+        RemoteAction::ResumeRecording => {
+            Processor::process_event(Event::ResumeTranscription)?;
+        }
+        RemoteAction::TogglePause => match AppState::get_context().conversation_context.state {
+            ConversationState::Listening => Processor::process_event(Event::PauseTranscription)?,
+            ConversationState::Paused => Processor::process_event(Event::ResumeTranscription)?,
+            state => return Err(format!("Cannot toggle pause in state {}", state).into()),
+        },
+        RemoteAction::CancelProcessing => {
+            Processor::process_event(Event::ActionCancelProcessing)?;
+        }
+        RemoteAction::GetConversationState => {
+            return Ok(Some(
+                AppState::get_context()
+                    .conversation_context
+                    .state
+                    .to_string(),
+            ));
+        }
+        RemoteAction::ApproveToolCall => {
+            let tool_call_id = resolve_tool_call_id(command.tool_call_id.as_deref())?;
+            Processor::process_event(Event::ActionApproveToolCall(tool_call_id))?;
+        }
+        RemoteAction::DenyToolCall => {
+            let tool_call_id = resolve_tool_call_id(command.tool_call_id.as_deref())?;
+            Processor::process_event(Event::ActionDenyToolCall(tool_call_id))?;
+        }
+        RemoteAction::GetPendingToolApprovals => {
+            let pending_tool_approvals = AppState::get_context()
+                .conversation_context
+                .pending_tool_approvals;
+            return Ok(Some(serde_json::to_string(&pending_tool_approvals)?));
+        }
+        RemoteAction::SearchHistory => {
+            let query = command.search.clone().unwrap_or_default();
+            return Ok(Some(serde_json::to_string(&history_store::search(&query)?)?));
+        }
+        RemoteAction::GetUsageStats => {
+            let query = command.usage.clone().unwrap_or_default();
+            return Ok(Some(serde_json::to_string(&usage::stats(&query)?)?));
+        }
+        RemoteAction::GetNetworkMode => {
+            return Ok(Some(
+                AppState::get_context()
+                    .connectivity_context
+                    .mode
+                    .to_string(),
+            ));
+        }
+        RemoteAction::ToggleLocalOnly => {
+            let local_only = AppState::get_context().connectivity_context.local_only;
+            Processor::process_event(Event::ActionChangeLocalOnly(!local_only))?;
+        }
     }
 
-    Ok(())
+    Ok(None)
+}
+
+// Without an explicit id the oldest pending approval is answered
+fn resolve_tool_call_id(tool_call_id: Option<&str>) -> Result<String, Box<dyn Error>> {
+    if let Some(tool_call_id) = tool_call_id {
+        return Ok(tool_call_id.to_string());
+    }
+
+    AppState::get_context()
+        .conversation_context
+        .pending_tool_approvals
+        .first()
+        .map(|request| request.tool_call.id.clone())
+        .ok_or_else(|| "No tool call is waiting for approval".into())
 }
 
 #[derive(Deserialize)]
@@ -307,6 +380,12 @@ struct WebsocketCommand {
     action: RemoteAction,
     #[serde(default)]
     password: Option<String>,
+    #[serde(default)]
+    tool_call_id: Option<String>,
+    #[serde(default)]
+    search: Option<HistorySearchQuery>,
+    #[serde(default)]
+    usage: Option<UsageStatsQuery>,
 }
 
 #[derive(Deserialize)]
@@ -323,6 +402,19 @@ enum RemoteAction {
     CopyText,
     ToggleMinimized,
     SwitchLanguage,
+    PauseRecording,
+    ResumeRecording,
+    TogglePause,
+    CancelProcessing,
+    #[serde(rename = "get_conversation_state", alias = "get_state")]
+    GetConversationState,
+    ApproveToolCall,
+    DenyToolCall,
+    GetPendingToolApprovals,
+    SearchHistory,
+    GetUsageStats,
+    GetNetworkMode,
+    ToggleLocalOnly,
 }
 
 impl RemoteAction {
@@ -337,6 +429,18 @@ impl RemoteAction {
             RemoteAction::CopyText => "copy_text",
             RemoteAction::ToggleMinimized => "toggle_minimized",
             RemoteAction::SwitchLanguage => "switch_language",
+            RemoteAction::PauseRecording => "pause_recording",
+            RemoteAction::ResumeRecording => "resume_recording",
+            RemoteAction::TogglePause => "toggle_pause",
+            RemoteAction::CancelProcessing => "cancel_processing",
+            RemoteAction::GetConversationState => "get_conversation_state",
+            RemoteAction::ApproveToolCall => "approve_tool_call",
+            RemoteAction::DenyToolCall => "deny_tool_call",
+            RemoteAction::GetPendingToolApprovals => "get_pending_tool_approvals",
+            RemoteAction::SearchHistory => "search_history",
+            RemoteAction::GetUsageStats => "get_usage_stats",
+            RemoteAction::GetNetworkMode => "get_network_mode",
+            RemoteAction::ToggleLocalOnly => "toggle_local_only",
         }
     }
 }
@@ -1,9 +1,21 @@
 import React, { useCallback, useEffect, useState } from "react";
 import { ChatCompletionToolCall } from "@renderer/hooks/useConversationState";
-import { ChevronRight, FileTextIcon, ListIcon, Search, FolderPlus, Columns3, Code2, FolderSearch } from "lucide-react";
+import {
+  ChevronRight,
+  FileTextIcon,
+  ListIcon,
+  Search,
+  FolderPlus,
+  Columns3,
+  Code2,
+  FolderSearch,
+  Pencil,
+  RefreshCw,
+} from "lucide-react";
 import { useTranslation } from "react-i18next";
 import { cn } from "@renderer/utils/cn";
 import { Button } from "@renderer/components/button";
+import { Textarea } from "@renderer/components/textarea";
 import { CopyIcon, CheckIcon } from "lucide-react";
 import { useCopyToClipboard } from "usehooks-ts";
 import { Markdown } from "@renderer/components/markdown";
@@ -17,10 +29,14 @@ import {
 export function AssistantTextMessage({
   message,
   createdAt,
+  onRegenerate,
 }: {
   message: ChatCompletionMessageTextContent;
   createdAt: string | null;
+  onRegenerate?: () => void;
 }) {
+  const { t } = useTranslation("recorder");
+
   return (
     <TextMessageWrapper>
       <Markdown>{message.text}</Markdown>
@@ -29,6 +45,12 @@ export function AssistantTextMessage({
         <MessageCreatedAt>{createdAt}</MessageCreatedAt>
 
         <MessageCopyButton message={message} />
+
+        {onRegenerate ? (
+          <MessageActionButton onClick={onRegenerate} title={t("regenerateResponse")}>
+            <RefreshCw />
+          </MessageActionButton>
+        ) : null}
       </TextMessageFooter>
     </TextMessageWrapper>
   );
@@ -37,10 +59,61 @@ export function AssistantTextMessage({
 export function UserTextMessage({
   message,
   createdAt,
+  onEdit,
 }: {
   message: ChatCompletionMessageTextContent;
   createdAt: string | null;
+  onEdit?: (text: string) => void;
 }) {
+  const { t } = useTranslation("recorder");
+  const [isEditing, setIsEditing] = useState(false);
+  const [text, setText] = useState(message.text);
+
+  const startEditing = useCallback(() => {
+    setText(message.text);
+    setIsEditing(true);
+  }, [message.text]);
+
+  // Sending the edit drops everything after this message and continues from there
+  const submitEdit = useCallback(() => {
+    setIsEditing(false);
+    if (text.trim() === "" || text === message.text) return;
+    onEdit?.(text);
+  }, [text, message.text, onEdit]);
+
+  const onKeyDown = (e: React.KeyboardEvent<HTMLTextAreaElement>) => {
+    if (e.key === "Enter" && !e.shiftKey) {
+      e.preventDefault();
+      submitEdit();
+    }
+    if (e.key === "Escape") {
+      setIsEditing(false);
+    }
+  };
+
+  if (isEditing) {
+    return (
+      <TextMessageWrapper className="my-3 w-full">
+        <Textarea
+          autoFocus
+          className="min-h-[60px] text-sm"
+          value={text}
+          onChange={(e) => setText(e.target.value)}
+          onKeyDown={onKeyDown}
+        />
+
+        <TextMessageFooter className="justify-end mt-1">
+          <Button variant="ghost" size="sm" onClick={() => setIsEditing(false)}>
+            {t("cancelEdit")}
+          </Button>
+          <Button size="sm" onClick={submitEdit}>
+            {t("sendEdit")}
+          </Button>
+        </TextMessageFooter>
+      </TextMessageWrapper>
+    );
+  }
+
   return (
     <TextMessageWrapper className="my-3" key={message.text}>
       <UserMessageText className="text-gray-500 dark:text-foreground/60">{message.text}</UserMessageText>
@@ -49,6 +122,12 @@ export function UserTextMessage({
         <MessageCreatedAt>{createdAt}</MessageCreatedAt>
 
         <MessageCopyButton message={message} />
+
+        {onEdit ? (
+          <MessageActionButton onClick={startEditing} title={t("editMessage")}>
+            <Pencil />
+          </MessageActionButton>
+        ) : null}
       </TextMessageFooter>
     </TextMessageWrapper>
   );
@@ -176,6 +255,17 @@ export function resolveToolCallIcon(toolCall: ChatCompletionToolCall) {
   }
 }
 
+function MessageActionButton({ className, ...rest }: React.ButtonHTMLAttributes<HTMLButtonElement>) {
+  return (
+    <Button
+      variant="ghost"
+      size="icon"
+      className={cn("w-5 h-5 [&_svg]:size-3 text-foreground/80", className)}
+      {...rest}
+    />
+  );
+}
+
 function MessageCopyButton({ message }: { message: ChatCompletionMessageTextContent }) {
   const { t } = useTranslation();
   const [isCopied, setIsCopied] = useState(false);
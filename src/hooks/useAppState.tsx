@@ -1,7 +1,24 @@
 import { Shortcuts } from "@renderer/shared/communicator/state-machine.types";
 import { invoke } from "@tauri-apps/api/core";
-import { useStateContext, InterfaceTheme, MCPServerConfig } from "./useNewState";
+import {
+  useStateContext,
+  InterfaceTheme,
+  MCPServerConfig,
+  MCPResource,
+  BuiltinToolsConfig,
+  MCPToolPolicy,
+  ModelPrice,
+  AudioRetentionContext,
+  UsageStats,
+  UsageStatsQuery,
+} from "./useNewState";
 import { useCallback, useMemo } from "react";
+import {
+  ContextStrategy,
+  HistoryConversation,
+  HistorySearchQuery,
+  HistorySearchResult,
+} from "./useConversationState";
 
 export function useAppState() {
   const { state } = useStateContext();
@@ -30,6 +47,14 @@ export function useAppState() {
     return invokeEvent("ActionRecording");
   };
 
+  const pauseRecording = () => {
+    return invokeEvent("PauseTranscription");
+  };
+
+  const resumeRecording = () => {
+    return invokeEvent("ResumeTranscription");
+  };
+
   const togglePersonas = () => {
     return invokeEvent("ActionPersonaCycleNext");
   };
@@ -58,6 +83,18 @@ export function useAppState() {
     return invokeEvent("ActionChangeConversationModel", conversation_model);
   };
 
+  const updateFallbackTranscriptionModel = (transcription_model: string | null) => {
+    return invokeEvent("ActionChangeFallbackTranscriptionModel", transcription_model);
+  };
+
+  const updateFallbackConversationModel = (conversation_model: string | null) => {
+    return invokeEvent("ActionChangeFallbackConversationModel", conversation_model);
+  };
+
+  const updateModelPrice = (model_name: string, price: ModelPrice | null) => {
+    return invokeEvent("ActionUpdateModelPrice", { model_name, price });
+  };
+
   const updateTheme = (theme: InterfaceTheme) => {
     return invokeEvent("ActionChangeTheme", theme);
   };
@@ -66,6 +103,38 @@ export function useAppState() {
     return invokeEvent("ActionChangeOpenSettingsOnStart", openSettingsOnStart);
   };
 
+  const updateLocalOnly = (localOnly: boolean) => {
+    return invokeEvent("ActionChangeLocalOnly", localOnly);
+  };
+
+  const updateMaxToolIterations = (maxToolIterations: number) => {
+    return invokeEvent("ActionChangeMaxToolIterations", maxToolIterations);
+  };
+
+  const updateMeetingChunkMinutes = (minutes: number) => {
+    return invokeEvent("ActionChangeMeetingChunkMinutes", minutes);
+  };
+
+  const updatePreRollEnabled = (enabled: boolean) => {
+    return invokeEvent("ActionChangePreRollEnabled", enabled);
+  };
+
+  const updatePreRollMs = (durationMs: number) => {
+    return invokeEvent("ActionChangePreRollMs", durationMs);
+  };
+
+  const updateAudioRetentionSettings = (settings: AudioRetentionContext) => {
+    return invokeEvent("ActionUpdateAudioRetentionSettings", settings);
+  };
+
+  const runAudioRetention = () => {
+    return invokeEvent("ActionRunAudioRetention");
+  };
+
+  const updateContextStrategy = (strategy: ContextStrategy) => {
+    return invokeEvent("ActionChangeContextStrategy", strategy);
+  };
+
   const updateWebsocketServerSettings = (settings: { enabled: boolean; port: number; password: string }) => {
     const password = settings.password ?? "";
     return invokeEvent("ActionUpdateWebsocketServerSettings", {
@@ -75,6 +144,10 @@ export function useAppState() {
     });
   };
 
+  const updateMCPServerSettings = (settings: { enabled: boolean; port: number }) => {
+    return invokeEvent("ActionUpdateMCPServerSettings", settings);
+  };
+
   const closeSettingsWindow = () => {
     return invokeEvent("CloseSettings");
   };
@@ -99,6 +172,30 @@ export function useAppState() {
     return invokeEvent("ActionUpdateTool", tool);
   };
 
+  const importTools = (json: string) => {
+    return invokeEvent("ActionImportTools", json);
+  };
+
+  const exportTools = () => {
+    return invoke<string>("export_mcp_servers");
+  };
+
+  const getHistoryConversation = (id: string) => {
+    return invoke<HistoryConversation | null>("get_history_conversation", { id });
+  };
+
+  const searchHistory = (query: HistorySearchQuery) => {
+    return invoke<HistorySearchResult[]>("search_history", { query });
+  };
+
+  const getUsageStats = (query: UsageStatsQuery) => {
+    return invoke<UsageStats>("get_usage_stats", { query });
+  };
+
+  const clearUsageStats = () => {
+    return invokeEvent("ActionClearUsageStats");
+  };
+
   const deleteTool = (id: string) => {
     return invokeEvent("ActionDeleteTool", id);
   };
@@ -111,6 +208,22 @@ export function useAppState() {
     return invokeEvent("ActionDisableTool", id);
   };
 
+  const updateBuiltinTools = (config: BuiltinToolsConfig) => {
+    return invokeEvent("ActionUpdateBuiltinTools", config);
+  };
+
+  const updateToolPolicy = (server_id: string, tool_name: string | null, policy: MCPToolPolicy | null) => {
+    return invokeEvent("ActionChangeToolPolicy", { server_id, tool_name, policy });
+  };
+
+  const updateToolTimeout = (server_id: string, tool_name: string | null, timeout_secs: number | null) => {
+    return invokeEvent("ActionChangeToolTimeout", { server_id, tool_name, timeout_secs });
+  };
+
+  const attachMCPResource = (resource: MCPResource) => {
+    return invokeEvent("ActionAddMCPResource", { server_key: resource.server_key, uri: resource.uri });
+  };
+
   const checkOnline = () => {
     return invoke("check_online");
   };
@@ -135,6 +248,26 @@ export function useAppState() {
     return invokeEvent("ActionStartNewConversation");
   };
 
+  const cancelProcessing = () => {
+    return invokeEvent("ActionCancelProcessing");
+  };
+
+  const editMessage = (message_index: number, text: string) => {
+    return invokeEvent("ActionEditMessage", { message_index, text });
+  };
+
+  const regenerateResponse = () => {
+    return invokeEvent("ActionRegenerateResponse");
+  };
+
+  const retryFailedStep = () => {
+    return invokeEvent("ActionRetryFailedStep");
+  };
+
+  const forkHistory = (history_id: string, message_index: number) => {
+    return invokeEvent("ActionForkHistory", { history_id, message_index });
+  };
+
   const addFile = (file: File) => {
     return new Promise((resolve, reject) => {
       const reader = new FileReader();
@@ -169,11 +302,7 @@ export function useAppState() {
 
       return reversed
         .filter((conversation) => conversation.persona_name === personaName)
-        .filter((conversation) =>
-          conversation.conversation.some(
-            (message) => message.role === "user" && "content" in message && message.content.length > 0,
-          ),
-        );
+        .filter((conversation) => conversation.preview !== null);
     },
     [state],
   );
@@ -191,16 +320,30 @@ export function useAppState() {
     updateInputDevice,
     toggleRecording,
     togglePersonas,
+    pauseRecording,
+    resumeRecording,
     closeRecordingWindow,
     updateTranscriptionModel,
     updateShortcuts,
     takeScreenshot,
     copyText,
     updateConversationModel,
+    updateFallbackTranscriptionModel,
+    updateFallbackConversationModel,
+    updateModelPrice,
     toggleMinimized,
     updateTheme,
     updateOpenSettingsOnStart,
+    updateLocalOnly,
+    updateMaxToolIterations,
+    updateMeetingChunkMinutes,
+    updatePreRollEnabled,
+    updatePreRollMs,
+    updateAudioRetentionSettings,
+    runAudioRetention,
+    updateContextStrategy,
     updateWebsocketServerSettings,
+    updateMCPServerSettings,
     closeSettingsWindow,
     minimizeSettingsWindow,
     clearHistory,
@@ -210,13 +353,28 @@ export function useAppState() {
     deleteTool,
     enableTool,
     disableTool,
+    importTools,
+    exportTools,
+    updateBuiltinTools,
+    updateToolPolicy,
+    updateToolTimeout,
+    attachMCPResource,
     checkOnline,
     closeOnboardingWindow,
     getReleases,
     sendTextMessage,
     loadHistoryConversation,
+    getHistoryConversation,
+    searchHistory,
+    getUsageStats,
+    clearUsageStats,
     addFile,
     startNewConversation,
+    cancelProcessing,
+    editMessage,
+    regenerateResponse,
+    retryFailedStep,
+    forkHistory,
     getHistoryForPersona,
   };
 }
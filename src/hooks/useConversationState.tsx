@@ -2,7 +2,14 @@ import { RecordingStatus } from "@renderer/recorder/recorder.reducer";
 import { createContext, useContext, useMemo } from "react";
 import { useAppState } from "./useAppState";
 
-export type ConversationState = "Idle" | "Listening" | "Transcribing" | "Transforming" | "Error";
+export type ConversationState =
+  | "Idle"
+  | "Listening"
+  | "Paused"
+  | "Transcribing"
+  | "Transforming"
+  | "AwaitingToolApproval"
+  | "Error";
 
 export type CopyTextState = "Idle" | "Copying";
 export type ScreenshotState = "Idle" | "Screenshotting";
@@ -43,13 +50,26 @@ export interface ChatCompletionToolCallFunction {
   arguments: string;
 }
 
+export type ToolResultContent =
+  | { type: "text"; text: string }
+  | { type: "image"; data: string; mime_type: string }
+  | { type: "resource"; uri: string; mime_type: string | null; text: string | null };
+
 export interface ChatCompletionToolCallResultMessage {
   role: "tool";
   tool_call_id: string;
   content: string;
+  parts?: ToolResultContent[];
+  is_error?: boolean;
   created_at: string;
 }
 
+export interface ToolApprovalRequest {
+  tool_call: ChatCompletionToolCall;
+  requested_at: string;
+  expires_at: string;
+}
+
 export type HistoryConversationMessage = ChatCompletionMessage & {
   audio_file_path: string | null;
 };
@@ -61,6 +81,52 @@ export interface HistoryConversation {
   model_name: string;
   persona_name: string;
   title: string | null;
+  parent: HistoryParent | null;
+}
+
+export interface HistoryParent {
+  id: string;
+  // Number of messages shared with the parent entry
+  message_count: number;
+}
+
+export interface HistorySummary {
+  id: string;
+  title: string | null;
+  persona_name: string | null;
+  model_name: string;
+  created_at: string;
+  message_count: number;
+  preview: string | null;
+  has_audio: boolean;
+  parent: HistoryParent | null;
+}
+
+export interface HistorySearchQuery {
+  text?: string;
+  persona_name?: string | null;
+  model_name?: string | null;
+  from?: string | null;
+  to?: string | null;
+  has_audio?: boolean | null;
+  limit?: number | null;
+}
+
+export type HistorySearchResult = HistorySummary & {
+  // Matched words are wrapped in <mark>
+  snippet: string;
+};
+
+export type ContextStrategy = "drop_oldest" | "summarize" | "strip_images";
+
+// A step that failed and can be run again, the recording is kept for a while
+export type FailedStep = { step: "transcription"; audio_file_path: string } | { step: "transformation" };
+
+export interface ContextUsage {
+  // Estimated for the whole conversation, before anything was left out
+  tokens: number;
+  limit: number;
+  compacted_messages: number;
 }
 
 export interface ConversationContext {
@@ -72,6 +138,15 @@ export interface ConversationContext {
   state: ConversationState;
   copy_text_state: CopyTextState;
   screenshot_state: ScreenshotState;
+  pending_tool_approvals: ToolApprovalRequest[];
+  voice_input_requested: boolean;
+  meeting_chunk_minutes: number;
+  pre_roll_enabled: boolean;
+  pre_roll_ms: number;
+  max_tool_iterations: number;
+  context_strategy: ContextStrategy;
+  context_usage: ContextUsage | null;
+  failed_step: FailedStep | null;
 }
 
 export interface ConversationStateMachine {
@@ -87,11 +162,18 @@ export function useConversationState() {
       return "recording";
     }
 
+    if (state?.context.conversation_context.state === "Paused") {
+      return "paused";
+    }
+
     if (state?.context.conversation_context.state === "Transcribing") {
       return "transcribing";
     }
 
-    if (state?.context.conversation_context.state === "Transforming") {
+    if (
+      state?.context.conversation_context.state === "Transforming" ||
+      state?.context.conversation_context.state === "AwaitingToolApproval"
+    ) {
       return "transforming";
     }
 
@@ -40,18 +40,21 @@ export interface TranscriptionModel {
   intelligence: number;
 }
 
+export type LlmProvider = "openai" | "anthropic" | "ollama" | "gemini";
+
+export interface ModelConfig {
+  url: string;
+  model: string;
+  api_key?: string;
+  supports_vision: boolean;
+  supports_tools: boolean;
+}
+
 export interface ConversationModel {
   name: string;
   model: string;
-  config: {
-    openai: {
-      url: string;
-      model: string;
-      api_key?: string;
-      supports_vision: boolean;
-      supports_tools: boolean;
-    };
-  };
+  // Keyed by the provider, only one is set
+  config: Partial<Record<LlmProvider, ModelConfig>>;
   repository: string;
   vision: VisionModel | null;
   supports_tools: boolean;
@@ -63,12 +66,19 @@ export interface ConversationModel {
   is_local: boolean;
   speed: number;
   intelligence: number;
+  // Tokens the model accepts in one request
+  context_length: number;
 }
 
 export function isConversationModel(model: TranscriptionModel | ConversationModel): model is ConversationModel {
   return "vision" in model;
 }
 
+export function getModelConfig(model: ConversationModel): ModelConfig & { provider: LlmProvider } {
+  const [provider, config] = Object.entries(model.config)[0] as [LlmProvider, ModelConfig];
+  return { ...config, provider };
+}
+
 export interface VisionModel {
   name: string;
   repository: string;
@@ -88,7 +98,13 @@ export function useModelsState(): {
   deleteTranscriptionModel: (modelId: string) => Promise<void>;
   downloadConversationModel: (modelId: string) => Promise<void>;
   deleteConversationModel: (modelId: string) => Promise<void>;
-  addConversationModel: (model: { model: string; url: string; api_key?: string }) => Promise<void>;
+  addConversationModel: (model: {
+    model: string;
+    url: string;
+    api_key?: string;
+    context_length?: number;
+    provider?: LlmProvider;
+  }) => Promise<void>;
   updateConversationModel: (model: {
     original_model: string;
     model: string;
@@ -96,6 +112,8 @@ export function useModelsState(): {
     api_key?: string;
     supports_tools: boolean;
     supports_vision: boolean;
+    context_length: number;
+    provider: LlmProvider;
   }) => Promise<void>;
   deleteCustomConversationModel: (modelId: string) => Promise<void>;
   refetchConversationModels: () => Promise<void>;
@@ -118,7 +136,13 @@ export function useModelsState(): {
     return invokeEvent<void>("ActionDeleteConversationModel", modelId);
   };
 
-  const addConversationModel = (model: { model: string; url: string; api_key?: string }) => {
+  const addConversationModel = (model: {
+    model: string;
+    url: string;
+    api_key?: string;
+    context_length?: number;
+    provider?: LlmProvider;
+  }) => {
     return invokeEvent<void>("ActionAddConversationModel", model);
   };
 
@@ -129,6 +153,8 @@ export function useModelsState(): {
     api_key?: string;
     supports_tools: boolean;
     supports_vision: boolean;
+    context_length: number;
+    provider: LlmProvider;
   }) => {
     return invokeEvent<void>("ActionUpdateConversationModel", model);
   };
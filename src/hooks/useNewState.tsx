@@ -3,7 +3,7 @@ import { Channel, invoke } from "@tauri-apps/api/core";
 import { createContext, useContext, useEffect, useRef, useState } from "react";
 import { Persona, PersonasContext } from "./usePersonas";
 import { ModelsContext } from "./useModelsState";
-import { ConversationContext, HistoryConversation } from "./useConversationState";
+import { ConversationContext, HistorySummary } from "./useConversationState";
 import { Operation } from "fast-json-patch";
 import { JsonPatch } from "@renderer/shared/json-patch";
 
@@ -14,8 +14,12 @@ export interface AppStateContext {
   interface_language: string;
   preferred_languages: string[];
   input_device: string | null;
+  input_devices: InputDevice[];
   transcription_model: string | null;
   conversation_model: string | null;
+  fallback_transcription_model: string | null;
+  fallback_conversation_model: string | null;
+  model_prices: Record<string, ModelPrice>;
   active_persona: Persona | null;
   recording_window_context: RecordingWindowContext;
   models_context: ModelsContext;
@@ -31,7 +35,17 @@ export interface AppStateContext {
   challenge_context: ChallengeContext;
   mcp_context: MCPContext;
   websocket_server_context: WebsocketServerContext;
+  mcp_server_context: MCPServerContext;
   releases_context: ReleasesContext;
+  connectivity_context: ConnectivityContext;
+  audio_retention_context: AudioRetentionContext;
+}
+
+export interface InputDevice {
+  id: string;
+  name: string;
+  // The name, numbered when several devices share it
+  label: string;
 }
 
 export interface Release {
@@ -46,8 +60,57 @@ export interface ReleasesContext {
   releases: Release[];
 }
 
+export interface ModelPrice {
+  input_per_million_tokens: number;
+  output_per_million_tokens: number;
+  audio_per_minute: number;
+}
+
+export interface UsageStatsQuery {
+  // Local days as YYYY-MM-DD, both included
+  from?: string | null;
+  to?: string | null;
+  model_name?: string | null;
+  persona_name?: string | null;
+}
+
+export interface UsageStatsRow {
+  day: string;
+  model_name: string;
+  persona_name: string | null;
+  requests: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  audio_seconds: number;
+  cost: number;
+}
+
+export interface UsageStats {
+  rows: UsageStatsRow[];
+  prompt_tokens: number;
+  completion_tokens: number;
+  audio_seconds: number;
+  cost: number;
+}
+
 type LocalTool = { Local: { command: string; env_vars: Record<string, string> } };
-type ExternalTool = { External: { url: string } };
+export type MCPExternalTransport = "Sse" | "StreamableHttp";
+
+export type MCPExternalAuth = "None" | "Bearer" | { ApiKey: { header: string } };
+
+type ExternalTool = {
+  External: {
+    url: string;
+    transport?: MCPExternalTransport;
+    headers?: Record<string, string>;
+    auth?: MCPExternalAuth;
+    // Write only, the backend moves it to the system keychain
+    auth_secret?: string;
+    request_timeout_secs?: number;
+    reconnect_attempts?: number;
+    reconnect_delay_secs?: number;
+  };
+};
 
 export type MCPServerKind = LocalTool | ExternalTool;
 
@@ -63,6 +126,8 @@ export function isExternalTool(tool?: MCPServerKind): tool is ExternalTool {
 
 export type MCPServerState = "Disabled" | "Starting" | "Enabled" | "Stopping" | "Error";
 
+export type MCPToolPolicy = "Allow" | "Ask" | "Deny";
+
 export interface MCPServerConfig {
   id: string;
   name: string;
@@ -71,10 +136,49 @@ export interface MCPServerConfig {
   kind: MCPServerKind;
   enabled: boolean;
   state: MCPServerState;
+  tool_policy?: MCPToolPolicy;
+  tool_policies?: Record<string, MCPToolPolicy>;
+  tool_timeout_secs?: number;
+  tool_timeouts?: Record<string, number>;
+}
+
+export interface MCPServerTool {
+  name: string;
+  description: string | null;
+}
+
+export interface MCPPromptArgument {
+  name: string;
+  description: string | null;
+  required: boolean;
+}
+
+export interface MCPPrompt {
+  server_key: string;
+  name: string;
+  description: string | null;
+  arguments: MCPPromptArgument[];
+}
+
+export interface MCPResource {
+  server_key: string;
+  uri: string;
+  name: string;
+  description: string | null;
+  mime_type: string | null;
+}
+
+export interface BuiltinToolsConfig {
+  enabled: boolean;
+  tool_policies: Record<string, MCPToolPolicy>;
+  notes_file: string | null;
 }
 
 export interface MCPContext {
   server_configs: MCPServerConfig[];
+  prompts?: MCPPrompt[];
+  resources?: MCPResource[];
+  builtin_tools?: BuiltinToolsConfig;
 }
 
 export interface ChallengeContext {
@@ -144,7 +248,7 @@ export interface AppError {
 }
 
 export interface HistoryContext {
-  history: HistoryConversation[];
+  history: HistorySummary[];
 }
 
 export interface AccountContext {
@@ -185,6 +289,30 @@ export interface WebsocketServerContext {
   password: string | null;
 }
 
+export interface MCPServerContext {
+  enabled: boolean;
+  port: number;
+  token: string;
+}
+
+export type AudioRetentionFormat = "Wav" | "Flac" | "Opus";
+
+// Null limits keep recordings forever
+export interface AudioRetentionContext {
+  format: AudioRetentionFormat;
+  max_age_days: number | null;
+  max_size_mb: number | null;
+  remove_orphans: boolean;
+}
+
+export type NetworkMode = "online" | "offline" | "local_only";
+
+export interface ConnectivityContext {
+  online: boolean;
+  local_only: boolean;
+  mode: NetworkMode;
+}
+
 export type RecordingWindowState =
   | {
       Open: RecordingWindowView;
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { useStateContext } from "./useNewState";
+import { MCPPrompt, useStateContext } from "./useNewState";
 
 export interface PersonaExample {
   question: string;
@@ -19,21 +19,66 @@ export interface Persona {
   paste_on_finish: boolean;
   icon: string | null;
   record_output_audio: boolean;
+  meeting_mode?: boolean;
   examples: PersonaExample[];
+  tools?: PersonaTools;
+  generation?: GenerationParams;
+  structured_output?: StructuredOutput | null;
+  cloud_only?: boolean;
 }
 
+// Unset values are left to the model's defaults
+export interface GenerationParams {
+  temperature: number | null;
+  top_p: number | null;
+  max_tokens: number | null;
+  stop: string[];
+}
+
+// The answer is checked against the schema, then formatted with the template and sent to the webhook
+export interface StructuredOutput {
+  schema: Record<string, unknown>;
+  template: string;
+  webhook_url: string | null;
+}
+
+// Patterns are a server key or `server--tool`, both may contain `*`
+export type PersonaTools = "All" | "Disabled" | { Only: string[] };
+
 export interface PersonasStateMachine {
   context: PersonasContext;
 }
 
+export function getMCPPromptPersonaId(prompt: MCPPrompt) {
+  return `mcp-prompt:${prompt.server_key}:${prompt.name}`;
+}
+
 export function usePersonas() {
   const { state } = useStateContext();
 
+  // Prompts with required arguments can't be used without asking for them first
+  const mcpPrompts = (state?.mcp_context.prompts ?? []).filter((prompt) =>
+    prompt.arguments.every((argument) => !argument.required),
+  );
+
   const changePersona = (personaId: string | null) => {
+    const prompt = mcpPrompts.find((prompt) => getMCPPromptPersonaId(prompt) === personaId);
+    if (prompt) {
+      return selectMCPPrompt(prompt);
+    }
+
     const persona = state?.personas_context.personas.find((persona) => persona.id === personaId) ?? null;
     return invokeEvent("ActionChangePersona", persona);
   };
 
+  const selectMCPPrompt = (prompt: MCPPrompt, args: Record<string, string> = {}) => {
+    return invokeEvent("ActionSelectMCPPrompt", {
+      server_key: prompt.server_key,
+      name: prompt.name,
+      arguments: args,
+    });
+  };
+
   const addPersona = (persona: Omit<Persona, "id">) => {
     return invokeEvent("ActionAddPersona", persona);
   };
@@ -54,9 +99,11 @@ export function usePersonas() {
     state: state
       ? {
           context: state.personas_context,
+          mcpPrompts,
         }
       : null,
     changePersona,
+    selectMCPPrompt,
     addPersona,
     updatePersona,
     deletePersona,
@@ -1,25 +1,9 @@
-import { useEffect, useState } from "react";
-import { invoke } from "@tauri-apps/api/core";
-
-export type InputDevice = {
-  name: string;
-  label: string;
-};
+import { useAppState } from "@renderer/hooks/useAppState";
 
+// The app watches the devices, so the list stays current while a window is open
 export function useInputDevices() {
-  const [devices, setDevices] = useState<InputDevice[]>([]);
-
-  useEffect(() => {
-    const getAvailableMicrophones = async () => {
-      try {
-        const devices: string[] = await invoke("get_audio_devices");
-
-        setDevices(devices.map((device) => ({ name: device, label: device })));
-      } catch (err) {}
-    };
-
-    getAvailableMicrophones();
-  }, []);
+  const { state } = useAppState();
+  const devices = state?.context.input_devices ?? [];
 
   return { devices };
 }
@@ -50,14 +50,14 @@ export function MediaSelect() {
           >
             {devices.map((device, index) => (
               <motion.label
-                key={device.name}
+                key={device.id}
                 initial={{ opacity: 0, y: 20 }}
                 animate={{ opacity: 1, y: 0 }}
                 transition={{ delay: 0.1 + index * 0.05 }}
                 className="relative"
               >
                 <RadioGroupItem
-                  value={device.name}
+                  value={device.id}
                   className="peer absolute top-1/2 left-3 -translate-y-1/2 text-yellow-500 border-yellow-500"
                 />
                 <div className="flex items-center gap-2 border w-full h-fit py-3 pr-3 pl-9 rounded-lg text-sm cursor-pointer peer-data-[state=checked]:bg-yellow-500/10 peer-data-[state=checked]:border-yellow-500">
@@ -0,0 +1,48 @@
+import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@renderer/components/tooltip";
+import { ContextUsage } from "@renderer/hooks/useConversationState";
+import { cn } from "@renderer/utils/cn";
+import { useTranslation } from "react-i18next";
+
+// Requests are kept under this part of the window, older messages are compacted past it
+const CONTEXT_USAGE_LIMIT = 0.8;
+
+export function ContextUsageIndicator({ usage }: { usage: ContextUsage | null }) {
+  const { t } = useTranslation();
+
+  if (!usage || usage.limit === 0) return null;
+
+  const percentage = Math.round((usage.tokens / usage.limit) * 100);
+  const isOverLimit = usage.tokens > usage.limit * CONTEXT_USAGE_LIMIT;
+
+  return (
+    <TooltipProvider>
+      <Tooltip>
+        <TooltipTrigger asChild>
+          <span
+            className={cn("text-xs tabular-nums text-muted-foreground/70 px-1 cursor-default", {
+              "text-destructive": isOverLimit,
+            })}
+          >
+            {percentage}%
+          </span>
+        </TooltipTrigger>
+
+        <TooltipContent>
+          <div className="flex flex-col gap-0.5">
+            <span>
+              {t("contextUsage", {
+                tokens: usage.tokens.toLocaleString(),
+                limit: usage.limit.toLocaleString(),
+              })}
+            </span>
+            {usage.compacted_messages > 0 ? (
+              <span className="text-muted-foreground/70">
+                {t("contextCompactedMessages", { count: usage.compacted_messages })}
+              </span>
+            ) : null}
+          </div>
+        </TooltipContent>
+      </Tooltip>
+    </TooltipProvider>
+  );
+}
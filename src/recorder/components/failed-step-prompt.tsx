@@ -0,0 +1,29 @@
+import { Button } from "@renderer/components/button";
+import { FailedStep } from "@renderer/hooks/useConversationState";
+import { useAppState } from "@renderer/hooks/useAppState";
+import { RotateCcw } from "lucide-react";
+import { motion } from "motion/react";
+import { useTranslation } from "react-i18next";
+
+export function FailedStepPrompt({ failedStep }: { failedStep: FailedStep }) {
+  const { t } = useTranslation();
+  const { retryFailedStep } = useAppState();
+
+  return (
+    <motion.div
+      initial={{ opacity: 0, y: 3 }}
+      animate={{ opacity: 1, y: 0 }}
+      exit={{ opacity: 0, y: -3 }}
+      transition={{ duration: 0.2 }}
+      className="mx-3 mb-2 flex items-center justify-between gap-2 rounded-xl border border-input bg-background-surface p-3 no-drag"
+    >
+      <span className="text-sm text-muted-foreground">
+        {failedStep.step === "transcription" ? t("transcriptionFailed") : t("transformationFailed")}
+      </span>
+      <Button size="xs" onClick={retryFailedStep}>
+        <RotateCcw />
+        {t("retryFailedStep")}
+      </Button>
+    </motion.div>
+  );
+}
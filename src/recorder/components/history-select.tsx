@@ -1,10 +1,6 @@
 import { Select, SelectContent, SelectGroup, SelectItem, SelectLabel } from "@renderer/components/select";
 import { Clock } from "lucide-react";
-import {
-  ChatCompletionTextMessage,
-  HistoryConversation,
-  isTextMessageContent,
-} from "@renderer/hooks/useConversationState";
+import { HistorySummary } from "@renderer/hooks/useConversationState";
 import { useTranslation } from "react-i18next";
 import { useAppState } from "@renderer/hooks/useAppState";
 import { useMemo } from "react";
@@ -71,18 +67,12 @@ export function HistorySelect() {
   );
 }
 
-function HistorySelectItem({ conversation }: { conversation: HistoryConversation }) {
-  const firstUserMessage = conversation.conversation.find(
-    (message) => message.role === "user",
-  ) as ChatCompletionTextMessage;
-
-  if (!firstUserMessage) return null;
+function HistorySelectItem({ conversation }: { conversation: HistorySummary }) {
+  if (!conversation.preview) return null;
 
   return (
     <SelectItem value={conversation.id} indicator={false} className="max-w-[230px] line-clamp-1 leading-[24px]">
-      {conversation.title ??
-        firstUserMessage.content.find((content) => isTextMessageContent(content))?.text ??
-        "No messages"}
+      {conversation.title ?? conversation.preview}
     </SelectItem>
   );
 }
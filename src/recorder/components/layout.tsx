@@ -7,18 +7,24 @@ import { useIsomorphicLayoutEffect } from "usehooks-ts";
 import { useRecordingWindowState } from "@renderer/hooks/useRecordingWindowState";
 import { useAppState } from "@renderer/hooks/useAppState";
 import { Button } from "@renderer/components/button";
-import { Maximize2, Minus, Wifi, X } from "lucide-react";
+import { HardDrive, Maximize2, Minus, Wifi, WifiOff, X } from "lucide-react";
 import { cn } from "@renderer/utils/cn";
-import { useInternetConnection } from "@renderer/hooks/useInternetConnection";
 import { HistorySelect } from "./history-select";
 import { NewChatButton } from "./new-chat-button";
 import { LanguageSelect } from "./language-select";
 import { ShiningLogo } from "./shining-logo";
+import { NetworkMode } from "@renderer/hooks/useNewState";
+
+const NETWORK_MODE_LABELS: Record<NetworkMode, string> = {
+  online: "connected",
+  offline: "disconnected",
+  local_only: "localOnly",
+};
 
 export function Layout({ children }: { children: React.ReactNode }) {
   const { state: recordingWindowState } = useRecordingWindowState();
-  const { closeRecordingWindow, toggleMinimized } = useAppState();
-  const [online] = useInternetConnection();
+  const { state, closeRecordingWindow, toggleMinimized } = useAppState();
+  const mode = state?.context.connectivity_context.mode ?? "online";
 
   const { t } = useTranslation();
 
@@ -75,13 +81,14 @@ export function Layout({ children }: { children: React.ReactNode }) {
             className={cn(
               "mb-1 [&>svg]:size-3.5 p-0 pointer-events-auto rounded-full !bg-transparent border-transparent",
               {
-                "text-green-500": online,
-                "text-red-400": !online,
+                "text-green-500": mode === "online",
+                "text-red-400": mode === "offline",
+                "text-yellow-500": mode === "local_only",
               },
             )}
-            title={online ? t("connected") : t("disconnected")}
+            title={t(NETWORK_MODE_LABELS[mode])}
           >
-            <Wifi />
+            {mode === "online" ? <Wifi /> : mode === "offline" ? <WifiOff /> : <HardDrive />}
           </Button>
         </div>
       </div>
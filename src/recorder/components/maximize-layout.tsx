@@ -21,6 +21,10 @@ import { getShortcut } from "@renderer/utils/shortcut";
 import { Outlet } from "react-router";
 import { PersonaFooterButton } from "./personas-select";
 import { RecorderStatusIndicator } from "./recorder-status-indicator";
+import { ToolApprovalPrompt } from "./tool-approval-prompt";
+import { MCPResourceSelect } from "./mcp-resource-select";
+import { ContextUsageIndicator } from "./context-usage-indicator";
+import { FailedStepPrompt } from "./failed-step-prompt";
 import { AnimatePresence } from "motion/react";
 
 export function MaximizeLayout() {
@@ -71,6 +75,21 @@ export function MaximizeLayout() {
         <Outlet />
       </RecorderBody>
 
+      <AnimatePresence>
+        {state.conversation_context.pending_tool_approvals?.[0] ? (
+          <ToolApprovalPrompt
+            key={state.conversation_context.pending_tool_approvals[0].tool_call.id}
+            request={state.conversation_context.pending_tool_approvals[0]}
+          />
+        ) : null}
+      </AnimatePresence>
+
+      <AnimatePresence>
+        {state.conversation_context.failed_step && state.conversation_context.state === "Idle" ? (
+          <FailedStepPrompt failedStep={state.conversation_context.failed_step} />
+        ) : null}
+      </AnimatePresence>
+
       <RecorderFooter status={recordingStatus} ref={footerRef}>
         <RecorderFooterSection className="border-y border-y-background-surface-high dark:border-y-background-surface-high py-0.5 h-9">
           <div className="flex items-center gap-2">
@@ -88,7 +107,9 @@ export function MaximizeLayout() {
           </div>
 
           <div className="flex items-center gap-1">
+            <ContextUsageIndicator usage={state.conversation_context.context_usage} />
             <FileUploadButton onChange={addFile} />
+            <MCPResourceSelect disabled={recordingStatus === "transcribing" || recordingStatus === "transforming"} />
 
             {state.active_persona && (
               <FooterButton
@@ -0,0 +1,41 @@
+import { Select, SelectContent, SelectItem } from "@renderer/components/select";
+import { useAppState } from "@renderer/hooks/useAppState";
+import * as SelectPrimitive from "@radix-ui/react-select";
+import { Paperclip } from "lucide-react";
+import { useTranslation } from "react-i18next";
+import { FooterButton } from "./recorder.components";
+
+export function MCPResourceSelect({ disabled }: { disabled?: boolean }) {
+  const { t } = useTranslation();
+  const { state, attachMCPResource } = useAppState();
+
+  const resources = state?.context.mcp_context.resources ?? [];
+  if (resources.length === 0) return null;
+
+  const onValueChange = (value: string) => {
+    const resource = resources.find((resource) => `${resource.server_key}:${resource.uri}` === value);
+    if (resource) {
+      attachMCPResource(resource);
+    }
+  };
+
+  return (
+    <Select onValueChange={onValueChange} value="" disabled={disabled}>
+      <SelectPrimitive.Trigger asChild>
+        <FooterButton className="w-fit [&>svg]:size-4 [&>svg]:text-teal" tooltip={t("attachResource")}>
+          <Paperclip />
+        </FooterButton>
+      </SelectPrimitive.Trigger>
+      <SelectContent>
+        {resources.map((resource) => (
+          <SelectItem
+            key={`${resource.server_key}:${resource.uri}`}
+            value={`${resource.server_key}:${resource.uri}`}
+          >
+            {resource.name} <span className="text-muted-foreground">({resource.server_key})</span>
+          </SelectItem>
+        ))}
+      </SelectContent>
+    </Select>
+  );
+}
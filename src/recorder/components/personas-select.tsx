@@ -1,7 +1,7 @@
 import { Label } from "@renderer/components/label";
 import { RadioGroup, RadioGroupItem } from "@renderer/components/radio-group";
 import { useAppState } from "@renderer/hooks/useAppState";
-import { usePersonas } from "@renderer/hooks/usePersonas";
+import { getMCPPromptPersonaId, usePersonas } from "@renderer/hooks/usePersonas";
 import { Persona, PersonasState } from "@renderer/shared/types";
 import { cn } from "@renderer/utils/cn";
 import * as SelectPrimitive from "@radix-ui/react-select";
@@ -20,7 +20,7 @@ export interface PersonasSelectProps {
 export function PersonasSelect({ personas, onSelectPersona }: PersonasSelectProps) {
   const ref = useRef<HTMLButtonElement>(null);
   const [selectedPersona, setSelectedPersona] = useState<string | null>(
-    personas.currentPersonaId ? personas.personas.find((p) => p.id === personas.currentPersonaId)!.id : null,
+    personas.personas.find((p) => p.id === personas.currentPersonaId)?.id ?? null,
   );
 
   const onValueChange = (value: string | null) => {
@@ -70,7 +70,7 @@ export function PersonasSelect({ personas, onSelectPersona }: PersonasSelectProp
 
   useEffect(() => {
     setSelectedPersona(
-      personas.currentPersonaId ? personas.personas.find((p) => p.id === personas.currentPersonaId)!.id : null,
+      personas.personas.find((p) => p.id === personas.currentPersonaId)?.id ?? null,
     );
   }, [personas.currentPersonaId]);
 
@@ -163,8 +163,7 @@ export function PersonaFooterButton({ status }: { status: RecordingStatus }) {
         <div className="flex items-center gap-1 text-foreground py-0.5 px-2 bg-teal/10 dark:bg-teal/10 rounded-full border border-teal/20 hover:bg-teal/20 dark:hover:bg-teal/20">
           <RecordStatus status={status} className="w-1.5 h-1.5 mr-1" />
           <div className="text-xs text-teal font-medium">
-            {personasState?.context.personas.find((persona) => persona.id === appState?.context.active_persona?.id)
-              ?.name ?? t("noPersona")}
+            {appState?.context.active_persona?.name ?? t("noPersona")}
           </div>
 
           <SelectPrimitive.Icon asChild>
@@ -182,6 +181,12 @@ export function PersonaFooterButton({ status }: { status: RecordingStatus }) {
             {persona.name}
           </SelectItem>
         ))}
+
+        {personasState?.context.mcpPrompts.map((prompt) => (
+          <SelectItem key={getMCPPromptPersonaId(prompt)} value={getMCPPromptPersonaId(prompt)}>
+            {prompt.name} <span className="text-muted-foreground">({prompt.server_key})</span>
+          </SelectItem>
+        ))}
       </SelectContent>
     </Select>
   );
@@ -26,6 +26,18 @@ export function RecorderStatusIndicator({ status, model }: { status: RecordingSt
           </motion.div>
         ) : null}
 
+        {status === "paused" ? (
+          <motion.div
+            key="paused"
+            initial={{ opacity: 0, y: 3, filter: "blur(2px)" }}
+            animate={{ opacity: 1, y: 0, filter: "blur(0px)" }}
+            exit={{ opacity: 0, y: -3, filter: "blur(2px)" }}
+            transition={{ duration: 0.2 }}
+          >
+            <PausedStatusText />
+          </motion.div>
+        ) : null}
+
         {status === "transcribing" ? (
           <motion.div
             key="transcribing"
@@ -75,6 +87,10 @@ function RecordingStatusText() {
   );
 }
 
+function PausedStatusText() {
+  return <MessageStatusText>Paused</MessageStatusText>;
+}
+
 function TransformingStatusText({ model }: { model?: string | null }) {
   if (!model)
     return (
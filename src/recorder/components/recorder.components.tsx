@@ -3,15 +3,10 @@ import { cn } from "@renderer/utils/cn";
 import { forwardRef, useMemo, useState } from "react";
 import { RecordingStatus } from "../recorder.reducer";
 import { Tooltip, TooltipTrigger, TooltipContent, TooltipProvider } from "@renderer/components/tooltip";
-import {
-  HistoryConversation,
-  ChatCompletionTextMessage,
-  useConversationContext,
-  isTextMessageContent,
-} from "@renderer/hooks/useConversationState";
+import { HistorySummary, useConversationContext } from "@renderer/hooks/useConversationState";
 import { useAppState } from "@renderer/hooks/useAppState";
 import { getShortcut } from "@renderer/utils/shortcut";
-import { ArrowUp, CircleStop, MessageSquareText, Mic, Upload } from "lucide-react";
+import { ArrowUp, CircleStop, MessageSquareText, Mic, Pause, Play, Square, Upload } from "lucide-react";
 import { useTranslation } from "react-i18next";
 import { Visualizer } from "./visualizer";
 import { errorToast } from "@renderer/components/toasts";
@@ -148,6 +143,7 @@ export function RecordStatus({
         "w-1.5 h-1.5 relative rounded-full after:content-[''] after:absolute after:inset-0 after:rounded-full",
         {
           "bg-red-400 after:animate-ping after:bg-red-400": status === "recording",
+          "bg-yellow-500": status === "paused",
           "bg-blue-500 after:animate-ping after:bg-blue-500": status === "transcribing" || status === "transforming",
           "bg-green-500 shadow-xl inset-shadow-green-500": status === "finished" || status === "idle",
         },
@@ -184,12 +180,15 @@ export function RecorderInput({
 
 export function RecorderInputForm() {
   const { recordingStatus } = useConversationContext();
-  const { toggleRecording, state, sendTextMessage } = useAppState();
+  const { toggleRecording, pauseRecording, resumeRecording, state, sendTextMessage, cancelProcessing } =
+    useAppState();
   const { t } = useTranslation();
 
   const [inputValue, setInputValue] = useState("");
 
   const isInputEmpty = inputValue.trim() === "";
+  const isProcessing = recordingStatus === "transforming" || recordingStatus === "transcribing";
+  const isRecording = recordingStatus === "recording" || recordingStatus === "paused";
 
   const onSubmit = (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault();
@@ -212,27 +211,48 @@ export function RecorderInputForm() {
 
   return (
     <form onSubmit={onSubmit} className="h-full w-full flex items-center gap-2">
-      {recordingStatus === "recording" ? (
+      {isRecording ? (
         <Visualizer state={recordingStatus === "recording" ? "active" : "inactive"} size="sm" className="ml-2" />
       ) : (
         <RecorderInput status={recordingStatus} value={inputValue} onChange={onInputChange} />
       )}
 
-      {isInputEmpty ? (
+      {isRecording ? (
+        <FooterButton
+          variant="outline"
+          type="button"
+          onClick={recordingStatus === "paused" ? resumeRecording : pauseRecording}
+          className="w-8 h-8 [&>svg]:size-3.5"
+          tooltip={recordingStatus === "paused" ? t("resume") : t("pause")}
+        >
+          {recordingStatus === "paused" ? <Play /> : <Pause />}
+        </FooterButton>
+      ) : null}
+
+      {isProcessing ? (
+        <FooterButton
+          variant="outline"
+          type="button"
+          onClick={cancelProcessing}
+          className="w-8 h-8 [&>svg]:size-3 [&>svg]:fill-current"
+          tooltip={t("cancelProcessing")}
+        >
+          <Square />
+        </FooterButton>
+      ) : isInputEmpty ? (
         <FooterButton
           variant="default"
           type="button"
           onClick={toggleRecording}
-          disabled={recordingStatus === "transforming" || recordingStatus === "transcribing"}
           className="w-8 h-8 [&>svg]:size-4 text-white dark:text-foreground"
           tooltip={
             <>
-              {recordingStatus === "recording" ? t("stop") : t("record")}:{" "}
+              {isRecording ? t("stop") : t("record")}:{" "}
               {getShortcut(state?.context.shortcuts.recording).getKeysIcons()}
             </>
           }
         >
-          {recordingStatus === "recording" ? <CircleStop /> : <Mic />}
+          {isRecording ? <CircleStop /> : <Mic />}
         </FooterButton>
       ) : (
         <FooterButton type="submit" className="w-8 [&>svg]:size-3.5" variant="outline">
@@ -309,12 +329,8 @@ export function RecorderPreviousConversationItem({
   className,
   conversation,
   ...rest
-}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistoryConversation }) {
-  const firstUserMessage = conversation.conversation.find(
-    (message) => message.role === "user",
-  ) as ChatCompletionTextMessage;
-
-  if (!firstUserMessage) return null;
+}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistorySummary }) {
+  if (!conversation.preview) return null;
 
   return (
     <div
@@ -323,9 +339,7 @@ export function RecorderPreviousConversationItem({
     >
       <MessageSquareText className="size-3.5 mt-0.5 shrink-0" />
       <div className="text-sm line-clamp-1">
-        {conversation.title ??
-          firstUserMessage.content.find((content) => isTextMessageContent(content))?.text ??
-          "No messages"}
+        {conversation.title ?? conversation.preview}
       </div>
     </div>
   );
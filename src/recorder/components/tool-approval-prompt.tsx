@@ -0,0 +1,38 @@
+import { Button } from "@renderer/components/button";
+import { invokeEvent } from "@renderer/hooks/useAppState";
+import { ToolApprovalRequest } from "@renderer/hooks/useConversationState";
+import { Check, X } from "lucide-react";
+import { motion } from "motion/react";
+import { useTranslation } from "react-i18next";
+
+export function ToolApprovalPrompt({ request }: { request: ToolApprovalRequest }) {
+  const { t } = useTranslation();
+  const { id, function: toolFunction } = request.tool_call;
+
+  return (
+    <motion.div
+      initial={{ opacity: 0, y: 3 }}
+      animate={{ opacity: 1, y: 0 }}
+      exit={{ opacity: 0, y: -3 }}
+      transition={{ duration: 0.2 }}
+      className="mx-3 mb-2 flex flex-col gap-2 rounded-xl border border-input bg-background-surface p-3 no-drag"
+    >
+      <span className="text-sm font-medium">
+        {t("toolApprovalTitle", { tool: toolFunction.name, server: toolFunction.client_name })}
+      </span>
+      <pre className="max-h-24 overflow-auto whitespace-pre-wrap break-all text-xs text-muted-foreground scrollbar-custom">
+        {toolFunction.arguments}
+      </pre>
+      <div className="flex justify-end gap-2">
+        <Button size="xs" variant="outline" onClick={() => invokeEvent("ActionDenyToolCall", id)}>
+          <X />
+          {t("denyToolCall")}
+        </Button>
+        <Button size="xs" onClick={() => invokeEvent("ActionApproveToolCall", id)}>
+          <Check />
+          {t("approveToolCall")}
+        </Button>
+      </div>
+    </motion.div>
+  );
+}
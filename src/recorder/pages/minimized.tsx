@@ -3,7 +3,7 @@ import { useAppState } from "@renderer/hooks/useAppState";
 import { useConversationContext } from "@renderer/hooks/useConversationState";
 import { usePersonas } from "@renderer/hooks/usePersonas";
 import { useRecordingWindowState } from "@renderer/hooks/useRecordingWindowState";
-import { Minimize2, Play, Pause, Loader2 } from "lucide-react";
+import { Minimize2, Play, Pause, Loader2, Square } from "lucide-react";
 import { cn } from "@renderer/utils/cn";
 import { Maximize2 } from "lucide-react";
 import { useAudioDataContext } from "@renderer/hooks/useStreamingAudio";
@@ -12,7 +12,7 @@ import { RecordingStatus } from "../recorder.reducer";
 import { InterfaceTheme } from "@renderer/hooks/useNewState";
 
 export function Minimized() {
-  const { state, toggleRecording, toggleMinimized } = useAppState();
+  const { state, toggleRecording, pauseRecording, resumeRecording, toggleMinimized } = useAppState();
   const { state: personasState } = usePersonas();
   const { state: recordingWindowState } = useRecordingWindowState();
   const { recordingStatus } = useConversationContext();
@@ -88,6 +88,16 @@ export function Minimized() {
             theme={state.context.recording_window_context.theme}
           />
 
+          {recordingStatus === "recording" || recordingStatus === "paused" ? (
+            <Button
+              variant="secondary"
+              className="w-4 h-4 [&>svg]:size-2.5 p-0 pointer-events-auto bg-secondary hover:bg-tertiary hover:dark:text-gray-500 rounded-full text-foreground"
+              onClick={recordingStatus === "paused" ? resumeRecording : pauseRecording}
+            >
+              {recordingStatus === "paused" ? <Play /> : <Pause />}
+            </Button>
+          ) : null}
+
           <Button
             variant="secondary"
             disabled={recordingStatus === "transforming" || recordingStatus === "transcribing"}
@@ -95,7 +105,8 @@ export function Minimized() {
               "w-4 h-4 [&>svg]:size-2.5 p-0 pointer-events-auto bg-primary hover:bg-tertiary hover:dark:text-gray-500 rounded-full text-foreground",
               {
                 "opacity-50": recordingStatus === "transforming" || recordingStatus === "transcribing",
-                "hover:opacity-90": recordingStatus === "idle" || recordingStatus === "recording",
+                "hover:opacity-90":
+                  recordingStatus === "idle" || recordingStatus === "recording" || recordingStatus === "paused",
               },
             )}
             onClick={toggleRecording}
@@ -105,7 +116,7 @@ export function Minimized() {
             ) : recordingStatus === "transforming" || recordingStatus === "transcribing" ? (
               <Loader2 className="animate-spin" />
             ) : (
-              <Pause />
+              <Square />
             )}
           </Button>
         </div>
@@ -12,18 +12,26 @@ import {
 } from "@renderer/components/messages";
 
 export function Root() {
-  const { state } = useAppState();
+  const { state, editMessage, regenerateResponse } = useAppState();
   const { state: conversationState } = useConversationContext();
   const { state: recordingWindowState } = useRecordingWindowState();
 
   const hasUserMessage = conversationState?.context.conversation.some((message) => message.role === "user");
 
+  // Edits refer to the message position in the full conversation, so it is kept before filtering
   const messages = useMemo(() => {
     return [
-      ...(conversationState?.context.conversation || []).filter((message) => message.role !== "system"),
+      ...(conversationState?.context.conversation || [])
+        .map((message, index) => ({ ...message, index }))
+        .filter((message) => message.role !== "system"),
     ].reverse();
   }, [conversationState]);
 
+  const isIdle = conversationState?.context.state === "Idle";
+  const lastAssistantIndex = messages.find(
+    (message) => message.role === "assistant" && !("tool_calls" in message),
+  )?.index;
+
   if (!conversationState || !state || !recordingWindowState) return null;
 
   const getToolCallContent = useCallback(
@@ -52,9 +60,17 @@ export function Root() {
           });
 
           return message.role === "user" ? (
-            <UserTextMessage message={messageMessage} createdAt={createdAt} />
+            <UserTextMessage
+              message={messageMessage}
+              createdAt={createdAt}
+              onEdit={isIdle ? (text) => editMessage(message.index, text) : undefined}
+            />
           ) : (
-            <AssistantTextMessage message={messageMessage} createdAt={createdAt} />
+            <AssistantTextMessage
+              message={messageMessage}
+              createdAt={createdAt}
+              onRegenerate={isIdle && message.index === lastAssistantIndex ? regenerateResponse : undefined}
+            />
           );
         });
       })}
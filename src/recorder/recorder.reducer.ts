@@ -24,7 +24,7 @@ export type Action =
   | ReturnType<typeof clear>
   | ReturnType<typeof setPersonas>;
 
-export type RecordingStatus = "idle" | "recording" | "transcribing" | "finished" | "transforming";
+export type RecordingStatus = "idle" | "recording" | "paused" | "transcribing" | "finished" | "transforming";
 
 export const initialState = {
   personas: null as PersonasState | null,
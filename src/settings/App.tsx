@@ -18,6 +18,8 @@ import {
   Bug,
   Loader,
   Wifi,
+  Server,
+  ChartColumn,
 } from "lucide-react";
 import { InterfaceLanguageSelect } from "@renderer/components/interface-language-select";
 import { Providers } from "./pages/providers";
@@ -46,6 +48,8 @@ import { ToolsPage } from "./pages/tools";
 import { AddNewTool } from "./pages/tools-add";
 import { EditTool } from "./pages/tools-edit";
 import { WebsocketSettings } from "./pages/websocket";
+import { MCPServerSettings } from "./pages/mcp-server";
+import { UsagePage } from "./pages/usage";
 
 import { WindowCloseButton, WindowMinimizeButton } from "@renderer/components/window-management";
 import { Toaster } from "@renderer/components/toasts";
@@ -170,6 +174,12 @@ function App(): JSX.Element {
                   {t("History")}
                 </NavLink>
               </SidebarNavItem>
+              <SidebarNavItem>
+                <NavLink to="/usage">
+                  <ChartColumn className="w-3.5 h-3.5" />
+                  {t("Usage")}
+                </NavLink>
+              </SidebarNavItem>
               <SidebarNavItem>
                 <NavLink to="/tools">
                   <Wrench className="w-3.5 h-3.5" />
@@ -182,6 +192,12 @@ function App(): JSX.Element {
                   {t("WebSocketServer")}
                 </NavLink>
               </SidebarNavItem>
+              <SidebarNavItem>
+                <NavLink to="/mcp-server">
+                  <Server className="w-3.5 h-3.5" />
+                  {t("MCPServer")}
+                </NavLink>
+              </SidebarNavItem>
 
               {/* <SidebarNavItem>
               <NavLink to="/providers">
@@ -228,6 +244,7 @@ function App(): JSX.Element {
             <Route path="/models/add" element={<AddNewModel />} />
             <Route path="/models/edit/:id" element={<EditModel />} />
             <Route path="/history" element={<History />} />
+            <Route path="/usage" element={<UsagePage />} />
             <Route path="/personas" element={<Personas />} />
             <Route path="/personas/add" element={<AddPersona />} />
             <Route path="/personas/edit/:id" element={<EditPersona />} />
@@ -237,6 +254,7 @@ function App(): JSX.Element {
             <Route path="/tools/add" element={<AddNewTool />} />
             <Route path="/tools/edit/:id" element={<EditTool />} />
             <Route path="/websocket" element={<WebsocketSettings />} />
+            <Route path="/mcp-server" element={<MCPServerSettings />} />
           </Routes>
         </Main>
       </Layout>
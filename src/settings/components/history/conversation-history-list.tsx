@@ -1,10 +1,10 @@
 import { Badge } from "@renderer/components/badge";
 import { ItemListProps, ItemsList } from "@renderer/components/items-list";
-import { HistoryConversation, isTextMessageContent } from "@renderer/hooks/useConversationState";
+import { HistorySummary } from "@renderer/hooks/useConversationState";
 import { cn } from "@renderer/utils/cn";
 import { CustomDate } from "@renderer/utils/custom-date";
 
-export function ConversationHistoryList({ items, className, ...rest }: ItemListProps<HistoryConversation>) {
+export function ConversationHistoryList<T extends HistorySummary>({ items, className, ...rest }: ItemListProps<T>) {
   return <ItemsList items={items} className={cn("flex flex-col divide-y", className)} {...rest} />;
 }
 
@@ -40,7 +40,7 @@ export function ConversationHistoryItemTime({
   className,
   conversation,
   ...rest
-}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistoryConversation }) {
+}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistorySummary }) {
   return (
     <div className={cn("text-xs text-muted-foreground flex gap-1 ]", className)} {...rest}>
       <span>{CustomDate.format(conversation.created_at, "dd MMM")}</span>
@@ -50,23 +50,35 @@ export function ConversationHistoryItemTime({
   );
 }
 
-export function ConversationHistoryItemHeading({
+export function ConversationHistoryItemSnippet({
   className,
-  conversation,
+  snippet,
   ...rest
-}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistoryConversation }) {
-  const firstTextMessage = conversation.conversation.find(
-    (message) =>
-      message.role === "user" && "content" in message && message.content.some((content) => content.type === "text"),
+}: React.HTMLAttributes<HTMLParagraphElement> & { snippet: string }) {
+  // Highlighted parts alternate with the rest of the text
+  const parts = snippet.split(/<mark>|<\/mark>/);
+
+  return (
+    <p className={cn("text-xs text-muted-foreground line-clamp-2", className)} {...rest}>
+      {parts.map((part, index) =>
+        index % 2 === 1 ? (
+          <mark key={index} className="bg-primary/20 text-foreground rounded-sm">
+            {part}
+          </mark>
+        ) : (
+          part
+        ),
+      )}
+    </p>
   );
+}
 
-  const content =
-    firstTextMessage &&
-    firstTextMessage.role !== "tool" &&
-    "content" in firstTextMessage &&
-    isTextMessageContent(firstTextMessage.content[0])
-      ? (firstTextMessage.content[0].text ?? "No messages")
-      : "No messages";
+export function ConversationHistoryItemHeading({
+  className,
+  conversation,
+  ...rest
+}: React.HTMLAttributes<HTMLDivElement> & { conversation: HistorySummary }) {
+  const content = conversation.preview ?? "No messages";
 
   return (
     <h4
@@ -2,7 +2,8 @@ import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import { ItemListProps, ItemsList } from "@renderer/components/items-list";
 import { ChatCompletionToolCallResultMessage, HistoryConversationMessage } from "@renderer/hooks/useConversationState";
 import { cn } from "@renderer/utils/cn";
-import { Sparkles, UserIcon, PlayIcon, PauseIcon, CopyIcon, CheckIcon } from "lucide-react";
+import { Sparkles, UserIcon, PlayIcon, PauseIcon, CopyIcon, CheckIcon, GitBranch } from "lucide-react";
+import { useTranslation } from "react-i18next";
 import { readFile, BaseDirectory } from "@tauri-apps/plugin-fs";
 import { CustomDate } from "@renderer/utils/custom-date";
 import { Button } from "@renderer/components/button";
@@ -16,8 +17,9 @@ import "react-photo-view/dist/react-photo-view.css";
 export function ConversationHistoryMessages({
   items,
   className,
+  onFork,
   ...rest
-}: Omit<ItemListProps<HistoryConversationMessage>, "renderItem">) {
+}: Omit<ItemListProps<HistoryConversationMessage>, "renderItem"> & { onFork?: (index: number) => void }) {
   const isNotEmpty = (item: HistoryConversationMessage) => {
     if (item.role === "tool" || "tool_calls" in item) {
       return true;
@@ -30,7 +32,8 @@ export function ConversationHistoryMessages({
   };
 
   const withIds = useMemo(() => {
-    return [...items.map((item) => ({ ...item, id: uuidv4() })).filter((item) => item.role !== "system")]
+    // Forks refer to the message position in the stored conversation, so it is kept before filtering
+    return [...items.map((item, index) => ({ ...item, id: uuidv4(), index })).filter((item) => item.role !== "system")]
       .filter(isNotEmpty)
       .reverse();
   }, [items]);
@@ -49,7 +52,7 @@ export function ConversationHistoryMessages({
       className={cn("flex flex-col-reverse gap-3 grow overflow-y-auto scrollbar-custom", className)}
       items={withIds}
       renderItem={(item) => {
-        return <ConversationItem item={item} toolResponse={getToolCallContent} />;
+        return <ConversationItem item={item} toolResponse={getToolCallContent} onFork={onFork} />;
       }}
       {...rest}
     />
@@ -59,9 +62,11 @@ export function ConversationHistoryMessages({
 function ConversationItem({
   item,
   toolResponse,
+  onFork,
 }: {
-  item: HistoryConversationMessage & { id: string };
+  item: HistoryConversationMessage & { id: string; index: number };
   toolResponse: (id: string) => string;
+  onFork?: (index: number) => void;
 }) {
   if (item.role === "tool") {
     return null;
@@ -94,6 +99,8 @@ function ConversationItem({
               <MessageCopyButton text={content.text} />
 
               {item.audio_file_path ? <ConversationHistoryAudioPlayer path={item.audio_file_path} /> : null}
+
+              {onFork ? <MessageForkButton onClick={() => onFork(item.index)} /> : null}
             </ConversationHistoryFooter>
           </ConversationHistoryMessageContentWrapper>
         );
@@ -125,7 +132,7 @@ function ConversationItem({
 
       return null;
     });
-  }, [item]);
+  }, [item, onFork]);
 
   return (
     <ConversationHistoryMessage>
@@ -317,3 +324,19 @@ function MessageCopyButton({ text }: { text: string }) {
     </Button>
   );
 }
+
+function MessageForkButton({ onClick }: { onClick: () => void }) {
+  const { t } = useTranslation();
+
+  return (
+    <Button
+      variant="ghost"
+      size="icon"
+      className="w-5 h-5 [&_svg]:size-3 p-0 text-muted-foreground"
+      onClick={onClick}
+      title={t("ForkConversation")}
+    >
+      <GitBranch />
+    </Button>
+  );
+}
@@ -1,18 +1,34 @@
-import { HistoryConversation } from "@renderer/hooks/useConversationState";
-import { createContext, useContext, useState } from "react";
+import { HistoryConversation, HistorySummary } from "@renderer/hooks/useConversationState";
+import { useAppState } from "@renderer/hooks/useAppState";
+import { createContext, useContext, useRef, useState } from "react";
 
 type HistoryConversationContextType = {
   conversation: HistoryConversation | null;
-  setConversation: (conversation: HistoryConversation | null) => void;
+  setConversation: (conversation: HistorySummary | null) => void;
 };
 
 const HistoryConversationContext = createContext<HistoryConversationContextType | undefined>(undefined);
 
 export function HistoryConversationProvider({ children }: { children: React.ReactNode }) {
   const [conversation, setConversation] = useState<HistoryConversation | null>(null);
+  const { getHistoryConversation } = useAppState();
+  const requestedId = useRef<string | null>(null);
 
-  const onConversationChange = (conversation: HistoryConversation | null) => {
-    setConversation(conversation);
+  // History keeps only summaries, the messages are loaded when a conversation is opened
+  const onConversationChange = (summary: HistorySummary | null) => {
+    requestedId.current = summary?.id ?? null;
+    if (!summary) {
+      setConversation(null);
+      return;
+    }
+
+    getHistoryConversation(summary.id)
+      .then((loaded) => {
+        if (requestedId.current === summary.id) {
+          setConversation(loaded);
+        }
+      })
+      .catch(console.error);
   };
 
   return (
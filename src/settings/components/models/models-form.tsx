@@ -8,6 +8,7 @@ import { Checkbox } from "@renderer/components/checkbox";
 import { errorToast } from "@renderer/components/toasts";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
 import { MODEL_TEMPLATES } from "@renderer/shared/model-templates";
+import { LlmProvider } from "@renderer/hooks/useModelsState";
 
 type ConversationModelFormData = {
   model: string;
@@ -15,8 +16,20 @@ type ConversationModelFormData = {
   api_key?: string;
   supports_tools?: boolean;
   supports_vision?: boolean;
+  context_length?: number;
+  provider?: LlmProvider;
 };
 
+const PROVIDERS: { label: string; value: LlmProvider }[] = [
+  { label: "OpenAI compatible", value: "openai" },
+  { label: "Anthropic", value: "anthropic" },
+  { label: "Ollama", value: "ollama" },
+  { label: "Google Gemini", value: "gemini" },
+];
+
+const DEFAULT_CONTEXT_LENGTH = 32000;
+const MIN_CONTEXT_LENGTH = 1000;
+
 type ModelsFormProps = {
   onSave: (data: ConversationModelFormData) => void;
   onCancel: () => void;
@@ -32,6 +45,8 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
     api_key: defaultValues?.api_key || "",
     supports_tools: defaultValues?.supports_tools || false,
     supports_vision: defaultValues?.supports_vision || false,
+    context_length: String(defaultValues?.context_length || DEFAULT_CONTEXT_LENGTH),
+    provider: defaultValues?.provider || "openai",
   });
 
   const onSubmit = (e: React.FormEvent<HTMLFormElement>) => {
@@ -55,12 +70,20 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
       return;
     }
 
+    const contextLength = Number(state.context_length);
+    if (!Number.isInteger(contextLength) || contextLength < MIN_CONTEXT_LENGTH) {
+      errorToast(t("InvalidContextLength", { min: MIN_CONTEXT_LENGTH }));
+      return;
+    }
+
     onSave({
       model: state.model.trim(),
       url: state.url.trim(),
       api_key: state.api_key.trim() || undefined,
       supports_tools: state.supports_tools,
       supports_vision: state.supports_vision,
+      context_length: contextLength,
+      provider: state.provider,
     });
   };
 
@@ -76,6 +99,10 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
     dispatch(setApiKey(e.target.value));
   };
 
+  const onContextLengthChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+    dispatch(setContextLength(e.target.value));
+  };
+
   const onSupportsToolsChange = (checked: boolean) => {
     dispatch(setSupportsTools(checked));
   };
@@ -96,10 +123,14 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
         <div className="grid gap-2">
           <CardDescription>{t("ModelTemplate")}</CardDescription>
           <Select
-            value={MODEL_TEMPLATES.find((t) => t.url === state.url)?.url || "Custom"}
+            value={
+              MODEL_TEMPLATES.find((t) => t.url === state.url && t.provider === state.provider)?.label || "Custom"
+            }
             onValueChange={(value) => {
-              if (value !== "Custom") {
-                dispatch(setUrl(value));
+              const template = MODEL_TEMPLATES.find((t) => t.label === value);
+              if (template) {
+                dispatch(setUrl(template.url));
+                dispatch(setProvider(template.provider));
               }
             }}
           >
@@ -108,7 +139,7 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
             </SelectTrigger>
             <SelectContent>
               {MODEL_TEMPLATES.map((template) => (
-                <SelectItem key={template.label} value={template.url} indicator={false}>
+                <SelectItem key={template.label} value={template.label} indicator={false}>
                   {template.label}
                 </SelectItem>
               ))}
@@ -119,6 +150,22 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
           </Select>
         </div>
 
+        <div className="grid gap-2">
+          <CardDescription>{t("ModelProvider")}</CardDescription>
+          <Select value={state.provider} onValueChange={(value) => dispatch(setProvider(value as LlmProvider))}>
+            <SelectTrigger>
+              <SelectValue placeholder={t("ModelProvider")} />
+            </SelectTrigger>
+            <SelectContent>
+              {PROVIDERS.map((provider) => (
+                <SelectItem key={provider.value} value={provider.value} indicator={false}>
+                  {provider.label}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+        </div>
+
         <div className="grid gap-2">
           <CardDescription>{t("Model")}</CardDescription>
           <Input
@@ -154,6 +201,21 @@ export function ModelsForm({ onSave, onCancel, defaultValues }: ModelsFormProps)
           <CardDescription className="text-muted-foreground/60 text-xs">{t("APIKeyDescription")}</CardDescription>
         </div>
 
+        <div className="grid gap-2">
+          <CardDescription>{t("ContextLength")}</CardDescription>
+          <Input
+            id="context_length"
+            name="context_length"
+            type="number"
+            min={MIN_CONTEXT_LENGTH}
+            value={state.context_length}
+            onChange={onContextLengthChange}
+          />
+          <CardDescription className="text-muted-foreground/60 text-xs">
+            {t("ContextLengthDescription")}
+          </CardDescription>
+        </div>
+
         <div className="grid gap-3">
           <div className="flex items-center space-x-2">
             <Checkbox id="supports_tools" checked={state.supports_tools} onCheckedChange={onSupportsToolsChange} />
@@ -189,6 +251,9 @@ type State = {
   api_key: string;
   supports_tools: boolean;
   supports_vision: boolean;
+  // Kept as typed, validated on save
+  context_length: string;
+  provider: LlmProvider;
 };
 
 type Action =
@@ -196,7 +261,9 @@ type Action =
   | { type: "set_url"; payload: string }
   | { type: "set_api_key"; payload: string }
   | { type: "set_supports_tools"; payload: boolean }
-  | { type: "set_supports_vision"; payload: boolean };
+  | { type: "set_supports_vision"; payload: boolean }
+  | { type: "set_context_length"; payload: string }
+  | { type: "set_provider"; payload: LlmProvider };
 
 function modelFormReducer(state: State, action: Action): State {
   switch (action.type) {
@@ -210,6 +277,10 @@ function modelFormReducer(state: State, action: Action): State {
       return { ...state, supports_tools: action.payload };
     case "set_supports_vision":
       return { ...state, supports_vision: action.payload };
+    case "set_context_length":
+      return { ...state, context_length: action.payload };
+    case "set_provider":
+      return { ...state, provider: action.payload };
     default:
       return state;
   }
@@ -220,3 +291,5 @@ const setUrl = (payload: string) => ({ type: "set_url" as const, payload });
 const setApiKey = (payload: string) => ({ type: "set_api_key" as const, payload });
 const setSupportsTools = (payload: boolean) => ({ type: "set_supports_tools" as const, payload });
 const setSupportsVision = (payload: boolean) => ({ type: "set_supports_vision" as const, payload });
+const setContextLength = (payload: string) => ({ type: "set_context_length" as const, payload });
+const setProvider = (payload: LlmProvider) => ({ type: "set_provider" as const, payload });
@@ -0,0 +1,52 @@
+import { useEffect, useState } from "react";
+import { Input } from "@renderer/components/input";
+
+type NumberInputProps = {
+  value: number;
+  min: number;
+  max: number;
+  onCommit: (value: number) => void;
+  disabled?: boolean;
+  className?: string;
+};
+
+// Keeps the typed text until blur or Enter, so partial values aren't sent
+export function NumberInput({ value, min, max, onCommit, disabled, className }: NumberInputProps) {
+  const [text, setText] = useState(value.toString());
+
+  useEffect(() => {
+    setText(value.toString());
+  }, [value]);
+
+  const commit = () => {
+    const parsed = Number(text.trim());
+    if (text.trim().length === 0 || !Number.isInteger(parsed)) {
+      setText(value.toString());
+      return;
+    }
+
+    const normalized = Math.min(max, Math.max(min, parsed));
+    setText(normalized.toString());
+    if (normalized !== value) {
+      onCommit(normalized);
+    }
+  };
+
+  return (
+    <Input
+      type="number"
+      min={min}
+      max={max}
+      value={text}
+      onChange={(event) => setText(event.target.value)}
+      className={className ?? "w-[120px]"}
+      disabled={disabled}
+      onBlur={commit}
+      onKeyDown={(event) => {
+        if (event.key === "Enter") {
+          event.currentTarget.blur();
+        }
+      }}
+    />
+  );
+}
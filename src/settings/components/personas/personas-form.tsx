@@ -3,7 +3,13 @@ import { Button } from "@renderer/components/button";
 import { Plus, Trash2 } from "lucide-react";
 import { useReducer } from "react";
 import { SettingsCard, SettingsCardContent } from "../cards";
-import { Persona, PersonaExample } from "@renderer/hooks/usePersonas";
+import {
+  GenerationParams,
+  Persona,
+  PersonaExample,
+  PersonaTools,
+  StructuredOutput,
+} from "@renderer/hooks/usePersonas";
 import { CardDescription } from "@renderer/components/card";
 import { Input } from "@renderer/components/input";
 import { Textarea } from "@renderer/components/textarea";
@@ -22,6 +28,9 @@ import {
   DialogClose,
 } from "@renderer/components/dialog";
 import { IconSelectForm } from "./icon-select-form";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
+import { Checkbox } from "@renderer/components/checkbox";
+import { useAppState } from "@renderer/hooks/useAppState";
 
 type PersonasFormProps = {
   onSave: (data: Omit<Persona, "id"> & { id?: string }) => void;
@@ -31,6 +40,8 @@ type PersonasFormProps = {
 
 export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormProps) {
   const { t } = useTranslation();
+  const { state: appState } = useAppState();
+  const servers = appState?.context.mcp_context.server_configs ?? [];
 
   const [state, dispatch] = useReducer(toolFormReducer, {
     id: defaultValues?.id,
@@ -40,10 +51,31 @@ export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormPr
     voice_command: defaultValues?.voice_command || "",
     paste_on_finish: defaultValues?.paste_on_finish || false,
     record_output_audio: defaultValues?.record_output_audio || false,
+    meeting_mode: defaultValues?.meeting_mode || false,
+    cloud_only: defaultValues?.cloud_only || false,
     icon: defaultValues?.icon || null,
     examples: defaultValues?.examples || [],
+    tools: defaultValues?.tools || "All",
+    generation: {
+      temperature: defaultValues?.generation?.temperature?.toString() ?? "",
+      top_p: defaultValues?.generation?.top_p?.toString() ?? "",
+      max_tokens: defaultValues?.generation?.max_tokens?.toString() ?? "",
+      stop: defaultValues?.generation?.stop.join(", ") ?? "",
+    },
+    structured_output: {
+      schema: defaultValues?.structured_output
+        ? JSON.stringify(defaultValues.structured_output.schema, null, 2)
+        : "",
+      template: defaultValues?.structured_output?.template ?? "",
+      webhook_url: defaultValues?.structured_output?.webhook_url ?? "",
+    },
   });
 
+  const toolsMode = typeof state.tools === "object" ? "Only" : state.tools;
+  const toolPatterns = typeof state.tools === "object" ? state.tools.Only : [];
+  const serverPatterns = toolPatterns.filter((pattern) => servers.some((server) => server.key === pattern));
+  const extraPatterns = toolPatterns.filter((pattern) => !serverPatterns.includes(pattern));
+
   const onSubmit = (e: React.FormEvent<HTMLFormElement>) => {
     e.preventDefault();
 
@@ -57,10 +89,25 @@ export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormPr
       return;
     }
 
+    const generation = parseGeneration(state.generation);
+    if (!generation) {
+      errorToast(t("InvalidGenerationParams"));
+      return;
+    }
+
+    const structuredOutput = parseStructuredOutput(state.structured_output);
+    if (structuredOutput === undefined) {
+      errorToast(t("InvalidOutputSchema"));
+      return;
+    }
+
     onSave({
       ...defaultValues,
       ...state,
+      generation,
+      structured_output: structuredOutput,
       icon: (state.icon as string) || "sparkle",
+      tools: typeof state.tools === "object" ? { Only: state.tools.Only.filter(Boolean) } : state.tools,
     });
   };
 
@@ -92,6 +139,14 @@ export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormPr
     dispatch(setRecordOutputAudio(checked));
   };
 
+  const onMeetingModeChange = (checked: boolean) => {
+    dispatch(setMeetingMode(checked));
+  };
+
+  const onCloudOnlyChange = (checked: boolean) => {
+    dispatch(setCloudOnly(checked));
+  };
+
   const onAddExample = () => {
     dispatch(addExample({ question: "", answer: "" }));
   };
@@ -105,6 +160,29 @@ export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormPr
     dispatch(removeExample(index));
   };
 
+  const onGenerationChange = (field: keyof GenerationForm) => (e: React.ChangeEvent<HTMLInputElement>) => {
+    dispatch(setGeneration({ [field]: e.target.value }));
+  };
+
+  const onStructuredOutputChange =
+    (field: keyof StructuredOutputForm) => (e: React.ChangeEvent<HTMLInputElement | HTMLTextAreaElement>) => {
+      dispatch(setStructuredOutput({ [field]: e.target.value }));
+    };
+
+  const onToolsModeChange = (value: string) => {
+    dispatch(setTools(value === "Only" ? { Only: toolPatterns } : (value as "All" | "Disabled")));
+  };
+
+  const onToolServerChange = (key: string, checked: boolean) => {
+    const selected = checked ? [...serverPatterns, key] : serverPatterns.filter((pattern) => pattern !== key);
+    dispatch(setTools({ Only: [...selected, ...extraPatterns] }));
+  };
+
+  const onToolPatternsChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+    const patterns = e.target.value.split(",").map((pattern) => pattern.trim());
+    dispatch(setTools({ Only: [...serverPatterns, ...patterns] }));
+  };
+
   return (
     <form className="grow h-full" onSubmit={onSubmit}>
       <div className="flex flex-col gap-3 grow h-[calc(100%_-_45px)] px-1 pb-4 mb-3 overflow-y-auto scrollbar-custom">
@@ -263,6 +341,183 @@ export function PersonasForm({ onSave, onCancel, defaultValues }: PersonasFormPr
             <Switch checked={state.record_output_audio} onCheckedChange={onRecordOutputAudioChange} />
           </SettingsCardContent>
         </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardContent className="p-3 flex flex-row justify-between items-center">
+            <div>
+              <CardDescription className="text-foreground">{t("MeetingMode")}</CardDescription>
+              <CardDescription className="text-muted-foreground">{t("MeetingModeDescription")}</CardDescription>
+            </div>
+            <Switch checked={state.meeting_mode} onCheckedChange={onMeetingModeChange} />
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardContent className="p-3 flex flex-row justify-between items-center">
+            <div>
+              <CardDescription className="text-foreground">{t("CloudOnly")}</CardDescription>
+              <CardDescription className="text-muted-foreground">{t("CloudOnlyDescription")}</CardDescription>
+            </div>
+            <Switch checked={state.cloud_only} onCheckedChange={onCloudOnlyChange} />
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardContent className="p-3 flex flex-col gap-3">
+            <div className="flex flex-row justify-between items-center gap-2">
+              <div>
+                <CardDescription className="text-foreground">{t("PersonaTools")}</CardDescription>
+                <CardDescription className="text-muted-foreground">{t("PersonaToolsDescription")}</CardDescription>
+              </div>
+              <Select value={toolsMode} onValueChange={onToolsModeChange}>
+                <SelectTrigger className="w-[150px] shrink-0">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="All">{t("PersonaToolsAll")}</SelectItem>
+                  <SelectItem value="Only">{t("PersonaToolsOnly")}</SelectItem>
+                  <SelectItem value="Disabled">{t("PersonaToolsDisabled")}</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+
+            {toolsMode === "Only" ? (
+              <>
+                {servers.map((server) => (
+                  <div key={server.id} className="flex items-center space-x-2">
+                    <Checkbox
+                      id={`persona-tools-${server.key}`}
+                      checked={serverPatterns.includes(server.key)}
+                      onCheckedChange={(checked) => onToolServerChange(server.key, checked === true)}
+                    />
+                    <label
+                      htmlFor={`persona-tools-${server.key}`}
+                      className="text-sm text-muted-foreground cursor-pointer"
+                    >
+                      {server.name || server.key}
+                    </label>
+                  </div>
+                ))}
+
+                <div className="grid gap-2">
+                  <CardDescription>{t("PersonaToolPatterns")}</CardDescription>
+                  <Input
+                    value={extraPatterns.join(", ")}
+                    onChange={onToolPatternsChange}
+                    placeholder={t("PersonaToolPatternsPlaceholder")}
+                  />
+                </div>
+              </>
+            ) : null}
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardContent className="p-3 flex flex-col gap-3">
+            <div>
+              <CardDescription className="text-foreground">{t("GenerationParams")}</CardDescription>
+              <CardDescription className="text-muted-foreground">{t("GenerationParamsDescription")}</CardDescription>
+            </div>
+
+            <div className="flex items-center w-full gap-2">
+              <div className="grow grid gap-2">
+                <CardDescription>{t("Temperature")}</CardDescription>
+                <Input
+                  type="number"
+                  step="0.1"
+                  min={0}
+                  max={2}
+                  value={state.generation.temperature}
+                  onChange={onGenerationChange("temperature")}
+                  placeholder={t("Optional")}
+                />
+              </div>
+
+              <div className="grow grid gap-2">
+                <CardDescription>{t("TopP")}</CardDescription>
+                <Input
+                  type="number"
+                  step="0.05"
+                  min={0}
+                  max={1}
+                  value={state.generation.top_p}
+                  onChange={onGenerationChange("top_p")}
+                  placeholder={t("Optional")}
+                />
+              </div>
+
+              <div className="grow grid gap-2">
+                <CardDescription>{t("MaxTokens")}</CardDescription>
+                <Input
+                  type="number"
+                  min={1}
+                  value={state.generation.max_tokens}
+                  onChange={onGenerationChange("max_tokens")}
+                  placeholder={t("Optional")}
+                />
+              </div>
+            </div>
+
+            <div className="grid gap-2">
+              <CardDescription>{t("StopSequences")}</CardDescription>
+              <Input
+                value={state.generation.stop}
+                onChange={onGenerationChange("stop")}
+                placeholder={t("StopSequencesPlaceholder")}
+              />
+            </div>
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardContent className="p-3 flex flex-col gap-3">
+            <div>
+              <CardDescription className="text-foreground">{t("StructuredOutput")}</CardDescription>
+              <CardDescription className="text-muted-foreground">{t("StructuredOutputDescription")}</CardDescription>
+            </div>
+
+            <div className="grid gap-2">
+              <CardDescription>{t("OutputSchema")}</CardDescription>
+              <Textarea
+                className="font-mono text-xs"
+                value={state.structured_output.schema}
+                onChange={onStructuredOutputChange("schema")}
+                placeholder={t("OutputSchemaPlaceholder")}
+                rows={6}
+              />
+            </div>
+
+            {state.structured_output.schema.trim() ? (
+              <>
+                <div className="grid gap-2">
+                  <CardDescription>{t("OutputTemplate")}</CardDescription>
+                  <Textarea
+                    value={state.structured_output.template}
+                    onChange={onStructuredOutputChange("template")}
+                    placeholder={t("OutputTemplatePlaceholder", { example: "{{title}}: {{summary}}" })}
+                    rows={3}
+                  />
+                  <CardDescription className="text-muted-foreground/60">
+                    {t("OutputTemplateDescription", { placeholder: "{{field}}" })}
+                  </CardDescription>
+                </div>
+
+                <div className="grid gap-2">
+                  <CardDescription>{t("OutputWebhook")}</CardDescription>
+                  <Input
+                    type="url"
+                    value={state.structured_output.webhook_url}
+                    onChange={onStructuredOutputChange("webhook_url")}
+                    placeholder={t("Optional")}
+                  />
+                  <CardDescription className="text-muted-foreground/60">
+                    {t("OutputWebhookDescription")}
+                  </CardDescription>
+                </div>
+              </>
+            ) : null}
+          </SettingsCardContent>
+        </SettingsCard>
       </div>
 
       <footer className="flex gap-2">
@@ -285,8 +540,27 @@ type State = {
   voice_command: string;
   paste_on_finish: boolean;
   record_output_audio: boolean;
+  meeting_mode: boolean;
+  cloud_only: boolean;
   icon: keyof typeof personasIconsRegistry | null;
   examples: PersonaExample[];
+  tools: PersonaTools;
+  generation: GenerationForm;
+  structured_output: StructuredOutputForm;
+};
+
+// Kept as typed, the values are parsed on save
+type GenerationForm = {
+  temperature: string;
+  top_p: string;
+  max_tokens: string;
+  stop: string;
+};
+
+type StructuredOutputForm = {
+  schema: string;
+  template: string;
+  webhook_url: string;
 };
 
 type Action =
@@ -297,10 +571,15 @@ type Action =
   | { type: "set_paste_on_finish"; payload: boolean }
   | { type: "set_icon"; payload: keyof typeof personasIconsRegistry | null }
   | { type: "set_record_output_audio"; payload: boolean }
+  | { type: "set_meeting_mode"; payload: boolean }
+  | { type: "set_cloud_only"; payload: boolean }
   | { type: "set_examples"; payload: PersonaExample[] }
   | { type: "add_example"; payload: PersonaExample }
   | { type: "update_example"; payload: { index: number; example: PersonaExample } }
-  | { type: "remove_example"; payload: number };
+  | { type: "remove_example"; payload: number }
+  | { type: "set_tools"; payload: PersonaTools }
+  | { type: "set_generation"; payload: Partial<GenerationForm> }
+  | { type: "set_structured_output"; payload: Partial<StructuredOutputForm> };
 
 function toolFormReducer(state: State, action: Action): State {
   switch (action.type) {
@@ -340,6 +619,16 @@ function toolFormReducer(state: State, action: Action): State {
         ...state,
         record_output_audio: action.payload,
       };
+    case "set_meeting_mode":
+      return {
+        ...state,
+        meeting_mode: action.payload,
+      };
+    case "set_cloud_only":
+      return {
+        ...state,
+        cloud_only: action.payload,
+      };
     case "set_examples":
       return {
         ...state,
@@ -362,6 +651,21 @@ function toolFormReducer(state: State, action: Action): State {
         ...state,
         examples: state.examples.filter((_, index) => index !== action.payload),
       };
+    case "set_tools":
+      return {
+        ...state,
+        tools: action.payload,
+      };
+    case "set_generation":
+      return {
+        ...state,
+        generation: { ...state.generation, ...action.payload },
+      };
+    case "set_structured_output":
+      return {
+        ...state,
+        structured_output: { ...state.structured_output, ...action.payload },
+      };
     default:
       return state;
   }
@@ -391,6 +695,14 @@ function setRecordOutputAudio(payload: boolean) {
   return { type: "set_record_output_audio", payload: payload } as const;
 }
 
+function setMeetingMode(payload: boolean) {
+  return { type: "set_meeting_mode", payload: payload } as const;
+}
+
+function setCloudOnly(payload: boolean) {
+  return { type: "set_cloud_only", payload: payload } as const;
+}
+
 function setIcon(payload: keyof typeof personasIconsRegistry | null) {
   return { type: "set_icon", payload: payload } as const;
 }
@@ -406,3 +718,58 @@ function updateExample(index: number, example: PersonaExample) {
 function removeExample(payload: number) {
   return { type: "remove_example", payload: payload } as const;
 }
+
+function setTools(payload: PersonaTools) {
+  return { type: "set_tools", payload: payload } as const;
+}
+
+function setGeneration(payload: Partial<GenerationForm>) {
+  return { type: "set_generation", payload: payload } as const;
+}
+
+function setStructuredOutput(payload: Partial<StructuredOutputForm>) {
+  return { type: "set_structured_output", payload: payload } as const;
+}
+
+function parseGeneration(form: GenerationForm): GenerationParams | null {
+  const parseNumber = (value: string, min: number, max: number) => {
+    if (!value.trim()) return null;
+    const number = Number(value);
+    return Number.isFinite(number) && number >= min && number <= max ? number : undefined;
+  };
+
+  const temperature = parseNumber(form.temperature, 0, 2);
+  const top_p = parseNumber(form.top_p, 0, 1);
+  const max_tokens = parseNumber(form.max_tokens, 1, Number.MAX_SAFE_INTEGER);
+  if (temperature === undefined || top_p === undefined || max_tokens === undefined) return null;
+  if (max_tokens !== null && !Number.isInteger(max_tokens)) return null;
+
+  return {
+    temperature,
+    top_p,
+    max_tokens,
+    stop: form.stop
+      .split(",")
+      .map((sequence) => sequence.trim())
+      .filter(Boolean),
+  };
+}
+
+// `undefined` when the schema is not a JSON object, `null` when the persona answers with plain text
+function parseStructuredOutput(form: StructuredOutputForm): StructuredOutput | null | undefined {
+  if (!form.schema.trim()) return null;
+
+  let schema: unknown;
+  try {
+    schema = JSON.parse(form.schema);
+  } catch {
+    return undefined;
+  }
+  if (!schema || typeof schema !== "object" || Array.isArray(schema)) return undefined;
+
+  return {
+    schema: schema as Record<string, unknown>,
+    template: form.template,
+    webhook_url: form.webhook_url.trim() || null,
+  };
+}
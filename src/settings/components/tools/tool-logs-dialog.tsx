@@ -0,0 +1,77 @@
+import { Button } from "@renderer/components/button";
+import {
+  Dialog,
+  DialogClose,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@renderer/components/dialog";
+import { MCPServerConfig } from "@renderer/hooks/useNewState";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+
+export type MCPServerLogSource = "Stderr" | "Protocol" | "Supervisor";
+
+export interface MCPServerLogEntry {
+  timestamp: string;
+  source: MCPServerLogSource;
+  message: string;
+}
+
+const LOGS_REFRESH_INTERVAL = 2000;
+
+export function ToolLogsDialog({
+  tool,
+  open,
+  onOpenChange,
+}: {
+  tool: MCPServerConfig;
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+}) {
+  const { t } = useTranslation();
+  const [logs, setLogs] = useState<MCPServerLogEntry[]>([]);
+
+  useEffect(() => {
+    if (!open) return;
+
+    const fetchLogs = () =>
+      invoke<MCPServerLogEntry[]>("get_mcp_server_logs", { key: tool.key })
+        .then(setLogs)
+        .catch(console.error);
+
+    fetchLogs();
+    const interval = setInterval(fetchLogs, LOGS_REFRESH_INTERVAL);
+    return () => clearInterval(interval);
+  }, [open, tool.key]);
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-2xl">
+        <DialogHeader>
+          <DialogTitle>{t("ToolLogs", { name: tool.name })}</DialogTitle>
+          <DialogDescription>{t("ToolLogsDescription")}</DialogDescription>
+        </DialogHeader>
+
+        <pre className="h-72 overflow-auto whitespace-pre-wrap break-all rounded-md bg-background-surface-high p-2 text-xs scrollbar-custom">
+          {logs.length > 0
+            ? logs
+                .map((log) => `${new Date(log.timestamp).toLocaleTimeString()} [${log.source}] ${log.message}`)
+                .join("\n")
+            : t("ToolNoLogs")}
+        </pre>
+
+        <DialogFooter>
+          <DialogClose asChild>
+            <Button variant="outline" type="button" fullWidth size="sm">
+              {t("Close")}
+            </Button>
+          </DialogClose>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,187 @@
+import { Button } from "@renderer/components/button";
+import {
+  Dialog,
+  DialogClose,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from "@renderer/components/dialog";
+import { CardDescription } from "@renderer/components/card";
+import { EmptyMessage } from "@renderer/components/items-list";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
+import { useAppState } from "@renderer/hooks/useAppState";
+import { MCPServerConfig, MCPServerTool, MCPToolPolicy } from "@renderer/hooks/useNewState";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { ToolCardDescription, ToolCardTitle } from "./tools-list";
+import { NumberInput } from "../number-input";
+
+export const BUILTIN_SERVER_KEY = "qspeak";
+
+const DEFAULT_TOOL_TIMEOUT_SECS = 60;
+const MAX_TOOL_TIMEOUT_SECS = 3600;
+
+const DEFAULT_POLICY = "Default";
+
+export type ToolPoliciesTarget = {
+  // Server config id, or BUILTIN_SERVER_KEY for the built-in tools
+  serverId: string;
+  key: string;
+  name: string;
+  // Built-in tools have no server wide policy or timeout
+  serverPolicy?: MCPToolPolicy;
+  timeoutSecs?: number;
+  toolPolicies: Record<string, MCPToolPolicy>;
+};
+
+export function toolPoliciesTarget(tool: MCPServerConfig): ToolPoliciesTarget {
+  return {
+    serverId: tool.id,
+    key: tool.key,
+    name: tool.name || tool.key,
+    serverPolicy: tool.tool_policy ?? "Ask",
+    timeoutSecs: tool.tool_timeout_secs ?? DEFAULT_TOOL_TIMEOUT_SECS,
+    toolPolicies: tool.tool_policies ?? {},
+  };
+}
+
+export function ToolPolicySelect({
+  value,
+  onValueChange,
+  withDefault,
+  disabled,
+}: {
+  value: MCPToolPolicy | typeof DEFAULT_POLICY;
+  onValueChange: (value: MCPToolPolicy | null) => void;
+  withDefault?: boolean;
+  disabled?: boolean;
+}) {
+  const { t } = useTranslation();
+
+  return (
+    <Select
+      value={value}
+      onValueChange={(value) => onValueChange(value === DEFAULT_POLICY ? null : (value as MCPToolPolicy))}
+      disabled={disabled}
+    >
+      <SelectTrigger className="w-[120px] h-7 text-xs">
+        <SelectValue />
+      </SelectTrigger>
+      <SelectContent>
+        {withDefault ? <SelectItem value={DEFAULT_POLICY}>{t("ToolPolicyDefault")}</SelectItem> : null}
+        <SelectItem value="Allow">{t("ToolPolicyAllow")}</SelectItem>
+        <SelectItem value="Ask">{t("ToolPolicyAsk")}</SelectItem>
+        <SelectItem value="Deny">{t("ToolPolicyDeny")}</SelectItem>
+      </SelectContent>
+    </Select>
+  );
+}
+
+export function ToolPoliciesDialog({
+  target,
+  open,
+  onOpenChange,
+}: {
+  target: ToolPoliciesTarget;
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+}) {
+  const { t } = useTranslation();
+  const { updateToolPolicy, updateToolTimeout } = useAppState();
+  const [tools, setTools] = useState<MCPServerTool[] | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+
+    // Tools are only known while the server is running
+    invoke<MCPServerTool[]>("get_mcp_server_tools", { key: target.key })
+      .then(setTools)
+      .catch(() => setTools([]));
+  }, [open, target.key]);
+
+  // Overrides for tools the server no longer lists are still shown, so they can be removed
+  const toolNames = Array.from(
+    new Set([...(tools ?? []).map((tool) => tool.name), ...Object.keys(target.toolPolicies)]),
+  ).sort();
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="sm:max-w-2xl">
+        <DialogHeader>
+          <DialogTitle>{t("ToolPolicies", { name: target.name })}</DialogTitle>
+          <DialogDescription>{t("ToolPoliciesDescription")}</DialogDescription>
+        </DialogHeader>
+
+        {target.serverPolicy ? (
+          <div className="flex items-center justify-between gap-2">
+            <div>
+              <ToolCardTitle>{t("ToolServerPolicy")}</ToolCardTitle>
+              <ToolCardDescription>{t("ToolServerPolicyDescription")}</ToolCardDescription>
+            </div>
+            <ToolPolicySelect
+              value={target.serverPolicy}
+              onValueChange={(policy) => policy && updateToolPolicy(target.serverId, null, policy)}
+            />
+          </div>
+        ) : null}
+
+        {target.timeoutSecs ? (
+          <div className="flex items-center justify-between gap-2">
+            <div>
+              <ToolCardTitle>{t("ToolCallTimeout")}</ToolCardTitle>
+              <ToolCardDescription>{t("ToolCallTimeoutDescription")}</ToolCardDescription>
+            </div>
+            <NumberInput
+              value={target.timeoutSecs}
+              min={1}
+              max={MAX_TOOL_TIMEOUT_SECS}
+              onCommit={(timeoutSecs) => updateToolTimeout(target.serverId, null, timeoutSecs)}
+              className="w-[120px] h-7 text-xs"
+            />
+          </div>
+        ) : null}
+
+        <CardDescription>{t("ToolPerToolPolicies")}</CardDescription>
+        <div className="flex flex-col gap-2 h-60 overflow-y-auto pr-1 scrollbar-custom">
+          {toolNames.length > 0 ? (
+            toolNames.map((toolName) => {
+              const description = tools?.find((tool) => tool.name === toolName)?.description;
+              return (
+                <div key={toolName} className="flex items-center justify-between gap-2">
+                  <div className="min-w-0">
+                    <ToolCardTitle className="truncate">{toolName}</ToolCardTitle>
+                    {description ? (
+                      <ToolCardDescription className="line-clamp-1" title={description}>
+                        {description}
+                      </ToolCardDescription>
+                    ) : null}
+                  </div>
+                  <ToolPolicySelect
+                    withDefault
+                    value={target.toolPolicies[toolName] ?? DEFAULT_POLICY}
+                    onValueChange={(policy) => updateToolPolicy(target.serverId, toolName, policy)}
+                  />
+                </div>
+              );
+            })
+          ) : (
+            <EmptyMessage className="text-center block">
+              {tools === null ? t("ToolLoadingTools") : t("ToolNoToolsAvailable")}
+            </EmptyMessage>
+          )}
+        </div>
+
+        <DialogFooter>
+          <DialogClose asChild>
+            <Button variant="outline" type="button" fullWidth size="sm">
+              {t("Close")}
+            </Button>
+          </DialogClose>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -1,6 +1,13 @@
 import { Label } from "@renderer/components/label";
 import { Input } from "@renderer/components/input";
-import { isExternalTool, isLocalTool, MCPServerConfig, MCPServerKind } from "@renderer/hooks/useNewState";
+import {
+  isExternalTool,
+  isLocalTool,
+  MCPExternalAuth,
+  MCPExternalTransport,
+  MCPServerConfig,
+  MCPServerKind,
+} from "@renderer/hooks/useNewState";
 import { RadioGroup, RadioGroupItem } from "@renderer/components/radio-group";
 import { useTranslation } from "react-i18next";
 import { Cloud, HardDrive, Plus } from "lucide-react";
@@ -14,6 +21,8 @@ import { CardDescription } from "@renderer/components/card";
 import { EmptyMessage } from "@renderer/components/items-list";
 import { v4 as uuidv4 } from "uuid";
 import { errorToast } from "@renderer/components/toasts";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
+import { Textarea } from "@renderer/components/textarea";
 
 type ToolsFormProps = {
   onSave: (data: MCPServerConfig) => void;
@@ -30,6 +39,7 @@ export function ToolsForm({ onSave, onCancel, defaultValues }: ToolsFormProps) {
     key: defaultValues?.key || "",
     description: defaultValues?.description || "",
     kind: defaultValues?.kind || { Local: { command: "", env_vars: {} } },
+    headersText: isExternalTool(defaultValues?.kind) ? formatHeaders(defaultValues.kind.External.headers) : "",
   });
 
   const onToolTypeChange = (value: "Local" | "External") => {
@@ -68,6 +78,26 @@ export function ToolsForm({ onSave, onCancel, defaultValues }: ToolsFormProps) {
     dispatch(setUrl(e.target.value));
   };
 
+  const onExternalChange = (payload: Partial<ExternalOptions>) => {
+    dispatch(setExternal(payload));
+  };
+
+  const onAuthTypeChange = (value: string) => {
+    const auth: MCPExternalAuth =
+      value === "ApiKey" ? { ApiKey: { header: DEFAULT_API_KEY_HEADER } } : (value as "None" | "Bearer");
+    onExternalChange({ auth });
+  };
+
+  const onHeadersChange = (e: React.ChangeEvent<HTMLTextAreaElement>) => {
+    dispatch(setHeadersText(e.target.value));
+  };
+
+  const external = isExternalTool(state.kind) ? state.kind.External : undefined;
+  const authType = getAuthType(external?.auth);
+  const hadSecret = isExternalTool(defaultValues?.kind) && getAuthType(defaultValues.kind.External.auth) !== "None";
+  const hadHeaders =
+    isExternalTool(defaultValues?.kind) && Object.keys(defaultValues.kind.External.headers ?? {}).length > 0;
+
   const envVarEntries =
     state.type === "Local" && "Local" in state.kind
       ? Object.entries(state.kind.Local.env_vars).sort(([a], [b]) => {
@@ -92,11 +122,31 @@ export function ToolsForm({ onSave, onCancel, defaultValues }: ToolsFormProps) {
       return;
     }
 
+    const { headersText, ...toolState } = state;
+    const headers = parseHeaders(headersText);
+    if (isExternalTool(state.kind) && !headers) {
+      errorToast(t("ToolHeadersInvalid"));
+      return;
+    }
+    if (authType !== "None" && !external?.auth_secret && !hadSecret) {
+      errorToast(t("ToolAuthSecretRequired"));
+      return;
+    }
+
+    // Policies and timeouts are edited separately, keep them when the server is saved
+    const toolSettings = {
+      tool_policy: defaultValues?.tool_policy,
+      tool_policies: defaultValues?.tool_policies,
+      tool_timeout_secs: defaultValues?.tool_timeout_secs,
+      tool_timeouts: defaultValues?.tool_timeouts,
+    };
+
     let newTool: MCPServerConfig;
 
     if (isLocalTool(state.kind)) {
       newTool = {
-        ...state,
+        ...toolState,
+        ...toolSettings,
         id: uuidv4(),
         enabled: false,
         state: "Disabled",
@@ -117,11 +167,18 @@ export function ToolsForm({ onSave, onCancel, defaultValues }: ToolsFormProps) {
       };
     } else {
       newTool = {
-        ...state,
+        ...toolState,
+        ...toolSettings,
         id: uuidv4(),
         enabled: false,
         state: "Disabled",
-        kind: { External: { url: state.kind.External.url } },
+        kind: {
+          External: {
+            ...state.kind.External,
+            headers: headers ?? {},
+            auth_secret: state.kind.External.auth_secret || undefined,
+          },
+        },
       };
     }
 
@@ -256,16 +313,119 @@ export function ToolsForm({ onSave, onCancel, defaultValues }: ToolsFormProps) {
           </>
         ) : null}
 
-        {state.type === "External" && "External" in state.kind ? (
-          <div className="grid gap-2">
-            <Label htmlFor="url">{t("Server URL (SSE)")}</Label>
-            <Input
-              id="url"
-              name="url"
-              value={state.kind.External.url}
-              onChange={onUrlChange}
-              placeholder={t("Required, e.g., http://localhost:8080/events")}
-            />
+        {state.type === "External" && external ? (
+          <div className="flex flex-col gap-3 overflow-y-auto p-1">
+            <div className="flex items-end w-full gap-2">
+              <div className="grow">
+                <Label htmlFor="url">{t("ServerURL")}</Label>
+                <Input
+                  id="url"
+                  name="url"
+                  value={external.url}
+                  onChange={onUrlChange}
+                  placeholder={t("Required, e.g., http://localhost:8080/mcp")}
+                />
+              </div>
+
+              <div className="w-[180px]">
+                <CardDescription>{t("ToolTransport")}</CardDescription>
+                <Select
+                  value={external.transport ?? "Sse"}
+                  onValueChange={(value) => onExternalChange({ transport: value as MCPExternalTransport })}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="StreamableHttp">{t("ToolTransportStreamableHttp")}</SelectItem>
+                    <SelectItem value="Sse">{t("ToolTransportSse")}</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+            </div>
+
+            <div className="flex items-end w-full gap-2">
+              <div className="w-[180px]">
+                <CardDescription>{t("ToolAuth")}</CardDescription>
+                <Select value={authType} onValueChange={onAuthTypeChange}>
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="None">{t("ToolAuthNone")}</SelectItem>
+                    <SelectItem value="Bearer">{t("ToolAuthBearer")}</SelectItem>
+                    <SelectItem value="ApiKey">{t("ToolAuthApiKey")}</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+
+              {typeof external.auth === "object" ? (
+                <div className="grow">
+                  <CardDescription>{t("ToolAuthHeader")}</CardDescription>
+                  <Input
+                    value={external.auth.ApiKey.header}
+                    onChange={(e) => onExternalChange({ auth: { ApiKey: { header: e.target.value } } })}
+                    placeholder={DEFAULT_API_KEY_HEADER}
+                  />
+                </div>
+              ) : null}
+
+              {authType !== "None" ? (
+                <div className="grow">
+                  <CardDescription>{t("ToolAuthSecret")}</CardDescription>
+                  <Input
+                    type="password"
+                    autoComplete="off"
+                    value={external.auth_secret ?? ""}
+                    onChange={(e) => onExternalChange({ auth_secret: e.target.value })}
+                    placeholder={hadSecret ? t("ToolAuthSecretKeep") : t("Required")}
+                  />
+                </div>
+              ) : null}
+            </div>
+
+            <div className="grid gap-2">
+              <CardDescription>{t("ToolHeaders")}</CardDescription>
+              <Textarea
+                value={state.headersText}
+                onChange={onHeadersChange}
+                placeholder={t("ToolHeadersPlaceholder")}
+                rows={3}
+              />
+              {hadHeaders ? <CardDescription className="text-xs">{t("ToolHeadersKeep")}</CardDescription> : null}
+            </div>
+
+            <div className="flex items-center w-full gap-2">
+              <div className="grow">
+                <CardDescription>{t("ToolRequestTimeout")}</CardDescription>
+                <Input
+                  type="number"
+                  min={1}
+                  value={external.request_timeout_secs ?? DEFAULT_REQUEST_TIMEOUT_SECS}
+                  onChange={(e) => onExternalChange({ request_timeout_secs: Number(e.target.value) })}
+                />
+              </div>
+
+              <div className="grow">
+                <CardDescription>{t("ToolReconnectAttempts")}</CardDescription>
+                <Input
+                  type="number"
+                  min={0}
+                  value={external.reconnect_attempts ?? DEFAULT_RECONNECT_ATTEMPTS}
+                  onChange={(e) => onExternalChange({ reconnect_attempts: Number(e.target.value) })}
+                />
+              </div>
+
+              <div className="grow">
+                <CardDescription>{t("ToolReconnectDelay")}</CardDescription>
+                <Input
+                  type="number"
+                  min={0}
+                  value={external.reconnect_delay_secs ?? DEFAULT_RECONNECT_DELAY_SECS}
+                  onChange={(e) => onExternalChange({ reconnect_delay_secs: Number(e.target.value) })}
+                />
+              </div>
+            </div>
           </div>
         ) : null}
       </div>
@@ -288,9 +448,19 @@ type State = {
   key: string;
   description: string;
   kind: MCPServerKind;
+  headersText: string;
   nextEnvIndex?: number;
 };
 
+type ExternalOptions = Extract<MCPServerKind, { External: unknown }>["External"];
+
+type AuthType = "None" | "Bearer" | "ApiKey";
+
+const DEFAULT_API_KEY_HEADER = "X-API-Key";
+const DEFAULT_REQUEST_TIMEOUT_SECS = 30;
+const DEFAULT_RECONNECT_ATTEMPTS = 3;
+const DEFAULT_RECONNECT_DELAY_SECS = 2;
+
 type Action =
   | { type: "set_type"; payload: "Local" | "External" }
   | { type: "set_name"; payload: string }
@@ -300,7 +470,9 @@ type Action =
   | { type: "remove_env_var"; payload: string }
   | { type: "change_env_var"; payload: { envKey: string; newKey: string; newValue: string } }
   | { type: "add_env_var" }
-  | { type: "set_url"; payload: string };
+  | { type: "set_url"; payload: string }
+  | { type: "set_external"; payload: Partial<ExternalOptions> }
+  | { type: "set_headers_text"; payload: string };
 
 function toolFormReducer(state: State, action: Action): State {
   switch (action.type) {
@@ -308,7 +480,10 @@ function toolFormReducer(state: State, action: Action): State {
       return {
         ...state,
         type: action.payload,
-        kind: action.payload === "Local" ? { Local: { command: "", env_vars: {} } } : { External: { url: "" } },
+        kind:
+          action.payload === "Local"
+            ? { Local: { command: "", env_vars: {} } }
+            : { External: { url: "", transport: "StreamableHttp", auth: "None" } },
       };
     case "set_name":
       return { ...state, name: action.payload, key: generateKeyFromName(action.payload) };
@@ -397,6 +572,15 @@ function toolFormReducer(state: State, action: Action): State {
           External: { ...(isExternalTool(state.kind) ? state.kind.External : {}), url: action.payload },
         },
       };
+    case "set_external":
+      return {
+        ...state,
+        kind: {
+          External: { ...(isExternalTool(state.kind) ? state.kind.External : { url: "" }), ...action.payload },
+        },
+      };
+    case "set_headers_text":
+      return { ...state, headersText: action.payload };
     default:
       return state;
   }
@@ -438,6 +622,38 @@ function setUrl(payload: string) {
   return { type: "set_url", payload: payload } as const;
 }
 
+function setExternal(payload: Partial<ExternalOptions>) {
+  return { type: "set_external", payload: payload } as const;
+}
+
+function setHeadersText(payload: string) {
+  return { type: "set_headers_text", payload: payload } as const;
+}
+
+function getAuthType(auth?: MCPExternalAuth): AuthType {
+  if (!auth) return "None";
+  return typeof auth === "object" ? "ApiKey" : auth;
+}
+
+function formatHeaders(headers?: Record<string, string>) {
+  return Object.entries(headers ?? {})
+    .map(([name, value]) => `${name}: ${value}`)
+    .join("\n");
+}
+
+// One "Name: value" per line, returns null when a line can't be parsed
+function parseHeaders(text: string): Record<string, string> | null {
+  const headers: Record<string, string> = {};
+  for (const line of text.split("\n")) {
+    if (!line.trim()) continue;
+    const colonIndex = line.indexOf(":");
+    const name = line.substring(0, colonIndex).trim();
+    if (colonIndex === -1 || !name) return null;
+    headers[name] = line.substring(colonIndex + 1).trim();
+  }
+  return headers;
+}
+
 function buildEnvKey(index: number, key: string) {
   return `${index}-${key}`;
 }
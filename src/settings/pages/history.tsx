@@ -15,16 +15,18 @@ import {
   ConversationHistoryItem,
   ConversationHistoryList,
   ConversationHistoryItemPersona,
+  ConversationHistoryItemSnippet,
 } from "../components/history/conversation-history-list";
 import {
   HistoryConversationProvider,
   useHistoryConversation,
 } from "../components/history/history-conversation-provider";
 import { useStateContext } from "@renderer/hooks/useNewState";
-import { isTextMessageContent } from "@renderer/hooks/useConversationState";
+import { HistorySearchResult, HistorySummary } from "@renderer/hooks/useConversationState";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
 import { EmptyMessage } from "@renderer/components/items-list";
 import { Button } from "@renderer/components/button";
-import { Trash, TrashIcon } from "lucide-react";
+import { AudioLines, GitBranch, Trash, TrashIcon } from "lucide-react";
 import {
   AlertDialog,
   AlertDialogDescription,
@@ -41,6 +43,20 @@ import { useAppState } from "@renderer/hooks/useAppState";
 import { Carousel, CarouselContent, CarouselItem } from "@renderer/components/carousel";
 import { cn } from "@renderer/utils/cn";
 
+type DateRange = "any" | "today" | "week" | "month";
+
+const ALL_MODELS = "__all__";
+
+function dateRangeStart(range: DateRange) {
+  const days = { any: null, today: 0, week: 7, month: 30 }[range];
+  if (days === null) return null;
+
+  const start = new Date();
+  start.setHours(0, 0, 0, 0);
+  start.setDate(start.getDate() - days);
+  return start.toISOString();
+}
+
 export function History() {
   return (
     <HistoryConversationProvider>
@@ -55,10 +71,44 @@ function ConversationHistory() {
   const [personaFilter, setPersonaFilter] = useState<string | null>(null);
   const { conversation, setConversation } = useHistoryConversation();
   const { state } = useStateContext();
-  const { clearHistory, deleteHistory } = useAppState();
+  const [modelFilter, setModelFilter] = useState<string | null>(null);
+  const [dateRange, setDateRange] = useState<DateRange>("any");
+  const [audioOnly, setAudioOnly] = useState(false);
+  const [searchResults, setSearchResults] = useState<HistorySearchResult[] | null>(null);
+  const { clearHistory, deleteHistory, searchHistory, forkHistory } = useAppState();
 
   const history = useMemo(() => [...(state?.history_context?.history ?? [])].reverse(), [state?.history_context]);
 
+  const models = useMemo(() => [...new Set(history.map((item) => item.model_name))].sort(), [history]);
+
+  // Conversations are searched in the history database, the state only has their summaries
+  useEffect(() => {
+    const text = search.trim();
+    if (!text && !personaFilter && !modelFilter && dateRange === "any" && !audioOnly) {
+      setSearchResults(null);
+      return;
+    }
+
+    let cancelled = false;
+    searchHistory({
+      text,
+      persona_name: personaFilter,
+      model_name: modelFilter,
+      from: dateRangeStart(dateRange),
+      has_audio: audioOnly ? true : null,
+    })
+      .then((results) => {
+        if (!cancelled) {
+          setSearchResults(results);
+        }
+      })
+      .catch(console.error);
+
+    return () => {
+      cancelled = true;
+    };
+  }, [search, personaFilter, modelFilter, dateRange, audioOnly, history]);
+
   useEffect(() => {
     if (!conversation?.id && history && history.length > 0) {
       setConversation(history[0]);
@@ -73,23 +123,23 @@ function ConversationHistory() {
     setSearch("");
   };
 
-  const filteredHistory = useMemo(() => {
-    if (!search && !personaFilter) return history;
-
-    return history?.filter(
-      (item) =>
-        item.conversation.some(
-          (message) =>
-            message.role === "tool" ||
-            "tool_calls" in message ||
-            message.content.some(
-              (content) =>
-                (isTextMessageContent(content) && content.text.toLowerCase().includes(search.toLowerCase())) ||
-                content.type === "image_url",
-            ),
-        ) && (personaFilter ? item.persona_name === personaFilter : true),
-    );
-  }, [history, search, personaFilter]);
+  // The entry this conversation was branched from and the entries branched from it
+  const parent = useMemo(
+    () => history.find((item) => item.id === conversation?.parent?.id) ?? null,
+    [history, conversation?.parent?.id],
+  );
+  const branches = useMemo(
+    () => history.filter((item) => conversation && item.parent?.id === conversation.id),
+    [history, conversation?.id],
+  );
+
+  const onFork = (index: number) => {
+    if (!conversation) return;
+    forkHistory(conversation.id, index).catch(console.error);
+  };
+
+  // Ranked by relevance when searching for text
+  const filteredHistory: (HistorySummary & { snippet?: string })[] = searchResults ?? history;
 
   const changeCurrentConversation = () => {
     if (history && history.length > 0) {
@@ -156,6 +206,47 @@ function ConversationHistory() {
               ))}
             </CarouselContent>
           </Carousel>
+
+          <div className="mt-2 px-3 flex gap-1 items-center">
+            <Select value={dateRange} onValueChange={(value) => setDateRange(value as DateRange)}>
+              <SelectTrigger className="h-7 text-xs">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="any">{t("AnyTime")}</SelectItem>
+                <SelectItem value="today">{t("Today")}</SelectItem>
+                <SelectItem value="week">{t("Last7Days")}</SelectItem>
+                <SelectItem value="month">{t("Last30Days")}</SelectItem>
+              </SelectContent>
+            </Select>
+
+            <Select
+              value={modelFilter ?? ALL_MODELS}
+              onValueChange={(value) => setModelFilter(value === ALL_MODELS ? null : value)}
+            >
+              <SelectTrigger className="h-7 text-xs">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value={ALL_MODELS}>{t("AllModels")}</SelectItem>
+                {models.map((model) => (
+                  <SelectItem key={model} value={model}>
+                    {model}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+
+            <Button
+              size="xs"
+              variant={audioOnly ? "default" : "outline"}
+              className="shrink-0 [&_svg]:size-3"
+              title={t("WithAudio")}
+              onClick={() => setAudioOnly((value) => !value)}
+            >
+              <AudioLines />
+            </Button>
+          </div>
         </ConversationSidebarHeader>
 
         <ConversationSidebarBody>
@@ -203,6 +294,10 @@ function ConversationHistory() {
                     </AlertDialog>
                   </div>
 
+                  {search.trim() && item.snippet ? (
+                    <ConversationHistoryItemSnippet snippet={item.snippet} />
+                  ) : null}
+
                   <div className="flex gap-2 items-center justify-between">
                     <ConversationHistoryItemTime conversation={item} />
                     <ConversationHistoryItemPersona>
@@ -258,8 +353,33 @@ function ConversationHistory() {
           ) : null}
         </HistoryHeader>
 
+        {parent || branches.length > 0 ? (
+          <HistoryBranches>
+            {parent ? (
+              <HistoryBranchLink
+                label={t("BranchedFrom")}
+                title={parent.title ?? parent.preview}
+                onClick={() => setConversation(parent)}
+              />
+            ) : null}
+
+            {branches.map((branch) => (
+              <HistoryBranchLink
+                key={branch.id}
+                label={t("Branch")}
+                title={branch.title ?? branch.preview}
+                onClick={() => setConversation(branch)}
+              />
+            ))}
+          </HistoryBranches>
+        ) : null}
+
         {conversation?.conversation.length ? (
-          <ConversationHistoryMessages className="p-4 mt-3 mb-4" items={conversation?.conversation ?? []} />
+          <ConversationHistoryMessages
+            className="p-4 mt-3 mb-4"
+            items={conversation?.conversation ?? []}
+            onFork={onFork}
+          />
         ) : (
           <div className="flex flex-col items-center justify-center h-full">
             <EmptyMessage className="block p-3 text-muted-foreground italic text-center">
@@ -271,3 +391,17 @@ function ConversationHistory() {
     </HistoryWrapper>
   );
 }
+
+function HistoryBranches({ className, ...rest }: React.HTMLAttributes<HTMLDivElement>) {
+  return <div className={cn("flex flex-wrap gap-2 px-4 pt-2", className)} {...rest} />;
+}
+
+function HistoryBranchLink({ label, title, onClick }: { label: string; title: string | null; onClick: () => void }) {
+  return (
+    <Button variant="outline" size="sm" className="max-w-[300px] text-xs" onClick={onClick}>
+      <GitBranch />
+      <span className="text-muted-foreground">{label}</span>
+      <span className="truncate">{title}</span>
+    </Button>
+  );
+}
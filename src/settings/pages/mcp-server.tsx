@@ -0,0 +1,114 @@
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Switch } from "@renderer/components/switch";
+import { Input } from "@renderer/components/input";
+import { SettingsCard, SettingsCardContent, SettingsCardHeader, SettingsCardTitle } from "../components/cards";
+import { OptionContent, OptionDescription, OptionTitle, OptionWrapper, RouteWrapper } from "../components/layout";
+import { HistoryHeader, HistoryHeading, HistoryMain } from "../components/history/history-layout";
+import { CardDescription } from "@renderer/components/card";
+import { useAppState } from "@renderer/hooks/useAppState";
+
+const DEFAULT_MCP_SERVER_PORT = 4457;
+
+export function MCPServerSettings() {
+  const { t } = useTranslation();
+  const { state, updateMCPServerSettings } = useAppState();
+  const [portValue, setPortValue] = useState(DEFAULT_MCP_SERVER_PORT.toString());
+
+  const contextPort = state?.context.mcp_server_context.port ?? DEFAULT_MCP_SERVER_PORT;
+
+  useEffect(() => {
+    const nextValue = contextPort.toString();
+    setPortValue((prev) => (prev === nextValue ? prev : nextValue));
+  }, [contextPort]);
+
+  if (!state) {
+    return null;
+  }
+
+  const { enabled, port, token } = state.context.mcp_server_context;
+
+  const handlePortCommit = () => {
+    const parsed = Number(portValue.trim());
+    if (portValue.trim().length === 0 || !Number.isInteger(parsed)) {
+      setPortValue(port.toString());
+      return;
+    }
+
+    const normalized = Math.min(65535, Math.max(1, parsed));
+    setPortValue(normalized.toString());
+
+    if (normalized !== port) {
+      updateMCPServerSettings({ enabled, port: normalized });
+    }
+  };
+
+  return (
+    <HistoryMain>
+      <HistoryHeader className="pt-6 pb-0 select-none cursor-grab" data-tauri-drag-region>
+        <HistoryHeading>{t("MCPServer")}</HistoryHeading>
+        <CardDescription className="mt-1 max-w-lg" data-tauri-drag-region>
+          {t("MCPServerDescription")}
+        </CardDescription>
+      </HistoryHeader>
+
+      <RouteWrapper className="overflow-y-auto">
+        <SettingsCard>
+          <SettingsCardHeader>
+            <SettingsCardTitle>{t("MCPServerSettings")}</SettingsCardTitle>
+          </SettingsCardHeader>
+
+          <SettingsCardContent>
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("EnableMCPServer")}</OptionTitle>
+                <OptionDescription>{t("EnableMCPServerDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Switch
+                checked={enabled}
+                onCheckedChange={(checked) => updateMCPServerSettings({ enabled: checked, port })}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("MCPServerPort")}</OptionTitle>
+                <OptionDescription>{t("MCPServerPortDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Input
+                type="number"
+                min={1}
+                max={65535}
+                value={portValue}
+                onChange={(event) => setPortValue(event.target.value)}
+                className="w-[120px]"
+                disabled={!enabled}
+                onBlur={handlePortCommit}
+                onKeyDown={(event) => {
+                  if (event.key === "Enter") {
+                    event.currentTarget.blur();
+                  }
+                }}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("MCPServerConnection")}</OptionTitle>
+                <OptionDescription>{t("MCPServerConnectionDescription")}</OptionDescription>
+                <code className="mt-2 block select-text text-xs break-all">
+                  {`http://127.0.0.1:${port}/sse?token=${token}`}
+                </code>
+                <code className="mt-1 block select-text text-xs break-all">
+                  {`qspeak --mcp-stdio --port ${port} --token ${token}`}
+                </code>
+              </OptionContent>
+            </OptionWrapper>
+          </SettingsCardContent>
+        </SettingsCard>
+      </RouteWrapper>
+    </HistoryMain>
+  );
+}
@@ -9,6 +9,7 @@ import { RouteWrapper } from "../components/layout";
 import { useTransitionNavigate } from "@renderer/hooks/useNavigate";
 import { useModelsState } from "@renderer/hooks/useModelsState";
 import { useLocation } from "react-router";
+import { LlmProvider } from "@renderer/hooks/useModelsState";
 
 type ConversationModelFormData = {
   model: string;
@@ -16,6 +17,8 @@ type ConversationModelFormData = {
   api_key?: string;
   supports_tools?: boolean;
   supports_vision?: boolean;
+  context_length?: number;
+  provider?: LlmProvider;
 };
 
 export function AddNewModel() {
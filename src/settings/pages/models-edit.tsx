@@ -1,7 +1,7 @@
 import { BasicLink } from "@renderer/components/basic-link";
 import { Button } from "@renderer/components/button";
 import { CardDescription } from "@renderer/components/card";
-import { useModelsState } from "@renderer/hooks/useModelsState";
+import { getModelConfig, LlmProvider, useModelsState } from "@renderer/hooks/useModelsState";
 import { useTransitionNavigate } from "@renderer/hooks/useNavigate";
 import { ChevronLeft } from "lucide-react";
 import { useTranslation } from "react-i18next";
@@ -37,12 +37,16 @@ export function EditModel() {
     return null;
   }
 
+  const config = getModelConfig(model);
+
   const onSave = (updatedModel: {
     model: string;
     url: string;
     api_key?: string;
     supports_tools?: boolean;
     supports_vision?: boolean;
+    context_length?: number;
+    provider?: LlmProvider;
   }) => {
     updateConversationModel({
       original_model: model.model, // Original model ID from URL params
@@ -51,6 +55,8 @@ export function EditModel() {
       api_key: updatedModel.api_key,
       supports_tools: updatedModel.supports_tools || false,
       supports_vision: updatedModel.supports_vision || false,
+      context_length: updatedModel.context_length || model.context_length,
+      provider: updatedModel.provider || config.provider,
     });
 
     navigate("/models");
@@ -82,10 +88,12 @@ export function EditModel() {
           onCancel={onCancel}
           defaultValues={{
             model: model.model,
-            url: model.config.openai.url,
-            api_key: model.config.openai.api_key,
+            url: config.url,
+            api_key: config.api_key,
             supports_tools: model.supports_tools,
             supports_vision: model.supports_vision,
+            context_length: model.context_length,
+            provider: config.provider,
           }}
         />
       </RouteWrapper>
@@ -4,6 +4,7 @@ import { useAppState } from "@renderer/hooks/useAppState";
 import { useInternetConnection } from "@renderer/hooks/useInternetConnection";
 import {
   ConversationModel,
+  getModelConfig,
   isConversationModel,
   TranscriptionModel,
   useModelsState,
@@ -48,6 +49,7 @@ import {
 } from "@renderer/components/dropdown-menu";
 import { BasicLink } from "@renderer/components/basic-link";
 import { useTransitionNavigate } from "@renderer/hooks/useNavigate";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
 import { MODEL_TEMPLATES } from "@renderer/shared/model-templates";
 import { QSPEAK_API_V1_URL } from "@renderer/shared/constants";
 
@@ -137,7 +139,10 @@ export function Models() {
                   <DropdownMenuLabel>{t("Templates")}</DropdownMenuLabel>
                   {MODEL_TEMPLATES.map((template) => (
                     <DropdownMenuItem key={template.label} asChild>
-                      <BasicLink to="/models/add" state={{ template: { url: template.url } }}>
+                      <BasicLink
+                        to="/models/add"
+                        state={{ template: { url: template.url, provider: template.provider } }}
+                      >
                         {template.label}
                       </BasicLink>
                     </DropdownMenuItem>
@@ -169,7 +174,7 @@ export function Models() {
 
 function TranscriptionModelsTab() {
   const { t } = useTranslation();
-  const { state, updateTranscriptionModel } = useAppState();
+  const { state, updateTranscriptionModel, updateFallbackTranscriptionModel } = useAppState();
   const { state: modelsState, deleteTranscriptionModel, downloadTranscriptionModel } = useModelsState();
   const [online] = useInternetConnection();
   const [filter, setFilter] = useState<TypeFilter>("all");
@@ -216,6 +221,16 @@ function TranscriptionModelsTab() {
         </SettingsCardContent>
       </SettingsCard>
 
+      <FallbackModelSelect
+        description={t("FallbackTranscriptionModelDescription")}
+        models={(modelsState?.transcription_models ?? []).filter(
+          (model) =>
+            model.download_state.status === "downloaded" && model.model !== state?.context.transcription_model,
+        )}
+        value={state?.context.fallback_transcription_model ?? null}
+        onChange={updateFallbackTranscriptionModel}
+      />
+
       <ModelsFiltersWrapper>
         <ModelsFilters>
           <Button variant={filter === "all" ? "default" : "outline"} size="sm" onClick={() => onFilterChange("all")}>
@@ -283,7 +298,7 @@ function TranscriptionModelsTab() {
 
 function ConversationModelsTab() {
   const { t } = useTranslation();
-  const { state, updateConversationModel } = useAppState();
+  const { state, updateConversationModel, updateFallbackConversationModel } = useAppState();
   const {
     state: modelsState,
     deleteConversationModel,
@@ -316,7 +331,7 @@ function ConversationModelsTab() {
       .filter((model) => {
         // Hide API cloud models when offline (but keep custom models - user might have local endpoints)
         if (!online && !model.is_local) {
-          const isCustomModel = model.config.openai.url !== QSPEAK_API_V1_URL;
+          const isCustomModel = getModelConfig(model).url !== QSPEAK_API_V1_URL;
           if (!isCustomModel) return false; // Hide API models when offline
         }
 
@@ -344,6 +359,16 @@ function ConversationModelsTab() {
         </SettingsCardContent>
       </SettingsCard>
 
+      {/* Only one local model runs at a time, so it can't stand in for another */}
+      <FallbackModelSelect
+        description={t("FallbackConversationModelDescription")}
+        models={(modelsState?.conversation_models ?? []).filter(
+          (model) => !model.is_local && model.model !== state?.context.conversation_model,
+        )}
+        value={state?.context.fallback_conversation_model ?? null}
+        onChange={updateFallbackConversationModel}
+      />
+
       <ModelsFiltersWrapper>
         <ModelsFilters>
           <Button variant={filter === "all" ? "default" : "outline"} size="sm" onClick={() => onFilterChange("all")}>
@@ -419,6 +444,51 @@ function ConversationModelsTab() {
   );
 }
 
+const NO_FALLBACK = "none";
+
+function FallbackModelSelect({
+  description,
+  models,
+  value,
+  onChange,
+}: {
+  description: string;
+  models: (TranscriptionModel | ConversationModel)[];
+  value: string | null;
+  onChange: (model: string | null) => void;
+}) {
+  const { t } = useTranslation();
+
+  const onValueChange = (model: string) => {
+    onChange(model === NO_FALLBACK ? null : model);
+  };
+
+  return (
+    <SettingsCard>
+      <SettingsCardContent className="p-3 flex flex-row justify-between items-center gap-2">
+        <div>
+          <CardDescription className="text-foreground">{t("FallbackModel")}</CardDescription>
+          <CardDescription className="text-muted-foreground">{description}</CardDescription>
+        </div>
+
+        <Select onValueChange={onValueChange} value={value ?? NO_FALLBACK}>
+          <SelectTrigger className="w-[200px] shrink-0">
+            <SelectValue />
+          </SelectTrigger>
+          <SelectContent>
+            <SelectItem value={NO_FALLBACK}>{t("NoFallbackModel")}</SelectItem>
+            {models.map((model) => (
+              <SelectItem key={model.model} value={model.model}>
+                {model.name}
+              </SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      </SettingsCardContent>
+    </SettingsCard>
+  );
+}
+
 function ModelItem({
   model,
   onClick,
@@ -472,7 +542,7 @@ function ModelItem({
 
   // Check if this is a custom conversation model (user-added with custom URL)
   // Custom models use a different URL than the qSpeak API
-  const isCustomConversationModel = isConversationModel(model) && model.config.openai.url !== QSPEAK_API_V1_URL;
+  const isCustomConversationModel = isConversationModel(model) && getModelConfig(model).url !== QSPEAK_API_V1_URL;
 
   return (
     <ModelsCard onClick={onModelClick} isActive={active} isDownloaded={isDownloaded}>
@@ -11,10 +11,49 @@ import { ShortcutRecorder } from "../components/shortcut-recorder";
 import { PreferredLanguagesSelector } from "@renderer/components/preferred-languages-selector";
 import { HistoryHeader, HistoryHeading, HistoryMain } from "../components/history/history-layout";
 import { CardDescription } from "@renderer/components/card";
+import { ContextStrategy } from "@renderer/hooks/useConversationState";
+import { NumberInput } from "../components/number-input";
+import { Button } from "@renderer/components/button";
+import { AudioRetentionContext, AudioRetentionFormat } from "@renderer/hooks/useNewState";
+
+const MAX_TOOL_ITERATIONS = 50;
+const MAX_MEETING_CHUNK_MINUTES = 60;
+const MIN_PRE_ROLL_MS = 100;
+const MAX_PRE_ROLL_MS = 3000;
+const MAX_AUDIO_AGE_DAYS = 3650;
+const MAX_AUDIO_SIZE_MB = 1_000_000;
+
+const AUDIO_RETENTION_FORMATS: { name: string; value: AudioRetentionFormat }[] = [
+  { name: "AudioFormatWav", value: "Wav" },
+  { name: "AudioFormatFlac", value: "Flac" },
+  { name: "AudioFormatOpus", value: "Opus" },
+];
+
+const CONTEXT_STRATEGIES: { name: string; value: ContextStrategy }[] = [
+  { name: "ContextStrategyDropOldest", value: "drop_oldest" },
+  { name: "ContextStrategySummarize", value: "summarize" },
+  { name: "ContextStrategyStripImages", value: "strip_images" },
+];
 
 export function Settings() {
   const { t } = useTranslation();
-  const { state, updateInputDevice, updateLanguage, updatePreferredLanguages, updateShortcuts, updateTheme, updateOpenSettingsOnStart } = useAppState();
+  const {
+    state,
+    updateInputDevice,
+    updateLanguage,
+    updatePreferredLanguages,
+    updateShortcuts,
+    updateTheme,
+    updateOpenSettingsOnStart,
+    updateLocalOnly,
+    updateMeetingChunkMinutes,
+    updatePreRollEnabled,
+    updatePreRollMs,
+    updateAudioRetentionSettings,
+    runAudioRetention,
+    updateContextStrategy,
+    updateMaxToolIterations,
+  } = useAppState();
   const { devices } = useInputDevices();
   const onMicrophoneChange = (value: string) => {
     updateInputDevice(value);
@@ -50,6 +89,17 @@ export function Settings() {
     });
   }
 
+  function onAudioRetentionChange(settings: Partial<AudioRetentionContext>) {
+    if (!state) {
+      return;
+    }
+
+    updateAudioRetentionSettings({
+      ...state.context.audio_retention_context,
+      ...settings,
+    });
+  }
+
   if (!state) {
     return null;
   }
@@ -82,7 +132,7 @@ export function Settings() {
                 </SelectTrigger>
                 <SelectContent>
                   {devices.map((device) => (
-                    <SelectItem key={device.name} value={device.name}>
+                    <SelectItem key={device.id} value={device.id}>
                       {device.label}
                     </SelectItem>
                   ))}
@@ -165,6 +215,184 @@ export function Settings() {
               />
             </OptionWrapper>
 
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("LocalOnly")}</OptionTitle>
+                <OptionDescription>{t("LocalOnlyDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Switch checked={state.context.connectivity_context.local_only} onCheckedChange={updateLocalOnly} />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("ContextStrategy")}</OptionTitle>
+                <OptionDescription>{t("ContextStrategyDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Select
+                onValueChange={(value) => updateContextStrategy(value as ContextStrategy)}
+                value={state.context.conversation_context.context_strategy}
+              >
+                <SelectTrigger className="w-[150px]">
+                  <SelectValue placeholder={t("ContextStrategy")} />
+                </SelectTrigger>
+
+                <SelectContent position="item-aligned">
+                  {CONTEXT_STRATEGIES.map((strategy) => (
+                    <SelectItem key={strategy.value} value={strategy.value}>
+                      {t(strategy.name)}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("MaxToolIterations")}</OptionTitle>
+                <OptionDescription>{t("MaxToolIterationsDescription")}</OptionDescription>
+              </OptionContent>
+
+              <NumberInput
+                value={state.context.conversation_context.max_tool_iterations}
+                min={1}
+                max={MAX_TOOL_ITERATIONS}
+                onCommit={updateMaxToolIterations}
+              />
+            </OptionWrapper>
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardHeader>
+            <SettingsCardTitle>{t("Recording")}</SettingsCardTitle>
+          </SettingsCardHeader>
+
+          <SettingsCardContent>
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("PreRoll")}</OptionTitle>
+                <OptionDescription>{t("PreRollDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Switch
+                checked={state.context.conversation_context.pre_roll_enabled}
+                onCheckedChange={updatePreRollEnabled}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("PreRollLength")}</OptionTitle>
+                <OptionDescription>{t("PreRollLengthDescription")}</OptionDescription>
+              </OptionContent>
+
+              <NumberInput
+                value={state.context.conversation_context.pre_roll_ms}
+                min={MIN_PRE_ROLL_MS}
+                max={MAX_PRE_ROLL_MS}
+                onCommit={updatePreRollMs}
+                disabled={!state.context.conversation_context.pre_roll_enabled}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("MeetingChunkMinutes")}</OptionTitle>
+                <OptionDescription>{t("MeetingChunkMinutesDescription")}</OptionDescription>
+              </OptionContent>
+
+              <NumberInput
+                value={state.context.conversation_context.meeting_chunk_minutes}
+                min={1}
+                max={MAX_MEETING_CHUNK_MINUTES}
+                onCommit={updateMeetingChunkMinutes}
+              />
+            </OptionWrapper>
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardHeader>
+            <SettingsCardTitle>{t("AudioStorage")}</SettingsCardTitle>
+          </SettingsCardHeader>
+
+          <SettingsCardContent>
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("AudioFormat")}</OptionTitle>
+                <OptionDescription>{t("AudioFormatDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Select
+                onValueChange={(value) => onAudioRetentionChange({ format: value as AudioRetentionFormat })}
+                value={state.context.audio_retention_context.format}
+              >
+                <SelectTrigger className="w-[150px]">
+                  <SelectValue placeholder={t("AudioFormat")} />
+                </SelectTrigger>
+
+                <SelectContent position="item-aligned">
+                  {AUDIO_RETENTION_FORMATS.map((format) => (
+                    <SelectItem key={format.value} value={format.value}>
+                      {t(format.name)}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("AudioMaxAge")}</OptionTitle>
+                <OptionDescription>{t("AudioMaxAgeDescription")}</OptionDescription>
+              </OptionContent>
+
+              <NumberInput
+                value={state.context.audio_retention_context.max_age_days ?? 0}
+                min={0}
+                max={MAX_AUDIO_AGE_DAYS}
+                onCommit={(days) => onAudioRetentionChange({ max_age_days: days > 0 ? days : null })}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("AudioMaxSize")}</OptionTitle>
+                <OptionDescription>{t("AudioMaxSizeDescription")}</OptionDescription>
+              </OptionContent>
+
+              <NumberInput
+                value={state.context.audio_retention_context.max_size_mb ?? 0}
+                min={0}
+                max={MAX_AUDIO_SIZE_MB}
+                onCommit={(size) => onAudioRetentionChange({ max_size_mb: size > 0 ? size : null })}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("AudioRemoveOrphans")}</OptionTitle>
+                <OptionDescription>{t("AudioRemoveOrphansDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Switch
+                checked={state.context.audio_retention_context.remove_orphans}
+                onCheckedChange={(checked) => onAudioRetentionChange({ remove_orphans: checked })}
+              />
+            </OptionWrapper>
+
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("AudioCleanUpNow")}</OptionTitle>
+                <OptionDescription>{t("AudioCleanUpNowDescription")}</OptionDescription>
+              </OptionContent>
+
+              <Button size="sm" variant="outline" onClick={runAudioRetention}>
+                {t("AudioCleanUp")}
+              </Button>
+            </OptionWrapper>
           </SettingsCardContent>
         </SettingsCard>
 
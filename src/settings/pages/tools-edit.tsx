@@ -4,18 +4,21 @@ import { HistoryHeading, HistoryHeader, HistoryMain } from "../components/histor
 import { CardDescription } from "@renderer/components/card";
 import { Button } from "@renderer/components/button";
 import { BasicLink } from "@renderer/components/basic-link";
-import { ChevronLeft } from "lucide-react";
+import { ChevronLeft, ShieldCheck } from "lucide-react";
 import { ToolsForm } from "../components/tools/tools-form";
 import { RouteWrapper } from "../components/layout";
 import { MCPServerConfig } from "@renderer/hooks/useNewState";
 import { useTransitionNavigate } from "@renderer/hooks/useNavigate";
 import { useParams } from "react-router";
+import { useState } from "react";
+import { ToolPoliciesDialog, toolPoliciesTarget } from "../components/tools/tool-policies-dialog";
 
 export function EditTool() {
   const { state, updateTool } = useAppState();
   const { t } = useTranslation();
   const { id } = useParams();
   const navigate = useTransitionNavigate();
+  const [policiesOpen, setPoliciesOpen] = useState(false);
 
   if (!id) {
     navigate("/tools");
@@ -48,16 +51,22 @@ export function EditTool() {
           </BasicLink>
         </Button>
 
-        <div>
+        <div className="grow">
           <HistoryHeading className="flex items-center gap-1">{t("EditTool")}</HistoryHeading>
 
           <CardDescription data-tauri-drag-region>{t("manageToolsDescription1")}</CardDescription>
         </div>
+
+        <Button size="sm" variant="outline" onClick={() => setPoliciesOpen(true)}>
+          <ShieldCheck /> {t("ToolPermissions")}
+        </Button>
       </HistoryHeader>
 
       <RouteWrapper className="grow h-full">
         <ToolsForm onSave={onSave} onCancel={onCancel} defaultValues={tool} />
       </RouteWrapper>
+
+      <ToolPoliciesDialog target={toolPoliciesTarget(tool)} open={policiesOpen} onOpenChange={setPoliciesOpen} />
     </HistoryMain>
   );
 }
@@ -6,7 +6,7 @@ import { SearchInput } from "@renderer/components/input";
 import { Switch } from "@renderer/components/switch";
 import { CardDescription } from "@renderer/components/card";
 import { SettingsCard, SettingsCardContent } from "../components/cards";
-import { CircleCheck, Cloud, HardDrive, MoreVertical, Plus, TriangleAlert } from "lucide-react";
+import { CircleCheck, Cloud, Download, HardDrive, MoreVertical, Plus, TriangleAlert, Upload, Wrench } from "lucide-react";
 import { useAppState } from "@renderer/hooks/useAppState";
 import {
   DropdownMenu,
@@ -25,7 +25,7 @@ import {
   AlertDialogTitle,
   AlertDialogTrigger,
 } from "@renderer/components/alert-dialog";
-import { isLocalTool, MCPServerConfig } from "@renderer/hooks/useNewState";
+import { BuiltinToolsConfig, isLocalTool, MCPServerConfig } from "@renderer/hooks/useNewState";
 import { HistoryHeading, HistoryHeader } from "../components/history/history-layout";
 import { HistoryMain } from "../components/history/history-layout";
 import { BasicLink } from "@renderer/components/basic-link";
@@ -45,11 +45,29 @@ import {
   ToolCardTransitionState,
 } from "../components/tools/tools-list";
 import { useTransitionNavigate } from "@renderer/hooks/useNavigate";
+import { errorToast, successToast } from "@renderer/components/toasts";
+import { ToolLogsDialog } from "../components/tools/tool-logs-dialog";
+import {
+  BUILTIN_SERVER_KEY,
+  ToolPoliciesDialog,
+  ToolPoliciesTarget,
+  ToolPolicySelect,
+  toolPoliciesTarget,
+} from "../components/tools/tool-policies-dialog";
 
 type TypeFilter = "all" | "local" | "external" | "enabled";
 
 export function ToolsPage() {
-  const { state, enableTool, disableTool, deleteTool } = useAppState();
+  const {
+    state,
+    enableTool,
+    disableTool,
+    deleteTool,
+    importTools,
+    exportTools,
+    updateBuiltinTools,
+    updateToolPolicy,
+  } = useAppState();
   const { t } = useTranslation();
   const navigate = useTransitionNavigate();
 
@@ -75,13 +93,52 @@ export function ToolsPage() {
     removeBodyPointerEvents();
   };
 
+  const onImportTools = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = "";
+    if (!file) return;
+
+    await importTools(await file.text());
+  };
+
+  const onExportTools = async () => {
+    try {
+      await navigator.clipboard.writeText(await exportTools());
+      successToast(t("ToolsExported"));
+    } catch {
+      errorToast(t("ToolsExportError"));
+    }
+  };
+
+  const builtinTools: BuiltinToolsConfig = state?.context.mcp_context.builtin_tools ?? {
+    enabled: true,
+    tool_policies: {},
+    notes_file: null,
+  };
+
   const onEditTool = (tool: MCPServerConfig) => {
     navigate(`/tools/edit/${tool.id}`);
   };
 
+  const [logsTool, setLogsTool] = useState<MCPServerConfig | null>(null);
+  const [policiesServerId, setPoliciesServerId] = useState<string | null>(null);
   const [filter, setFilter] = useState<TypeFilter>("all");
   const [search, setSearch] = useState<string>("");
 
+  // Looked up on every render, so the dialog reflects policy changes right away
+  const policiesTool = state?.context.mcp_context.server_configs.find((tool) => tool.id === policiesServerId);
+  const policiesTarget: ToolPoliciesTarget | null =
+    policiesServerId === BUILTIN_SERVER_KEY
+      ? {
+          serverId: BUILTIN_SERVER_KEY,
+          key: BUILTIN_SERVER_KEY,
+          name: t("BuiltinTools"),
+          toolPolicies: builtinTools.tool_policies,
+        }
+      : policiesTool
+        ? toolPoliciesTarget(policiesTool)
+        : null;
+
   const onFilterChange = (value: string) => {
     setFilter(value as TypeFilter);
   };
@@ -126,11 +183,29 @@ export function ToolsPage() {
             </CardDescription>
           </div>
 
-          <Button size="sm" asChild>
-            <BasicLink to="/tools/add">
-              <Plus /> {t("AddNewTool")}
-            </BasicLink>
-          </Button>
+          <div className="flex items-center gap-2">
+            <input
+              type="file"
+              accept="application/json,.json"
+              onChange={onImportTools}
+              className="hidden"
+              id="tools-import"
+              name="tools-import"
+            />
+            <Button size="sm" variant="outline" asChild>
+              <label htmlFor="tools-import" className="cursor-pointer">
+                <Upload /> {t("ImportTools")}
+              </label>
+            </Button>
+            <Button size="sm" variant="outline" onClick={onExportTools}>
+              <Download /> {t("ExportTools")}
+            </Button>
+            <Button size="sm" asChild>
+              <BasicLink to="/tools/add">
+                <Plus /> {t("AddNewTool")}
+              </BasicLink>
+            </Button>
+          </div>
         </div>
       </HistoryHeader>
 
@@ -186,6 +261,44 @@ export function ToolsPage() {
         </ModelsFiltersWrapper>
 
         <div className="flex flex-col gap-2 overflow-y-auto max-h-[385px] pr-1 scrollbar-custom">
+          {filter === "all" && search === "" ? (
+            <ToolCard isActive={builtinTools.enabled}>
+              <ToolCardHeader>
+                <ToolCardIcon isLocal>
+                  <Wrench />
+                </ToolCardIcon>
+
+                <ToolCardHeaderContent>
+                  <ToolCardTitle>{t("BuiltinTools")}</ToolCardTitle>
+                  <ToolCardDescription>{t("BuiltinToolsDescription")}</ToolCardDescription>
+                  <ToolCardDetails>
+                    <ToolCardDetail title="qspeak">{t("ToolKeyDetail", { key: "qspeak" })}</ToolCardDetail>
+                  </ToolCardDetails>
+                </ToolCardHeaderContent>
+              </ToolCardHeader>
+
+              <ToolCardContent>
+                <Switch
+                  checked={builtinTools.enabled}
+                  onCheckedChange={(enabled) => updateBuiltinTools({ ...builtinTools, enabled })}
+                />
+
+                <DropdownMenu>
+                  <DropdownMenuTrigger asChild>
+                    <Button variant="ghost" size="icon" className="h-7 w-7">
+                      <MoreVertical className="w-4 h-4" />
+                    </Button>
+                  </DropdownMenuTrigger>
+                  <DropdownMenuContent align="end">
+                    <DropdownMenuItem onClick={() => setPoliciesServerId(BUILTIN_SERVER_KEY)}>
+                      {t("ToolPermissions")}
+                    </DropdownMenuItem>
+                  </DropdownMenuContent>
+                </DropdownMenu>
+              </ToolCardContent>
+            </ToolCard>
+          ) : null}
+
           {filteredTools.length > 0 ? (
             filteredTools.map((tool) => {
               const isTransitioning = tool.state === "Starting" || tool.state === "Stopping";
@@ -232,6 +345,11 @@ export function ToolsPage() {
                   </ToolCardHeader>
 
                   <ToolCardContent>
+                    <ToolPolicySelect
+                      value={tool.tool_policy ?? "Ask"}
+                      onValueChange={(policy) => policy && updateToolPolicy(tool.id, null, policy)}
+                    />
+
                     <Switch
                       checked={tool.enabled}
                       onCheckedChange={(checked) => toogleTool(tool.id, checked)}
@@ -248,6 +366,10 @@ export function ToolsPage() {
                         <DropdownMenuItem onClick={() => onEditTool(tool)} disabled={isTransitioning}>
                           {t("Edit")}
                         </DropdownMenuItem>
+                        <DropdownMenuItem onClick={() => setPoliciesServerId(tool.id)}>
+                          {t("ToolPermissions")}
+                        </DropdownMenuItem>
+                        <DropdownMenuItem onClick={() => setLogsTool(tool)}>{t("Logs")}</DropdownMenuItem>
                         <AlertDialog>
                           <AlertDialogTrigger asChild>
                             <DropdownMenuItem onSelect={(e) => e.preventDefault()} disabled={isTransitioning}>
@@ -282,6 +404,32 @@ export function ToolsPage() {
             <EmptyMessage className="text-center block">{t("NoToolsConfigured")}</EmptyMessage>
           )}
         </div>
+
+        {logsTool ? (
+          <ToolLogsDialog
+            tool={logsTool}
+            open={!!logsTool}
+            onOpenChange={(open) => {
+              if (!open) {
+                setLogsTool(null);
+                removeBodyPointerEvents();
+              }
+            }}
+          />
+        ) : null}
+
+        {policiesTarget ? (
+          <ToolPoliciesDialog
+            target={policiesTarget}
+            open={!!policiesTarget}
+            onOpenChange={(open) => {
+              if (!open) {
+                setPoliciesServerId(null);
+                removeBodyPointerEvents();
+              }
+            }}
+          />
+        ) : null}
       </RouteWrapper>
     </HistoryMain>
   );
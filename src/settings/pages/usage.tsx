@@ -0,0 +1,337 @@
+import { useEffect, useMemo, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { Input } from "@renderer/components/input";
+import { Button } from "@renderer/components/button";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@renderer/components/select";
+import { EmptyMessage } from "@renderer/components/items-list";
+import { CardDescription } from "@renderer/components/card";
+import {
+  AlertDialog,
+  AlertDialogDescription,
+  AlertDialogHeader,
+  AlertDialogCancel,
+  AlertDialogAction,
+  AlertDialogContent,
+  AlertDialogFooter,
+  AlertDialogTitle,
+  AlertDialogTrigger,
+} from "@renderer/components/alert-dialog";
+import { SettingsCard, SettingsCardContent, SettingsCardHeader, SettingsCardTitle } from "../components/cards";
+import { OptionContent, OptionDescription, OptionTitle, OptionWrapper, RouteWrapper } from "../components/layout";
+import { HistoryHeader, HistoryHeading, HistoryMain } from "../components/history/history-layout";
+import { useAppState } from "@renderer/hooks/useAppState";
+import { ModelPrice, UsageStats, UsageStatsRow } from "@renderer/hooks/useNewState";
+
+type DateRange = "any" | "today" | "week" | "month";
+
+type UsageTotals = Pick<UsageStatsRow, "requests" | "prompt_tokens" | "completion_tokens" | "audio_seconds" | "cost">;
+
+// Days are stored in local time, so the range has to be local as well
+function localDay(date: Date) {
+  const month = String(date.getMonth() + 1).padStart(2, "0");
+  const day = String(date.getDate()).padStart(2, "0");
+  return `${date.getFullYear()}-${month}-${day}`;
+}
+
+function dateRangeStart(range: DateRange) {
+  const days = { any: null, today: 0, week: 7, month: 30 }[range];
+  if (days === null) return null;
+
+  const start = new Date();
+  start.setDate(start.getDate() - days);
+  return localDay(start);
+}
+
+function groupRows(rows: UsageStatsRow[], key: (row: UsageStatsRow) => string) {
+  const groups = new Map<string, UsageTotals>();
+  for (const row of rows) {
+    const totals = groups.get(key(row)) ?? {
+      requests: 0,
+      prompt_tokens: 0,
+      completion_tokens: 0,
+      audio_seconds: 0,
+      cost: 0,
+    };
+    totals.requests += row.requests;
+    totals.prompt_tokens += row.prompt_tokens;
+    totals.completion_tokens += row.completion_tokens;
+    totals.audio_seconds += row.audio_seconds;
+    totals.cost += row.cost;
+    groups.set(key(row), totals);
+  }
+  return [...groups.entries()];
+}
+
+function formatCost(cost: number) {
+  return cost.toLocaleString(undefined, { minimumFractionDigits: 2, maximumFractionDigits: 4 });
+}
+
+export function UsagePage() {
+  const { t } = useTranslation();
+  const { state, getUsageStats, clearUsageStats } = useAppState();
+  const [dateRange, setDateRange] = useState<DateRange>("month");
+  const [stats, setStats] = useState<UsageStats | null>(null);
+
+  const modelPrices = state?.context.model_prices;
+  const conversationState = state?.context.conversation_context.state;
+
+  // Costs are counted with the current prices, and every finished request adds to the totals
+  useEffect(() => {
+    let cancelled = false;
+    getUsageStats({ from: dateRangeStart(dateRange) })
+      .then((stats) => {
+        if (!cancelled) {
+          setStats(stats);
+        }
+      })
+      .catch(console.error);
+
+    return () => {
+      cancelled = true;
+    };
+  }, [dateRange, modelPrices, conversationState]);
+
+  const rows = useMemo(() => stats?.rows ?? [], [stats]);
+  const requests = rows.reduce((sum, row) => sum + row.requests, 0);
+  const byModel = useMemo(() => groupRows(rows, (row) => row.model_name), [rows]);
+  const byPersona = useMemo(() => groupRows(rows, (row) => row.persona_name ?? ""), [rows]);
+  const byDay = useMemo(() => groupRows(rows, (row) => row.day), [rows]);
+
+  // Models that have a price but no usage in the range can still be edited
+  const pricedModels = Object.keys(modelPrices ?? {}).filter(
+    (model) => !byModel.some(([modelName]) => modelName === model),
+  );
+
+  const onClear = () => {
+    clearUsageStats().then(() => setStats(null));
+  };
+
+  const describe = (totals: UsageTotals) =>
+    t("UsageTotals", {
+      requests: totals.requests.toLocaleString(),
+      prompt: totals.prompt_tokens.toLocaleString(),
+      completion: totals.completion_tokens.toLocaleString(),
+      minutes: (totals.audio_seconds / 60).toLocaleString(undefined, { maximumFractionDigits: 1 }),
+    });
+
+  if (!state) {
+    return null;
+  }
+
+  return (
+    <HistoryMain>
+      <HistoryHeader className="pt-6 pb-0 select-none cursor-grab" data-tauri-drag-region>
+        <HistoryHeading>{t("Usage")}</HistoryHeading>
+        <CardDescription className="mt-1 max-w-lg" data-tauri-drag-region>
+          {t("UsageDescription")}
+        </CardDescription>
+      </HistoryHeader>
+
+      <RouteWrapper className="overflow-y-auto">
+        <SettingsCard>
+          <SettingsCardHeader className="flex flex-row items-center justify-between gap-2 space-y-0">
+            <SettingsCardTitle>{t("UsageTotal")}</SettingsCardTitle>
+
+            <div className="flex items-center gap-2">
+              <Select value={dateRange} onValueChange={(value) => setDateRange(value as DateRange)}>
+                <SelectTrigger className="h-7 text-xs w-[140px]">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="any">{t("AnyTime")}</SelectItem>
+                  <SelectItem value="today">{t("Today")}</SelectItem>
+                  <SelectItem value="week">{t("Last7Days")}</SelectItem>
+                  <SelectItem value="month">{t("Last30Days")}</SelectItem>
+                </SelectContent>
+              </Select>
+
+              <AlertDialog>
+                <AlertDialogTrigger asChild>
+                  <Button variant="outline" size="xs" disabled={rows.length === 0}>
+                    {t("Clear")}
+                  </Button>
+                </AlertDialogTrigger>
+                <AlertDialogContent>
+                  <AlertDialogHeader>
+                    <AlertDialogTitle>{t("AreYouAbsolutelySure")}</AlertDialogTitle>
+                    <AlertDialogDescription>{t("ClearUsageWarning")}</AlertDialogDescription>
+                  </AlertDialogHeader>
+                  <AlertDialogFooter>
+                    <AlertDialogCancel>{t("Cancel")}</AlertDialogCancel>
+                    <AlertDialogAction onClick={onClear}>{t("Clear")}</AlertDialogAction>
+                  </AlertDialogFooter>
+                </AlertDialogContent>
+              </AlertDialog>
+            </div>
+          </SettingsCardHeader>
+
+          <SettingsCardContent>
+            <OptionWrapper>
+              <OptionContent>
+                <OptionTitle>{t("UsageCost")}</OptionTitle>
+                <OptionDescription>{stats ? describe({ ...stats, requests }) : null}</OptionDescription>
+              </OptionContent>
+
+              <span className="text-sm font-semibold tabular-nums">{formatCost(stats?.cost ?? 0)}</span>
+            </OptionWrapper>
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <SettingsCard>
+          <SettingsCardHeader>
+            <SettingsCardTitle>{t("UsageByModel")}</SettingsCardTitle>
+            <CardDescription>{t("ModelPricesDescription")}</CardDescription>
+          </SettingsCardHeader>
+
+          <SettingsCardContent>
+            {byModel.length === 0 && pricedModels.length === 0 ? <EmptyMessage>{t("NoUsage")}</EmptyMessage> : null}
+
+            {byModel.map(([model, totals]) => (
+              <OptionWrapper key={model} className="flex-wrap">
+                <OptionContent>
+                  <OptionTitle>{model}</OptionTitle>
+                  <OptionDescription>{describe(totals)}</OptionDescription>
+                </OptionContent>
+
+                <div className="flex items-center gap-3">
+                  <ModelPriceInputs model={model} price={modelPrices?.[model] ?? null} />
+                  <span className="text-sm tabular-nums w-[80px] text-right">{formatCost(totals.cost)}</span>
+                </div>
+              </OptionWrapper>
+            ))}
+
+            {pricedModels.map((model) => (
+              <OptionWrapper key={model} className="flex-wrap">
+                <OptionContent>
+                  <OptionTitle>{model}</OptionTitle>
+                </OptionContent>
+
+                <div className="flex items-center gap-3">
+                  <ModelPriceInputs model={model} price={modelPrices?.[model] ?? null} />
+                  <span className="text-sm tabular-nums w-[80px] text-right">{formatCost(0)}</span>
+                </div>
+              </OptionWrapper>
+            ))}
+          </SettingsCardContent>
+        </SettingsCard>
+
+        <UsageGroupCard
+          title={t("UsageByPersona")}
+          groups={byPersona.map(([persona, totals]) => [persona || t("NoPersona"), totals])}
+          describe={describe}
+        />
+
+        <UsageGroupCard title={t("UsageByDay")} groups={byDay} describe={describe} />
+      </RouteWrapper>
+    </HistoryMain>
+  );
+}
+
+function UsageGroupCard({
+  title,
+  groups,
+  describe,
+}: {
+  title: string;
+  groups: [string, UsageTotals][];
+  describe: (totals: UsageTotals) => string;
+}) {
+  const { t } = useTranslation();
+
+  return (
+    <SettingsCard>
+      <SettingsCardHeader>
+        <SettingsCardTitle>{title}</SettingsCardTitle>
+      </SettingsCardHeader>
+
+      <SettingsCardContent>
+        {groups.length === 0 ? <EmptyMessage>{t("NoUsage")}</EmptyMessage> : null}
+
+        {groups.map(([name, totals]) => (
+          <OptionWrapper key={name}>
+            <OptionContent>
+              <OptionTitle>{name}</OptionTitle>
+              <OptionDescription>{describe(totals)}</OptionDescription>
+            </OptionContent>
+
+            <span className="text-sm tabular-nums">{formatCost(totals.cost)}</span>
+          </OptionWrapper>
+        ))}
+      </SettingsCardContent>
+    </SettingsCard>
+  );
+}
+
+const PRICE_FIELDS = [
+  ["input_per_million_tokens", "InputPrice"],
+  ["output_per_million_tokens", "OutputPrice"],
+  ["audio_per_minute", "AudioPrice"],
+] as const;
+
+type PriceForm = Record<keyof ModelPrice, string>;
+
+function toPriceForm(price: ModelPrice | null): PriceForm {
+  const format = (value: number | undefined) => (value ? String(value) : "");
+  return {
+    input_per_million_tokens: format(price?.input_per_million_tokens),
+    output_per_million_tokens: format(price?.output_per_million_tokens),
+    audio_per_minute: format(price?.audio_per_minute),
+  };
+}
+
+function ModelPriceInputs({ model, price }: { model: string; price: ModelPrice | null }) {
+  const { t } = useTranslation();
+  const { updateModelPrice } = useAppState();
+  const [form, setForm] = useState<PriceForm>(() => toPriceForm(price));
+
+  useEffect(() => {
+    setForm(toPriceForm(price));
+  }, [price]);
+
+  // Empty or invalid fields count as free, a model without any price is removed
+  const onCommit = () => {
+    const parse = (value: string) => {
+      const parsed = Number(value.trim().replace(",", "."));
+      return Number.isFinite(parsed) && parsed > 0 ? parsed : 0;
+    };
+    const next: ModelPrice = {
+      input_per_million_tokens: parse(form.input_per_million_tokens),
+      output_per_million_tokens: parse(form.output_per_million_tokens),
+      audio_per_minute: parse(form.audio_per_minute),
+    };
+    const isEmpty = Object.values(next).every((value) => value === 0);
+
+    if (
+      next.input_per_million_tokens === (price?.input_per_million_tokens ?? 0) &&
+      next.output_per_million_tokens === (price?.output_per_million_tokens ?? 0) &&
+      next.audio_per_minute === (price?.audio_per_minute ?? 0)
+    ) {
+      setForm(toPriceForm(price));
+      return;
+    }
+
+    updateModelPrice(model, isEmpty ? null : next);
+  };
+
+  return (
+    <div className="flex items-center gap-1">
+      {PRICE_FIELDS.map(([field, label]) => (
+        <Input
+          key={field}
+          inputMode="decimal"
+          value={form[field]}
+          placeholder={t(label)}
+          title={t(label)}
+          className="w-[110px] h-7 text-xs"
+          onChange={(event) => setForm((form) => ({ ...form, [field]: event.target.value }))}
+          onBlur={onCommit}
+          onKeyDown={(event) => {
+            if (event.key === "Enter") {
+              event.currentTarget.blur();
+            }
+          }}
+        />
+      ))}
+    </div>
+  );
+}
@@ -1,12 +1,14 @@
-export const MODEL_TEMPLATES = [
-  { label: "Anthropic", url: "https://api.anthropic.com/v1" },
-  { label: "DeepSeek", url: "https://api.deepseek.com/v1" },
-  { label: "Google Gemini", url: "https://generativelanguage.googleapis.com/v1beta" },
-  { label: "Groq", url: "https://api.groq.com/v1" },
-  { label: "LM Studio", url: "http://localhost:1234/v1" },
-  { label: "Mistral", url: "https://api.mistral.ai/v1" },
-  { label: "Ollama", url: "http://localhost:11434/v1" },
-  { label: "OpenAI", url: "https://api.openai.com/v1" },
-  { label: "OpenRouter", url: "https://openrouter.ai/api/v1" },
-  { label: "vLLM", url: "http://localhost:8000/v1" },
+import { LlmProvider } from "@renderer/hooks/useModelsState";
+
+export const MODEL_TEMPLATES: { label: string; url: string; provider: LlmProvider }[] = [
+  { label: "Anthropic", url: "https://api.anthropic.com/v1", provider: "anthropic" },
+  { label: "DeepSeek", url: "https://api.deepseek.com/v1", provider: "openai" },
+  { label: "Google Gemini", url: "https://generativelanguage.googleapis.com/v1beta", provider: "gemini" },
+  { label: "Groq", url: "https://api.groq.com/v1", provider: "openai" },
+  { label: "LM Studio", url: "http://localhost:1234/v1", provider: "openai" },
+  { label: "Mistral", url: "https://api.mistral.ai/v1", provider: "openai" },
+  { label: "Ollama", url: "http://localhost:11434", provider: "ollama" },
+  { label: "OpenAI", url: "https://api.openai.com/v1", provider: "openai" },
+  { label: "OpenRouter", url: "https://openrouter.ai/api/v1", provider: "openai" },
+  { label: "vLLM", url: "http://localhost:8000/v1", provider: "openai" },
 ];